@@ -0,0 +1,149 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use soft::{
+    scene::{RandomScene, ScenePreset, PRESETS},
+    vec2::{Real, Vec2},
+    world::{Particle, World},
+};
+
+// Physics steps per measured iteration, about one frame at 60 fps
+const STEPS: usize = 13;
+// Steps run before measuring so bodies are resting on each other instead of falling
+const SETTLE: usize = 800;
+// The largest is for comparing builds with and without the `simd` feature
+const PARTICLES: [usize; 4] = [256, 1024, 4096, 50_000];
+const SIZE: Real = 2048.0;
+
+type Builder = fn(&mut World, usize, Real);
+
+/// Side of a square world with room for `particles`, at least [`SIZE`].
+fn world_size(particles: usize) -> Real {
+    SIZE * (particles as Real / 4096.0).sqrt().max(1.0)
+}
+
+/// 4x4 boxes packed in rows.
+fn boxes(world: &mut World, particles: usize, size: Real) {
+    let cell = 5.0 * Particle::SPACING;
+    let per_row = (size / cell) as usize - 1;
+
+    for i in 0..particles / 16 {
+        let (x, y) = ((i % per_row) as Real, (i / per_row) as Real);
+        world
+            .spawn_rect(4, 4, cell * (x + 0.5), size - cell * (y + 1.0))
+            .unwrap();
+    }
+}
+
+/// Horizontal 32 particle ropes stacked on top of each other.
+fn ropes(world: &mut World, particles: usize, size: Real) {
+    let len = 31.0 * Particle::SPACING;
+    let per_column = (size / (3.0 * Particle::R)) as usize - 1;
+
+    for i in 0..particles / 32 {
+        let x = (i / per_column) as Real * (len + Particle::SPACING) + Particle::SPACING;
+        let y = size - (i % per_column + 1) as Real * 3.0 * Particle::R;
+        world
+            .spawn_rope(Vec2::new(x, y), Vec2::new(x + len, y))
+            .unwrap();
+    }
+}
+
+/// One square body.
+fn blob(world: &mut World, particles: usize, size: Real) {
+    let side = (particles as Real).sqrt() as usize;
+    let width = (side - 1) as Real * Particle::SPACING;
+    world
+        .spawn_rect(
+            side,
+            side,
+            Particle::SPACING,
+            size - width - Particle::SPACING,
+        )
+        .unwrap();
+}
+
+fn settled(size: Vec2, build: impl Fn(&mut World)) -> World {
+    let mut world = World::new(size.x, size.y);
+    build(&mut world);
+    world.step_n(SETTLE).unwrap();
+    world
+}
+
+fn update_vs_particles(c: &mut Criterion) {
+    let shapes: [(&str, Builder); 3] = [("boxes", boxes), ("ropes", ropes), ("blob", blob)];
+
+    for (name, build) in shapes {
+        let mut group = c.benchmark_group(format!("update/{name}"));
+
+        for particles in PARTICLES {
+            let size = world_size(particles);
+            let mut world = settled(Vec2::new(size, size), |world| {
+                build(world, particles, size);
+            });
+            group.throughput(Throughput::Elements(world.info().0 as u64));
+            group.bench_with_input(
+                BenchmarkId::from_parameter(particles),
+                &particles,
+                |b, _| b.iter(|| world.step_n(STEPS).unwrap()),
+            );
+        }
+
+        group.finish();
+    }
+}
+
+fn update_random(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update/random");
+
+    for count in [25, 100, 400] {
+        let size = Vec2::new(SIZE, SIZE);
+        let mut world = settled(size, |world| {
+            RandomScene { count, seed: 0 }.build(world, size)
+        });
+        group.throughput(Throughput::Elements(world.info().0 as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter(|| world.step_n(STEPS).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+fn update_presets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update/presets");
+
+    for preset in PRESETS {
+        let size = Vec2::new(1920.0, 1080.0);
+        let mut world = settled(size, |world| preset.build(world, size));
+        group.bench_function(preset.name(), |b| b.iter(|| world.step_n(STEPS).unwrap()));
+    }
+
+    group.finish();
+}
+
+/// Scenes mostly made of springs, for the `simd` feature. Run once per build against a
+/// saved baseline: `cargo bench --bench world -- springs --save-baseline scalar`, then
+/// `cargo bench --bench world --features simd -- springs --baseline scalar`.
+fn springs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("springs");
+
+    for (name, build) in [("ropes", ropes as Builder), ("blob", blob)] {
+        let particles = 16_384;
+        let size = world_size(particles);
+        let mut world = settled(Vec2::new(size, size), |world| {
+            build(world, particles, size);
+        });
+        group.throughput(Throughput::Elements(world.info().1 as u64));
+        group.bench_function(name, |b| b.iter(|| world.step_n(STEPS).unwrap()));
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    update_vs_particles,
+    update_random,
+    update_presets,
+    springs
+);
+criterion_main!(benches);
@@ -4,4 +4,29 @@ fn main() {
 
     #[cfg(target_os = "linux")]
     println!("cargo:rustc-link-arg=-Wl,-rpath,$ORIGIN");
+
+    #[cfg(feature = "ffi")]
+    ffi_header();
+}
+
+/// Declares the C API of `src/ffi.rs` in `include/soft.h`.
+#[cfg(feature = "ffi")]
+fn ffi_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+
+    let dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets the manifest dir");
+    std::fs::create_dir_all(format!("{dir}/include")).expect("include dir should be creatable");
+    let mut config = cbindgen::Config::default();
+    config.language = cbindgen::Language::C;
+    config.include_guard = Some(String::from("SOFT_H"));
+    config.usize_is_size_t = true;
+    // C enumerators share one namespace, `SoftStatus_Ok` instead of a bare `Ok`
+    config.enumeration.prefix_with_name = true;
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(format!("{dir}/src/ffi.rs"))
+        .generate()
+        .expect("the C API should be expressible in C")
+        .write_to_file(format!("{dir}/include/soft.h"));
 }
@@ -0,0 +1,112 @@
+#ifndef SOFT_H
+#define SOFT_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum SoftStatus {
+  SoftStatus_Ok = 0,
+  SoftStatus_NullWorld,
+  /**
+   * Out of range, too small or degenerate
+   */
+  SoftStatus_InvalidArgument,
+  /**
+   * A spring stretched so far the step was abandoned, the world should be reset
+   */
+  SoftStatus_Unstable,
+} SoftStatus;
+
+/**
+ * The simulation as handed out to C. Only ever handled through a pointer, and every
+ * function accepts a null one too.
+ */
+typedef struct SoftWorld SoftWorld;
+
+typedef struct SoftParticle {
+  double x;
+  double y;
+  double vx;
+  double vy;
+} SoftParticle;
+
+/**
+ * An empty world of `width` by `height`, to be released with [`soft_world_free`].
+ */
+struct SoftWorld *soft_world_new(double width, double height);
+
+/**
+ * # Safety
+ * `world` has to come from [`soft_world_new`] and not be used afterwards.
+ */
+void soft_world_free(struct SoftWorld *world);
+
+/**
+ * A `w` by `h` lattice of particles with its top left one at `x`, `y`.
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+enum SoftStatus soft_world_spawn_rect(struct SoftWorld *world,
+                                      size_t w,
+                                      size_t h,
+                                      double x,
+                                      double y);
+
+/**
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+enum SoftStatus soft_world_add_edge(struct SoftWorld *world,
+                                    double x1,
+                                    double y1,
+                                    double x2,
+                                    double y2);
+
+/**
+ * Advances exactly `n` physics steps, the same ones on every run.
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+enum SoftStatus soft_world_step(struct SoftWorld *world, size_t n);
+
+/**
+ * Seconds simulated so far, zero for a null world.
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+double soft_world_time(const struct SoftWorld *world);
+
+/**
+ * Zero for a null world.
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+size_t soft_world_particle_count(const struct SoftWorld *world);
+
+/**
+ * Copies up to `capacity` particles into `out`, returning how many were written.
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`], and `out` has to have room for
+ * `capacity` particles.
+ */
+size_t soft_world_read_particles(const struct SoftWorld *world,
+                                 struct SoftParticle *out,
+                                 size_t capacity);
+
+/**
+ * Changes whenever the simulated state does, see [`World::state_hash`].
+ *
+ * # Safety
+ * `world` has to be null or come from [`soft_world_new`].
+ */
+uint64_t soft_world_state_hash(const struct SoftWorld *world);
+
+#endif /* SOFT_H */
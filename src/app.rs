@@ -1,74 +1,335 @@
+#[cfg(feature = "audio")]
+use crate::audio::Audio;
+#[cfg(feature = "profile")]
+use crate::profile::{Profiler, Record};
+#[cfg(feature = "remote")]
+use crate::remote::{Command, Reply, Server, Snapshot};
 use crate::{
-    consts::{HEIGHT, SAVEFILE, WIDTH},
-    renderer::{Color, Renderer},
-    sdl2_renderer::SDL2CanvasWrapper,
-    vec2::Vec2,
-    world::{Edge, Particle, World},
+    backend::Backend,
+    camera::{Camera, View},
+    capture::{self, RecordFormat, Recorder},
+    cli::{Args, RendererKind},
+    config::{Config, InstabilityPolicy},
+    consts::{LOGFILE, PREFABFILE, RECORDINGS, SCREENSHOTS},
+    gamepad::{Gamepad, GamepadInput, Trigger},
+    gui::Gui,
+    image_import,
+    keybindings::{Action, Keybindings},
+    log::Log,
+    multiplayer::{Edit, Guest, Host},
+    physics::{Controls, Physics},
+    profile::span,
+    renderer::{BlendMode, Color, Renderer},
+    rng::Rng,
+    savefile::{Format, State},
+    scene::{RandomScene, ScenePreset, PRESETS},
+    slots::Id,
+    svg_import,
+    svg_renderer::SvgRenderer,
+    theme::{Theme, ThemeKind},
+    tool::Tool,
+    trajectory::Trajectory,
+    vec2::{consts::TAU, Real, Vec2},
+    world::{
+        BodyShape, Contact, Corners, Edge, Emission, Emitter, Fill, Friction, Group, Instability,
+        Layer, Material, Mover, MoverShape, Muscle, Particle, Prefab, SolverSettings, World,
+        WorldBounds, WorldEvent,
+    },
 };
+#[cfg(feature = "wgpu")]
+use crate::{gpu_collisions::GpuCollisions, wgpu_renderer::WgpuRenderer};
 use sdl2::{
-    event::Event,
+    clipboard::ClipboardUtil,
+    event::{Event, WindowEvent},
     gfx::framerate::FPSManager,
-    keyboard::{KeyboardState, Keycode, Mod, Scancode},
+    keyboard::{KeyboardState, Scancode},
     mouse::{MouseButton, MouseState},
+    render::Canvas,
     video::{Window, WindowBuildError},
     EventPump, IntegerOrSdlError, TimerSubsystem,
 };
+#[cfg(feature = "wgpu")]
+use std::sync::{Arc, Mutex};
 
 use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+const LOG_LINES: usize = 10;
+// Of the lists in the outline panel, past it they scroll
+const OUTLINE_HEIGHT: f32 = 400.0;
+const LAYER_NAME_WIDTH: f32 = 100.0;
+// Mouse movement over this many milliseconds is averaged into the fling velocity
+const FLING_WINDOW: u32 = 80;
+const MAX_FLING: Real = 3000.0;
+// Pace below which the simulation counts as falling behind, and how quickly it's tracked
+const BEHIND: Real = 0.95;
+const PACE_SMOOTHING: Real = 0.1;
+/// What the speed preset actions set the speed to, in order.
+pub const SPEED_PRESETS: [Real; 6] = [0.1, 0.25, 0.5, 1.0, 2.0, 4.0];
+// What the speed is scaled by while slow motion is held
+const SLOW_MOTION: Real = 0.2;
+// Most fast-forward speeds the simulation up by, and how quickly it gets there while there
+// is time to spare and backs off when there isn't
+const MAX_FAST_FORWARD: Real = 8.0;
+const FAST_FORWARD_GROWTH: Real = 1.02;
+const FAST_FORWARD_BACKOFF: Real = 0.9;
+// Share of real time the simulation may take to compute before fast-forward backs off
+const FAST_FORWARD_LOAD: Real = 0.75;
+// Degrees between the directions edges snap to
+const SNAP_ANGLE: Real = 15.0;
+// Angular acceleration the selected object is spun with, in radians per second squared
+const SPIN_ACCELERATION: Real = 20.0;
+// How much thicker an edge gets per notch of the scroll wheel
+const EDGE_RADIUS_STEP: Real = 1.25;
+// How much longer the springs of the object being inflated get per notch of the scroll wheel
+const INFLATE_STEP: Real = 1.05;
+// Zoom factor of one notch of the mouse wheel
+const ZOOM_STEP: Real = 1.1;
+// Pixels apart grid lines start fading out at, and are left out at
+const GRID_FADE: Real = 12.0;
+const GRID_MIN: Real = 3.0;
+// Every this many lines of the background grid one is bolder
+const GRID_MAJOR: Real = 10.0;
+// Radius of the disc the sand tool keeps filled while held down
+const SAND_BRUSH: Real = 30.0;
+// Pixels the pointer moves before the lasso gets another corner
+const LASSO_STEP: Real = 4.0;
+// Same for the tracks of movers, which are walked every step
+const TRACK_STEP: Real = 20.0;
+// Tracks let go of this close to where they started are closed
+const TRACK_CLOSE: Real = 20.0;
+// Speed emitters send things out at per pixel of the drag placing them
+const EMITTER_AIM: Real = 4.0;
+const TEXT_SIZE: Real = 12.0;
+const LINE_HEIGHT: Real = 14.0;
+// The UI font is monospace, with an advance of about 0.6 em
+const CHAR_WIDTH: Real = TEXT_SIZE * 0.6;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum EdgePoint {
+    Start,
+    End,
+    /// Anywhere between the ends, grabbed with the pointer last at the given position
+    Body(Vec2),
+}
 
-struct Log<const N: usize> {
-    buffer: std::collections::VecDeque<String>,
+/// Objects and edges picked with the selection rectangle, moved, copied and deleted together.
+/// Held by id, so what's removed meanwhile just drops out of it.
+#[derive(Default)]
+struct Selection {
+    objects: Vec<Id>,
+    edges: Vec<Id>,
 }
 
-impl<const N: usize> Log<N> {
-    pub fn new() -> Self {
+impl Selection {
+    fn new(world: &World, objects: &[usize], edges: &[usize]) -> Self {
         Self {
-            buffer: std::collections::VecDeque::with_capacity(N),
+            objects: objects.iter().filter_map(|&n| world.object_id(n)).collect(),
+            edges: edges.iter().filter_map(|&n| world.edge_id(n)).collect(),
         }
     }
 
-    pub fn log(&mut self, msg: String) {
-        if self.buffer.len() == N {
-            self.buffer.pop_back();
-        }
-        self.buffer.push_front(msg);
+    /// Indices of the objects and edges that are still there.
+    fn indices(&self, world: &World) -> (Vec<usize>, Vec<usize>) {
+        (
+            self.objects
+                .iter()
+                .filter_map(|&id| world.object_index(id))
+                .collect(),
+            self.edges
+                .iter()
+                .filter_map(|&id| world.edge_index(id))
+                .collect(),
+        )
     }
+}
+
+/// An object or an edge, as listed in the scene outline.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outlined {
+    Object(usize),
+    Edge(usize),
+}
 
-    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &String> {
-        self.buffer.iter()
+/// What gravity zones do to the gravity of the settings, see [`World::add_gravity_zone`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ZoneGravity {
+    Inverted,
+    Left,
+    Right,
+    Zero,
+}
+
+impl ZoneGravity {
+    const ALL: [Self; 4] = [Self::Inverted, Self::Left, Self::Right, Self::Zero];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Inverted => "inverted",
+            Self::Left => "sideways left",
+            Self::Right => "sideways right",
+            Self::Zero => "zero-g",
+        }
     }
 
-    pub fn len(&self) -> usize {
-        self.buffer.len()
+    /// Gravity in the zone when it's `gravity` outside.
+    fn of(self, gravity: Vec2) -> Vec2 {
+        match self {
+            Self::Inverted => -gravity,
+            Self::Left => gravity.perp(),
+            Self::Right => -gravity.perp(),
+            Self::Zero => Vec2::null(),
+        }
     }
 }
 
-#[derive(Clone, Copy, Serialize, Deserialize)]
-enum EdgePoint {
-    Start,
-    End,
+/// A rectangle to be spawned with exact numbers from the spawn dialog.
+#[derive(Clone, Copy)]
+struct RectSpec {
+    top_left: Vec2,
+    cols: usize,
+    rows: usize,
+    spacing: Real,
+    stiffness: Real,
 }
 
-#[derive(Serialize, Deserialize)]
-struct State {
-    world: World,
-    speed: f64,
-    simulate: bool,
-    draw_springs: bool,
-    draw_particles: bool,
+#[derive(Clone, Copy)]
+enum GroupDrag {
+    /// Moving the selection, last seen with the pointer at the given position
+    Move(Vec2),
+    /// Dragging out a copy of the selection, grabbed with the pointer at the given position
+    Copy(Vec2),
+}
+
+impl State {
+    fn draw(&self, selected_object: Option<usize>, theme: &Theme, canvas: &mut impl Renderer) {
+        if self.draw_trails {
+            self.world.draw_trails(theme, canvas);
+        }
+        self.world.draw_drains(theme, canvas);
+        self.world.draw_gravity_zones(theme, canvas);
+        if self.draw_springs {
+            self.world.draw_springs(theme, canvas);
+        }
+        if self.draw_particles {
+            self.world.draw_particles(theme, canvas);
+        }
+        if !(self.draw_particles || self.draw_springs) {
+            self.world.draw_polys(theme, canvas);
+        }
+        if self.draw_temperatures {
+            self.world.draw_temperatures(canvas);
+        }
+        if let Some(n) = selected_object {
+            self.world.draw_selection(n, theme, canvas);
+        }
+        self.world.draw_rods(theme, canvas);
+        self.world.draw_pins(theme, canvas);
+        self.world.draw_drag(theme, canvas);
+        self.world.draw_edges(theme, canvas);
+        self.world.draw_movers(theme, canvas);
+        self.world.draw_emitters(theme, canvas);
+    }
 }
+
 pub struct App {
     state: State,
+    physics: Physics,
+    /// Input for the simulation, sent along with the time to simulate
+    controls: Controls,
+    #[cfg(feature = "wgpu")]
+    gpu_collisions: Option<Arc<Mutex<GpuCollisions>>>,
+    #[cfg(feature = "remote")]
+    remote: Option<Server>,
+    host: Option<Host>,
+    guest: Option<Guest>,
     timer: TimerSubsystem,
     fps_manager: FPSManager,
-    canvas: SDL2CanvasWrapper<Window>,
+    /// Draws through the camera, its `screen` in pixels
+    canvas: View<Backend>,
     events: EventPump,
+    clipboard: ClipboardUtil,
     fps: u8,
+    target_fps: u32,
+    /// Smoothed share of the requested time the simulation manages to cover
+    pace: Real,
+    /// Slow motion is held down
+    slow_motion: bool,
+    /// What fast-forward currently speeds the simulation up by, while it's on
+    fast_forward: Option<Real>,
+    tool: Tool,
     rect_start: Option<Vec2>,
+    /// Rectangle waiting in the spawn dialog
+    rect_spec: Option<RectSpec>,
     line_start: Option<Vec2>,
-    selected_edge: Option<(usize, EdgePoint)>,
-    log: Log<10>,
+    rope_start: Option<Vec2>,
+    shape_start: Option<Vec2>,
+    /// The sand tool is held down
+    pouring: bool,
+    emitter_start: Option<Vec2>,
+    drain_start: Option<Vec2>,
+    zone_start: Option<Vec2>,
+    /// What gravity is turned into in zones placed next
+    zone_gravity: ZoneGravity,
+    /// Corners of the track of the mover being placed
+    track: Option<Vec<Vec2>>,
+    /// Of movers placed next
+    mover_shape: MoverShape,
+    mover_speed: Real,
+    /// Particle the rod being connected starts at
+    rod_start: Option<Id>,
+    /// Where the measure tool's line starts, and where it ends once clicked
+    measure: Option<(Vec2, Option<Vec2>)>,
+    /// Shift is held: edges being drawn snap to the grid and to angle increments, and
+    /// spawning a rectangle asks for its size
+    shift: bool,
+    /// Ctrl is held: edges grabbed by their middle and selections are copied when dragged
+    ctrl: bool,
+    mouse_track: VecDeque<(u32, Vec2)>,
+    selected_edge: Option<(Id, EdgePoint)>,
+    selected_object: Option<Id>,
+    selected_emitter: Option<usize>,
+    /// The selected emitter is being dragged around
+    moving_emitter: bool,
+    selection: Selection,
+    /// Corner the selection rectangle is being dragged out from
+    selection_start: Option<Vec2>,
+    group_drag: Option<GroupDrag>,
+    /// The spring that blew up and the revision of the world it did in, highlighted while
+    /// paused over it
+    instability: Option<(u64, Instability)>,
+    /// Corners of the lasso springs are being picked with
+    lasso: Option<Vec<Vec2>>,
+    /// Springs picked with the lasso
+    picked_springs: Vec<Id>,
+    random: RandomScene,
+    texture_path: String,
+    log: Log<100>,
     draw_log: bool,
+    draw_outline: bool,
+    draw_profiler: bool,
+    #[cfg(feature = "profile")]
+    profiler: Option<Profiler>,
+    /// Spans of the previous frame
+    #[cfg(feature = "profile")]
+    profile: Vec<Record>,
+    savefile: PathBuf,
+    keybindings: Keybindings,
+    gamepad: Option<Gamepad>,
+    #[cfg(feature = "audio")]
+    audio: Option<Audio>,
+    gui: Gui,
+    theme: ThemeKind,
+    config: Config,
+    config_path: PathBuf,
+    screenshot: bool,
+    recorder: Option<Recorder>,
+    record_format: RecordFormat,
+    record_fixed_step: bool,
+    trajectory: Option<Trajectory>,
 }
 #[derive(Debug)]
 #[allow(clippy::module_name_repetitions)]
@@ -80,6 +341,8 @@ pub enum AppConstructorError {
     CouldNotGetCanvas(IntegerOrSdlError),
     CouldNotGetEventPump(String),
     CouldNotSetFPS(String),
+    #[cfg(feature = "wgpu")]
+    CouldNotInitGpu(String),
 }
 
 impl std::fmt::Display for AppConstructorError {
@@ -106,29 +369,633 @@ impl std::fmt::Display for AppConstructorError {
             AppConstructorError::CouldNotSetFPS(msg) => {
                 f.write_fmt(format_args!("could not set fps: {msg}"))
             }
+            #[cfg(feature = "wgpu")]
+            AppConstructorError::CouldNotInitGpu(msg) => {
+                f.write_fmt(format_args!("could not initialize wgpu: {msg}"))
+            }
         }
     }
 }
 
 impl std::error::Error for AppConstructorError {}
 
+fn trigger_tool(trigger: Trigger) -> Tool {
+    match trigger {
+        Trigger::Left => Tool::Grab,
+        Trigger::Right => Tool::SpawnRect,
+    }
+}
+
+fn snap_to_grid(pos: Vec2, spacing: Real) -> Vec2 {
+    (pos / spacing).round() * spacing
+}
+
+/// Columns, rows and top left corner of the rect dragged out from `start` to `end`.
+fn rect_lattice(start: Vec2, end: Vec2) -> (usize, usize, Vec2) {
+    let w = ((start.x - end.x).abs() / Particle::SPACING) as usize + 1;
+    let h = ((start.y - end.y).abs() / Particle::SPACING) as usize + 1;
+    (w, h, start.min(end))
+}
+
+/// Turns the edge from `start` to `end` to the closest multiple of [`SNAP_ANGLE`] and rounds
+/// its length to a multiple of `spacing`, so straight edges from a grid point end on one too.
+fn snap_edge_end(start: Vec2, end: Vec2, spacing: Real) -> Vec2 {
+    let diff = end - start;
+    let step = SNAP_ANGLE.to_radians();
+    let angle = (diff.to_angle() / step).round() * step;
+    let len = (diff.len() / spacing).round() * spacing;
+
+    start + Vec2::from_angle(angle) * len
+}
+
+/// Sliders for how `muscle` moves its springs, returns whether any was changed.
+fn draw_muscle(ui: &mut egui::Ui, muscle: &mut Muscle) -> bool {
+    let mut changed = false;
+    changed |= ui
+        .add(egui::Slider::new(&mut muscle.amplitude, 0.0..=0.5).text("amplitude"))
+        .changed();
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut muscle.frequency, 0.1..=5.0)
+                .logarithmic(true)
+                .text("frequency"),
+        )
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut muscle.phase, 0.0..=TAU).text("phase"))
+        .changed();
+    changed
+}
+
+/// Picks one of `layers`, returns whether another one was picked.
+fn draw_layer_choice(
+    ui: &mut egui::Ui,
+    id_salt: &str,
+    layers: &[Layer],
+    layer: &mut usize,
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::new(id_salt, "layer")
+        .selected_text(layers.get(*layer).map_or("", |layer| &layer.name))
+        .show_ui(ui, |ui| {
+            for (n, option) in layers.iter().enumerate() {
+                changed |= ui.selectable_value(layer, n, &option.name).changed();
+            }
+        });
+    changed
+}
+
+/// Contents of the layers section of the outline panel. Returns whether a layer was hidden
+/// or locked, what's on it can't stay selected.
+fn draw_layers(ui: &mut egui::Ui, world: &mut World) -> bool {
+    let mut unpicked = false;
+    let mut remove = None;
+    let active = world.active_layer();
+    for (n, layer) in world.layers().to_vec().into_iter().enumerate() {
+        let mut edited = layer.clone();
+        ui.horizontal(|ui| {
+            if ui
+                .radio(n == active, "")
+                .on_hover_text("new objects and edges go here")
+                .clicked()
+            {
+                world.set_active_layer(n);
+            }
+            ui.add(egui::TextEdit::singleline(&mut edited.name).desired_width(LAYER_NAME_WIDTH));
+            ui.checkbox(&mut edited.hidden, "hide");
+            ui.checkbox(&mut edited.locked, "lock");
+            // What's on a removed layer moves to the first one
+            if n > 0 && ui.button("remove").clicked() {
+                remove = Some(n);
+            }
+        });
+
+        if edited != layer {
+            unpicked |= layer.is_pickable() && !edited.is_pickable();
+            world.set_layer(n, edited);
+        }
+    }
+
+    if let Some(n) = remove {
+        world.remove_layer(n);
+    }
+    if ui.button("add layer").clicked() {
+        let n = world.add_layer(format!("layer {}", world.layers().len()));
+        world.set_active_layer(n);
+    }
+    unpicked
+}
+
+/// Contents of the outline panel: every object and edge by name, or by index without one.
+/// The entry selected alone can be renamed and moved to another layer. Entries on hidden or
+/// locked layers can't be picked. Returns the entry clicked, if any.
+fn draw_outline(ui: &mut egui::Ui, world: &mut World, selected: &[Outlined]) -> Option<Outlined> {
+    let mut clicked = None;
+    let (_, _, _, edges, objects) = world.info();
+    egui::ScrollArea::vertical()
+        .max_height(OUTLINE_HEIGHT)
+        .show(ui, |ui| {
+            egui::CollapsingHeader::new(format!("objects ({objects})"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for n in 0..objects {
+                        let entry = Outlined::Object(n);
+                        let mut label = world
+                            .object_name(n)
+                            .map_or_else(|| format!("object {n}"), String::from);
+                        if world.is_frozen(n) {
+                            label.push_str(" (frozen)");
+                        }
+                        let pickable = world
+                            .object_layer(n)
+                            .and_then(|layer| world.layers().get(layer))
+                            .is_some_and(Layer::is_pickable);
+                        if ui
+                            .add_enabled_ui(pickable, |ui| {
+                                ui.selectable_label(selected.contains(&entry), label)
+                            })
+                            .inner
+                            .clicked()
+                        {
+                            clicked = Some(entry);
+                        }
+                    }
+                });
+            egui::CollapsingHeader::new(format!("edges ({edges})"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for (n, edge) in world.edges_iter().enumerate() {
+                        let entry = Outlined::Edge(n);
+                        let label = edge
+                            .name()
+                            .map_or_else(|| format!("edge {n}"), String::from);
+                        let pickable = world
+                            .edge_layer(n)
+                            .and_then(|layer| world.layers().get(layer))
+                            .is_some_and(Layer::is_pickable);
+                        if ui
+                            .add_enabled_ui(pickable, |ui| {
+                                ui.selectable_label(selected.contains(&entry), label)
+                            })
+                            .inner
+                            .clicked()
+                        {
+                            clicked = Some(entry);
+                        }
+                    }
+                });
+        });
+
+    if let &[entry] = selected {
+        let name = match entry {
+            Outlined::Object(n) => world.object_name(n),
+            Outlined::Edge(n) => world.edges_iter().nth(n).and_then(Edge::name),
+        };
+        let mut name = name.unwrap_or_default().to_owned();
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("name");
+            if ui.text_edit_singleline(&mut name).changed() {
+                match entry {
+                    Outlined::Object(n) => world.set_object_name(n, Some(name)),
+                    Outlined::Edge(n) => world.set_edge_name(n, Some(name)),
+                }
+            }
+        });
+
+        let layer = match entry {
+            Outlined::Object(n) => world.object_layer(n),
+            Outlined::Edge(n) => world.edge_layer(n),
+        };
+        if let Some(mut layer) = layer {
+            let layers = world.layers().to_vec();
+            if draw_layer_choice(ui, "outline layer", &layers, &mut layer) {
+                match entry {
+                    Outlined::Object(n) => world.set_object_layer(n, layer),
+                    Outlined::Edge(n) => world.set_edge_layer(n, layer),
+                }
+            }
+        }
+    }
+    clicked
+}
+
+/// Contents of the spring groups panel: every muscle of the world, to rename, tune, give
+/// the `picked` springs to or delete. Returns the springs of a group to pick instead, if
+/// asked for.
+fn draw_spring_groups(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    picked: &[usize],
+) -> Option<Vec<usize>> {
+    let mut pick = None;
+    ui.label(format!("{} springs picked", picked.len()))
+        .on_hover_text("drag a lasso around springs to pick them, hold shift to add more");
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(!picked.is_empty(), egui::Button::new("new group"))
+            .clicked()
+        {
+            let name = format!("group {}", world.muscles_iter().count() + 1);
+            let m = world.add_muscle(Muscle {
+                name,
+                ..Muscle::default()
+            });
+            world.set_springs_muscle(picked, Some(m));
+        }
+        if ui
+            .add_enabled(!picked.is_empty(), egui::Button::new("ungroup"))
+            .clicked()
+        {
+            world.set_springs_muscle(picked, None);
+        }
+    });
+
+    let mut removed = None;
+    for m in 0..world.muscles_iter().count() {
+        let Some(mut muscle) = world.muscle(m).cloned() else {
+            continue;
+        };
+        ui.separator();
+        ui.push_id(m, |ui| {
+            let mut changed = ui.text_edit_singleline(&mut muscle.name).changed();
+            changed |= draw_muscle(ui, &mut muscle);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!picked.is_empty(), egui::Button::new("assign"))
+                    .clicked()
+                {
+                    world.set_springs_muscle(picked, Some(m));
+                }
+                if ui.button("pick").clicked() {
+                    pick = Some(world.muscle_springs(m));
+                }
+                if ui.button("delete").clicked() {
+                    removed = Some(m);
+                }
+            });
+            if changed {
+                world.set_muscle(m, muscle);
+            }
+        });
+    }
+    if let Some(m) = removed {
+        world.remove_muscle(m);
+    }
+    pick
+}
+
+/// Contents of the emitter panel, changes apply to emitter `n` right away.
+fn draw_emitter(ui: &mut egui::Ui, world: &mut World, n: usize) {
+    let Some(&(mut emitter)) = world.emitter(n) else {
+        return;
+    };
+
+    // Aimed straight down before it was given a direction
+    let dir = if emitter.vel.len() > 0.0 {
+        emitter.vel.normalize()
+    } else {
+        Vec2::new(0.0, 1.0)
+    };
+    let mut speed = emitter.vel.len();
+
+    let mut changed = ui
+        .add(
+            egui::Slider::new(&mut emitter.rate, 0.1..=Emitter::MAX_RATE)
+                .logarithmic(true)
+                .text("per second"),
+        )
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut speed, 0.0..=MAX_FLING).text("speed"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut emitter.max_alive, 1..=Emitter::MAX_ALIVE).text("max alive"))
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut emitter.lifetime, 0.0..=Emitter::MAX_LIFETIME).text("lifetime"))
+        .on_hover_text("seconds, zero keeps them around")
+        .changed();
+    egui::ComboBox::from_label("emits")
+        .selected_text(emitter.emission.name())
+        .show_ui(ui, |ui| {
+            for emission in Emission::ALL {
+                changed |= ui
+                    .selectable_value(&mut emitter.emission, emission, emission.name())
+                    .changed();
+            }
+        });
+
+    if changed {
+        emitter.vel = dir * speed;
+        world.set_emitter(n, emitter);
+    }
+}
+
+/// Sliders for how particles bounce off and slide along things, returns whether they moved.
+fn draw_friction(ui: &mut egui::Ui, friction: &mut Friction) -> bool {
+    let mut changed = ui
+        .add(egui::Slider::new(&mut friction.restitution, 0.0..=1.0).text("restitution"))
+        .on_hover_text("how much of their speed things bounce back with")
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut friction.static_friction, 0.0..=2.0).text("static friction"))
+        .on_hover_text("how steep a slope things stay put on")
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut friction.kinetic_friction, 0.0..=2.0).text("kinetic friction"))
+        .on_hover_text("how fast things slow down sliding")
+        .changed();
+    changed
+}
+
+/// Sliders for how particles push off each other, returns whether they moved.
+fn draw_contact(ui: &mut egui::Ui, contact: &mut Contact) -> bool {
+    let mut changed = ui
+        .add(egui::Slider::new(&mut contact.restitution, 0.0..=1.0).text("contact restitution"))
+        .on_hover_text("how much of their speed particles bounce off each other with")
+        .changed();
+    changed |= ui
+        .add(egui::Slider::new(&mut contact.damping, 0.0..=1.0).text("contact damping"))
+        .on_hover_text("settles piles that keep jittering")
+        .changed();
+    ui.horizontal(|ui| {
+        for (name, preset) in [("elastic", Contact::ELASTIC), ("settled", Contact::SETTLED)] {
+            if ui
+                .add_enabled(*contact != preset, egui::Button::new(name))
+                .clicked()
+            {
+                *contact = preset;
+                changed = true;
+            }
+        }
+    });
+    changed
+}
+
+/// Sliders for the solver settings of `world`. It's drawn every frame the panel is open,
+/// only a setting that actually moved is an edit.
+fn draw_solver(ui: &mut egui::Ui, world: &mut World) {
+    let mut settings = *world.settings();
+    ui.add(egui::Slider::new(&mut settings.gravity.x, -1000.0..=1000.0).text("gravity x"));
+    ui.add(egui::Slider::new(&mut settings.gravity.y, -1000.0..=1000.0).text("gravity y"));
+    ui.add(
+        egui::Slider::new(&mut settings.stiffness, 100.0..=20000.0)
+            .logarithmic(true)
+            .text("stiffness"),
+    );
+    ui.add(egui::Slider::new(&mut settings.damping, 0.0..=500.0).text("damping"));
+    draw_friction(ui, &mut settings.friction);
+    draw_contact(ui, &mut settings.contact);
+    ui.horizontal(|ui| {
+        ui.label("bounds");
+        for bounds in WorldBounds::ALL {
+            ui.radio_value(&mut settings.bounds, bounds, bounds.name());
+        }
+    });
+    ui.checkbox(&mut settings.heat, "simulate heat")
+        .on_hover_text("hot edges melt objects touching them");
+    ui.add_enabled(
+        settings.heat,
+        egui::Slider::new(&mut settings.melting_point, 10.0..=500.0).text("melting point"),
+    );
+    ui.checkbox(&mut settings.limit_spring_force, "limit spring force")
+        .on_hover_text("keeps stiff objects from blowing up, but lets them stretch");
+    ui.add_enabled(
+        settings.limit_spring_force,
+        egui::Slider::new(&mut settings.max_spring_force, 500.0..=50000.0)
+            .logarithmic(true)
+            .text("max spring force"),
+    );
+    ui.checkbox(&mut settings.limit_speed, "limit speed")
+        .on_hover_text("keeps particles that shot off from going through edges");
+    ui.add_enabled(
+        settings.limit_speed,
+        egui::Slider::new(&mut settings.max_speed, 500.0..=20000.0)
+            .logarithmic(true)
+            .text("max speed"),
+    );
+    ui.add(
+        egui::Slider::new(&mut settings.relaxation_iterations, 0..=10)
+            .text("relaxation iterations"),
+    )
+    .on_hover_text("pulls stretched springs back to their length after every step");
+
+    if ui.button("reset solver").clicked() {
+        settings = SolverSettings::default();
+    }
+    world.set_settings(settings);
+}
+
+/// Contents of the object panel: what `n` is made of and how it looks. The material is only
+/// editable while `simulate` is off. Returns texture loading errors.
+fn draw_object(
+    ui: &mut egui::Ui,
+    world: &mut World,
+    n: usize,
+    simulate: bool,
+    texture_path: &mut String,
+    palette: [Color; 7],
+) -> Option<String> {
+    let (Some(fill), Some((particles, springs)), Some(state), Some(mut material)) = (
+        world.fill(n).cloned(),
+        world.object_info(n),
+        world.object_state(n),
+        world.material(n),
+    ) else {
+        return None;
+    };
+    let mut error = None;
+    let (center, vel, size) = (state.center_of_mass, state.vel, state.max - state.min);
+
+    let mut name = world.object_name(n).unwrap_or_default().to_owned();
+    ui.horizontal(|ui| {
+        ui.label("name");
+        if ui.text_edit_singleline(&mut name).changed() {
+            world.set_object_name(n, Some(name));
+        }
+    });
+    if let Some(mut layer) = world.object_layer(n) {
+        let layers = world.layers().to_vec();
+        if draw_layer_choice(ui, "object layer", &layers, &mut layer) {
+            world.set_object_layer(n, layer);
+        }
+    }
+    ui.label(format!("particles: {particles}"));
+    ui.label(format!("springs: {springs}"));
+    ui.label(format!("mass: {:.1}", state.mass));
+    ui.label(format!(
+        "center of mass: ({:.1}, {:.1})",
+        center.x, center.y
+    ));
+    ui.label(format!("velocity: ({:.1}, {:.1})", vel.x, vel.y));
+    ui.label(format!("bounds: {:.0} x {:.0}", size.x, size.y));
+    let mut frozen = world.is_frozen(n);
+    if ui
+        .checkbox(&mut frozen, "frozen")
+        .on_hover_text("held where it is, things still bump into it. I toggles it")
+        .changed()
+    {
+        world.set_frozen(n, frozen);
+    }
+    if world.is_quarantined(n) {
+        ui.label("quarantined: went NaN or infinite")
+            .on_hover_text("its particles were pinned, delete it or unpin them");
+    }
+    if let Some((_, _, spin)) = world.rotation(n) {
+        ui.label(format!("spin: {spin:.2} rad/s"))
+            .on_hover_text("hold Q or E to spin it");
+    }
+
+    ui.separator();
+    let own = world.has_material(n);
+    let mut changed = false;
+    ui.add_enabled_ui(!simulate, |ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut material.stiffness, 100.0..=20000.0)
+                    .logarithmic(true)
+                    .text("stiffness"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut material.damping, 0.0..=500.0).text("damping"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut material.mass, 0.5..=10.0)
+                    .logarithmic(true)
+                    .text("mass"),
+            )
+            .changed();
+        changed |= draw_friction(ui, &mut material.friction);
+        changed |= draw_contact(ui, &mut material.contact);
+        if ui
+            .add_enabled(own, egui::Button::new("use solver material"))
+            .clicked()
+        {
+            world.set_material(n, None);
+        }
+    })
+    .response
+    .on_disabled_hover_text("pause to edit");
+    if changed {
+        world.set_material(n, Some(material));
+    }
+
+    ui.separator();
+    let muscle = world.object_muscle(n);
+    let mut moving = muscle.is_some();
+    if ui
+        .checkbox(&mut moving, "muscle")
+        .on_hover_text("springs stretch and contract by themselves")
+        .changed()
+    {
+        let springs = world.springs_of(&[n]);
+        let muscle = moving.then(|| {
+            let name = format!("object {n}");
+            world.add_muscle(Muscle {
+                name,
+                ..Muscle::default()
+            })
+        });
+        world.set_springs_muscle(&springs, muscle);
+    }
+    if let Some((m, mut params)) = muscle
+        .filter(|_| moving)
+        .and_then(|m| Some((m, world.muscle(m)?.clone())))
+    {
+        if draw_muscle(ui, &mut params) {
+            world.set_muscle(m, params);
+        }
+    }
+
+    ui.separator();
+    let mut new_fill = None;
+
+    ui.horizontal(|ui| {
+        if ui.radio(fill == Fill::Outline, "outline").clicked() {
+            new_fill = Some(Fill::Outline);
+        }
+        if ui.radio(fill == Fill::Solid, "solid").clicked() {
+            new_fill = Some(Fill::Solid);
+        }
+        if ui
+            .radio(matches!(fill, Fill::Texture(_)), "texture")
+            .clicked()
+        {
+            new_fill = Some(Fill::Texture(texture_path.clone()));
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(texture_path);
+        if ui.button("apply texture").clicked() {
+            new_fill = Some(Fill::Texture(texture_path.clone()));
+        }
+    });
+
+    if let Some(fill) = new_fill {
+        error = world.set_fill(n, fill).err();
+    }
+
+    let current = world.object_color(n).unwrap_or(palette[n % palette.len()]);
+    let mut color = None;
+    ui.horizontal(|ui| {
+        for swatch in palette {
+            let button = egui::Button::new("")
+                .fill(swatch)
+                .min_size(egui::vec2(16.0, 16.0));
+            if ui.add(button).clicked() {
+                color = Some(Some(swatch));
+            }
+        }
+
+        let mut custom = current.into();
+        if ui.color_edit_button_srgba(&mut custom).changed() {
+            color = Some(Some(custom.into()));
+        }
+        if ui.button("auto").clicked() {
+            color = Some(None);
+        }
+    });
+
+    if let Some(color) = color {
+        world.set_object_color(n, color);
+    }
+
+    error
+}
+
 impl App {
-    pub fn new() -> Result<Self, AppConstructorError> {
+    pub fn new(args: &Args) -> Result<Self, AppConstructorError> {
         let ctx = sdl2::init().map_err(AppConstructorError::CouldNotGetContext)?;
         let video = ctx
             .video()
             .map_err(AppConstructorError::CouldNotGetVideoSubsystem)?;
-        let window = video
-            .window("soft", WIDTH as u32, HEIGHT as u32)
-            .fullscreen()
+
+        let mut window = video.window("soft", args.width, args.height);
+        if args.windowed {
+            window.resizable();
+        } else {
+            window.fullscreen();
+        }
+        let window = window
             .build()
             .map_err(AppConstructorError::CouldNotCreateWindow)?;
-        let canvas = window
-            .into_canvas()
-            .accelerated()
-            .build()
-            .map_err(AppConstructorError::CouldNotGetCanvas)?
-            .into();
+
+        let mut canvas = match args.renderer {
+            RendererKind::Gfx => Backend::Gfx(Self::sdl_canvas(window, args.vsync)?.into()),
+            RendererKind::Software => {
+                Backend::Software(Self::sdl_canvas(window, args.vsync)?.into())
+            }
+            #[cfg(feature = "wgpu")]
+            RendererKind::Wgpu => Backend::Wgpu(
+                WgpuRenderer::new(window, args.vsync)
+                    .map_err(AppConstructorError::CouldNotInitGpu)?,
+            ),
+        };
+        canvas.set_size(args.width, args.height);
+        canvas.set_blend_mode(BlendMode::Blend);
         let timer = ctx
             .timer()
             .map_err(AppConstructorError::CouldNotGetTimerSubsystem)?;
@@ -137,370 +1004,1878 @@ impl App {
             .map_err(AppConstructorError::CouldNotGetEventPump)?;
 
         let mut app = App {
-            state: State {
-                world: World::new(),
-                speed: 1.0,
-                simulate: false,
-                draw_springs: false,
-                draw_particles: false,
-            },
+            state: State::new(World::new(args.width as Real, args.height as Real)),
+            physics: Physics::start(),
+            controls: Controls::default(),
+            #[cfg(feature = "wgpu")]
+            gpu_collisions: None,
+            #[cfg(feature = "remote")]
+            remote: None,
+            host: None,
+            guest: None,
             timer,
             fps_manager: FPSManager::new(),
-            canvas,
+            canvas: View::new(canvas),
             events,
+            clipboard: video.clipboard(),
             fps: 0,
+            target_fps: args.fps,
+            pace: 1.0,
+            slow_motion: false,
+            fast_forward: None,
+            tool: Tool::Select,
             rect_start: None,
+            rect_spec: None,
             line_start: None,
+            rope_start: None,
+            shape_start: None,
+            pouring: false,
+            emitter_start: None,
+            drain_start: None,
+            zone_start: None,
+            zone_gravity: ZoneGravity::Inverted,
+            track: None,
+            mover_shape: MoverShape::ALL[0],
+            mover_speed: 100.0,
+            rod_start: None,
+            measure: None,
+            shift: false,
+            ctrl: false,
+            mouse_track: VecDeque::new(),
             selected_edge: None,
+            selected_object: None,
+            selected_emitter: None,
+            moving_emitter: false,
+            selection: Selection::default(),
+            selection_start: None,
+            group_drag: None,
+            instability: None,
+            lasso: None,
+            picked_springs: vec![],
+            random: RandomScene {
+                count: args.random.unwrap_or(50),
+                seed: args.seed,
+            },
+            texture_path: String::new(),
             log: Log::new(),
             draw_log: true,
+            draw_outline: false,
+            draw_profiler: false,
+            #[cfg(feature = "profile")]
+            profiler: None,
+            #[cfg(feature = "profile")]
+            profile: vec![],
+            savefile: args.savefile.clone(),
+            keybindings: Keybindings::default(),
+            gamepad: None,
+            #[cfg(feature = "audio")]
+            audio: None,
+            gui: Gui::new(),
+            theme: ThemeKind::default(),
+            config: Config::default(),
+            config_path: args.config.clone(),
+            screenshot: false,
+            recorder: None,
+            record_format: args.record_format,
+            record_fixed_step: args.record_fixed_step,
+            trajectory: args.trajectory.clone().map(|path| {
+                Trajectory::new(
+                    path,
+                    args.trajectory_every,
+                    args.trajectory_objects,
+                    args.trajectory_limit,
+                )
+            }),
         };
 
+        if args.keybindings.exists() {
+            match Keybindings::load(&args.keybindings) {
+                Ok(keybindings) => app.keybindings = keybindings,
+                Err(err) => app
+                    .log
+                    .warn(format!("could not load keybindings, using defaults: {err}")),
+            }
+        }
+
+        if args.log_file {
+            if let Err(err) = app.log.mirror_to(Path::new(LOGFILE)) {
+                app.log.error(format!("could not open {LOGFILE}: {err}"));
+            }
+        }
+
+        match Gamepad::new(&ctx) {
+            Ok(gamepad) => app.gamepad = Some(gamepad),
+            Err(err) => app
+                .log
+                .warn(format!("could not init game controllers: {err}")),
+        }
+
+        #[cfg(feature = "profile")]
+        match Profiler::install() {
+            Ok(profiler) => app.profiler = Some(profiler),
+            Err(err) => app.log.warn(format!("could not install profiler: {err}")),
+        }
+
+        #[cfg(feature = "wgpu")]
+        if args.gpu_collisions {
+            match GpuCollisions::new() {
+                Ok(gpu) => app.gpu_collisions = Some(Arc::new(Mutex::new(gpu))),
+                Err(err) => app
+                    .log
+                    .warn(format!("could not init gpu collisions: {err}")),
+            }
+        }
+
+        #[cfg(feature = "remote")]
+        if let Some(addr) = &args.remote {
+            match Server::start(addr) {
+                Ok(server) => {
+                    app.remote = Some(server);
+                    app.log.info(format!("remote control listening on {addr}"));
+                }
+                Err(err) => app
+                    .log
+                    .warn(format!("could not start remote control: {err}")),
+            }
+        }
+
+        if let Some(addr) = &args.host {
+            match Host::start(addr) {
+                Ok(host) => {
+                    app.host = Some(host);
+                    app.log.info(format!("hosting on {addr}"));
+                }
+                Err(err) => app.log.warn(format!("could not host on {addr}: {err}")),
+            }
+        }
+        if let Some(addr) = &args.join {
+            match Guest::join(addr) {
+                Ok(guest) => {
+                    app.guest = Some(guest);
+                    app.log.info(format!("joined {addr}"));
+                }
+                Err(err) => app.log.warn(format!("could not join {addr}: {err}")),
+            }
+        }
+
+        #[cfg(feature = "audio")]
+        match Audio::new(&ctx) {
+            Ok(audio) => app.audio = Some(audio),
+            Err(err) => app.log.warn(format!("could not init audio: {err}")),
+        }
+
+        if args.config.exists() {
+            match Config::load(&args.config) {
+                Ok(config) => app.config = config,
+                Err(err) => app
+                    .log
+                    .warn(format!("could not load config, using defaults: {err}")),
+            }
+        }
+        app.apply_theme(args.theme.unwrap_or(app.config.theme));
+
         app.fps_manager
-            .set_framerate(60)
+            .set_framerate(args.fps)
             .map_err(AppConstructorError::CouldNotSetFPS)?;
 
         Ok(app)
     }
 
-    #[allow(unused_must_use)]
-    pub fn init_default_world(&mut self) {
-        let world = &mut self.state.world;
+    fn sdl_canvas(window: Window, vsync: bool) -> Result<Canvas<Window>, AppConstructorError> {
+        let mut canvas = window.into_canvas().accelerated();
+        if vsync {
+            canvas = canvas.present_vsync();
+        }
 
-        world.add_edge(Vec2::new(0.0, 400.0), Vec2::new(280.0, 400.0));
-        world.add_edge(Vec2::new(400.0, 700.0), Vec2::new(680.0, 700.0));
+        canvas
+            .build()
+            .map_err(AppConstructorError::CouldNotGetCanvas)
+    }
 
-        world.add_edge(Vec2::new(850.0, 1080.0), Vec2::new(1920.0, 800.0));
+    fn theme(&self) -> &'static Theme {
+        self.theme.theme()
+    }
 
-        // Screen edges
-        world.add_edge(
-            Vec2::new(-Edge::R, -Edge::R),
-            Vec2::new(-Edge::R, 1080.0 + Edge::R),
-        );
-        world.add_edge(
-            Vec2::new(-Edge::R, -Edge::R),
-            Vec2::new(1920.0 + Edge::R, -Edge::R),
-        );
-        world.add_edge(
-            Vec2::new(-Edge::R, 1080.0 + Edge::R),
-            Vec2::new(1920.0 + Edge::R, 1080.0 + Edge::R),
-        );
-        world.add_edge(
-            Vec2::new(1920.0 + Edge::R, -Edge::R),
-            Vec2::new(1920.0 + Edge::R, 1080.0 + Edge::R),
-        );
+    fn apply_theme(&mut self, theme: ThemeKind) {
+        self.theme = theme;
+        self.gui.set_light(theme == ThemeKind::Light);
     }
 
-    pub fn load_or_default(&mut self) {
-        match std::fs::read_to_string(SAVEFILE) {
-            Ok(save) => {
-                let msg = if let Ok(state) = serde_json::from_str(save.as_str()) {
-                    self.load_state(state);
-                    "savefile loaded succesfully"
-                } else {
-                    self.init_default_world();
-                    "could not deserialize savefile"
-                };
+    fn set_theme(&mut self, theme: ThemeKind) {
+        self.apply_theme(theme);
+        self.config.theme = theme;
 
-                self.log.log(msg.into());
-            }
-            Err(_) => self.init_default_world(),
+        match self.config.save(&self.config_path) {
+            Ok(()) => self
+                .log
+                .info(format!("switched to the {} theme", theme.name())),
+            Err(err) => self.log.error(format!("could not save config: {err}")),
         }
     }
 
-    fn load_state(&mut self, state: State) {
-        self.state = state;
-        self.selected_edge = None;
-    }
+    fn set_muted(&mut self, muted: bool) {
+        self.config.muted = muted;
 
-    fn save_state(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string(&self.state)
+        match self.config.save(&self.config_path) {
+            Ok(()) => self.log.info(if muted {
+                "sound muted".to_owned()
+            } else {
+                "sound unmuted".to_owned()
+            }),
+            Err(err) => self.log.error(format!("could not save config: {err}")),
+        }
     }
 
-    #[allow(clippy::too_many_lines)]
-    fn handle_events(&mut self) -> bool {
-        let lctrl = self
-            .events
-            .keyboard_state()
-            .is_scancode_pressed(Scancode::LCtrl);
+    /// Replaces the world with a freshly built `preset`, keeping the solver settings.
+    fn load_scene(&mut self, preset: &dyn ScenePreset) {
+        let (w, h) = self.canvas.size();
+        let mut world = World::new(w as Real, h as Real);
+        world.set_settings(*self.state.world.settings());
+        preset.build(&mut world, Vec2::new(w as Real, h as Real));
 
-        let events: Vec<Event> = self.events.poll_iter().collect();
-        for event in events {
-            match event {
-                Event::Quit { .. }
-                | Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
-                } => {
-                    return false;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Space),
-                    ..
-                } => {
-                    self.state.simulate = !self.state.simulate;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::F1),
-                    ..
-                } => {
-                    self.state.draw_particles = !self.state.draw_particles;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::F2),
-                    ..
-                } => {
-                    self.state.draw_springs = !self.state.draw_springs;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::F3),
-                    ..
-                } => {
-                    self.draw_log = !self.draw_log;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::F4),
-                    ..
-                } => {
-                    let msg = match std::fs::write(
-                        SAVEFILE,
-                        self.save_state().expect("state should be valid to save"),
-                    ) {
-                        Ok(_) => format!("world saved to {SAVEFILE}"),
-                        Err(err) => format!("Could not save file: {err}"),
-                    };
+        self.state.world = world;
+        self.selected_edge = None;
+        self.selected_object = None;
+        self.selected_emitter = None;
+        self.selection = Selection::default();
+        self.picked_springs.clear();
+        self.set_tool(self.tool);
+        self.canvas.camera = Camera::default();
+        self.log.info(format!("loaded the {} scene", preset.name()));
+    }
 
-                    self.log.log(msg);
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::F5),
-                    ..
-                } => match std::fs::read_to_string(SAVEFILE) {
-                    Ok(save) => {
-                        let msg = if let Ok(state) = serde_json::from_str(save.as_str()) {
-                            self.load_state(state);
-                            "savefile loaded succesfully"
-                        } else {
-                            "could not deserialize savefile"
-                        };
-
-                        self.log.log(msg.into());
-                    }
-                    Err(err) => self.log.log(format!("could not open savefile: {err}")),
-                },
-                Event::KeyDown {
-                    keycode: Some(Keycode::Left),
-                    ..
-                } if self.state.speed > 0.0 => {
-                    self.state.speed -= 0.01;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Right),
-                    ..
-                } if self.state.speed < 2.0 => {
-                    self.state.speed += 0.01;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Backspace),
-                    ..
-                } => {
-                    self.state.world.remove_last();
-                }
-                Event::MouseButtonDown {
-                    mouse_btn: MouseButton::Right,
-                    x,
-                    y,
-                    ..
-                } => {
-                    if lctrl {
-                        self.line_start = Some(Vec2::new(f64::from(x), f64::from(y)));
-                        self.rect_start = None;
-                    } else {
-                        self.rect_start = Some(Vec2::new(f64::from(x), f64::from(y)));
-                        self.line_start = None;
-                    }
-                }
-                Event::MouseButtonUp {
-                    mouse_btn: MouseButton::Right,
-                    x,
-                    y,
-                    ..
-                } if self.rect_start.is_some() => {
-                    if let Err((w, h)) = self.state.world.spawn_rect(
-                        ((self.rect_start.unwrap().x - f64::from(x)).abs() / Particle::SPACING)
-                            as usize
-                            + 1,
-                        ((self.rect_start.unwrap().y - f64::from(y)).abs() / Particle::SPACING)
-                            as usize
-                            + 1,
-                        f64::min(self.rect_start.unwrap().x, f64::from(x)),
-                        f64::min(self.rect_start.unwrap().y, f64::from(y)),
-                    ) {
-                        self.log.log(format!(
-                            "error while spawning new rect: Rect is too small: ({w}, {h}) < (2, 2)"
-                        ));
-                    }
+    pub fn load_random_scene(&mut self) {
+        let random = self.random;
+        self.load_scene(&random);
+        // Shapes that found no free spot are left out, so report what actually fit
+        let (_, _, _, edges, objects) = self.state.world.info();
+        self.log.info(format!(
+            "{objects} objects and {edges} edges from seed {}",
+            random.seed
+        ));
+    }
 
-                    self.rect_start = None;
-                }
-                Event::MouseButtonUp {
-                    mouse_btn: MouseButton::Right,
-                    x,
-                    y,
-                    ..
-                } if self.line_start.is_some() => {
-                    if let Err(msg) = self.state.world.add_edge(
-                        self.line_start.unwrap(),
-                        Vec2::new(f64::from(x), f64::from(y)),
-                    ) {
-                        self.log.log(msg.into());
-                    }
-                    self.line_start = None;
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Delete),
-                    keymod: Mod::NOMOD,
-                    ..
-                } => {
-                    self.state.world.clear();
-                }
-                Event::KeyDown {
-                    keycode: Some(Keycode::Delete),
-                    keymod: Mod::LCTRLMOD,
-                    ..
-                } => {
-                    if let Some((n, _)) = self.selected_edge {
-                        self.state.world.remove_edge(n);
-                        self.selected_edge = None;
-                    }
+    pub fn load_or_default(&mut self, scene: Option<&Path>) {
+        let path = scene.unwrap_or(&self.savefile);
+        match std::fs::read(path) {
+            Ok(save) => {
+                if let Ok(state) = Format::from_path(path).decode(&save) {
+                    self.load_state(state);
+                    self.log.info("savefile loaded succesfully".into());
+                } else {
+                    self.load_scene(PRESETS[0]);
+                    self.log.error("could not deserialize savefile".into());
                 }
-
-                _ => {}
             }
+            Err(_) => self.load_scene(PRESETS[0]),
         }
-        true
     }
 
-    pub fn run(mut self) {
-        'running: loop {
-            let (begin, mouse, _) = self.begin_frame();
+    /// Adds the outlines drawn in the SVG at `path` as edges.
+    pub fn import_svg(&mut self, path: &Path, tolerance: Real) {
+        let segments = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|svg| svg_import::segments(&svg, tolerance));
 
-            if !self.handle_events() {
-                break 'running;
+        match segments {
+            Ok(segments) => {
+                // Zero length ones, like a subpath closed where it already ends, are skipped
+                let added = segments
+                    .into_iter()
+                    .filter(|&(start, end)| self.state.world.add_edge(start, end).is_ok())
+                    .count();
+                self.log
+                    .info(format!("imported {added} edges from {}", path.display()));
             }
+            Err(err) => self.log.error(format!("could not import svg: {err}")),
+        }
+    }
 
-            if self.state.simulate {
-                self.update_physics();
+    /// Adds the largest shape in the PNG at `path` as a body, in the middle of the view.
+    pub fn import_image(&mut self, path: &Path, scale: Real, threshold: Real) {
+        let outline = match image_import::silhouette(path, threshold) {
+            Ok(outline) => outline,
+            Err(err) => {
+                self.log.error(format!("could not import image: {err}"));
+                return;
             }
+        };
 
-            self.draw_world();
-            self.draw_ui();
+        let min = outline.iter().fold(outline[0], |min, &p| min.min(p));
+        let max = outline.iter().fold(outline[0], |max, &p| max.max(p));
+        let (top_left, bottom_right) = self.canvas.visible();
+        let offset = (top_left + bottom_right) / 2.0 - (min + max) / 2.0 * scale;
+        let outline: Vec<Vec2> = outline.iter().map(|&p| p * scale + offset).collect();
 
-            let mouse_pos = Vec2::new(f64::from(mouse.x()), f64::from(mouse.y()));
+        match self.state.world.spawn_outline(&outline) {
+            Ok(()) => self
+                .log
+                .info(format!("imported {} as a body", path.display())),
+            Err(err) => self.log.error(format!("could not import image: {err}")),
+        }
+    }
 
-            self.handle_new_rect(mouse_pos);
-            self.handle_new_line(mouse_pos);
-            self.handle_line_manip(mouse, mouse_pos);
+    pub fn pause(&mut self) {
+        self.state.simulate = false;
+    }
 
-            self.end_frame(begin);
-        }
+    /// Of the window, the world keeps its own size.
+    fn resize(&mut self, width: u32, height: u32) {
+        self.canvas.screen.set_size(width, height);
     }
 
-    fn handle_line_manip(&mut self, mouse: MouseState, mouse_pos: Vec2) {
-        if let Some((n, which_end)) = self.selected_edge {
-            let e = self
-                .state
-                .world
-                .edges_iter_mut()
-                .nth(n)
-                .expect("Index of edge should always be valid");
+    /// Shows the whole world, zoomed out as far as it takes.
+    fn fit_camera(&mut self) {
+        let (w, h) = self.canvas.size();
+        self.canvas
+            .camera
+            .fit(self.state.world.size(), Vec2::new(w as Real, h as Real));
+    }
 
-            match which_end {
-                EdgePoint::Start => {
-                    self.canvas
-                        .set_color(Color::CYAN)
-                        .filled_circle(e.get_start(), Edge::R);
+    fn load_state(&mut self, mut state: State) {
+        // Saved before worlds had a size apart from the window's
+        let size = match state.world.size() {
+            size if size == Vec2::null() => {
+                let (w, h) = self.canvas.size();
+                Vec2::new(w as Real, h as Real)
+            }
+            size => size,
+        };
+        state.world.resize(size.x, size.y);
+        self.canvas.camera = Camera::default();
 
-                    if mouse.is_mouse_button_pressed(MouseButton::Left) {
-                        e.set_start(mouse_pos);
-                    } else {
-                        self.selected_edge = None;
-                    }
-                }
-                EdgePoint::End => {
-                    self.canvas
-                        .set_color(Color::CYAN)
-                        .filled_circle(e.get_end(), Edge::R);
+        self.state = state;
+        self.selected_edge = None;
+        self.selected_object = None;
+        self.selected_emitter = None;
+        self.selection = Selection::default();
+        self.picked_springs.clear();
+        self.set_tool(self.tool);
+        self.load_textures();
+    }
 
-                    if mouse.is_mouse_button_pressed(MouseButton::Left) {
-                        e.set_end(mouse_pos);
-                    } else {
-                        self.selected_edge = None;
+    fn load_textures(&mut self) {
+        for err in self.state.world.load_textures() {
+            self.log.error(format!("could not load texture {err}"));
+        }
+    }
+
+    /// The state encoded the way the savefile's extension asks for.
+    fn save_state(&self) -> Result<Vec<u8>, String> {
+        Format::from_path(&self.savefile).encode(&self.state)
+    }
+
+    fn export_prefab(&mut self) {
+        let Some(prefab) = self
+            .selected_object()
+            .and_then(|n| self.state.world.extract_prefab(n))
+        else {
+            self.log.warn("no object selected to export".into());
+            return;
+        };
+
+        match std::fs::write(
+            PREFABFILE,
+            serde_json::to_string(&prefab).expect("prefab should be valid to save"),
+        ) {
+            Ok(_) => self.log.info(format!("object exported to {PREFABFILE}")),
+            Err(err) => self.log.error(format!("could not export prefab: {err}")),
+        }
+    }
+
+    fn stamp_prefab(&mut self, pos: Vec2) {
+        match std::fs::read_to_string(PREFABFILE) {
+            Ok(save) => match serde_json::from_str::<Prefab>(save.as_str()) {
+                Ok(prefab) => match self.state.world.insert_prefab(&prefab, pos) {
+                    Ok(()) => {
+                        self.push_out_last();
+                        self.load_textures();
                     }
+                    Err(err) => self.log.error(format!("invalid prefab: {err}")),
+                },
+                Err(_) => self.log.error("could not deserialize prefab".into()),
+            },
+            Err(err) => self.log.error(format!("could not open prefab: {err}")),
+        }
+    }
+
+    /// The selected object, as long as it's still there.
+    fn selected_object(&self) -> Option<usize> {
+        self.selected_object
+            .and_then(|id| self.state.world.object_index(id))
+    }
+
+    /// The particle the rod being connected starts at and where it is, as long as it's
+    /// still there.
+    fn rod_start(&self) -> Option<(Id, Vec2)> {
+        let id = self.rod_start?;
+        Some((id, self.state.world.particle_info(id)?.1))
+    }
+
+    /// The springs picked with the lasso that are still there.
+    fn picked_springs(&self) -> Vec<usize> {
+        self.picked_springs
+            .iter()
+            .filter_map(|&id| self.state.world.spring_index(id))
+            .collect()
+    }
+
+    fn pick_springs(&mut self, springs: &[usize]) {
+        let world = &self.state.world;
+        self.picked_springs = springs.iter().filter_map(|&n| world.spring_id(n)).collect();
+    }
+
+    /// The spring that blew up, as long as the world wasn't edited since.
+    fn instability(&self) -> Option<Instability> {
+        let (revision, instability) = self.instability?;
+        (revision == self.state.world.revision()).then_some(instability)
+    }
+
+    /// The selected edge and the part of it held, as long as it's still there.
+    fn selected_edge(&self) -> Option<(usize, EdgePoint)> {
+        let (id, point) = self.selected_edge?;
+        Some((self.state.world.edge_index(id)?, point))
+    }
+
+    /// The edge under the pointer, or else the edges in the selection.
+    fn selected_edges(&self) -> Vec<usize> {
+        match self.selected_edge() {
+            Some((n, _)) => vec![n],
+            None => self.selection.indices(&self.state.world).1,
+        }
+    }
+
+    /// What editing operations act on: the selection, or the selected object without one.
+    fn selected_group(&self) -> (Vec<usize>, Vec<usize>) {
+        if self.selection.objects.is_empty() && self.selection.edges.is_empty() {
+            (self.selected_object().into_iter().collect(), vec![])
+        } else {
+            self.selection.indices(&self.state.world)
+        }
+    }
+
+    /// Puts the selection, or the selected object, on the clipboard as JSON.
+    fn copy_selection(&mut self) {
+        let (objects, edges) = self.selected_group();
+        if objects.is_empty() && edges.is_empty() {
+            self.log.warn("nothing selected to copy".into());
+            return;
+        }
+
+        let group = self.state.world.extract_group(&objects, &edges);
+        let json = serde_json::to_string(&group).expect("group should be valid to serialize");
+        match self.clipboard.set_clipboard_text(&json) {
+            Ok(()) => self.log.info(format!(
+                "copied {} objects and {} edges",
+                objects.len(),
+                edges.len()
+            )),
+            Err(err) => self
+                .log
+                .error(format!("could not copy to clipboard: {err}")),
+        }
+    }
+
+    /// Inserts what [`App::copy_selection`] put on the clipboard, centered on `pos`.
+    fn paste(&mut self, pos: Vec2) {
+        let text = match self.clipboard.clipboard_text() {
+            Ok(text) => text,
+            Err(err) => {
+                self.log.error(format!("could not read clipboard: {err}"));
+                return;
+            }
+        };
+
+        match serde_json::from_str::<Group>(&text) {
+            Ok(group) => match self.state.world.insert_group(&group, pos) {
+                Ok((objects, edges)) => {
+                    self.selection = Selection::new(&self.state.world, &objects, &edges);
+                    self.selected_object = None;
+                    self.load_textures();
                 }
+                Err(err) => self
+                    .log
+                    .error(format!("invalid objects on clipboard: {err}")),
+            },
+            Err(_) => self
+                .log
+                .warn("clipboard does not hold copied objects".into()),
+        }
+    }
+
+    fn cycle_object_color(&mut self) {
+        let Some(n) = self.selected_object() else {
+            self.log.warn("no object selected to color".into());
+            return;
+        };
+
+        let palette = &self.theme().objects;
+        let current = self
+            .state
+            .world
+            .object_color(n)
+            .unwrap_or(palette[n % palette.len()]);
+        let next = palette
+            .iter()
+            .position(|&color| color == current)
+            .map_or(0, |i| (i + 1) % palette.len());
+
+        self.state.world.set_object_color(n, Some(palette[next]));
+    }
+
+    fn handle_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => {
+                return false;
+            }
+            Action::ToggleSimulation => {
+                self.state.simulate = !self.state.simulate;
+            }
+            Action::ToggleParticles => {
+                self.state.draw_particles = !self.state.draw_particles;
+            }
+            Action::ToggleSprings => {
+                self.state.draw_springs = !self.state.draw_springs;
+            }
+            Action::ToggleTrails => {
+                self.state.draw_trails = !self.state.draw_trails;
+                self.state.world.clear_trails();
+            }
+            Action::ToggleTemperatures => {
+                self.state.draw_temperatures = !self.state.draw_temperatures;
+            }
+            Action::ToggleLog => {
+                self.draw_log = !self.draw_log;
+            }
+            Action::ToggleOutline => {
+                self.draw_outline = !self.draw_outline;
+            }
+            Action::ToggleGrid => {
+                self.config.draw_grid = !self.config.draw_grid;
+                if let Err(err) = self.config.save(&self.config_path) {
+                    self.log.error(format!("could not save config: {err}"));
+                }
+            }
+            Action::ScrollLogUp => {
+                self.log.scroll_up();
+            }
+            Action::ScrollLogDown => {
+                self.log.scroll_down();
+            }
+            Action::FitCamera => self.fit_camera(),
+            Action::ToggleSettings => {
+                self.gui.visible = !self.gui.visible;
+            }
+            Action::ToggleProfiler => {
+                if cfg!(feature = "profile") {
+                    self.draw_profiler = !self.draw_profiler;
+                } else {
+                    self.log
+                        .warn("built without the profile feature, nothing to show".into());
+                }
+            }
+            Action::CycleTheme => self.set_theme(self.theme.next()),
+            Action::ToggleMute => self.set_muted(!self.config.muted),
+            Action::Save => {
+                match std::fs::write(
+                    &self.savefile,
+                    self.save_state().expect("state should be valid to save"),
+                ) {
+                    Ok(_) => self
+                        .log
+                        .info(format!("world saved to {}", self.savefile.display())),
+                    Err(err) => self.log.error(format!("Could not save file: {err}")),
+                }
+            }
+            Action::Load => match std::fs::read(&self.savefile) {
+                Ok(save) => {
+                    if let Ok(state) = Format::from_path(&self.savefile).decode(&save) {
+                        self.load_state(state);
+                        self.log.info("savefile loaded succesfully".into());
+                    } else {
+                        self.log.error("could not deserialize savefile".into());
+                    }
+                }
+                Err(err) => self.log.error(format!("could not open savefile: {err}")),
+            },
+            Action::Screenshot => {
+                self.screenshot = true;
+            }
+            Action::ToggleRecording => self.toggle_recording(),
+            Action::ExportSvg => self.export_svg(),
+            Action::ExportPrefab => {
+                self.export_prefab();
+            }
+            Action::StampPrefab => {
+                self.stamp_prefab(self.pointer());
+            }
+            Action::Copy => self.copy_selection(),
+            Action::Paste => self.paste(self.pointer()),
+            Action::MirrorHorizontal | Action::MirrorVertical => {
+                let (objects, edges) = self.selected_group();
+                let vertical = action == Action::MirrorVertical;
+                self.state.world.mirror_group(&objects, &edges, vertical);
+            }
+            Action::RotateSelection => {
+                let (objects, edges) = self.selected_group();
+                self.state.world.rotate_group(&objects, &edges);
+            }
+            Action::ToggleFrozen => {
+                let (objects, _) = self.selected_group();
+                let world = &mut self.state.world;
+                // Mixed selections all freeze, so a second press lets them all go together
+                let frozen = !objects.iter().all(|&n| world.is_frozen(n));
+                for n in objects {
+                    world.set_frozen(n, frozen);
+                }
+            }
+            Action::UnfreezeAll => {
+                let world = &mut self.state.world;
+                let (.., objects) = world.info();
+                for n in 0..objects {
+                    if world.is_frozen(n) {
+                        world.set_frozen(n, false);
+                    }
+                }
+            }
+            Action::RandomScene => {
+                // Chained from the last seed, so a session can be replayed from its first one
+                self.random.seed = Rng::new(self.random.seed).next_u64();
+                self.load_random_scene();
+            }
+            Action::CycleObjectColor => self.cycle_object_color(),
+            Action::SpeedTenth => self.state.speed = SPEED_PRESETS[0],
+            Action::SpeedQuarter => self.state.speed = SPEED_PRESETS[1],
+            Action::SpeedHalf => self.state.speed = SPEED_PRESETS[2],
+            Action::SpeedNormal => self.state.speed = SPEED_PRESETS[3],
+            Action::SpeedDouble => self.state.speed = SPEED_PRESETS[4],
+            Action::SpeedQuadruple => self.state.speed = SPEED_PRESETS[5],
+            Action::ToggleFastForward => {
+                self.fast_forward = match self.fast_forward {
+                    Some(_) => None,
+                    None => Some(1.0),
+                };
+            }
+            Action::RemoveLast => self.state.world.remove_last(),
+            Action::ClearWorld => {
+                self.state.world.clear();
+                self.selected_object = None;
+                self.selection = Selection::default();
+            }
+            Action::DeleteEdge => {
+                if let Some((n, _)) = self.selected_edge() {
+                    self.state.world.remove_edge(n);
+                    self.selected_edge = None;
+                }
+            }
+            Action::ToggleOneWay => {
+                if let Some((n, _)) = self.selected_edge() {
+                    self.state.world.toggle_one_way(n);
+                }
+            }
+            Action::ToggleSticky => {
+                if let Some((n, _)) = self.selected_edge() {
+                    let world = &mut self.state.world;
+                    let sticky = world
+                        .edges_iter()
+                        .nth(n)
+                        .is_some_and(|e| e.stickiness() > 0.0);
+                    world.set_edge_stickiness(n, if sticky { 0.0 } else { Edge::STICKINESS });
+                }
+            }
+            Action::ToggleBreakable => {
+                if let Some((n, _)) = self.selected_edge() {
+                    let world = &mut self.state.world;
+                    let breakable = world
+                        .edges_iter()
+                        .nth(n)
+                        .is_some_and(|e| e.strength() > 0.0);
+                    world.set_edge_strength(n, if breakable { 0.0 } else { Edge::STRENGTH });
+                }
+            }
+            Action::ToggleHot => {
+                if let Some((n, _)) = self.selected_edge() {
+                    let world = &mut self.state.world;
+                    let hot = world
+                        .edges_iter()
+                        .nth(n)
+                        .is_some_and(|e| e.temperature() > 0.0);
+                    world.set_edge_temperature(n, if hot { 0.0 } else { Edge::HOT });
+                }
+            }
+            Action::ToggleSharpCorners => {
+                if let Some((n, _)) = self.selected_edge() {
+                    let world = &mut self.state.world;
+                    let corners = match world.edges_iter().nth(n).map(Edge::corners) {
+                        Some(Corners::Rounded) => Corners::Sharp,
+                        _ => Corners::Rounded,
+                    };
+                    world.set_chain_corners(n, corners);
+                }
+            }
+            Action::RecordKeyframe => {
+                let edges = self.selected_edges();
+                if edges.is_empty() {
+                    self.log.warn("no edge to record a keyframe of".into());
+                }
+                for &n in &edges {
+                    self.state.world.add_keyframe(n);
+                }
+            }
+            Action::ToggleLooping => {
+                for n in self.selected_edges() {
+                    self.state.world.toggle_looping(n);
+                }
+            }
+            Action::ClearKeyframes => {
+                for n in self.selected_edges() {
+                    self.state.world.clear_keyframes(n);
+                }
+            }
+            Action::DriveForward
+            | Action::DriveBackward
+            | Action::SpinClockwise
+            | Action::SpinCounterclockwise
+            | Action::SlowMotion => {}
+            Action::DeleteSelection => {
+                let (objects, edges) =
+                    std::mem::take(&mut self.selection).indices(&self.state.world);
+                self.state.world.remove_group(&objects, &edges);
+                self.selected_edge = None;
+                self.selected_object = None;
+                self.group_drag = None;
+            }
+            Action::SelectTool => self.set_tool(Tool::Select),
+            Action::SpawnRectTool => self.set_tool(Tool::SpawnRect),
+            Action::DrawEdgeTool => self.set_tool(Tool::DrawEdge),
+            Action::DrawRopeTool => self.set_tool(Tool::DrawRope),
+            Action::DeleteTool => self.set_tool(Tool::Delete),
+            Action::PinTool => self.set_tool(Tool::Pin),
+            Action::GrabTool => self.set_tool(Tool::Grab),
+            Action::SpawnRingTool => self.set_tool(Tool::SpawnShape(BodyShape::Ring)),
+            Action::SpawnStarTool => self.set_tool(Tool::SpawnShape(BodyShape::Star)),
+            Action::SpawnWheelTool => self.set_tool(Tool::SpawnShape(BodyShape::Wheel)),
+            Action::SandTool => self.set_tool(Tool::Sand),
+            Action::EmitterTool => self.set_tool(Tool::Emitter),
+            Action::DrainTool => self.set_tool(Tool::Drain),
+            Action::GravityZoneTool => self.set_tool(Tool::GravityZone),
+            Action::InflateTool => self.set_tool(Tool::Inflate),
+            Action::SpringGroupsTool => self.set_tool(Tool::SpringGroups),
+            Action::MoverTool => self.set_tool(Tool::Mover),
+            Action::RodTool => self.set_tool(Tool::Rod),
+            Action::MeasureTool => self.set_tool(Tool::Measure),
+        }
+
+        true
+    }
+
+    fn set_tool(&mut self, tool: Tool) {
+        self.tool = tool;
+        self.rect_start = None;
+        self.line_start = None;
+        self.rope_start = None;
+        self.shape_start = None;
+        self.pouring = false;
+        self.emitter_start = None;
+        self.drain_start = None;
+        self.zone_start = None;
+        self.track = None;
+        self.rod_start = None;
+        self.measure = None;
+        self.lasso = None;
+        self.moving_emitter = false;
+        self.selected_edge = None;
+        self.selection_start = None;
+        self.group_drag = None;
+        self.state.world.release();
+        // Springs are picked out of bodies standing still
+        if tool == Tool::SpringGroups {
+            self.state.simulate = false;
+        }
+    }
+
+    fn tool_down(&mut self, pos: Vec2) {
+        match self.tool {
+            Tool::Select if self.selection_contains(pos) => {
+                self.selected_edge = None;
+                self.group_drag = Some(if self.ctrl {
+                    GroupDrag::Copy(pos)
+                } else {
+                    GroupDrag::Move(pos)
+                });
+            }
+            Tool::Select => match self.selected_edge() {
+                Some((n, point @ EdgePoint::Body(_))) if self.ctrl => {
+                    let world = &mut self.state.world;
+                    let copy = world.duplicate_edge(n);
+                    self.selected_edge = copy
+                        .and_then(|copy| world.edge_id(copy))
+                        .map(|id| (id, point));
+                }
+                Some(_) => {}
+                None => {
+                    self.selected_emitter = self.state.world.emitter_at(pos);
+                    self.moving_emitter = self.selected_emitter.is_some();
+                    if !self.moving_emitter {
+                        let world = &self.state.world;
+                        self.selected_object =
+                            world.object_at(pos).and_then(|n| world.object_id(n));
+                        self.selection = Selection::default();
+                        if self.selected_object.is_none() {
+                            self.selection_start = Some(pos);
+                        }
+                    }
+                }
+            },
+            Tool::SpawnRect => self.rect_start = Some(pos),
+            Tool::DrawEdge => self.line_start = Some(self.edge_start(pos)),
+            Tool::DrawRope => self.rope_start = Some(pos),
+            Tool::SpawnShape(_) => self.shape_start = Some(pos),
+            Tool::Sand => self.pouring = true,
+            Tool::Emitter => self.emitter_start = Some(pos),
+            Tool::Drain => self.drain_start = Some(pos),
+            Tool::GravityZone => self.zone_start = Some(pos),
+            Tool::Mover => self.track = Some(vec![pos]),
+            // The first particle clicked starts a rod, the second one ends it
+            Tool::Rod => {
+                let world = &self.state.world;
+                match (self.rod_start(), world.particle_at(pos)) {
+                    (Some((a, start)), Some(b)) if a != b => {
+                        self.rod_start = None;
+                        self.edit(Edit::AddRod { a: start, b: pos });
+                    }
+                    (_, b) => self.rod_start = b,
+                }
+            }
+            Tool::SpringGroups => self.lasso = Some(vec![pos]),
+            // With Ctrl held along the edge clicked, else between two clicks
+            Tool::Measure => {
+                let world = &self.state.world;
+                let edge = world
+                    .edge_at(pos)
+                    .filter(|_| self.ctrl)
+                    .and_then(|n| world.edges_iter().nth(n));
+                self.measure = match (edge, self.measure) {
+                    (Some(edge), _) => Some((edge.get_start(), Some(edge.get_end()))),
+                    (None, Some((start, None))) => Some((start, Some(self.edge_start(pos)))),
+                    (None, _) => Some((self.edge_start(pos), None)),
+                };
+            }
+            Tool::Inflate => {
+                let world = &self.state.world;
+                self.selected_object = world.object_at(pos).and_then(|n| world.object_id(n));
+            }
+            Tool::Delete => {
+                // Emitters are held by index, the ones after it move down
+                if self.state.world.emitter_at(pos).is_some() {
+                    self.selected_emitter = None;
+                }
+                self.edit(Edit::Delete { pos });
+            }
+            Tool::Pin => self.edit(Edit::TogglePin { pos }),
+            Tool::Grab => {
+                if let Some(n) = self.state.world.particle_at(pos) {
+                    self.state.world.grab(n, pos);
+                }
+            }
+        }
+    }
+
+    /// Where tools act this frame, in the world: under the gamepad cursor while one is in
+    /// use, else under the mouse.
+    fn update_pointer(&mut self, mouse: MouseState) -> Vec2 {
+        let mouse_pos = Vec2::new(mouse.x() as Real, mouse.y() as Real);
+        let (w, h) = self.canvas.size();
+        let dt = 1.0 / self.target_fps as Real;
+
+        let screen = self.gamepad.as_mut().map_or(mouse_pos, |gamepad| {
+            gamepad.update_cursor(mouse_pos, Vec2::new(w as Real, h as Real), dt);
+            gamepad.cursor().unwrap_or(mouse_pos)
+        });
+        self.canvas.camera.to_world(screen)
+    }
+
+    fn pointer(&self) -> Vec2 {
+        let mouse = self.events.mouse_state();
+        let screen = self
+            .gamepad
+            .as_ref()
+            .and_then(Gamepad::cursor)
+            .unwrap_or_else(|| Vec2::new(mouse.x() as Real, mouse.y() as Real));
+        self.canvas.camera.to_world(screen)
+    }
+
+    fn handle_gamepad(&mut self, input: GamepadInput) -> bool {
+        match input {
+            GamepadInput::Action(action) => return self.handle_action(action),
+            GamepadInput::TriggerDown(trigger) => {
+                self.set_tool(trigger_tool(trigger));
+                self.tool_down(self.pointer());
+            }
+            GamepadInput::TriggerUp(trigger) => {
+                // Only release what this trigger started, not a tool picked since
+                if self.tool == trigger_tool(trigger) {
+                    self.tool_up(self.pointer());
+                }
+            }
+        }
+
+        true
+    }
+
+    fn track_mouse(&mut self, ticks: u32, pos: Vec2) {
+        self.mouse_track.push_back((ticks, pos));
+        while let Some(&(t, _)) = self.mouse_track.front() {
+            if ticks - t <= FLING_WINDOW {
+                break;
+            }
+            self.mouse_track.pop_front();
+        }
+    }
+
+    fn mouse_velocity(&self) -> Vec2 {
+        match (self.mouse_track.front(), self.mouse_track.back()) {
+            (Some(&(t0, p0)), Some(&(t1, p1))) if t1 > t0 => {
+                ((p1 - p0) * 1000.0 / (t1 - t0) as Real).clamp_len(0.0, MAX_FLING)
+            }
+            _ => Vec2::null(),
+        }
+    }
+
+    fn tool_up(&mut self, pos: Vec2) {
+        self.pouring = false;
+        self.moving_emitter = false;
+        let vel = self.mouse_velocity();
+        let (_, _, _, _, objects) = self.state.world.info();
+
+        if let Some(start) = self.rect_start.take() {
+            let (w, h, top_left) = rect_lattice(start, pos);
+
+            if self.shift {
+                // Filled in with what the drag would have spawned
+                self.rect_spec = Some(RectSpec {
+                    top_left,
+                    cols: w.max(2),
+                    rows: h.max(2),
+                    spacing: Particle::SPACING,
+                    stiffness: self.state.world.settings().stiffness,
+                });
+            } else {
+                self.edit(Edit::SpawnRect {
+                    w,
+                    h,
+                    x: top_left.x,
+                    y: top_left.y,
+                });
+            }
+        }
+
+        if let Some(start) = self.line_start.take() {
+            let end = self.edge_end(start, pos);
+            self.edit(Edit::AddEdge { start, end });
+        }
+
+        if let Some(start) = self.rope_start.take() {
+            self.edit(Edit::SpawnRope { start, end: pos });
+        }
+
+        if let Some(a) = self.drain_start.take() {
+            self.edit(Edit::AddDrain { a, b: pos });
+        }
+
+        if let Some(a) = self.zone_start.take() {
+            let gravity = self.zone_gravity.of(self.state.world.settings().gravity);
+            self.edit(Edit::AddGravityZone { a, b: pos, gravity });
+        }
+
+        if let Some(mut track) = self.track.take() {
+            track.push(pos);
+            let closed = track.len() > 2 && track[0].dist(pos) <= TRACK_CLOSE;
+            if closed {
+                track.pop();
+            }
+            self.edit(Edit::AddMover {
+                mover: Mover::new(track, closed, self.mover_speed, self.mover_shape),
+            });
+        }
+
+        if let Some(start) = self.emitter_start.take() {
+            self.edit(Edit::AddEmitter {
+                emitter: Emitter::new(start, (pos - start) * EMITTER_AIM),
+            });
+        }
+
+        if let (Some(center), Tool::SpawnShape(shape)) = (self.shape_start.take(), self.tool) {
+            let radius = center.dist(pos);
+            self.edit(Edit::SpawnShape {
+                shape,
+                center,
+                radius,
+            });
+        }
+
+        // Whatever was just spawned or let go of keeps the mouse's momentum
+        if self.state.world.info().4 > objects {
+            self.state.world.add_velocity_to_object(objects, vel);
+        }
+        if let Some(n) = self.state.world.dragged_object() {
+            self.state.world.add_velocity_to_object(n, vel);
+        }
+
+        if let Some(lasso) = self.lasso.take() {
+            let mut springs = self.state.world.springs_in(&lasso);
+            // Shift adds to what's picked already
+            if self.shift {
+                springs.extend(self.picked_springs());
+                springs.sort_unstable();
+                springs.dedup();
+            }
+            self.pick_springs(&springs);
+        }
+
+        if let Some(start) = self.selection_start.take() {
+            let (objects, edges) = self.state.world.group_in(start, pos);
+            self.selection = Selection::new(&self.state.world, &objects, &edges);
+        }
+
+        if let Some(GroupDrag::Copy(from)) = self.group_drag.take() {
+            if pos != from {
+                let world = &mut self.state.world;
+                let (objects, edges) = self.selection.indices(world);
+                let (objects, edges) = world.duplicate(&objects, &edges);
+                world.translate(&objects, &edges, pos - from);
+                self.selection = Selection::new(world, &objects, &edges);
+            }
+        }
+
+        self.state.world.release();
+    }
+
+    /// Makes `edit` to the world, or asks the host to when joined to one.
+    fn edit(&mut self, edit: Edit) {
+        let result = match &mut self.guest {
+            Some(guest) => guest.send(edit),
+            None => edit.apply(&mut self.state.world),
+        };
+        if let Err(msg) = result {
+            self.log.warn(msg);
+        }
+    }
+
+    fn selection_contains(&self, pos: Vec2) -> bool {
+        let world = &self.state.world;
+        world
+            .object_at(pos)
+            .and_then(|n| world.object_id(n))
+            .is_some_and(|id| self.selection.objects.contains(&id))
+            || world
+                .edge_at(pos)
+                .and_then(|n| world.edge_id(n))
+                .is_some_and(|id| self.selection.edges.contains(&id))
+    }
+
+    fn handle_lasso(&mut self, mouse_pos: Vec2) {
+        let Some(lasso) = &mut self.lasso else {
+            return;
+        };
+        if lasso
+            .last()
+            .is_none_or(|&last| last.dist(mouse_pos) >= LASSO_STEP)
+        {
+            lasso.push(mouse_pos);
+        }
+
+        self.canvas.set_color(self.theme.theme().selection);
+        for (&a, &b) in lasso.iter().zip(&lasso[1..]) {
+            self.canvas.aa_line(a, b);
+        }
+    }
+
+    fn handle_new_rod(&mut self, mouse_pos: Vec2) {
+        if let Some((_, start)) = self.rod_start() {
+            self.canvas
+                .set_color(self.theme().edge_end)
+                .aa_filled_circle(start, Particle::R)
+                .aa_line(start, mouse_pos);
+        }
+    }
+
+    fn handle_measure(&mut self, mouse_pos: Vec2) {
+        let Some((start, end)) = self.measure else {
+            return;
+        };
+        let end = end.unwrap_or_else(|| self.edge_start(mouse_pos));
+        self.canvas
+            .set_color(self.theme().highlight)
+            .aa_line(start, end)
+            .aa_filled_circle(start, Particle::R * 0.3)
+            .aa_filled_circle(end, Particle::R * 0.3);
+
+        let diff = end - start;
+        let lines = [
+            format!("distance: {:.1}", diff.len()),
+            format!("spacings: {:.2}", diff.len() / Particle::SPACING),
+            format!("angle: {:.1}°", -diff.to_angle().to_degrees()),
+            format!("dx: {:.1}, dy: {:.1}", diff.x, diff.y),
+        ];
+        self.draw_tooltip(self.canvas.camera.to_screen(mouse_pos), &lines);
+    }
+
+    fn handle_new_track(&mut self, mouse_pos: Vec2) {
+        let Some(track) = &mut self.track else {
+            return;
+        };
+        if track
+            .last()
+            .is_none_or(|&last| last.dist(mouse_pos) >= TRACK_STEP)
+        {
+            track.push(mouse_pos);
+        }
+
+        let theme = self.theme.theme();
+        self.canvas
+            .set_color(theme.edge_end)
+            .polyline(track.iter().copied().chain([mouse_pos]));
+        // Where it starts out, lit up once letting go would close the track
+        let start = track[0];
+        let closing = track.len() > 2 && start.dist(mouse_pos) <= TRACK_CLOSE;
+        self.canvas
+            .set_color(if closing { theme.highlight } else { theme.edge });
+        match self.mover_shape {
+            MoverShape::Disc(radius) => {
+                self.canvas.aa_filled_circle(start, radius);
+            }
+            MoverShape::Bar(half) => {
+                self.canvas
+                    .thick_line(start - half, start + half, 2.0 * Edge::R);
+            }
+        }
+    }
+
+    fn handle_selection(&mut self, mouse_pos: Vec2) {
+        if let Some(start) = self.selection_start {
+            self.canvas
+                .set_color(self.theme().selection)
+                .rectangle(start, mouse_pos);
+        }
+
+        let (objects, edges) = self.selection.indices(&self.state.world);
+        match self.group_drag {
+            Some(GroupDrag::Move(last)) => {
+                self.state
+                    .world
+                    .translate(&objects, &edges, mouse_pos - last);
+                self.group_drag = Some(GroupDrag::Move(mouse_pos));
+            }
+            Some(GroupDrag::Copy(from)) => self.state.world.draw_group(
+                &objects,
+                &edges,
+                mouse_pos - from,
+                self.theme(),
+                &mut self.canvas,
+            ),
+            None => {}
+        }
+    }
+
+    fn handle_events(&mut self) -> bool {
+        span!("events");
+        // One at a time rather than collected up front, a frame's events don't allocate
+        while let Some(event) = self.events.poll_event() {
+            if !self.handle_event(event) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns false if the app should quit.
+    fn handle_event(&mut self, event: Event) -> bool {
+        if self.gui.handle_event(&event) {
+            return true;
+        }
+
+        if let Some(gamepad) = &mut self.gamepad {
+            match gamepad.handle_event(&event) {
+                Ok(Some(input)) => return self.handle_gamepad(input),
+                Ok(None) => {}
+                Err(err) => self.log.warn(format!("could not open controller: {err}")),
+            }
+        }
+
+        match event {
+            Event::Quit { .. } => {
+                return false;
+            }
+            Event::Window {
+                win_event: WindowEvent::Resized(w, h),
+                ..
+            } => {
+                self.resize(w as u32, h as u32);
+            }
+            Event::KeyDown {
+                keycode: Some(keycode),
+                keymod,
+                ..
+            } => {
+                if let Some(action) = self.keybindings.action(keycode, keymod) {
+                    return self.handle_action(action);
+                }
+            }
+            Event::MouseMotion {
+                mousestate,
+                xrel,
+                yrel,
+                ..
+            } => {
+                if let Some(gamepad) = &mut self.gamepad {
+                    gamepad.hide_cursor();
+                }
+                if mousestate.middle() {
+                    self.canvas
+                        .camera
+                        .pan(Vec2::new(xrel as Real, yrel as Real));
+                }
+            }
+            Event::MouseWheel { y, .. } => {
+                if let Some(n) = self
+                    .selected_object()
+                    .filter(|_| self.tool == Tool::Inflate)
+                {
+                    self.state.world.scale_rest_lengths(n, INFLATE_STEP.powi(y));
+                } else if let Some((n, _)) = self.selected_edge() {
+                    let world = &mut self.state.world;
+                    let radius = world.edges_iter().nth(n).map_or(Edge::R, Edge::radius);
+                    world.set_edge_radius(n, radius * EDGE_RADIUS_STEP.powi(y));
+                } else {
+                    let mouse = self.events.mouse_state();
+                    let mouse = Vec2::new(mouse.x() as Real, mouse.y() as Real);
+                    self.canvas.camera.zoom_at(mouse, ZOOM_STEP.powi(y));
+                }
+            }
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                let pos = self.canvas.camera.to_world(Vec2::new(x as Real, y as Real));
+                self.tool_down(pos);
+            }
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => {
+                let pos = self.canvas.camera.to_world(Vec2::new(x as Real, y as Real));
+                self.tool_up(pos);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    pub fn run(mut self) {
+        'running: loop {
+            #[cfg(feature = "profile")]
+            if let Some(profiler) = &self.profiler {
+                self.profile = profiler.next_frame();
+            }
+            span!("frame");
+
+            let (begin, mouse, keyboard) = self.begin_frame();
+            let shift = keyboard.is_scancode_pressed(Scancode::LShift)
+                || keyboard.is_scancode_pressed(Scancode::RShift);
+            let ctrl = keyboard.is_scancode_pressed(Scancode::LCtrl)
+                || keyboard.is_scancode_pressed(Scancode::RCtrl);
+            (self.shift, self.ctrl) = (shift, ctrl);
+            self.handle_held_actions();
+            let mouse_pos = self.update_pointer(mouse);
+            self.track_mouse(begin, mouse_pos);
+
+            if !self.handle_events() {
+                break 'running;
+            }
+            #[cfg(feature = "remote")]
+            self.handle_remote();
+
+            self.handle_guests();
+            if self.guest.is_some() {
+                self.sync_with_host();
+            } else if self.state.simulate {
+                self.update_physics();
+            }
+            if let Some(host) = &mut self.host {
+                host.share(&self.state.world);
+            }
+
+            if self.config.draw_grid {
+                self.draw_background_grid();
+            } else if self.tool == Tool::DrawEdge && self.shift && self.config.draw_snap_grid {
+                self.draw_grid_lines(self.config.snap_grid, self.theme().preview);
+            }
+            self.draw_world();
+
+            self.draw_ui(mouse_pos);
+
+            self.handle_new_rect(mouse_pos);
+            self.handle_new_line(mouse_pos);
+            self.handle_new_rope(mouse_pos);
+            self.handle_new_shape(mouse_pos);
+            self.handle_new_emitter(mouse_pos);
+            self.handle_new_drain(mouse_pos);
+            self.handle_new_gravity_zone(mouse_pos);
+
+            match self.tool {
+                Tool::Select => {
+                    self.handle_line_manip(mouse, mouse_pos);
+                    self.handle_selection(mouse_pos);
+                    self.handle_emitter_drag(mouse_pos);
+                }
+                Tool::Grab => self.controls.drag = Some(mouse_pos),
+                Tool::SpringGroups => self.handle_lasso(mouse_pos),
+                Tool::Mover => self.handle_new_track(mouse_pos),
+                Tool::Rod => self.handle_new_rod(mouse_pos),
+                Tool::Measure => self.handle_measure(mouse_pos),
+                // Only counts as an edit when grains fit, so the ones poured keep falling
+                Tool::Sand if self.pouring => self.edit(Edit::SpawnSand {
+                    center: mouse_pos,
+                    radius: SAND_BRUSH,
+                }),
+                _ => {}
+            }
+
+            self.draw_gui();
+
+            self.end_frame(begin);
+        }
+
+        if self.recorder.is_some() {
+            self.toggle_recording();
+        }
+        self.save_trajectory();
+    }
+
+    fn handle_line_manip(&mut self, mouse: MouseState, mouse_pos: Vec2) {
+        let pressed = mouse.is_mouse_button_pressed(MouseButton::Left);
+
+        if let Some((n, point)) = self.selected_edge() {
+            let e = self
+                .state
+                .world
+                .edges_iter()
+                .nth(n)
+                .expect("Index of edge should always be valid");
+            let (start, end, radius) = (e.get_start(), e.get_end(), e.radius());
+
+            // Where it's animated to go
+            self.canvas.set_color(self.theme.theme().selection);
+            for k in e.keyframes() {
+                self.canvas.line(k.start, k.end);
+            }
+
+            self.canvas.set_color(self.theme.theme().highlight);
+            match point {
+                EdgePoint::Start | EdgePoint::End => {
+                    let vertex = if matches!(point, EdgePoint::Start) {
+                        start
+                    } else {
+                        end
+                    };
+                    self.canvas.filled_circle(vertex, radius);
+
+                    // Edges sharing the vertex follow along
+                    if pressed {
+                        self.state.world.move_edge_vertex(vertex, mouse_pos);
+                    }
+                }
+                EdgePoint::Body(last) => {
+                    self.canvas.thick_line(start, end, radius * 2.0);
+
+                    if pressed {
+                        self.state.world.move_edge(n, mouse_pos - last);
+                        self.selected_edge = self
+                            .state
+                            .world
+                            .edge_id(n)
+                            .map(|id| (id, EdgePoint::Body(mouse_pos)));
+                    }
+                }
+            }
+
+            if !pressed {
+                self.selected_edge = None;
+            }
+        }
+        //FIXME: This snippet must go after the previous. fix this.
+        // Only hovering picks an edge up, not dragging across one
+        // Or when the one held broke
+        if self.selected_edge().is_none() && !pressed {
+            let world = &self.state.world;
+            self.selected_edge = world
+                .edges_iter()
+                .enumerate()
+                .find_map(|(i, e)| {
+                    // Thin edges still have ends big enough to grab
+                    let reach = e.radius().max(Edge::R);
+                    if Vec2::dist_sqr(e.get_start(), mouse_pos) < reach * reach {
+                        Some((i, EdgePoint::Start))
+                    } else if Vec2::dist_sqr(e.get_end(), mouse_pos) < reach * reach {
+                        Some((i, EdgePoint::End))
+                    } else {
+                        None
+                    }
+                })
+                .or_else(|| {
+                    let n = world.edge_at(mouse_pos)?;
+                    Some((n, EdgePoint::Body(mouse_pos)))
+                })
+                .and_then(|(n, point)| Some((world.edge_id(n)?, point)));
+        }
+    }
+
+    /// Where an edge pressed down at `pos` starts. Joining an existing edge's end nearby
+    /// takes precedence over the grid.
+    fn edge_start(&self, pos: Vec2) -> Vec2 {
+        if let Some(vertex) = self.state.world.edge_vertex_near(pos) {
+            vertex
+        } else if self.shift {
+            snap_to_grid(pos, self.config.snap_grid)
+        } else {
+            pos
+        }
+    }
+
+    /// Where an edge started at `start` ends when let go of at `pos`.
+    fn edge_end(&self, start: Vec2, pos: Vec2) -> Vec2 {
+        if let Some(vertex) = self
+            .state
+            .world
+            .edge_vertex_near(pos)
+            // Not back onto the vertex it started from
+            .filter(|v| v.dist_sqr(start) > Edge::R * Edge::R)
+        {
+            vertex
+        } else if self.shift {
+            snap_edge_end(start, pos, self.config.snap_grid)
+        } else {
+            pos
+        }
+    }
+
+    /// Lines `spacing` apart over the part of the world on screen. They fade out as zooming
+    /// out crowds them closer than [`GRID_FADE`] pixels, and are left out below
+    /// [`GRID_MIN`].
+    fn draw_grid_lines(&mut self, spacing: Real, color: Color) {
+        let apart = spacing * self.canvas.camera.zoom;
+        if apart < GRID_MIN {
+            return;
+        }
+        let alpha = Real::from(color.a) * (apart / GRID_FADE).min(1.0);
+        let (min, max) = self.canvas.visible();
+        let first = (min / spacing).floor() * spacing;
+
+        self.canvas
+            .set_color(Color::RGBA(color.r, color.g, color.b, alpha as u8));
+        for i in 0..=((max.x - first.x) / spacing) as usize {
+            let x = first.x + i as Real * spacing;
+            self.canvas.line(Vec2::new(x, min.y), Vec2::new(x, max.y));
+        }
+        for i in 0..=((max.y - first.y) / spacing) as usize {
+            let y = first.y + i as Real * spacing;
+            self.canvas.line(Vec2::new(min.x, y), Vec2::new(max.x, y));
+        }
+    }
+
+    /// The snap grid, every tenth line bolder so it still shows zoomed far out.
+    fn draw_background_grid(&mut self) {
+        let (spacing, color) = (self.config.snap_grid, self.theme().preview);
+        self.draw_grid_lines(spacing * GRID_MAJOR, color);
+        self.draw_grid_lines(spacing, Color::RGBA(color.r, color.g, color.b, color.a / 2));
+    }
+
+    /// Length and angle of the line from `a` to `b` next to `b`, upwards being positive.
+    fn draw_ruler(&mut self, a: Vec2, b: Vec2) {
+        let diff = b - a;
+        let label = format!("{:.1} at {:.1}°", diff.len(), -diff.to_angle().to_degrees());
+        let pos = self.canvas.camera.to_screen(b) + Vec2::new(12.0, 12.0);
+        self.canvas
+            .screen
+            .set_color(self.theme().text)
+            .text_sized(pos, &label, TEXT_SIZE);
+    }
+
+    fn handle_new_line(&mut self, mouse_pos: Vec2) {
+        if let Some(start_pos) = self.line_start {
+            let mouse_pos = self.edge_end(start_pos, mouse_pos);
+            let theme = self.theme();
+            if self.state.world.can_add_edge(start_pos, mouse_pos) {
+                self.canvas.set_color(theme.edge);
+            } else {
+                self.canvas.set_color(theme.alert);
+            };
+            self.canvas
+                .thick_line(start_pos, mouse_pos, Edge::R * 2.0)
+                .set_color(theme.edge_end)
+                .filled_circle(start_pos, Edge::R)
+                .filled_circle(mouse_pos, Edge::R);
+            self.draw_ruler(start_pos, mouse_pos);
+        }
+    }
+
+    fn handle_new_rope(&mut self, mouse_pos: Vec2) {
+        if let Some(start_pos) = self.rope_start {
+            let theme = self.theme();
+            if self.state.world.can_spawn_rope(start_pos, mouse_pos) {
+                self.canvas.set_color(theme.edge_end);
+            } else {
+                self.canvas.set_color(theme.alert);
+            };
+
+            let n = self.state.world.rope_len(start_pos, mouse_pos);
+            let step = (mouse_pos - start_pos).normalize() * Particle::SPACING;
+
+            self.canvas.line(start_pos, mouse_pos);
+            for i in 0..n {
+                self.canvas
+                    .filled_circle(start_pos + step * i as Real, Particle::R * 0.5);
+            }
+            self.draw_ruler(start_pos, mouse_pos);
+        }
+    }
+
+    fn handle_new_shape(&mut self, mouse_pos: Vec2) {
+        if let (Some(center), Tool::SpawnShape(shape)) = (self.shape_start, self.tool) {
+            let theme = self.theme();
+            let radius = center.dist(mouse_pos);
+            if self.state.world.can_spawn_shape(shape, radius) {
+                self.canvas.set_color(theme.edge_end);
+            } else {
+                self.canvas.set_color(theme.alert);
+            };
+
+            self.canvas
+                .line(center, mouse_pos)
+                .polygon(shape.outline(center, radius).into_iter())
+                .filled_circle(center, Particle::R * 0.5);
+        }
+    }
+
+    fn handle_new_emitter(&mut self, mouse_pos: Vec2) {
+        if let Some(start) = self.emitter_start {
+            self.canvas
+                .set_color(self.theme().edge_end)
+                .line(start, mouse_pos)
+                .filled_circle(start, Emitter::R);
+        }
+    }
+
+    fn handle_new_drain(&mut self, mouse_pos: Vec2) {
+        if let Some(start) = self.drain_start {
+            let theme = self.theme();
+            if self.state.world.can_add_drain(start, mouse_pos) {
+                self.canvas.set_color(theme.edge_end);
+            } else {
+                self.canvas.set_color(theme.alert);
             };
+            self.canvas.rectangle(start, mouse_pos);
         }
-        //FIXME: This snippet must go after the previous. fix this.
-        let mut itr = self.state.world.edges_iter().enumerate();
-        while self.selected_edge.is_none() && let Some((i,e)) = itr.next() {
+    }
+
+    fn handle_new_gravity_zone(&mut self, mouse_pos: Vec2) {
+        if let Some(start) = self.zone_start {
+            let theme = self.theme();
+            if self.state.world.can_add_gravity_zone(start, mouse_pos) {
+                self.canvas.set_color(theme.edge_end);
+            } else {
+                self.canvas.set_color(theme.alert);
+            };
+            self.canvas.rectangle(start, mouse_pos);
+        }
+    }
 
-            if Vec2::dist_sqr(e.get_start(), mouse_pos) < Edge::R * Edge::R {
-                self.selected_edge = Some((i, EdgePoint::Start));
-            } else if Vec2::dist_sqr(e.get_end(), mouse_pos) < Edge::R * Edge::R {
-                self.selected_edge = Some((i, EdgePoint::End));
+    fn handle_emitter_drag(&mut self, mouse_pos: Vec2) {
+        let world = &mut self.state.world;
+        if let (true, Some(n)) = (self.moving_emitter, self.selected_emitter) {
+            if let Some(&(mut emitter)) = world.emitter(n).filter(|e| e.pos != mouse_pos) {
+                emitter.pos = mouse_pos;
+                world.set_emitter(n, emitter);
             }
         }
     }
 
-    fn handle_new_line(&mut self, mouse_pos: Vec2) {
-        if let Some(start_pos) = self.line_start {
-            if self.state.world.can_add_edge(start_pos, mouse_pos) {
-                self.canvas.set_color(Color::RGB(44, 56, 80));
-            } else {
-                self.canvas.set_color(Color::RED);
-            };
-            self.canvas
-                .thick_line(start_pos, mouse_pos, Edge::R * 2.0)
-                .set_color(Color::RGB(88, 112, 161))
-                .filled_circle(start_pos, Edge::R)
-                .filled_circle(mouse_pos, Edge::R);
+    fn spawn_rect_spec(&mut self, spec: RectSpec) {
+        let world = &mut self.state.world;
+        let pos = spec.top_left;
+        if let Err((w, h)) =
+            world.spawn_rect_spaced(spec.cols, spec.rows, pos.x, pos.y, spec.spacing)
+        {
+            self.log.warn(format!(
+                "error while spawning new rect: Rect is too small: ({w}, {h}) < (2, 2)"
+            ));
+            return;
+        }
+
+        // Only objects that differ from the solver get a material of their own
+        if spec.stiffness != world.settings().stiffness {
+            let n = world.info().4 - 1;
+            world.set_material(
+                n,
+                Some(Material {
+                    stiffness: spec.stiffness,
+                    ..world.settings().material()
+                }),
+            );
+        }
+        self.push_out_last();
+    }
+
+    /// Moves the object just spawned out of whatever it landed on.
+    fn push_out_last(&mut self) {
+        let world = &mut self.state.world;
+        let (.., objects) = world.info();
+        if !world.push_out(objects - 1) {
+            self.log
+                .warn("new object is stuck overlapping others".into());
         }
     }
 
     fn handle_new_rect(&mut self, mouse_pos: Vec2) {
-        if let Some(start_pos) = self.rect_start {
-            let size = (Vec2::abs_diff(start_pos, mouse_pos) / Particle::SPACING).ceil();
+        if let Some(spec) = self.rect_spec {
+            let size = Vec2::new((spec.cols - 1) as Real, (spec.rows - 1) as Real) * spec.spacing;
+            self.canvas
+                .set_color(self.theme().preview)
+                .rectangle(spec.top_left, spec.top_left + size);
+        }
 
-            if self
+        if let Some(start_pos) = self.rect_start {
+            let (w, h, top_left) = rect_lattice(start_pos, mouse_pos);
+            let text = match self
                 .state
                 .world
-                .can_spawn_rect(size.x as usize, size.y as usize)
+                .check_spawn_rect(w, h, top_left.x, top_left.y)
             {
-                self.canvas.set_color(Color::RGB(44, 56, 80));
-            } else {
-                self.canvas.set_color(Color::RED);
+                Ok(()) => {
+                    self.canvas.set_color(self.theme().preview);
+                    format!("{w} x {h}")
+                }
+                Err(reason) => {
+                    self.canvas.set_color(self.theme().alert);
+                    format!("{w} x {h}, {reason}")
+                }
             };
 
             self.canvas.rectangle(start_pos, mouse_pos);
 
-            self.canvas.text(
-                start_pos + Vec2::new(10.0, -10.0),
-                format!("{:.0} x {:.0}", size.x, size.y).as_str(),
+            self.canvas.text_sized(
+                start_pos + Vec2::new(10.0, -LINE_HEIGHT),
+                text.as_str(),
+                TEXT_SIZE,
             );
         }
     }
 
+    /// Actions that last for as long as their keys are down.
+    fn handle_held_actions(&mut self) {
+        let keyboard = self.events.keyboard_state();
+        let held = |action| Real::from(i8::from(self.keybindings.held(action, &keyboard)));
+        let throttle = held(Action::DriveForward) - held(Action::DriveBackward);
+        let spin = held(Action::SpinClockwise) - held(Action::SpinCounterclockwise);
+        self.slow_motion = held(Action::SlowMotion) > 0.0;
+
+        self.controls.throttle = throttle;
+        self.controls.torque = None;
+
+        let Some(n) = self.selected_object().filter(|_| spin != 0.0) else {
+            return;
+        };
+        if let Some((_, inertia, current)) = self.state.world.rotation(n) {
+            // Scaled by inertia so big and small objects pick up speed alike, up to what
+            // motors go at
+            let target = spin * World::MOTOR_SPIN;
+            let torque = inertia
+                * SPIN_ACCELERATION
+                * ((target - current) / World::MOTOR_SPIN).clamp(-1.0, 1.0);
+            self.controls.torque = Some((n, torque));
+        }
+    }
+
+    /// Deals with a spring that blew up the way the config says to.
+    fn handle_instability(&mut self, instability: Instability) {
+        let message = format!(
+            "suspiciously large spring stretch detected. diff_len={}.",
+            instability.diff_len
+        );
+        let object = self
+            .state
+            .world
+            .particle_info(instability.a)
+            .and_then(|info| info.0);
+        match (self.config.on_instability, object) {
+            (InstabilityPolicy::Pause, _) => {
+                self.log
+                    .error(format!("{message} Paused, the spring is highlighted."));
+                self.state.simulate = false;
+                self.instability = Some((self.state.world.revision(), instability));
+            }
+            (InstabilityPolicy::RemoveObject, Some(n)) => {
+                self.log.error(format!("{message} Object {n} removed."));
+                self.state.world.remove_object(n);
+            }
+            (InstabilityPolicy::RemoveObject, None) | (InstabilityPolicy::Reset, _) => {
+                self.log.error(format!("{message} World reset."));
+                self.state.world.clear();
+            }
+        }
+    }
+
+    /// Trades the world with the physics thread: edits go over, finished updates come back.
     fn update_physics(&mut self) {
-        if let Err(diff_len) = self.state.world.update() {
-            self.log.log(format!(
-                "suspiciously large spring strech detected. diff_len={diff_len}. World reset."
-            ));
-            self.state.world.clear();
+        span!("physics");
+        #[cfg(feature = "wgpu")]
+        self.state
+            .world
+            .set_gpu_collisions(self.gpu_collisions.clone());
+
+        let Some(result) = self.physics.sync(&mut self.state.world) else {
+            return;
+        };
+
+        // Before an instability gets the world paused or reset, a quarantine earlier in the
+        // update is still news
+        for event in self.state.world.events() {
+            match event {
+                WorldEvent::EdgeBroken { pos } => {
+                    self.log
+                        .info(format!("an edge broke at ({:.0}, {:.0})", pos.x, pos.y));
+                }
+                WorldEvent::Quarantined { object } => self.log.warn(format!(
+                    "object {object} went NaN or infinite, it was pinned where it was"
+                )),
+                _ => {}
+            }
+        }
+
+        if let Err(instability) = result {
+            self.handle_instability(instability);
+            return;
+        }
+        self.instability = None;
+
+        if let Some(trajectory) = &mut self.trajectory {
+            trajectory.record(&self.state.world);
+        }
+        #[cfg(feature = "remote")]
+        if let Some(remote) = &mut self.remote {
+            remote.stream(|| Snapshot::of(&self.state.world, false));
+        }
+
+        self.pace += (self.state.world.pace() - self.pace) * PACE_SMOOTHING;
+        let load = self.physics.load();
+        if let Some(boost) = &mut self.fast_forward {
+            // Takes up whatever the steps of a frame leave of its budget
+            *boost = if self.pace < BEHIND || load > FAST_FORWARD_LOAD {
+                (*boost * FAST_FORWARD_BACKOFF).max(1.0)
+            } else {
+                (*boost * FAST_FORWARD_GROWTH).min(MAX_FAST_FORWARD)
+            };
+        }
+        let boosted = self.fast_forward.is_some_and(|boost| boost > 1.0);
+        if self.state.auto_slow_down && self.pace < BEHIND && !boosted {
+            // Ease down until the steps of a frame fit in its budget again
+            self.state.speed = (self.state.speed * 0.98).max(0.05);
+        }
+
+        #[cfg(feature = "audio")]
+        if let Some(audio) = self.audio.as_ref().filter(|_| !self.config.muted) {
+            audio.play(self.state.world.events(), self.state.world.size().x);
+        }
+    }
+
+    /// Carries out the commands remote clients sent since the last frame.
+    #[cfg(feature = "remote")]
+    fn handle_remote(&mut self) {
+        let Some(remote) = &mut self.remote else {
+            return;
+        };
+
+        while let Some(request) = remote.poll() {
+            let world = &mut self.state.world;
+            let point = |[x, y]: [Real; 2]| Vec2::new(x, y);
+
+            let result = match request.command {
+                Command::SpawnRect { w, h, x, y } => world
+                    .check_spawn_rect(w, h, x, y)
+                    .and_then(|()| {
+                        world
+                            .spawn_rect(w, h, x, y)
+                            .map_err(|_| "too small, it needs to be at least 2 x 2".into())
+                    })
+                    .map_err(|reason| format!("cant spawn rect, {reason}")),
+                Command::SpawnRope { start, end } => world
+                    .spawn_rope(point(start), point(end))
+                    .map_err(String::from),
+                Command::AddEdge { start, end } => world
+                    .add_edge(point(start), point(end))
+                    .map_err(String::from),
+                Command::SetGravity { x, y } => {
+                    world.settings_mut().gravity = Vec2::new(x, y);
+                    Ok(())
+                }
+                Command::Pause | Command::Resume => {
+                    self.state.simulate = matches!(request.command, Command::Resume);
+                    Ok(())
+                }
+                Command::Query => {
+                    let snapshot = Snapshot::of(world, !self.state.simulate);
+                    remote.reply(request.client, &Reply::Snapshot(&snapshot));
+                    continue;
+                }
+                Command::Subscribe { .. } | Command::Unsubscribe => Ok(()),
+            };
+
+            match result {
+                Ok(()) => remote.reply(request.client, &Reply::Ok),
+                Err(err) => remote.reply(request.client, &Reply::Error(err)),
+            }
+        }
+    }
+
+    /// Makes the edits guests asked for since the last frame.
+    fn handle_guests(&mut self) {
+        let Some(host) = &self.host else {
+            return;
+        };
+        while let Some(edit) = host.poll() {
+            // Guests see the same previews, they know when an edit won't fit
+            let _ = edit.apply(&mut self.state.world);
+        }
+    }
+
+    /// Shows the host's world in place of simulating this one.
+    fn sync_with_host(&mut self) {
+        let Some(guest) = &mut self.guest else {
+            return;
+        };
+        match guest.sync(&mut self.state.world) {
+            Ok(false) => {}
+            Ok(true) => {
+                self.selected_edge = None;
+                self.selected_object = None;
+                self.selection = Selection::default();
+                self.picked_springs.clear();
+                self.load_textures();
+            }
+            Err(err) => {
+                self.log.error(format!("{err}, simulating on without it"));
+                self.guest = None;
+            }
         }
     }
 
-    fn begin_frame(&mut self) -> (u32, MouseState, KeyboardState) {
-        self.canvas.set_color(Color::RGB(11, 14, 20));
+    fn begin_frame(&mut self) -> (u32, MouseState, KeyboardState<'_>) {
+        self.canvas.set_color(self.theme().background);
         self.canvas.clear();
 
         let begin = self.timer.ticks();
@@ -510,70 +2885,790 @@ impl App {
     }
 
     fn draw_world(&mut self) {
-        if self.state.draw_springs {
-            self.state.world.draw_springs(&mut self.canvas);
+        span!("draw world");
+        self.state
+            .draw(self.selected_object(), self.theme(), &mut self.canvas);
+        if let Some(emitter) = self
+            .selected_emitter
+            .and_then(|n| self.state.world.emitter(n))
+        {
+            self.canvas
+                .set_color(self.theme().highlight)
+                .aa_filled_circle(emitter.pos, Emitter::R * 0.5);
+        }
+        let springs = self.picked_springs();
+        if !springs.is_empty() {
+            self.state
+                .world
+                .draw_spring_selection(&springs, self.theme.theme(), &mut self.canvas);
+        }
+        if let Some(instability) = self.instability() {
+            self.state
+                .world
+                .draw_instability(&instability, self.theme(), &mut self.canvas);
+        }
+        let (objects, edges) = self.selection.indices(&self.state.world);
+        self.state.world.draw_group(
+            &objects,
+            &edges,
+            Vec2::null(),
+            self.theme(),
+            &mut self.canvas,
+        );
+    }
+
+    fn export_svg(&mut self) {
+        let (w, h) = self.canvas.size();
+        let mut svg = View {
+            camera: self.canvas.camera,
+            screen: SvgRenderer::new(w, h),
+        };
+
+        svg.set_color(self.theme().background).clear();
+        self.state
+            .draw(self.selected_object(), self.theme(), &mut svg);
+
+        match capture::timestamped_path(SCREENSHOTS, "svg").and_then(|path| {
+            std::fs::write(&path, svg.screen.into_svg())
+                .map(|()| path)
+                .map_err(|err| err.to_string())
+        }) {
+            Ok(path) => self.log.info(format!("svg saved to {}", path.display())),
+            Err(err) => self.log.error(format!("could not save svg: {err}")),
+        }
+    }
+
+    fn take_screenshot(&mut self) {
+        let Some((w, h, pixels)) = self.canvas.read_pixels() else {
+            self.log
+                .error("could not save screenshot: renderer has no pixels".into());
+            return;
+        };
+
+        match capture::timestamped_path(SCREENSHOTS, "png")
+            .and_then(|path| capture::write_png(&path, w, h, &pixels).map(|()| path))
+        {
+            Ok(path) => self
+                .log
+                .info(format!("screenshot saved to {}", path.display())),
+            Err(err) => self.log.error(format!("could not save screenshot: {err}")),
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            match recorder.finish() {
+                Ok(path) => self
+                    .log
+                    .info(format!("recording saved to {}", path.display())),
+                Err(err) => self.log.error(format!("could not save recording: {err}")),
+            }
+            return;
+        }
+
+        match capture::timestamped_path(RECORDINGS, self.record_format.extension()) {
+            Ok(path) => {
+                self.recorder = Some(Recorder::start(self.record_format, path, self.target_fps));
+                self.log.info(String::from("recording started"));
+            }
+            Err(err) => self.log.error(format!("could not start recording: {err}")),
         }
-        if self.state.draw_particles {
-            self.state.world.draw_particles(&mut self.canvas);
+    }
+
+    fn save_trajectory(&mut self) {
+        let Some(trajectory) = self.trajectory.take() else {
+            return;
+        };
+
+        match trajectory.finish() {
+            Ok((path, 0)) => self
+                .log
+                .info(format!("trajectory saved to {}", path.display())),
+            Ok((path, dropped)) => self.log.warn(format!(
+                "trajectory saved to {}, without its first {dropped} samples",
+                path.display()
+            )),
+            Err(err) => self.log.error(format!("could not save trajectory: {err}")),
         }
-        if !(self.state.draw_particles || self.state.draw_springs) {
-            self.state.world.draw_polys(&mut self.canvas);
+    }
+
+    fn record_frame(&mut self) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+
+        // The encoder hung up early, finishing surfaces its error
+        let pushed = self
+            .canvas
+            .read_pixels()
+            .is_some_and(|(w, h, pixels)| recorder.push(w, h, pixels));
+        if !pushed {
+            self.toggle_recording();
         }
-        self.state.world.draw_edges(&mut self.canvas);
     }
 
     fn end_frame(&mut self, begin: u32) {
-        self.canvas.finish();
+        {
+            span!("capture");
+            if std::mem::take(&mut self.screenshot) {
+                self.take_screenshot();
+            }
+            self.record_frame();
+        }
+        {
+            span!("present");
+            self.canvas.finish();
+        }
         self.fps_manager.delay();
 
-        let frame_time = f64::from(self.timer.ticks() - begin);
+        let frame_time = (self.timer.ticks() - begin) as Real;
         self.fps = (1000.0 / frame_time) as u8;
 
+        let dt = if self.recorder.is_some() && self.record_fixed_step {
+            1.0 / self.target_fps as Real
+        } else {
+            frame_time / 1000.0
+        };
+
+        let controls = std::mem::take(&mut self.controls);
         if self.state.simulate {
-            self.state
-                .world
-                .end_frame(self.state.speed * (frame_time / 1000.0));
+            let controls = Controls {
+                record_trails: self.state.draw_trails,
+                track_metrics: self.state.draw_metrics,
+                speed: self.speed(),
+                ..controls
+            };
+            self.physics.advance(dt, controls);
         }
     }
 
-    fn draw_ui(&mut self) {
-        let (p_len, s_len, b_len, e_len, o_len) = self.state.world.info();
+    /// Speed the simulation runs at right now, with slow motion and fast-forward.
+    fn speed(&self) -> Real {
+        let slow_motion = if self.slow_motion { SLOW_MOTION } else { 1.0 };
+        self.state.speed * slow_motion * self.fast_forward.unwrap_or(1.0)
+    }
+
+    fn draw_gui(&mut self) {
+        span!("gui");
+        let selected_object = self.selected_object();
+        let selected_emitter = self.selected_emitter;
+        let spring_groups = self.tool == Tool::SpringGroups;
+        let picked_springs = self.picked_springs();
+        let mut pick = None;
+        let outline = self.draw_outline;
+        let outlined: Vec<Outlined> = {
+            let (objects, edges) = self.selection.indices(&self.state.world);
+            selected_object
+                .into_iter()
+                .chain(objects)
+                .map(Outlined::Object)
+                .chain(edges.into_iter().map(Outlined::Edge))
+                .collect()
+        };
+        let mut clicked = None;
+        let mut unpicked = false;
+        let state = &mut self.state;
+        let draw_log = &mut self.draw_log;
+        let texture_path = &mut self.texture_path;
+        let mut theme = self.theme;
+        let mut muted = self.config.muted;
+        let mut snap_grid = self.config.snap_grid;
+        let mut draw_snap_grid = self.config.draw_snap_grid;
+        let mut draw_grid = self.config.draw_grid;
+        let mut on_instability = self.config.on_instability;
+        let mut zone_gravity = self.zone_gravity;
+        let mut mover_shape = self.mover_shape;
+        let mut mover_speed = self.mover_speed;
+        let mut save_config = false;
+        let mut scene = None;
+        let mut random = self.random;
+        let mut generate = false;
+        let mut rect_spec = self.rect_spec;
+        let mut spawn = None;
+        let mut cancel = false;
+        let palette = self.theme.theme().objects;
+        let mut error = None;
+
+        self.gui.frame(&mut self.canvas.screen, |ctx| {
+            egui::Window::new("settings").show(ctx, |ui| {
+                let size = state.world.size();
+                let (mut w, mut h) = (size.x, size.y);
+                ui.horizontal(|ui| {
+                    ui.label("world size");
+                    let range = 1.0..=World::MAX_SIZE;
+                    let changed = ui
+                        .add(egui::DragValue::new(&mut w).range(range.clone()))
+                        .changed()
+                        | ui.add(egui::DragValue::new(&mut h).range(range)).changed();
+                    if changed {
+                        state.world.resize(w, h);
+                    }
+                });
+                draw_solver(ui, &mut state.world);
+                egui::ComboBox::from_label("new gravity zones")
+                    .selected_text(zone_gravity.name())
+                    .show_ui(ui, |ui| {
+                        for kind in ZoneGravity::ALL {
+                            ui.selectable_value(&mut zone_gravity, kind, kind.name());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("new movers")
+                        .selected_text(mover_shape.name())
+                        .show_ui(ui, |ui| {
+                            for shape in MoverShape::ALL {
+                                ui.selectable_value(&mut mover_shape, shape, shape.name());
+                            }
+                        });
+                    ui.add(egui::Slider::new(&mut mover_speed, 10.0..=1000.0).text("speed"));
+                });
+
+                ui.separator();
+
+                ui.add(egui::Slider::new(&mut state.speed, 0.0..=4.0).text("speed"));
+                ui.checkbox(&mut state.auto_slow_down, "slow down when behind");
+                ui.checkbox(&mut state.simulate, "simulate");
+                ui.checkbox(&mut state.draw_particles, "draw particles");
+                ui.checkbox(&mut state.draw_springs, "draw springs");
+                if ui.checkbox(&mut state.draw_trails, "draw trails").changed() {
+                    state.world.clear_trails();
+                }
+                ui.checkbox(&mut state.draw_temperatures, "draw temperatures");
+                ui.checkbox(&mut state.draw_metrics, "draw solver metrics")
+                    .on_hover_text("energy, spring error, overlap and steps per update");
+                ui.checkbox(draw_log, "draw log");
+                ui.checkbox(&mut muted, "mute sound");
+
+                // Saved once a drag is over instead of on every frame of it
+                let snap = ui
+                    .add(egui::Slider::new(&mut snap_grid, 5.0..=100.0).text("snap grid"))
+                    .on_hover_text("hold shift while drawing edges");
+                save_config |= snap.drag_stopped() || (snap.changed() && !snap.dragged());
+                save_config |= ui.checkbox(&mut draw_snap_grid, "draw snap grid").changed();
+                save_config |= ui
+                    .checkbox(&mut draw_grid, "draw grid")
+                    .on_hover_text("the snap grid behind everything, with a cursor readout")
+                    .changed();
+
+                egui::ComboBox::from_label("when unstable")
+                    .selected_text(on_instability.name())
+                    .show_ui(ui, |ui| {
+                        for policy in InstabilityPolicy::ALL {
+                            save_config |= ui
+                                .selectable_value(&mut on_instability, policy, policy.name())
+                                .changed();
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "what to do when a spring stretches so far the simulation blew up",
+                    );
+
+                egui::ComboBox::from_label("theme")
+                    .selected_text(theme.name())
+                    .show_ui(ui, |ui| {
+                        for kind in ThemeKind::ALL {
+                            ui.selectable_value(&mut theme, kind, kind.name());
+                        }
+                    });
+            });
+
+            if let Some(n) = selected_object {
+                egui::Window::new(format!("object {n}"))
+                    .id(egui::Id::new("object"))
+                    .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+                    .show(ctx, |ui| {
+                        error = draw_object(
+                            ui,
+                            &mut state.world,
+                            n,
+                            state.simulate,
+                            texture_path,
+                            palette,
+                        );
+                    });
+            }
+
+            if let Some(n) = selected_emitter {
+                egui::Window::new(format!("emitter {n}"))
+                    .id(egui::Id::new("emitter"))
+                    .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+                    .show(ctx, |ui| draw_emitter(ui, &mut state.world, n));
+            }
+
+            if outline {
+                egui::Window::new("outline").show(ctx, |ui| {
+                    egui::CollapsingHeader::new("layers").show(ui, |ui| {
+                        unpicked = draw_layers(ui, &mut state.world);
+                    });
+                    clicked = draw_outline(ui, &mut state.world, &outlined);
+                });
+            }
+
+            if spring_groups {
+                egui::Window::new("spring groups").show(ctx, |ui| {
+                    pick = draw_spring_groups(ui, &mut state.world, &picked_springs);
+                });
+            }
+
+            egui::Window::new("scenes").show(ctx, |ui| {
+                for preset in PRESETS {
+                    if ui
+                        .button(preset.name())
+                        .on_hover_text(preset.description())
+                        .clicked()
+                    {
+                        scene = Some(preset);
+                    }
+                }
+
+                ui.separator();
+                ui.add(egui::Slider::new(&mut random.count, 1..=500).text("shapes"));
+                ui.horizontal(|ui| {
+                    ui.label("seed");
+                    ui.add(egui::DragValue::new(&mut random.seed));
+                });
+                generate = ui
+                    .button(random.name())
+                    .on_hover_text(random.description())
+                    .clicked();
+            });
+
+            if let Some(spec) = &mut rect_spec {
+                egui::Window::new("spawn rect")
+                    .collapsible(false)
+                    .show(ctx, |ui| {
+                        egui::Grid::new("spawn rect").show(ui, |ui| {
+                            ui.label("width");
+                            ui.add(
+                                egui::DragValue::new(&mut spec.cols)
+                                    .range(2..=World::MAX_RECT_SIDE),
+                            );
+                            ui.end_row();
+                            ui.label("height");
+                            ui.add(
+                                egui::DragValue::new(&mut spec.rows)
+                                    .range(2..=World::MAX_RECT_SIDE),
+                            );
+                            ui.end_row();
+                            ui.label("spacing");
+                            ui.add(
+                                egui::DragValue::new(&mut spec.spacing)
+                                    .range(2.0 * Particle::R..=60.0)
+                                    .speed(0.1),
+                            );
+                            ui.end_row();
+                            ui.label("stiffness");
+                            ui.add(
+                                egui::DragValue::new(&mut spec.stiffness).range(100.0..=20000.0),
+                            );
+                            ui.end_row();
+                        });
+
+                        ui.horizontal(|ui| {
+                            if ui.button("spawn").clicked() {
+                                spawn = Some(*spec);
+                            }
+                            cancel = ui.button("cancel").clicked();
+                        });
+                    });
+            }
+        });
+
+        if let Some(springs) = pick {
+            self.pick_springs(&springs);
+        }
+        if let Some(err) = error {
+            self.log.error(format!("could not load texture {err}"));
+        }
+        if theme != self.theme {
+            self.set_theme(theme);
+        }
+        if muted != self.config.muted {
+            self.set_muted(muted);
+        }
+        self.config.snap_grid = snap_grid;
+        self.config.draw_snap_grid = draw_snap_grid;
+        self.config.draw_grid = draw_grid;
+        self.config.on_instability = on_instability;
+        self.zone_gravity = zone_gravity;
+        self.mover_shape = mover_shape;
+        self.mover_speed = mover_speed;
+        if save_config {
+            if let Err(err) = self.config.save(&self.config_path) {
+                self.log.error(format!("could not save config: {err}"));
+            }
+        }
+        self.random = random;
+        if unpicked {
+            self.selected_object = None;
+            self.selection = Selection::default();
+        }
+        let focus = match clicked {
+            Some(Outlined::Object(n)) => {
+                self.selected_object = self.state.world.object_id(n);
+                self.selection = Selection::default();
+                self.state.world.object_center(n)
+            }
+            Some(Outlined::Edge(n)) => {
+                self.selected_object = None;
+                self.selection = Selection::new(&self.state.world, &[], &[n]);
+                Some(self.state.world.group_center(&[], &[n]))
+            }
+            None => None,
+        };
+        if let Some(center) = focus {
+            let (w, h) = self.canvas.size();
+            self.canvas
+                .camera
+                .focus(center, Vec2::new(w as Real, h as Real));
+        }
+        self.rect_spec = rect_spec.filter(|_| spawn.is_none() && !cancel);
+        if let Some(spec) = spawn {
+            self.spawn_rect_spec(spec);
+        }
+        if let Some(preset) = scene {
+            self.load_scene(preset);
+        }
+        if generate {
+            self.load_random_scene();
+        }
+    }
+
+    fn draw_tooltip(&mut self, pos: Vec2, lines: &[String]) {
+        let width = lines.iter().map(String::len).max().unwrap_or_default() as Real * CHAR_WIDTH;
+        let height = lines.len() as Real * LINE_HEIGHT;
+        let pos = pos + Vec2::new(15.0, 15.0);
+        let theme = self.theme();
 
         self.canvas
-            .set_color(Color::RGBA(88, 112, 160, 120))
-            .filled_rounded_rectangle(Vec2::new(15.0, 15.0), Vec2::new(145.0, 110.0), 5.0)
-            .set_color(Color::CYAN)
-            .text(Vec2::new(20.0, 25.0), format!("{} FPS", self.fps).as_str())
-            .set_color(Color::RGB(176, 224, 255))
-            .text(Vec2::new(20.0, 40.0), format!("{p_len} particles").as_str())
-            .text(Vec2::new(20.0, 50.0), format!("{s_len} springs").as_str())
-            .text(
-                Vec2::new(20.0, 60.0),
-                format!("{b_len} boundaries").as_str(),
-            )
-            .text(Vec2::new(20.0, 70.0), format!("{e_len} edges").as_str())
-            .text(Vec2::new(20.0, 80.0), format!("{o_len} objects").as_str());
+            .screen
+            .set_color(theme.tooltip)
+            .filled_rounded_rectangle(pos, pos + Vec2::new(width + 10.0, height + 8.0), 5.0)
+            .set_color(theme.text);
+
+        for (i, line) in lines.iter().enumerate() {
+            self.canvas.screen.text_sized(
+                pos + Vec2::new(5.0, 5.0 + LINE_HEIGHT * i as Real),
+                line.as_str(),
+                TEXT_SIZE,
+            );
+        }
+    }
 
-        let spd = if self.state.simulate {
-            format!("speed: {:.2}x", self.state.speed)
+    fn draw_inspector(&mut self, mouse_pos: Vec2) {
+        let world = &self.state.world;
+
+        let particle = world
+            .particle_at(mouse_pos)
+            .and_then(|id| world.particle_index(id).zip(world.particle_info(id)));
+        let lines = if let Some((n, (obj, pos, vel, springs))) = particle {
+            let obj = obj.map_or_else(|| String::from("none"), |o| o.to_string());
+
+            vec![
+                format!("particle {n}"),
+                format!("object: {obj}"),
+                format!("pos: ({:.1}, {:.1})", pos.x, pos.y),
+                format!("vel: ({:.1}, {:.1})", vel.x, vel.y),
+                format!("springs: {springs}"),
+            ]
+        } else if let Some(n) = world.edge_at(mouse_pos) {
+            let len = world.edge_info(n);
+            let name = match world.edges_iter().nth(n).and_then(Edge::name) {
+                Some(name) => format!("edge {n}: {name}"),
+                None => format!("edge {n}"),
+            };
+            let mut lines = vec![name, format!("length: {len:.1}")];
+            let chain = world.chain_of(n).len();
+            if chain > 1 {
+                let corners = world.edges_iter().nth(n).map_or("", |e| e.corners().name());
+                lines.push(format!("chain of {chain}, {corners} corners"));
+            }
+            if let Some(e) = world
+                .edges_iter()
+                .nth(n)
+                .filter(|e| !e.keyframes().is_empty())
+            {
+                let looping = if e.is_looping() { ", looping" } else { "" };
+                lines.push(format!("keyframes: {}{looping}", e.keyframes().len()));
+            }
+            lines
         } else {
-            String::from("paused")
+            return;
         };
-        self.canvas.text(Vec2::new(20.0, 90.0), spd.as_str());
+
+        self.draw_tooltip(self.canvas.camera.to_screen(mouse_pos), &lines);
+    }
+
+    fn draw_ui(&mut self, mouse_pos: Vec2) {
+        span!("draw ui");
+        let (p_len, s_len, b_len, e_len, o_len) = self.state.world.info();
+        let theme = self.theme();
+
+        let line = |i: usize| Vec2::new(20.0, 20.0 + LINE_HEIGHT * i as Real);
+
+        let mut stats = vec![
+            format!("{p_len} particles"),
+            format!("{s_len} springs"),
+            format!("{b_len} boundaries"),
+            format!("{e_len} edges"),
+            format!("{o_len} objects"),
+            if self.state.simulate {
+                let fast_forward = if self.fast_forward.is_some() {
+                    " >>"
+                } else {
+                    ""
+                };
+                format!("speed: {:.2}x{fast_forward}", self.speed())
+            } else {
+                String::from("paused")
+            },
+        ];
+        if let Some(metrics) = self
+            .state
+            .world
+            .metrics()
+            .filter(|_| self.state.draw_metrics)
+        {
+            stats.extend([
+                format!("energy: {:.3e}", metrics.kinetic_energy),
+                format!("spring error: {:.2}%", metrics.spring_error * 100.0),
+                format!("overlap: {:.2}", metrics.max_penetration),
+                format!("steps: {}", metrics.steps),
+            ]);
+        }
+        if self.config.draw_grid {
+            stats.extend([
+                format!("cursor: ({:.0}, {:.0})", mouse_pos.x, mouse_pos.y),
+                format!("zoom: {:.0}%", self.canvas.camera.zoom * 100.0),
+            ]);
+        }
+        let behind = (self.state.simulate && self.pace < BEHIND)
+            .then(|| format!("sim at {:.0}% real time", self.pace * self.speed() * 100.0));
+        let stats_bottom = line(stats.len() + 1 + usize::from(behind.is_some())).y + 5.0;
+
+        self.canvas
+            .screen
+            .set_color(theme.panel)
+            .filled_rounded_rectangle(Vec2::new(15.0, 15.0), Vec2::new(175.0, stats_bottom), 5.0)
+            .set_color(theme.highlight)
+            .text_sized(line(0), format!("{} FPS", self.fps).as_str(), TEXT_SIZE);
+
+        if self.recorder.is_some() {
+            self.canvas.screen.set_color(theme.alert).text_sized(
+                Vec2::new(140.0, line(0).y),
+                "REC",
+                TEXT_SIZE,
+            );
+        }
+
+        self.canvas.screen.set_color(theme.text);
+        for (i, stat) in stats.iter().enumerate() {
+            self.canvas
+                .screen
+                .text_sized(line(i + 1), stat.as_str(), TEXT_SIZE);
+        }
+        if let Some(behind) = behind {
+            self.canvas.screen.set_color(theme.alert).text_sized(
+                line(stats.len() + 1),
+                &behind,
+                TEXT_SIZE,
+            );
+        }
+
+        let tools_top = stats_bottom + 10.0;
+        self.canvas
+            .screen
+            .set_color(theme.panel)
+            .filled_rounded_rectangle(
+                Vec2::new(15.0, tools_top),
+                Vec2::new(
+                    175.0,
+                    tools_top + 10.0 + LINE_HEIGHT * Tool::ALL.len() as Real,
+                ),
+                5.0,
+            );
+        // What they're bound to by default
+        const TOOL_KEYS: [char; Tool::ALL.len()] = [
+            '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', '-', '=', '\\', '[', ']', ';', '\'',
+            ',', '.',
+        ];
+        for (i, &tool) in Tool::ALL.iter().enumerate() {
+            let color = if tool == self.tool {
+                theme.highlight
+            } else {
+                theme.text
+            };
+
+            self.canvas.screen.set_color(color).text_sized(
+                Vec2::new(20.0, tools_top + 5.0 + LINE_HEIGHT * i as Real),
+                format!("{} {}", TOOL_KEYS[i], tool.name()).as_str(),
+                TEXT_SIZE,
+            );
+        }
 
         if self.draw_log && self.log.len() != 0 {
+            let width = self.canvas.screen.width() as Real;
+
             self.canvas
-                .set_color(Color::RGBA(88, 112, 160, 120))
+                .screen
+                .set_color(theme.panel)
                 .filled_rounded_rectangle(
                     Vec2::new(385.0, 5.0),
-                    Vec2::new(WIDTH - 385.0, 9.0 * 15.0),
+                    Vec2::new(width - 385.0, 20.0 + LINE_HEIGHT * LOG_LINES as Real),
                     5.0,
-                )
-                .set_color(Color::RGB(176, 224, 255));
-            for (i, msg) in self.log.iter().rev().enumerate() {
+                );
+            for (i, entry) in self.log.iter().take(LOG_LINES).rev().enumerate() {
                 self.canvas
-                    .text(Vec2::new(400.0, 15.0 + 10.0 * i as f64), msg.as_str());
+                    .screen
+                    .set_color(entry.level.color(theme))
+                    .text_sized(
+                        Vec2::new(400.0, 12.0 + LINE_HEIGHT * i as Real),
+                        entry.to_string().as_str(),
+                        TEXT_SIZE,
+                    );
             }
         }
+
+        if let Some(cursor) = self.gamepad.as_ref().and_then(Gamepad::cursor) {
+            let (h, v) = (Vec2::new(8.0, 0.0), Vec2::new(0.0, 8.0));
+            self.canvas
+                .screen
+                .set_color(theme.highlight)
+                .line(cursor - h, cursor + h)
+                .line(cursor - v, cursor + v);
+        }
+
+        if !self.state.simulate {
+            self.draw_inspector(mouse_pos);
+        }
+
+        #[cfg(feature = "profile")]
+        if self.draw_profiler {
+            self.draw_profile();
+        }
+    }
+
+    /// The spans of the last frame as a timeline along the bottom of the screen, one row per
+    /// nesting level, the full width being one frame at the target rate.
+    #[cfg(feature = "profile")]
+    fn draw_profile(&mut self) {
+        const ROW: Real = LINE_HEIGHT + 4.0;
+        let theme = self.theme();
+        let (w, h) = self.canvas.screen.size();
+        let budget = 1.0 / f64::from(self.target_fps);
+        let rows = self.profile.iter().map(|r| r.depth + 1).max().unwrap_or(1);
+        let (left, width) = (20.0, w as Real - 40.0);
+        let top = h as Real - 20.0 - ROW * rows as Real;
+
+        self.canvas
+            .screen
+            .set_color(theme.panel)
+            .filled_rounded_rectangle(
+                Vec2::new(left - 5.0, top - 5.0),
+                Vec2::new(left + width + 5.0, h as Real - 15.0),
+                5.0,
+            );
+
+        for record in &self.profile {
+            let x = |t: f64| left + (t / budget).min(1.0) as Real * width;
+            let (start, end) = (x(record.start), x(record.end).max(x(record.start) + 1.0));
+            let y = top + ROW * record.depth as Real;
+            // Same span, same color, frame after frame
+            let hash = record.name.bytes().map(usize::from).sum::<usize>();
+
+            self.canvas
+                .screen
+                .set_color(theme.objects[hash % theme.objects.len()])
+                .filled_rectangle(Vec2::new(start, y), Vec2::new(end, y + ROW - 2.0));
+
+            let label = format!(
+                "{} {:.2}ms",
+                record.name,
+                (record.end - record.start) * 1000.0
+            );
+            if label.len() as Real * CHAR_WIDTH + 4.0 < end - start {
+                self.canvas.screen.set_color(theme.background).text_sized(
+                    Vec2::new(start + 2.0, y + 2.0),
+                    &label,
+                    TEXT_SIZE,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// The panels of a selected object and emitter, placed like the app places them.
+    #[test]
+    fn panels_look_the_same() {
+        let mut world = World::new(960.0, 720.0);
+        world.spawn_rect(4, 3, 100.0, 100.0).unwrap();
+        world.add_emitter(Emitter::new(Vec2::new(400.0, 100.0), Vec2::null()));
+        let theme = ThemeKind::default().theme();
+        let mut gui = Gui::new();
+        gui.visible = true;
+
+        let mut canvas = Framebuffer::new(960, 720);
+        // Windows fade in over their first frames
+        for _ in 0..10 {
+            canvas.set_color(theme.background).clear();
+            gui.frame(&mut canvas, |ctx| {
+                egui::Window::new("object 0")
+                    .id(egui::Id::new("object"))
+                    .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+                    .show(ctx, |ui| {
+                        draw_object(ui, &mut world, 0, true, &mut String::new(), theme.objects);
+                    });
+                egui::Window::new("emitter 0")
+                    .id(egui::Id::new("emitter"))
+                    .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -10.0))
+                    .show(ctx, |ui| draw_emitter(ui, &mut world, 0));
+                egui::Window::new("outline").show(ctx, |ui| {
+                    draw_outline(ui, &mut world, &[Outlined::Object(0)]);
+                });
+            });
+        }
+        canvas.assert_golden("panels");
+    }
+
+    /// Drawing the settings panel without touching it mustn't count as an edit, or the
+    /// physics thread starts over with every frame and never hands an update back.
+    #[test]
+    fn untouched_settings_let_updates_through() {
+        let mut world = World::new(800.0, 600.0);
+        world.spawn_rect(3, 3, 100.0, 100.0).unwrap();
+        let mut gui = Gui::new();
+        gui.visible = true;
+        let mut canvas = Framebuffer::new(800, 600);
+        let mut physics = Physics::start();
+        let controls = Controls {
+            speed: 1.0,
+            ..Controls::default()
+        };
+
+        let started = Instant::now();
+        loop {
+            gui.frame(&mut canvas, |ctx| {
+                egui::Window::new("settings").show(ctx, |ui| draw_solver(ui, &mut world));
+            });
+            physics.advance(1.0 / 60.0, controls);
+            if physics.sync(&mut world).is_some() {
+                break;
+            }
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "no update came back"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
     }
 }
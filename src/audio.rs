@@ -0,0 +1,112 @@
+use crate::{
+    vec2::{to_f64, Real},
+    world::WorldEvent,
+};
+use sdl2::{
+    mixer::{self, Channel, Chunk},
+    AudioSubsystem, Sdl,
+};
+use std::f64::consts::TAU;
+
+/// Sound effects synthesized at startup and played in response to [`WorldEvent`]s.
+pub struct Audio {
+    _subsystem: AudioSubsystem,
+    impact: Chunk,
+    boing: Chunk,
+}
+
+impl Audio {
+    const CHANNELS: i32 = 16;
+    // Speeds at and above which an effect plays at full volume
+    const LOUD_IMPACT: Real = 800.0;
+    const LOUD_COMPRESSION: Real = 300.0;
+
+    pub fn new(ctx: &Sdl) -> Result<Self, String> {
+        let subsystem = ctx.audio()?;
+        mixer::open_audio(
+            mixer::DEFAULT_FREQUENCY,
+            mixer::DEFAULT_FORMAT,
+            mixer::DEFAULT_CHANNELS,
+            512,
+        )?;
+        mixer::allocate_channels(Self::CHANNELS);
+
+        // The device may not give us what we asked for, chunks have to match what it did
+        let (frequency, format, channels) = mixer::query_spec()?;
+        if format != mixer::DEFAULT_FORMAT {
+            return Err(format!("unsupported sample format {format:#x}"));
+        }
+
+        // A dull knock: a few inharmonic partials dying out quickly
+        let impact = synth(frequency, channels, 0.15, |t| {
+            ((TAU * 90.0 * t).sin() + 0.5 * (TAU * 143.0 * t).sin() + 0.3 * (TAU * 217.0 * t).sin())
+                / 1.8
+                * (-35.0 * t).exp()
+        });
+        // A wobbling tone, the classic cartoon spring
+        let boing = synth(frequency, channels, 0.5, |t| {
+            (TAU * 220.0 * t + 1.6 * (TAU * 9.0 * t).sin()).sin() * (-6.0 * t).exp()
+        });
+
+        Ok(Self {
+            _subsystem: subsystem,
+            impact: Chunk::from_raw_buffer(impact)?,
+            boing: Chunk::from_raw_buffer(boing)?,
+        })
+    }
+
+    /// Plays the loudest impact and compression of a frame, panned by where they happened
+    /// on a screen `width` pixels wide.
+    pub fn play(&self, events: &[WorldEvent], width: Real) {
+        let mut impact = None;
+        let mut compression = None;
+
+        for event in events {
+            let (loudest, pos, speed) = match *event {
+                WorldEvent::Impact { pos, speed } => (&mut impact, pos, speed),
+                WorldEvent::Compression { pos, closing } => (&mut compression, pos, closing),
+                // As loud as impacts get
+                WorldEvent::EdgeBroken { pos } => (&mut impact, pos, Self::LOUD_IMPACT),
+                WorldEvent::Despawned { .. } | WorldEvent::Quarantined { .. } => continue,
+            };
+            if loudest.is_none_or(|(_, s)| speed > s) {
+                *loudest = Some((pos.x / width, speed));
+            }
+        }
+
+        if let Some((pan, speed)) = impact {
+            Self::play_chunk(&self.impact, to_f64(speed / Self::LOUD_IMPACT), to_f64(pan));
+        }
+        if let Some((pan, closing)) = compression {
+            Self::play_chunk(
+                &self.boing,
+                to_f64(closing / Self::LOUD_COMPRESSION),
+                to_f64(pan),
+            );
+        }
+    }
+
+    fn play_chunk(chunk: &Chunk, loudness: f64, pan: f64) {
+        // With every channel busy the sound is simply dropped
+        let Ok(channel) = Channel::all().play(chunk, 0) else {
+            return;
+        };
+
+        channel.set_volume((loudness.clamp(0.0, 1.0) * f64::from(mixer::MAX_VOLUME)) as i32);
+        let right = (pan.clamp(0.0, 1.0) * 255.0) as u8;
+        // Panning only fails on mono output, where it doesn't matter
+        let _ = channel.set_panning(255 - right, right);
+    }
+}
+
+/// Samples `wave` over `seconds` into interleaved signed 16 bit frames.
+fn synth(frequency: i32, channels: i32, seconds: f64, wave: impl Fn(f64) -> f64) -> Box<[i16]> {
+    let frames = (f64::from(frequency) * seconds) as usize;
+
+    (0..frames)
+        .flat_map(|i| {
+            let sample = wave(i as f64 / f64::from(frequency)) * f64::from(i16::MAX) * 0.8;
+            std::iter::repeat_n(sample as i16, channels as usize)
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "wgpu")]
+use crate::wgpu_renderer::WgpuRenderer;
+use crate::{
+    renderer::{BlendMode, Color, Renderer},
+    sdl2_renderer::{SDL2CanvasWrapper, SDL2FramebufferWrapper},
+    vec2::{Real, Vec2},
+};
+use sdl2::video::Window;
+
+/// The renderer picked at startup, dispatching to the concrete backend.
+pub enum Backend {
+    Gfx(SDL2CanvasWrapper<Window>),
+    Software(SDL2FramebufferWrapper),
+    #[cfg(feature = "wgpu")]
+    Wgpu(WgpuRenderer),
+}
+
+macro_rules! dispatch {
+    ($self:ident, $r:ident => $e:expr) => {
+        match $self {
+            Backend::Gfx($r) => $e,
+            Backend::Software($r) => $e,
+            #[cfg(feature = "wgpu")]
+            Backend::Wgpu($r) => $e,
+        }
+    };
+}
+
+impl Backend {
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        dispatch!(self, r => r.set_size(width, height));
+    }
+}
+
+impl Renderer for Backend {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        dispatch!(self, r => { r.filled_circle(center, radius); });
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        dispatch!(self, r => { r.line(a, b); });
+        self
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        dispatch!(self, r => { r.thick_line(a, b, thickness); });
+        self
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        dispatch!(self, r => { r.rectangle(a, b); });
+        self
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        dispatch!(self, r => { r.filled_rectangle(a, b); });
+        self
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        dispatch!(self, r => { r.filled_rounded_rectangle(a, b, radius); });
+        self
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        dispatch!(self, r => { r.polygon(vertices); });
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        dispatch!(self, r => { r.filled_polygon(vertices); });
+        self
+    }
+
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        dispatch!(self, r => { r.aa_line(a, b); });
+        self
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        dispatch!(self, r => { r.aa_filled_circle(center, radius); });
+        self
+    }
+
+    fn aa_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        dispatch!(self, r => { r.aa_polygon(vertices); });
+        self
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        dispatch!(self, r => { r.set_blend_mode(mode); });
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        dispatch!(self, r => { r.text_sized(pos, text, size); });
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        dispatch!(self, r => r.size())
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        dispatch!(self, r => r.read_pixels())
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        dispatch!(self, r => { r.set_color(color); });
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        dispatch!(self, r => { r.clear(); });
+        self
+    }
+
+    fn finish(&mut self) {
+        dispatch!(self, r => r.finish());
+    }
+}
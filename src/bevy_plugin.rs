@@ -0,0 +1,112 @@
+use crate::{
+    slots::Id,
+    vec2::{Real, Vec2},
+    world::World,
+};
+use bevy::{
+    prelude::{
+        App, Commands, Component, DespawnRecursiveExt, DetectChanges, Entity, FixedUpdate, Plugin,
+        Quat, Query, Res, ResMut, Resource, Time, Transform, Update, Vec3,
+    },
+    time::Fixed,
+};
+
+/// Runs a soft body world inside a Bevy app: the world is the [`Simulation`] resource,
+/// stepped on the fixed timestep, and every object in it gets a [`SoftObject`] entity
+/// whose transform follows it.
+///
+/// World coordinates are pixels with y pointing down, transforms have y flipped to
+/// point up like in Bevy.
+pub struct SoftPlugin {
+    pub width: Real,
+    pub height: Real,
+}
+
+impl Plugin for SoftPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Simulation(World::new(self.width, self.height)))
+            .add_systems(FixedUpdate, step)
+            .add_systems(Update, mirror);
+    }
+}
+
+/// Spawn into and edit the world through this, objects are mirrored once it's changed.
+#[derive(Resource)]
+pub struct Simulation(pub World);
+
+/// Object `id` of the world, see [`World::object_id`]. The entity is despawned once the
+/// object is removed.
+#[derive(Component)]
+pub struct SoftObject {
+    pub id: Id,
+    /// Particle of the object furthest out from its center when the entity was spawned,
+    /// and the way there from the center then. The rotation is measured against it
+    particle: usize,
+    reference: Vec2,
+}
+
+fn step(time: Res<Time<Fixed>>, mut simulation: ResMut<Simulation>) {
+    let world = &mut simulation.0;
+    world.end_frame(time.delta_secs_f64() as Real);
+    // The app resets a world that blew up the same way
+    if world.update().is_err() {
+        world.clear();
+    }
+}
+
+fn mirror(
+    mut commands: Commands,
+    simulation: Res<Simulation>,
+    mut objects: Query<(Entity, &SoftObject, &mut Transform)>,
+) {
+    if !simulation.is_changed() {
+        return;
+    }
+    let world = &simulation.0;
+
+    let mut mirrored = vec![false; world.info().4];
+    for (entity, object, mut transform) in &mut objects {
+        let Some((n, (center, arm))) = world
+            .object_index(object.id)
+            .and_then(|n| Some((n, pose(world, n, object.particle)?)))
+        else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        mirrored[n] = true;
+        transform.translation = Vec3::new(center.x as f32, -center.y as f32, 0.0);
+        // Clockwise on screen is counterclockwise with y up
+        transform.rotation = Quat::from_rotation_z(-object.reference.angle(arm) as f32);
+    }
+
+    for (index, _) in mirrored.iter().enumerate().filter(|(_, &done)| !done) {
+        let (Some(id), Some(state)) = (world.object_id(index), world.object_state(index)) else {
+            continue;
+        };
+        let center = state.center_of_mass;
+        let Some((particle, reference)) = world.object_particles(index).and_then(|particles| {
+            particles
+                .iter()
+                .map(|p| p.get_pos() - center)
+                .enumerate()
+                .max_by(|(_, a), (_, b)| a.len_sqr().total_cmp(&b.len_sqr()))
+        }) else {
+            continue;
+        };
+        commands.spawn((
+            SoftObject {
+                id,
+                particle,
+                reference,
+            },
+            Transform::from_xyz(center.x as f32, -center.y as f32, 0.0),
+        ));
+    }
+}
+
+/// Center of mass of object `n` and the way from it to the object's `particle`th one.
+fn pose(world: &World, n: usize, particle: usize) -> Option<(Vec2, Vec2)> {
+    let center = world.object_state(n)?.center_of_mass;
+    let pos = world.object_particles(n)?.get(particle)?.get_pos();
+    Some((center, pos - center))
+}
@@ -0,0 +1,193 @@
+use crate::{
+    renderer::{BlendMode, Color, Renderer},
+    vec2::{Real, Vec2},
+};
+
+/// Which part of the world is on screen: the point of it at the top left corner, and how
+/// many pixels a unit of it takes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Camera {
+    pub pos: Vec2,
+    pub zoom: Real,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            pos: Vec2::null(),
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    pub const MIN_ZOOM: Real = 0.05;
+    pub const MAX_ZOOM: Real = 20.0;
+
+    pub fn to_world(self, screen: Vec2) -> Vec2 {
+        self.pos + screen / self.zoom
+    }
+
+    pub fn to_screen(self, world: Vec2) -> Vec2 {
+        (world - self.pos) * self.zoom
+    }
+
+    /// Moves the view by `offset` pixels, the world follows the mouse dragging it.
+    pub fn pan(&mut self, offset: Vec2) {
+        self.pos -= offset / self.zoom;
+    }
+
+    /// Zooms in by `factor`, out below one, keeping the point under `screen` where it is.
+    pub fn zoom_at(&mut self, screen: Vec2, factor: Real) {
+        let anchor = self.to_world(screen);
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.pos = anchor - screen / self.zoom;
+    }
+
+    /// Centers `pos` on a screen `screen` pixels large.
+    pub fn focus(&mut self, pos: Vec2, screen: Vec2) {
+        self.pos = pos - screen / (2.0 * self.zoom);
+    }
+
+    /// Zooms out until a world `size` large fits on a screen `screen` pixels large, never
+    /// in past one to one, and centers it.
+    pub fn fit(&mut self, size: Vec2, screen: Vec2) {
+        self.zoom = (screen.x / size.x)
+            .min(screen.y / size.y)
+            .clamp(Self::MIN_ZOOM, 1.0);
+        self.focus(size / 2.0, screen);
+    }
+}
+
+/// Draws in world coordinates through `camera`, onto `screen` that draws in pixels. Lines
+/// and text stay as thin and as large as they are, everything else scales with the zoom.
+pub struct View<R> {
+    pub camera: Camera,
+    /// For what stays put on screen, like panels and tooltips
+    pub screen: R,
+}
+
+impl<R: Renderer> View<R> {
+    pub fn new(screen: R) -> Self {
+        Self {
+            camera: Camera::default(),
+            screen,
+        }
+    }
+
+    /// The part of the world on screen, top left and bottom right corner.
+    pub fn visible(&self) -> (Vec2, Vec2) {
+        let (w, h) = self.screen.size();
+        (
+            self.camera.to_world(Vec2::null()),
+            self.camera.to_world(Vec2::new(w as Real, h as Real)),
+        )
+    }
+
+    fn at(&self, pos: Vec2) -> Vec2 {
+        self.camera.to_screen(pos)
+    }
+}
+
+impl<R: Renderer> Renderer for View<R> {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let (center, radius) = (self.at(center), radius * self.camera.zoom);
+        self.screen.filled_circle(center, radius);
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (a, b) = (self.at(a), self.at(b));
+        self.screen.line(a, b);
+        self
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        let (a, b, thickness) = (self.at(a), self.at(b), thickness * self.camera.zoom);
+        self.screen.thick_line(a, b, thickness);
+        self
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (a, b) = (self.at(a), self.at(b));
+        self.screen.rectangle(a, b);
+        self
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (a, b) = (self.at(a), self.at(b));
+        self.screen.filled_rectangle(a, b);
+        self
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        let (a, b, radius) = (self.at(a), self.at(b), radius * self.camera.zoom);
+        self.screen.filled_rounded_rectangle(a, b, radius);
+        self
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let camera = self.camera;
+        self.screen.polygon(vertices.map(|v| camera.to_screen(v)));
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let camera = self.camera;
+        self.screen
+            .filled_polygon(vertices.map(|v| camera.to_screen(v)));
+        self
+    }
+
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (a, b) = (self.at(a), self.at(b));
+        self.screen.aa_line(a, b);
+        self
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let (center, radius) = (self.at(center), radius * self.camera.zoom);
+        self.screen.aa_filled_circle(center, radius);
+        self
+    }
+
+    fn aa_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let camera = self.camera;
+        self.screen
+            .aa_polygon(vertices.map(|v| camera.to_screen(v)));
+        self
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        self.screen.set_blend_mode(mode);
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        let pos = self.at(pos);
+        self.screen.text_sized(pos, text, size);
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.screen.size()
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        self.screen.read_pixels()
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        self.screen.set_color(color);
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.screen.clear();
+        self
+    }
+
+    fn finish(&mut self) {
+        self.screen.finish();
+    }
+}
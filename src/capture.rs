@@ -0,0 +1,179 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub fn write_png(path: &Path, width: usize, height: usize, rgba: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|err| err.to_string())?;
+
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(|err| err.to_string())
+}
+
+pub fn timestamped_path(dir: &str, extension: &str) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    Ok(Path::new(dir).join(format!("soft-{millis}.{extension}")))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum RecordFormat {
+    Gif,
+    Mp4,
+}
+
+impl RecordFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Gif => "gif",
+            Self::Mp4 => "mp4",
+        }
+    }
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    rgba: Vec<u8>,
+}
+
+/// Encodes frames on a background thread so the render loop only pays for the readback.
+pub struct Recorder {
+    sender: SyncSender<Frame>,
+    worker: JoinHandle<Result<PathBuf, String>>,
+}
+
+impl Recorder {
+    // Bounded so a slow encoder throttles the app instead of buffering frames without limit
+    const QUEUE: usize = 8;
+
+    pub fn start(format: RecordFormat, path: PathBuf, fps: u32) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(Self::QUEUE);
+
+        let worker = thread::spawn(move || {
+            match format {
+                RecordFormat::Gif => encode_gif(&path, fps, &receiver),
+                RecordFormat::Mp4 => encode_mp4(&path, fps, &receiver),
+            }
+            .map(|()| path)
+        });
+
+        Self { sender, worker }
+    }
+
+    /// Queues a frame, returns false if the encoder has stopped.
+    pub fn push(&self, width: usize, height: usize, rgba: Vec<u8>) -> bool {
+        self.sender
+            .send(Frame {
+                width,
+                height,
+                rgba,
+            })
+            .is_ok()
+    }
+
+    pub fn finish(self) -> Result<PathBuf, String> {
+        drop(self.sender);
+        self.worker
+            .join()
+            .map_err(|_| String::from("encoder thread panicked"))?
+    }
+}
+
+fn encode_gif(path: &Path, fps: u32, frames: &Receiver<Frame>) -> Result<(), String> {
+    let Ok(first) = frames.recv() else {
+        return Err(String::from("no frames recorded"));
+    };
+
+    let file = File::create(path).map_err(|err| err.to_string())?;
+    let mut encoder = gif::Encoder::new(
+        BufWriter::new(file),
+        first.width as u16,
+        first.height as u16,
+        &[],
+    )
+    .map_err(|err| err.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|err| err.to_string())?;
+
+    let delay = (100 / fps.max(1)).max(1) as u16;
+    let (width, height) = (first.width, first.height);
+
+    for mut frame in std::iter::once(first).chain(frames) {
+        if (frame.width, frame.height) != (width, height) {
+            continue;
+        }
+
+        let mut gif_frame = gif::Frame::from_rgba_speed(
+            frame.width as u16,
+            frame.height as u16,
+            &mut frame.rgba,
+            10,
+        );
+        gif_frame.delay = delay;
+
+        encoder
+            .write_frame(&gif_frame)
+            .map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn encode_mp4(path: &Path, fps: u32, frames: &Receiver<Frame>) -> Result<(), String> {
+    let Ok(first) = frames.recv() else {
+        return Err(String::from("no frames recorded"));
+    };
+
+    let mut ffmpeg = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+        .args(["-pixel_format", "rgba", "-video_size"])
+        .arg(format!("{}x{}", first.width, first.height))
+        .arg("-framerate")
+        .arg(fps.to_string())
+        .args(["-i", "-", "-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("could not start ffmpeg: {err}"))?;
+
+    let mut stdin = ffmpeg.stdin.take().expect("stdin should be piped");
+    let (width, height) = (first.width, first.height);
+
+    for frame in std::iter::once(first).chain(frames) {
+        // ffmpeg can't change resolution mid stream, drop frames from before/after a resize
+        if (frame.width, frame.height) != (width, height) {
+            continue;
+        }
+
+        stdin
+            .write_all(&frame.rgba)
+            .map_err(|err| err.to_string())?;
+    }
+    drop(stdin);
+
+    let status = ffmpeg.wait().map_err(|err| err.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("ffmpeg exited with {status}"))
+    }
+}
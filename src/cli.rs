@@ -0,0 +1,174 @@
+use crate::{
+    capture::RecordFormat,
+    consts::{CONFIG, HEIGHT, KEYBINDINGS, SAVEFILE, WIDTH},
+    theme::ThemeKind,
+    vec2::Real,
+};
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum RendererKind {
+    /// sdl2_gfx primitives
+    Gfx,
+    /// Built-in CPU rasterizer, presented through an SDL texture
+    Software,
+    /// Batched GPU draws through wgpu
+    #[cfg(feature = "wgpu")]
+    Wgpu,
+}
+
+/// Interactive 2D soft body sandbox
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Args {
+    /// Run in a window instead of fullscreen
+    #[arg(long)]
+    pub windowed: bool,
+
+    /// Window width in pixels
+    #[arg(long, default_value_t = WIDTH as u32)]
+    pub width: u32,
+
+    /// Window height in pixels
+    #[arg(long, default_value_t = HEIGHT as u32)]
+    pub height: u32,
+
+    /// File the world is saved to and loaded from (F4/F5), as MessagePack when it ends in
+    /// .msgpack or .mpk, CBOR in .cbor and JSON otherwise
+    #[arg(long, default_value = SAVEFILE)]
+    pub savefile: PathBuf,
+
+    /// Scene to load on startup instead of the savefile, in any of the savefile formats
+    #[arg(long)]
+    pub scene: Option<PathBuf>,
+
+    /// SVG whose paths, polylines, polygons and lines are added as edges on startup
+    #[arg(long)]
+    pub svg: Option<PathBuf>,
+
+    /// How far edges may stray from the curves of the SVG, in pixels
+    #[arg(long, default_value_t = 1.0)]
+    pub svg_tolerance: Real,
+
+    /// PNG whose largest shape is added as a soft body on startup, traced from its
+    /// transparency or, in fully opaque images, from how dark it is
+    #[arg(long)]
+    pub image: Option<PathBuf>,
+
+    /// Pixels of the window per pixel of the image
+    #[arg(long, default_value_t = 1.0)]
+    pub image_scale: Real,
+
+    /// How opaque, or dark, pixels have to be to belong to the shape, from 0 to 1
+    #[arg(long, default_value_t = 0.5)]
+    pub image_threshold: Real,
+
+    /// Start with a world of this many random rectangles, ropes and edges (G generates more)
+    #[arg(long)]
+    pub random: Option<usize>,
+
+    /// Seed of the random world, the same seed always generates the same one
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// Target frames per second
+    #[arg(long, default_value_t = 60)]
+    pub fps: u32,
+
+    /// Start with the simulation paused
+    #[arg(long)]
+    pub paused: bool,
+
+    /// Synchronize presentation with the display refresh rate
+    #[arg(long)]
+    pub vsync: bool,
+
+    /// Mirror the log to soft.log
+    #[arg(long)]
+    pub log_file: bool,
+
+    /// File the keybindings are loaded from
+    #[arg(long, default_value = KEYBINDINGS)]
+    pub keybindings: PathBuf,
+
+    /// File user preferences like the theme are loaded from and saved to
+    #[arg(long, default_value = CONFIG)]
+    pub config: PathBuf,
+
+    /// Color theme, overriding the one stored in the config (F11 cycles at runtime)
+    #[arg(long, value_enum)]
+    pub theme: Option<ThemeKind>,
+
+    /// Container recordings (F10) are encoded to, mp4 requires ffmpeg on the PATH
+    #[arg(long, value_enum, default_value_t = RecordFormat::Gif)]
+    pub record_format: RecordFormat,
+
+    /// Advance the simulation by exactly one frame of time per recorded frame,
+    /// so clips play back at real speed regardless of how fast they were captured
+    #[arg(long)]
+    pub record_fixed_step: bool,
+
+    /// Log particle positions and velocities every frame, written to this file on exit as
+    /// Parquet when it ends in .parquet (needs the parquet feature) and CSV otherwise
+    #[arg(long)]
+    pub trajectory: Option<PathBuf>,
+
+    /// Record the trajectory only every this many simulated frames
+    #[arg(long, default_value_t = 1)]
+    pub trajectory_every: u64,
+
+    /// Record the center of mass of every object instead of every particle
+    #[arg(long)]
+    pub trajectory_objects: bool,
+
+    /// Most samples held for the trajectory, the oldest are dropped past that
+    #[arg(long, default_value_t = 10_000_000)]
+    pub trajectory_limit: usize,
+
+    /// How primitives are rasterized
+    #[arg(long, value_enum, default_value_t = RendererKind::Gfx)]
+    pub renderer: RendererKind,
+
+    /// Resolve particle collisions in compute shaders, for scenes of 100k particles and more
+    #[cfg(feature = "wgpu")]
+    #[arg(long)]
+    pub gpu_collisions: bool,
+
+    /// Address to accept WebSocket connections from scripts and dashboards on, like
+    /// 127.0.0.1:9001. They send JSON commands and can subscribe to world snapshots
+    #[cfg(feature = "remote")]
+    #[arg(long)]
+    pub remote: Option<String>,
+
+    /// Run the simulation for others to join and edit along, accepting them on this
+    /// address, like 0.0.0.0:7878
+    #[arg(long, conflicts_with = "join")]
+    pub host: Option<String>,
+
+    /// Show and edit the simulation hosted at this address. Spawning, drawing edges,
+    /// deleting and pinning go to the host, other edits are undone
+    #[arg(long)]
+    pub join: Option<String>,
+
+    /// Draw the scene into this PNG with the software rasterizer and exit, without
+    /// opening a window
+    #[arg(long)]
+    pub render: Option<PathBuf>,
+
+    /// Physics steps simulated before the scene is drawn by --render
+    #[arg(long, default_value_t = 0, requires = "render")]
+    pub render_steps: usize,
+
+    /// Also compare what --render draws against this PNG, failing when they differ
+    #[arg(long, requires = "render")]
+    pub golden: Option<PathBuf>,
+
+    /// Most a channel of a pixel may be off by to still match --golden
+    #[arg(long, default_value_t = 2, requires = "golden")]
+    pub golden_tolerance: u8,
+
+    /// Print the default keybindings and exit
+    #[arg(long)]
+    pub dump_keybindings: bool,
+}
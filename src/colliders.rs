@@ -0,0 +1,51 @@
+use crate::{
+    vec2::{Real, Vec2},
+    world::Particle,
+};
+
+/// A static shape boundary particles bounce off, registered on a world with
+/// [`crate::world::World::add_collider`]. Edges are one too.
+pub trait Collider: Send + Sync {
+    /// Smallest box around the shape. Objects are only tested against it when their
+    /// bounds come within a particle's reach of it.
+    fn bounds(&self) -> (Vec2, Vec2);
+
+    /// How far `point` is from the surface of the shape, negative inside it, and the
+    /// direction out of the shape from there.
+    fn distance(&self, point: Vec2) -> (Real, Vec2);
+
+    /// Pushes `particle` out of the shape, returns the speed it hit at, zero if it didn't
+    /// touch it.
+    fn collide(&self, particle: &mut Particle) -> Real {
+        let (distance, normal) = self.distance(particle.get_pos());
+        if distance > Particle::R {
+            return 0.0;
+        }
+        particle.bounce_off(Particle::R - distance, normal)
+    }
+}
+
+/// Any shape given as a signed distance function, negative inside. Directions out of it
+/// are estimated from the function's gradient.
+pub struct Sdf<F> {
+    pub sdf: F,
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl<F: Fn(Vec2) -> Real + Send + Sync> Collider for Sdf<F> {
+    fn bounds(&self) -> (Vec2, Vec2) {
+        (self.min, self.max)
+    }
+
+    fn distance(&self, point: Vec2) -> (Real, Vec2) {
+        // Central differences, a small fraction of a particle across
+        const H: Real = 0.01;
+        let (dx, dy) = (Vec2::new(H, 0.0), Vec2::new(0.0, H));
+        let gradient = Vec2::new(
+            (self.sdf)(point + dx) - (self.sdf)(point - dx),
+            (self.sdf)(point + dy) - (self.sdf)(point - dy),
+        );
+        ((self.sdf)(point), gradient.normalize())
+    }
+}
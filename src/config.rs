@@ -0,0 +1,67 @@
+use crate::{theme::ThemeKind, vec2::Real};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// User preferences that outlive a single world, kept apart from the savefile.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeKind,
+    pub muted: bool,
+    /// Spacing of the grid edges snap to while Shift is held
+    pub snap_grid: Real,
+    pub draw_snap_grid: bool,
+    /// The snap grid behind the world all the time, for building to scale
+    pub draw_grid: bool,
+    pub on_instability: InstabilityPolicy,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: ThemeKind::default(),
+            muted: false,
+            snap_grid: 25.0,
+            draw_snap_grid: true,
+            draw_grid: false,
+            on_instability: InstabilityPolicy::default(),
+        }
+    }
+}
+
+/// What to do when a spring blows up, see [`Instability`](crate::world::Instability).
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstabilityPolicy {
+    /// Stop simulating and highlight the spring
+    #[default]
+    Pause,
+    /// Remove the object the spring belongs to and carry on
+    RemoveObject,
+    /// Clear the whole world
+    Reset,
+}
+
+impl InstabilityPolicy {
+    pub const ALL: [Self; 3] = [Self::Pause, Self::RemoveObject, Self::Reset];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::RemoveObject => "remove object",
+            Self::Reset => "reset world",
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        toml::from_str(&file).map_err(|err| err.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let file = toml::to_string(self).expect("config should be valid to serialize");
+        std::fs::write(path, file).map_err(|err| err.to_string())
+    }
+}
@@ -1,3 +1,11 @@
-pub const WIDTH: f64 = 1920.0;
-pub const HEIGHT: f64 = 1080.0;
+use crate::vec2::Real;
+
+pub const WIDTH: Real = 1920.0;
+pub const HEIGHT: Real = 1080.0;
 pub const SAVEFILE: &str = "./save.json";
+pub const PREFABFILE: &str = "./prefab.json";
+pub const KEYBINDINGS: &str = "./keybindings.toml";
+pub const CONFIG: &str = "./config.toml";
+pub const LOGFILE: &str = "./soft.log";
+pub const SCREENSHOTS: &str = "./screenshots";
+pub const RECORDINGS: &str = "./recordings";
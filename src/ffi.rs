@@ -0,0 +1,168 @@
+use crate::{
+    vec2::{to_f64, Real, Vec2},
+    world::World,
+};
+use std::ptr;
+
+/// The simulation as handed out to C. Only ever handled through a pointer, and every
+/// function accepts a null one too.
+pub struct SoftWorld(World);
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SoftStatus {
+    Ok = 0,
+    NullWorld,
+    /// Out of range, too small or degenerate
+    InvalidArgument,
+    /// A spring stretched so far the step was abandoned, the world should be reset
+    Unstable,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SoftParticle {
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+}
+
+/// An empty world of `width` by `height`, to be released with [`soft_world_free`].
+#[no_mangle]
+pub extern "C" fn soft_world_new(width: f64, height: f64) -> *mut SoftWorld {
+    Box::into_raw(Box::new(SoftWorld(World::new(
+        width as Real,
+        height as Real,
+    ))))
+}
+
+/// # Safety
+/// `world` has to come from [`soft_world_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_free(world: *mut SoftWorld) {
+    if !world.is_null() {
+        drop(Box::from_raw(world));
+    }
+}
+
+/// A `w` by `h` lattice of particles with its top left one at `x`, `y`.
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_spawn_rect(
+    world: *mut SoftWorld,
+    w: usize,
+    h: usize,
+    x: f64,
+    y: f64,
+) -> SoftStatus {
+    let Some(SoftWorld(world)) = world.as_mut() else {
+        return SoftStatus::NullWorld;
+    };
+    match world.spawn_rect(w, h, x as Real, y as Real) {
+        Ok(()) => SoftStatus::Ok,
+        Err(_) => SoftStatus::InvalidArgument,
+    }
+}
+
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_add_edge(
+    world: *mut SoftWorld,
+    x1: f64,
+    y1: f64,
+    x2: f64,
+    y2: f64,
+) -> SoftStatus {
+    let Some(SoftWorld(world)) = world.as_mut() else {
+        return SoftStatus::NullWorld;
+    };
+    match world.add_edge(
+        Vec2::new(x1 as Real, y1 as Real),
+        Vec2::new(x2 as Real, y2 as Real),
+    ) {
+        Ok(()) => SoftStatus::Ok,
+        Err(_) => SoftStatus::InvalidArgument,
+    }
+}
+
+/// Advances exactly `n` physics steps, the same ones on every run.
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_step(world: *mut SoftWorld, n: usize) -> SoftStatus {
+    let Some(SoftWorld(world)) = world.as_mut() else {
+        return SoftStatus::NullWorld;
+    };
+    match world.step_n(n) {
+        Ok(()) => SoftStatus::Ok,
+        Err(_) => SoftStatus::Unstable,
+    }
+}
+
+/// Seconds simulated so far, zero for a null world.
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_time(world: *const SoftWorld) -> f64 {
+    world
+        .as_ref()
+        .map_or(0.0, |SoftWorld(world)| to_f64(world.time()))
+}
+
+/// Zero for a null world.
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_particle_count(world: *const SoftWorld) -> usize {
+    world.as_ref().map_or(0, |SoftWorld(world)| world.info().0)
+}
+
+/// Copies up to `capacity` particles into `out`, returning how many were written.
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`], and `out` has to have room for
+/// `capacity` particles.
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_read_particles(
+    world: *const SoftWorld,
+    out: *mut SoftParticle,
+    capacity: usize,
+) -> usize {
+    let Some(SoftWorld(world)) = world.as_ref() else {
+        return 0;
+    };
+    if out.is_null() {
+        return 0;
+    }
+
+    let particles = world.particles_iter().take(capacity).map(|p| SoftParticle {
+        x: to_f64(p.get_pos().x),
+        y: to_f64(p.get_pos().y),
+        vx: to_f64(p.get_vel().x),
+        vy: to_f64(p.get_vel().y),
+    });
+    let mut written = 0;
+    for particle in particles {
+        ptr::write(out.add(written), particle);
+        written += 1;
+    }
+    written
+}
+
+/// Changes whenever the simulated state does, see [`World::state_hash`].
+///
+/// # Safety
+/// `world` has to be null or come from [`soft_world_new`].
+#[no_mangle]
+pub unsafe extern "C" fn soft_world_state_hash(world: *const SoftWorld) -> u64 {
+    world
+        .as_ref()
+        .map_or(0, |SoftWorld(world)| world.state_hash())
+}
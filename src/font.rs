@@ -0,0 +1,73 @@
+use crate::vec2::{Real, Vec2};
+use fontdue::{FontSettings, Metrics};
+use std::collections::HashMap;
+
+/// TrueType text rasterizer with a cache of the glyphs it has already rendered.
+pub struct Font {
+    font: fontdue::Font,
+    glyphs: HashMap<(char, u32), (Metrics, Vec<u8>)>,
+}
+
+impl Font {
+    /// Loads the monospace font egui ships with, so the world and the gui share a typeface.
+    pub fn new() -> Self {
+        let fonts = egui::FontDefinitions::default();
+        let data = &fonts.font_data["Hack"];
+
+        Self {
+            font: fontdue::Font::from_bytes(
+                &*data.font,
+                FontSettings {
+                    collection_index: data.index,
+                    ..FontSettings::default()
+                },
+            )
+            .expect("the bundled font should be valid"),
+            glyphs: HashMap::new(),
+        }
+    }
+
+    /// Lays out `text` with its top left corner at `pos` and calls `pixel` with the
+    /// coverage of every pixel it touches.
+    pub fn rasterize(
+        &mut self,
+        pos: Vec2,
+        text: &str,
+        size: Real,
+        mut pixel: impl FnMut(i64, i64, u8),
+    ) {
+        let px = size as f32;
+        let Some(line) = self.font.horizontal_line_metrics(px) else {
+            return;
+        };
+
+        let mut x = pos.x.round() as i64;
+        let mut baseline = (pos.y + Real::from(line.ascent)).round() as i64;
+
+        for c in text.chars() {
+            if c == '\n' {
+                x = pos.x.round() as i64;
+                baseline += line.new_line_size.round() as i64;
+                continue;
+            }
+
+            let font = &self.font;
+            let (metrics, coverage) = self
+                .glyphs
+                .entry((c, px.to_bits()))
+                .or_insert_with(|| font.rasterize(c, px));
+
+            let left = x + metrics.xmin as i64;
+            let top = baseline - (metrics.height as i64 + metrics.ymin as i64);
+
+            for (i, &alpha) in coverage.iter().enumerate() {
+                if alpha != 0 {
+                    let (col, row) = (i % metrics.width, i / metrics.width);
+                    pixel(left + col as i64, top + row as i64, alpha);
+                }
+            }
+
+            x += metrics.advance_width.round() as i64;
+        }
+    }
+}
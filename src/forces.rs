@@ -0,0 +1,41 @@
+use crate::{
+    vec2::{Real, Vec2},
+    world::ParticleView,
+};
+
+/// Something pushing particles around every physics step, registered on a world with
+/// [`crate::world::World::add_force_generator`]. Runs after springs and before collisions,
+/// on the physics thread.
+pub trait ForceGenerator: Send {
+    fn apply(&mut self, particles: &mut ParticleView, dt: Real);
+}
+
+/// The same acceleration for every particle, whatever its mass. The world applies the one
+/// from its settings itself.
+#[derive(Clone, Copy, Debug)]
+pub struct Gravity(pub Vec2);
+
+impl ForceGenerator for Gravity {
+    fn apply(&mut self, particles: &mut ParticleView, _dt: Real) {
+        for n in 0..particles.len() {
+            particles.accelerate(n, self.0);
+        }
+    }
+}
+
+/// Air moving at `velocity`, dragging particles along the harder the more their own
+/// velocity differs from it.
+#[derive(Clone, Copy, Debug)]
+pub struct Wind {
+    pub velocity: Vec2,
+    pub drag: Real,
+}
+
+impl ForceGenerator for Wind {
+    fn apply(&mut self, particles: &mut ParticleView, _dt: Real) {
+        for n in 0..particles.len() {
+            let force = (self.velocity - particles.vel(n)) * self.drag;
+            particles.apply_force(n, force);
+        }
+    }
+}
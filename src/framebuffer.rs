@@ -0,0 +1,400 @@
+use crate::{
+    capture,
+    font::Font,
+    renderer::{BlendMode, Color, Renderer},
+    texture::Texture,
+    vec2::{Real, Vec2},
+};
+use std::path::Path;
+
+/// CPU rasterizer drawing into an RGBA8 pixel buffer, independent of `sdl2_gfx`.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+    color: Color,
+    blend_mode: BlendMode,
+    font: Font,
+    // Kept between draw calls so drawing a frame doesn't allocate once they've grown
+    vertices: Vec<Vec2>,
+    crossings: Vec<Real>,
+    covered: Vec<(i64, i64, u8)>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+            color: Color::BLACK,
+            blend_mode: BlendMode::Blend,
+            font: Font::new(),
+            vertices: vec![],
+            crossings: vec![],
+            covered: vec![],
+        }
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.pixels.clear();
+        self.pixels.resize(width * height * 4, 0);
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn write_png(&self, path: &Path) -> Result<(), String> {
+        capture::write_png(path, self.width, self.height, &self.pixels)
+    }
+
+    /// How many pixels differ from the PNG at `path` by more than `tolerance` in any channel,
+    /// for checking drawing code against images known to be right.
+    pub fn compare_png(&self, path: &Path, tolerance: u8) -> Result<usize, String> {
+        let image = Texture::load(path)?;
+        if image.size() != (self.width, self.height) {
+            return Err(format!(
+                "{} is {:?}, not {:?}",
+                path.display(),
+                image.size(),
+                (self.width, self.height)
+            ));
+        }
+
+        let differing = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                let Color { r, g, b, a } = image.texel(x, y);
+                let i = (y * self.width + x) * 4;
+                self.pixels[i..i + 4]
+                    .iter()
+                    .zip([r, g, b, a])
+                    .any(|(&p, q)| p.abs_diff(q) > tolerance)
+            })
+            .count();
+        Ok(differing)
+    }
+
+    /// Checks the pixels against `tests/golden/{name}.png`, or writes them there when
+    /// `SOFT_BLESS` is set, after a change to how things look.
+    #[cfg(test)]
+    pub fn assert_golden(&self, name: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{name}.png"));
+        if std::env::var_os("SOFT_BLESS").is_some() {
+            self.write_png(&path).unwrap();
+            return;
+        }
+
+        let differing = self
+            .compare_png(&path, 2)
+            .unwrap_or_else(|err| panic!("{err}, run with SOFT_BLESS=1 to write it"));
+        if differing > 0 {
+            let actual = std::env::temp_dir().join(format!("{name}.png"));
+            let _ = self.write_png(&actual);
+            panic!(
+                "{differing} pixels differ from {}, drawn into {}",
+                path.display(),
+                actual.display()
+            );
+        }
+    }
+
+    fn blend(&mut self, x: i64, y: i64) {
+        self.blend_coverage(x, y, 1.0);
+    }
+
+    /// Blends the current color into a pixel, with its alpha scaled by `coverage` in 0..=1.
+    fn blend_coverage(&mut self, x: i64, y: i64, coverage: Real) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+
+        let i = (y as usize * self.width + x as usize) * 4;
+        let Color { r, g, b, a } = self.color;
+        let a = (Real::from(a) * coverage.clamp(0.0, 1.0)).round() as u8;
+
+        match self.blend_mode {
+            BlendMode::None => self.pixels[i..i + 4].copy_from_slice(&[r, g, b, a]),
+            BlendMode::Blend if a == 255 => {
+                self.pixels[i..i + 4].copy_from_slice(&[r, g, b, 255]);
+            }
+            BlendMode::Blend => {
+                let alpha = u32::from(a);
+                for (dst, src) in self.pixels[i..i + 3].iter_mut().zip([r, g, b]) {
+                    *dst = ((u32::from(src) * alpha + u32::from(*dst) * (255 - alpha)) / 255) as u8;
+                }
+                self.pixels[i + 3] = self.pixels[i + 3].max(a);
+            }
+            BlendMode::Add => {
+                let alpha = u32::from(a);
+                for (dst, src) in self.pixels[i..i + 3].iter_mut().zip([r, g, b]) {
+                    *dst = (u32::from(*dst) + u32::from(src) * alpha / 255).min(255) as u8;
+                }
+            }
+        }
+    }
+
+    fn hline(&mut self, x0: i64, x1: i64, y: i64) {
+        if y < 0 || y >= self.height as i64 {
+            return;
+        }
+
+        for x in x0.max(0)..=x1.min(self.width as i64 - 1) {
+            self.blend(x, y);
+        }
+    }
+
+    /// Even-odd fill of a polygon, `crossings` is scratch space.
+    fn scanline_fill(&mut self, vertices: &[Vec2], crossings: &mut Vec<Real>) {
+        if vertices.len() < 3 {
+            return;
+        }
+
+        let top = vertices.iter().map(|v| v.y).fold(Real::INFINITY, Real::min);
+        let bottom = vertices
+            .iter()
+            .map(|v| v.y)
+            .fold(Real::NEG_INFINITY, Real::max);
+
+        for y in (top.floor() as i64).max(0)..=(bottom.ceil() as i64).min(self.height as i64) {
+            // Sample at the pixel center so shared edges are only filled once
+            let sy = y as Real + 0.5;
+            crossings.clear();
+
+            for (i, &a) in vertices.iter().enumerate() {
+                let b = vertices[(i + 1) % vertices.len()];
+                if (a.y <= sy) != (b.y <= sy) {
+                    crossings.push(a.x + (sy - a.y) / (b.y - a.y) * (b.x - a.x));
+                }
+            }
+
+            crossings.sort_by(Real::total_cmp);
+            for span in crossings.chunks_exact(2) {
+                self.hline(span[0].round() as i64, span[1].round() as i64 - 1, y);
+            }
+        }
+    }
+}
+
+impl Renderer for Framebuffer {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let (cx, cy) = (center.x.round() as i64, center.y.round() as i64);
+        let r = radius.round() as i64;
+
+        for dy in -r..=r {
+            let dx = ((r * r - dy * dy) as Real).sqrt().round() as i64;
+            self.hline(cx - dx, cx + dx, cy + dy);
+        }
+
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (mut x0, mut y0) = (a.x.round() as i64, a.y.round() as i64);
+        let (x1, y1) = (b.x.round() as i64, b.y.round() as i64);
+
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let mut err = dx + dy;
+
+        loop {
+            self.blend(x0, y0);
+            if x0 == x1 && y0 == y1 {
+                break;
+            }
+
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x0 += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y0 += sy;
+            }
+        }
+
+        self
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        if thickness <= 1.0 || a == b {
+            return self.line(a, b);
+        }
+
+        let offset = (b - a).normal() * (thickness / 2.0);
+        self.filled_polygon([a + offset, b + offset, b - offset, a - offset].into_iter())
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (c, d) = (Vec2::new(b.x, a.y), Vec2::new(a.x, b.y));
+        self.polygon([a, c, b, d].into_iter())
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.filled_rounded_rectangle(a, b, 0.0)
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        let (min, max) = (a.min(b), a.max(b));
+        let (x0, x1) = (min.x.round() as i64, max.x.round() as i64);
+        let (y0, y1) = (min.y.round() as i64, max.y.round() as i64);
+        let r = radius.min((max.x - min.x) / 2.0).min((max.y - min.y) / 2.0);
+
+        for y in y0..=y1 {
+            // Distance into the corner arc, measured from the nearest horizontal edge
+            let dy = (r - (y - y0).min(y1 - y) as Real).max(0.0);
+            let inset = (r - (r * r - dy * dy).max(0.0).sqrt()).round() as i64;
+            self.hline(x0 + inset, x1 - inset, y);
+        }
+
+        self
+    }
+
+    fn polygon(&mut self, mut vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let Some(first) = vertices.next() else {
+            return self;
+        };
+
+        let mut a = first;
+        for b in vertices.chain([first]) {
+            self.line(a, b);
+            a = b;
+        }
+
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let (mut scratch, mut crossings) = (
+            std::mem::take(&mut self.vertices),
+            std::mem::take(&mut self.crossings),
+        );
+        scratch.clear();
+        scratch.extend(vertices);
+        self.scanline_fill(&scratch, &mut crossings);
+        (self.vertices, self.crossings) = (scratch, crossings);
+
+        self
+    }
+
+    /// Xiaolin Wu's line, coverage split between the two pixels straddling the ideal line.
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+        let (mut a, mut b) = if steep {
+            (Vec2::new(a.y, a.x), Vec2::new(b.y, b.x))
+        } else {
+            (a, b)
+        };
+        if a.x > b.x {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        let gradient = if b.x - a.x == 0.0 {
+            1.0
+        } else {
+            (b.y - a.y) / (b.x - a.x)
+        };
+
+        let mut y = a.y + gradient * (a.x.round() - a.x);
+        for x in (a.x.round() as i64)..=(b.x.round() as i64) {
+            let (row, fract) = (y.floor() as i64, y - y.floor());
+
+            if steep {
+                self.blend_coverage(row, x, 1.0 - fract);
+                self.blend_coverage(row + 1, x, fract);
+            } else {
+                self.blend_coverage(x, row, 1.0 - fract);
+                self.blend_coverage(x, row + 1, fract);
+            }
+
+            y += gradient;
+        }
+
+        self
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let top = (center.y - radius).floor() as i64;
+        let bottom = (center.y + radius).ceil() as i64;
+
+        for y in top..=bottom {
+            let dy = y as Real + 0.5 - center.y;
+            if dy.abs() > radius {
+                continue;
+            }
+
+            let half = (radius * radius - dy * dy).sqrt();
+            let (left, right) = (center.x - half, center.x + half);
+
+            // Solid span, then partially covered pixels at both ends
+            self.hline(left.ceil() as i64, right.floor() as i64 - 1, y);
+            self.blend_coverage(left.floor() as i64, y, left.ceil() - left);
+            self.blend_coverage(right.floor() as i64, y, right - right.floor());
+        }
+
+        self
+    }
+
+    fn aa_polygon(&mut self, mut vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let Some(first) = vertices.next() else {
+            return self;
+        };
+
+        let mut a = first;
+        for b in vertices.chain([first]) {
+            self.aa_line(a, b);
+            a = b;
+        }
+
+        self
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        self.blend_mode = mode;
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        let mut covered = std::mem::take(&mut self.covered);
+        covered.clear();
+        self.font
+            .rasterize(pos, text, size, |x, y, alpha| covered.push((x, y, alpha)));
+
+        for &(x, y, alpha) in &covered {
+            self.blend_coverage(x, y, Real::from(alpha) / 255.0);
+        }
+        self.covered = covered;
+
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        Some((self.width, self.height, self.pixels.clone()))
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        let Color { r, g, b, a } = self.color;
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[r, g, b, a]);
+        }
+
+        self
+    }
+
+    fn finish(&mut self) {}
+}
@@ -0,0 +1,133 @@
+use crate::{
+    keybindings::Action,
+    vec2::{Real, Vec2},
+};
+use sdl2::{
+    controller::{Axis, Button, GameController},
+    event::Event,
+    GameControllerSubsystem, Sdl,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+pub enum GamepadInput {
+    Action(Action),
+    TriggerDown(Trigger),
+    TriggerUp(Trigger),
+}
+
+/// Game controllers, translated into the same [`Action`]s the keyboard produces plus a
+/// stick-driven cursor standing in for the mouse.
+pub struct Gamepad {
+    subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+    cursor: Option<Vec2>,
+    triggers: [bool; 2],
+}
+
+impl Gamepad {
+    const DEADZONE: i16 = 8000;
+    // Trigger travel past which it counts as pressed, and back under which it's released
+    const TRIGGER_DOWN: i16 = 16000;
+    const TRIGGER_UP: i16 = 8000;
+    const CURSOR_SPEED: Real = 900.0;
+
+    pub fn new(ctx: &Sdl) -> Result<Self, String> {
+        Ok(Self {
+            subsystem: ctx.game_controller()?,
+            controllers: vec![],
+            cursor: None,
+            triggers: [false; 2],
+        })
+    }
+
+    fn action(button: Button) -> Option<Action> {
+        Some(match button {
+            Button::A => Action::ToggleSimulation,
+            Button::B => Action::RemoveLast,
+            Button::X => Action::ToggleParticles,
+            Button::Y => Action::ToggleSprings,
+            Button::Back => Action::ToggleLog,
+            Button::Start => Action::ToggleSettings,
+            Button::DPadLeft => Action::SpeedNormal,
+            Button::DPadRight => Action::ToggleFastForward,
+            Button::DPadUp => Action::SelectTool,
+            Button::DPadDown => Action::PinTool,
+            Button::LeftShoulder => Action::DrawEdgeTool,
+            Button::RightShoulder => Action::DrawRopeTool,
+            _ => return None,
+        })
+    }
+
+    /// Handles controller hotplugging and turns button and trigger events into input.
+    pub fn handle_event(&mut self, event: &Event) -> Result<Option<GamepadInput>, String> {
+        match *event {
+            Event::ControllerDeviceAdded { which, .. } => {
+                let controller = self.subsystem.open(which).map_err(|err| err.to_string())?;
+                self.controllers.push(controller);
+                Ok(None)
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.controllers.retain(|c| c.instance_id() != which);
+                Ok(None)
+            }
+            Event::ControllerButtonDown { button, .. } => {
+                Ok(Self::action(button).map(GamepadInput::Action))
+            }
+            Event::ControllerAxisMotion { axis, value, .. } => {
+                let trigger = match axis {
+                    Axis::TriggerLeft => Trigger::Left,
+                    Axis::TriggerRight => Trigger::Right,
+                    _ => return Ok(None),
+                };
+
+                let pressed = &mut self.triggers[trigger as usize];
+                if !*pressed && value > Self::TRIGGER_DOWN {
+                    *pressed = true;
+                    Ok(Some(GamepadInput::TriggerDown(trigger)))
+                } else if *pressed && value < Self::TRIGGER_UP {
+                    *pressed = false;
+                    Ok(Some(GamepadInput::TriggerUp(trigger)))
+                } else {
+                    Ok(None)
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Moves the cursor with the right stick, starting from `from` the first time the
+    /// stick leaves its dead zone. The left stick is reserved for panning the view.
+    pub fn update_cursor(&mut self, from: Vec2, size: Vec2, dt: Real) {
+        let stick = |value: i16| {
+            if value.unsigned_abs() < Self::DEADZONE.unsigned_abs() {
+                0.0
+            } else {
+                Real::from(value) / Real::from(i16::MAX)
+            }
+        };
+
+        let dir = self.controllers.iter().fold(Vec2::null(), |acc, c| {
+            acc + Vec2::new(stick(c.axis(Axis::RightX)), stick(c.axis(Axis::RightY)))
+        });
+        if dir == Vec2::null() {
+            return;
+        }
+
+        let cursor = self.cursor.unwrap_or(from) + dir * Self::CURSOR_SPEED * dt;
+        self.cursor = Some(cursor.clamp(Vec2::null(), size));
+    }
+
+    pub fn cursor(&self) -> Option<Vec2> {
+        self.cursor
+    }
+
+    /// Hands pointing back to the mouse.
+    pub fn hide_cursor(&mut self) {
+        self.cursor = None;
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{
+    renderer::{Color, Renderer},
+    vec2::{Real, Vec2},
+};
+use egui::{
+    epaint::{ColorMode, RectShape, TextShape},
+    Color32, Modifiers, MouseWheelUnit, PointerButton, Pos2, RawInput, Rect, Shape,
+};
+use sdl2::{event::Event, mouse::MouseButton};
+
+pub struct Gui {
+    ctx: egui::Context,
+    events: Vec<egui::Event>,
+    pub visible: bool,
+}
+
+impl From<Color32> for Color {
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        Self::RGBA(r, g, b, a)
+    }
+}
+
+impl From<Color> for Color32 {
+    fn from(Color { r, g, b, a }: Color) -> Self {
+        Self::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+impl From<Pos2> for Vec2 {
+    fn from(pos: Pos2) -> Self {
+        Self::new(Real::from(pos.x), Real::from(pos.y))
+    }
+}
+
+impl Gui {
+    const FONT_SIZE: f32 = 13.0;
+
+    pub fn new() -> Self {
+        let ctx = egui::Context::default();
+        ctx.all_styles_mut(|style| {
+            for font in style.text_styles.values_mut() {
+                font.size = Self::FONT_SIZE;
+            }
+        });
+
+        Self {
+            ctx,
+            events: vec![],
+            visible: false,
+        }
+    }
+
+    pub fn set_light(&self, light: bool) {
+        self.ctx.set_visuals(if light {
+            egui::Visuals::light()
+        } else {
+            egui::Visuals::dark()
+        });
+    }
+
+    /// Feeds an input event to the gui and returns whether the gui consumed it.
+    pub fn handle_event(&mut self, event: &Event) -> bool {
+        if !self.visible {
+            return false;
+        }
+
+        let pointer_event = match *event {
+            Event::MouseMotion { x, y, .. } => {
+                self.events
+                    .push(egui::Event::PointerMoved(Pos2::new(x as f32, y as f32)));
+                true
+            }
+            Event::MouseButtonDown {
+                mouse_btn, x, y, ..
+            }
+            | Event::MouseButtonUp {
+                mouse_btn, x, y, ..
+            } => {
+                let button = match mouse_btn {
+                    MouseButton::Right => PointerButton::Secondary,
+                    MouseButton::Middle => PointerButton::Middle,
+                    _ => PointerButton::Primary,
+                };
+
+                self.events.push(egui::Event::PointerButton {
+                    pos: Pos2::new(x as f32, y as f32),
+                    button,
+                    pressed: matches!(event, Event::MouseButtonDown { .. }),
+                    modifiers: Modifiers::default(),
+                });
+                true
+            }
+            Event::MouseWheel { x, y, .. } => {
+                self.events.push(egui::Event::MouseWheel {
+                    unit: MouseWheelUnit::Line,
+                    delta: egui::vec2(x as f32, y as f32),
+                    modifiers: Modifiers::default(),
+                });
+                true
+            }
+            Event::TextInput { ref text, .. } => {
+                self.events.push(egui::Event::Text(text.clone()));
+                false
+            }
+            _ => false,
+        };
+
+        if pointer_event {
+            self.ctx.is_pointer_over_area() || self.ctx.is_using_pointer()
+        } else {
+            self.ctx.wants_keyboard_input()
+        }
+    }
+
+    pub fn frame(&mut self, canvas: &mut impl Renderer, run_ui: impl FnMut(&egui::Context)) {
+        if !self.visible {
+            self.events.clear();
+            return;
+        }
+
+        let (w, h) = canvas.size();
+        let input = RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                egui::vec2(w as f32, h as f32),
+            )),
+            events: std::mem::take(&mut self.events),
+            ..RawInput::default()
+        };
+
+        let output = self.ctx.run(input, run_ui);
+
+        for clipped in &output.shapes {
+            Self::paint(&clipped.shape, canvas);
+        }
+    }
+
+    fn paint(shape: &Shape, canvas: &mut impl Renderer) {
+        match shape {
+            Shape::Vec(shapes) => {
+                for shape in shapes {
+                    Self::paint(shape, canvas);
+                }
+            }
+            Shape::Circle(circle) => {
+                let center = circle.center.into();
+                let radius = Real::from(circle.radius);
+
+                if circle.fill != Color32::TRANSPARENT {
+                    canvas
+                        .set_color(circle.fill.into())
+                        .filled_circle(center, radius);
+                }
+                if !circle.stroke.is_empty() {
+                    canvas.set_color(circle.stroke.color.into()).polygon(
+                        (0..16).map(|i| center + Vec2::from_angle_deg(22.5 * i as Real) * radius),
+                    );
+                }
+            }
+            Shape::LineSegment { points, stroke } => {
+                canvas.set_color(stroke.color.into()).thick_line(
+                    points[0].into(),
+                    points[1].into(),
+                    Real::from(stroke.width).max(1.0),
+                );
+            }
+            Shape::Path(path) => {
+                let points = path.points.iter().map(|&p| Vec2::from(p));
+
+                if path.closed && path.fill != Color32::TRANSPARENT {
+                    canvas
+                        .set_color(path.fill.into())
+                        .filled_polygon(points.clone());
+                }
+                if let ColorMode::Solid(color) = path.stroke.color {
+                    if path.stroke.width > 0.0 && color != Color32::TRANSPARENT {
+                        canvas.set_color(color.into());
+                        if path.closed {
+                            canvas.polygon(points);
+                        } else {
+                            canvas.polyline(points);
+                        }
+                    }
+                }
+            }
+            Shape::Rect(rect) => Self::paint_rect(rect, canvas),
+            Shape::Text(text) => Self::paint_text(text, canvas),
+            _ => {}
+        }
+    }
+
+    fn paint_rect(rect: &RectShape, canvas: &mut impl Renderer) {
+        let a = rect.rect.min.into();
+        let b = rect.rect.max.into();
+
+        if rect.fill != Color32::TRANSPARENT {
+            canvas.set_color(rect.fill.into()).filled_rounded_rectangle(
+                a,
+                b,
+                Real::from(rect.corner_radius.nw),
+            );
+        }
+        if !rect.stroke.is_empty() {
+            canvas.set_color(rect.stroke.color.into()).rectangle(a, b);
+        }
+    }
+
+    fn paint_text(text: &TextShape, canvas: &mut impl Renderer) {
+        let mut buf = [0; 4];
+
+        for row in &text.galley.rows {
+            for glyph in &row.glyphs {
+                let color = text.override_text_color.unwrap_or_else(|| {
+                    row.visuals
+                        .mesh
+                        .vertices
+                        .get(glyph.first_vertex as usize)
+                        .map_or(text.fallback_color, |v| v.color)
+                });
+
+                // Glyph positions are on the baseline, text_sized wants the top left corner
+                let pos = Vec2::from(text.pos) + Vec2::from(row.pos) + Vec2::from(glyph.pos)
+                    - Vec2::new(0.0, Real::from(glyph.font_ascent));
+
+                canvas.set_color(color.into()).text_sized(
+                    pos,
+                    glyph.chr.encode_utf8(&mut buf),
+                    Real::from(Self::FONT_SIZE),
+                );
+            }
+        }
+    }
+}
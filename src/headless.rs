@@ -0,0 +1,125 @@
+use crate::{
+    cli::Args,
+    framebuffer::Framebuffer,
+    renderer::Renderer,
+    savefile::Format,
+    scene::{RandomScene, ScenePreset, PRESETS},
+    vec2::{Real, Vec2},
+    world::World,
+};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Only the world out of a savefile, the rest of the app state isn't drawn.
+#[derive(Deserialize)]
+struct Scene {
+    world: World,
+}
+
+/// Draws the scene picked by `args` into the PNG at `path` with the software rasterizer,
+/// checking it against `args.golden` if given. Nothing here needs a window or a GPU, so
+/// it runs in CI to catch drawing regressions and makes the images for the docs.
+pub fn render(args: &Args, path: &Path) -> Result<(), String> {
+    let (w, h) = (args.width as usize, args.height as usize);
+    let mut world = load(args, Vec2::new(w as Real, h as Real))?;
+    if let Some(err) = world.load_textures().into_iter().next() {
+        return Err(format!("could not load texture {err}"));
+    }
+    world
+        .step_n(args.render_steps)
+        .map_err(|err| format!("simulation blew up, diff_len={}", err.diff_len))?;
+
+    // Not the theme from the config, images for comparing have to come out the same anywhere
+    let theme = args.theme.unwrap_or_default().theme();
+    let mut canvas = Framebuffer::new(w, h);
+    canvas.set_color(theme.background).clear();
+    world.draw_drains(theme, &mut canvas);
+    world.draw_gravity_zones(theme, &mut canvas);
+    world.draw_polys(theme, &mut canvas);
+    world.draw_rods(theme, &mut canvas);
+    world.draw_pins(theme, &mut canvas);
+    world.draw_edges(theme, &mut canvas);
+    world.draw_movers(theme, &mut canvas);
+    world.draw_emitters(theme, &mut canvas);
+    canvas.write_png(path)?;
+
+    if let Some(golden) = &args.golden {
+        let differing = canvas.compare_png(golden, args.golden_tolerance)?;
+        if differing > 0 {
+            return Err(format!(
+                "{differing} pixels of {} differ from {}",
+                path.display(),
+                golden.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The `--scene` file, the random scene or else the first preset, never the savefile.
+fn load(args: &Args, size: Vec2) -> Result<World, String> {
+    if let Some(path) = &args.scene {
+        let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+        let Scene { mut world } = Format::from_path(path).decode(&bytes)?;
+        // Drawn from the top left corner either way, only ones saved without a size of their
+        // own take the image's
+        let size = match world.size() {
+            saved if saved == Vec2::null() => size,
+            saved => saved,
+        };
+        world.resize(size.x, size.y);
+        return Ok(world);
+    }
+
+    let mut world = World::new(size.x, size.y);
+    match args.random {
+        Some(count) => RandomScene {
+            count,
+            seed: args.seed,
+        }
+        .build(&mut world, size),
+        None => PRESETS[0].build(&mut world, size),
+    }
+    Ok(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scene::StackedBoxes, theme::ThemeKind};
+
+    const SIZE: Vec2 = Vec2::new(960.0, 540.0);
+
+    fn canvas() -> Framebuffer {
+        let mut canvas = Framebuffer::new(SIZE.x as usize, SIZE.y as usize);
+        canvas
+            .set_color(ThemeKind::default().theme().background)
+            .clear();
+        canvas
+    }
+
+    #[test]
+    fn polys_look_the_same() {
+        let mut world = World::new(SIZE.x, SIZE.y);
+        StackedBoxes.build(&mut world, SIZE);
+        world.step_n(400).unwrap();
+
+        let mut canvas = canvas();
+        world.draw_polys(ThemeKind::default().theme(), &mut canvas);
+        canvas.assert_golden("polys");
+    }
+
+    #[test]
+    fn edges_look_the_same() {
+        let mut world = World::new(SIZE.x, SIZE.y);
+        PRESETS[0].build(&mut world, SIZE);
+        world
+            .add_edge(Vec2::new(100.0, 100.0), Vec2::new(300.0, 160.0))
+            .unwrap();
+        world.toggle_one_way(world.edges_iter().count() - 1);
+
+        let mut canvas = canvas();
+        world.draw_edges(ThemeKind::default().theme(), &mut canvas);
+        canvas.assert_golden("edges");
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    texture::Texture,
+    vec2::{Real, Vec2},
+};
+use std::{collections::HashMap, path::Path};
+
+/// Outline of the largest shape in the PNG at `path`, in pixels from its top left corner,
+/// for spawning it with [`crate::world::World::spawn_outline`]. Pixels at least
+/// `threshold` opaque belong to shapes, or at least that dark in images without any
+/// transparency. Traced with marching squares, between pixel centers. Holes are left out.
+pub fn silhouette(path: &Path, threshold: Real) -> Result<Vec<Vec2>, String> {
+    let texture = Texture::load(path)?;
+    let (width, height) = texture.size();
+
+    let opaque = (0..height).all(|y| (0..width).all(|x| texture.texel(x, y).a == 255));
+    let coverage = |x: usize, y: usize| {
+        let texel = texture.texel(x, y);
+        if opaque {
+            let luma = 0.299 * Real::from(texel.r)
+                + 0.587 * Real::from(texel.g)
+                + 0.114 * Real::from(texel.b);
+            1.0 - luma / 255.0
+        } else {
+            Real::from(texel.a) / 255.0
+        }
+    };
+
+    // A ring of empty samples around the image, so shapes touching its borders close too
+    let (w, h) = (width + 2, height + 2);
+    let mut samples = vec![0.0; w * h];
+    for y in 0..height {
+        for x in 0..width {
+            samples[(y + 1) * w + x + 1] = coverage(x, y);
+        }
+    }
+
+    march(&samples, w, h, threshold)
+        .into_iter()
+        .max_by(|a, b| area(a).abs().total_cmp(&area(b).abs()))
+        .ok_or_else(|| String::from("image has no shape in it"))
+}
+
+/// Every contour at `level` through the `w` by `h` grid of `samples`, as loops of points.
+fn march(samples: &[Real], w: usize, h: usize, level: Real) -> Vec<Vec<Vec2>> {
+    // Edges of the grid are numbered twice their first sample, plus one for vertical ones
+    let horizontal = |x: usize, y: usize| 2 * (y * w + x);
+    let vertical = |x: usize, y: usize| 2 * (y * w + x) + 1;
+    let crossing = |edge: usize| {
+        let (x, y) = ((edge / 2) % w, (edge / 2) / w);
+        let (other, dir) = if edge.is_multiple_of(2) {
+            (edge / 2 + 1, Vec2::new(1.0, 0.0))
+        } else {
+            (edge / 2 + w, Vec2::new(0.0, 1.0))
+        };
+        let (a, b) = (samples[edge / 2], samples[other]);
+        // Pixel centers, one in from the padding
+        Vec2::new(x as Real - 0.5, y as Real - 0.5) + dir * ((level - a) / (b - a))
+    };
+
+    // Every crossing is shared by exactly two segments of the contour through it
+    let mut links: HashMap<usize, Vec<usize>> = HashMap::new();
+    for y in 0..h - 1 {
+        for x in 0..w - 1 {
+            let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            let [tl, tr, br, bl] = corners.map(|(x, y)| samples[y * w + x] >= level);
+            let (top, right) = (horizontal(x, y), vertical(x + 1, y));
+            let (bottom, left) = (horizontal(x, y + 1), vertical(x, y));
+
+            // Diagonal corners alone inside are joined if the middle of the cell is too
+            let center = corners
+                .iter()
+                .map(|&(x, y)| samples[y * w + x])
+                .sum::<Real>()
+                / 4.0;
+            let joined = center >= level;
+
+            let segments: &[(usize, usize)] = match (tl, tr, br, bl) {
+                (false, false, false, false) | (true, true, true, true) => &[],
+                (false, false, false, true) | (true, true, true, false) => &[(left, bottom)],
+                (false, false, true, false) | (true, true, false, true) => &[(bottom, right)],
+                (false, false, true, true) | (true, true, false, false) => &[(left, right)],
+                (false, true, false, false) | (true, false, true, true) => &[(top, right)],
+                (false, true, true, false) | (true, false, false, true) => &[(top, bottom)],
+                (false, true, true, true) | (true, false, false, false) => &[(top, left)],
+                (false, true, false, true) if joined => &[(top, left), (bottom, right)],
+                (true, false, true, false) if !joined => &[(top, left), (bottom, right)],
+                (false, true, false, true) | (true, false, true, false) => {
+                    &[(top, right), (left, bottom)]
+                }
+            };
+            for &(a, b) in segments {
+                links.entry(a).or_default().push(b);
+                links.entry(b).or_default().push(a);
+            }
+        }
+    }
+
+    let mut starts: Vec<usize> = links.keys().copied().collect();
+    // Walked in a fixed order, the map alone would pick different starting points every run
+    starts.sort_unstable();
+
+    let mut contours = vec![];
+    for start in starts {
+        let Some(neighbors) = links.remove(&start) else {
+            continue;
+        };
+        let mut contour = vec![crossing(start)];
+        let mut at = neighbors[0];
+
+        // Around until the only way on leads back to the start
+        while let Some(neighbors) = links.remove(&at) {
+            contour.push(crossing(at));
+            match neighbors.iter().find(|&to| links.contains_key(to)) {
+                Some(&to) => at = to,
+                None => break,
+            }
+        }
+        contours.push(contour);
+    }
+
+    contours
+}
+
+/// Signed area of the polygon `points`, positive for clockwise on screen.
+fn area(points: &[Vec2]) -> Real {
+    let doubled: Real = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.cross(*b))
+        .sum();
+    doubled / 2.0
+}
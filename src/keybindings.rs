@@ -0,0 +1,367 @@
+use sdl2::keyboard::{KeyboardState, Keycode, Mod, Scancode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ToggleSimulation,
+    ToggleParticles,
+    ToggleSprings,
+    ToggleTrails,
+    ToggleTemperatures,
+    ToggleLog,
+    ToggleOutline,
+    ToggleGrid,
+    ToggleSettings,
+    ToggleProfiler,
+    CycleTheme,
+    ToggleMute,
+    ScrollLogUp,
+    ScrollLogDown,
+    FitCamera,
+    Save,
+    Load,
+    Screenshot,
+    ExportSvg,
+    ToggleRecording,
+    ExportPrefab,
+    StampPrefab,
+    Copy,
+    Paste,
+    MirrorHorizontal,
+    MirrorVertical,
+    RotateSelection,
+    /// Of the selection, or the selected object without one
+    ToggleFrozen,
+    UnfreezeAll,
+    RandomScene,
+    CycleObjectColor,
+    /// Speed presets, see [`crate::app::SPEED_PRESETS`]
+    SpeedTenth,
+    SpeedQuarter,
+    SpeedHalf,
+    SpeedNormal,
+    SpeedDouble,
+    SpeedQuadruple,
+    #[serde(alias = "speed_up")]
+    ToggleFastForward,
+    RemoveLast,
+    ClearWorld,
+    DeleteEdge,
+    ToggleOneWay,
+    ToggleSticky,
+    ToggleBreakable,
+    ToggleHot,
+    /// Of the whole chain of the edge under the pointer
+    ToggleSharpCorners,
+    /// Of the edge under the pointer, else of the edges in the selection
+    RecordKeyframe,
+    ToggleLooping,
+    ClearKeyframes,
+    DeleteSelection,
+    /// Held down rather than pressed, like the ones after it
+    DriveForward,
+    DriveBackward,
+    SpinClockwise,
+    SpinCounterclockwise,
+    #[serde(alias = "slow_down")]
+    SlowMotion,
+    SelectTool,
+    SpawnRectTool,
+    DrawEdgeTool,
+    DrawRopeTool,
+    DeleteTool,
+    PinTool,
+    GrabTool,
+    SpawnRingTool,
+    SpawnStarTool,
+    SpawnWheelTool,
+    SandTool,
+    EmitterTool,
+    DrainTool,
+    GravityZoneTool,
+    InflateTool,
+    SpringGroupsTool,
+    MoverTool,
+    RodTool,
+    MeasureTool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyBinding {
+    keycode: Keycode,
+    ctrl: bool,
+    shift: bool,
+    alt: bool,
+}
+
+impl KeyBinding {
+    pub const fn new(keycode: Keycode) -> Self {
+        Self {
+            keycode,
+            ctrl: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    pub const fn ctrl(mut self) -> Self {
+        self.ctrl = true;
+        self
+    }
+
+    pub const fn shift(mut self) -> Self {
+        self.shift = true;
+        self
+    }
+
+    pub fn matches(self, keycode: Keycode, keymod: Mod) -> bool {
+        self.keycode == keycode
+            && self.ctrl == keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD)
+            && self.shift == keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+            && self.alt == keymod.intersects(Mod::LALTMOD | Mod::RALTMOD)
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut parts: Vec<&str> = value.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+
+        let mut binding =
+            KeyBinding::new(Keycode::from_name(key).ok_or_else(|| format!("unknown key: {key}"))?);
+
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" => binding.ctrl = true,
+                "shift" => binding.shift = true,
+                "alt" => binding.alt = true,
+                _ => return Err(format!("unknown modifier: {modifier}")),
+            }
+        }
+
+        Ok(binding)
+    }
+}
+
+impl From<KeyBinding> for String {
+    fn from(binding: KeyBinding) -> Self {
+        let mut s = String::new();
+
+        if binding.ctrl {
+            s.push_str("Ctrl+");
+        }
+        if binding.shift {
+            s.push_str("Shift+");
+        }
+        if binding.alt {
+            s.push_str("Alt+");
+        }
+
+        s.push_str(&binding.keycode.name());
+        s
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Keybindings(BTreeMap<Action, Vec<KeyBinding>>);
+
+impl Keybindings {
+    pub fn action(&self, keycode: Keycode, keymod: Mod) -> Option<Action> {
+        self.0.iter().find_map(|(&action, bindings)| {
+            bindings
+                .iter()
+                .any(|b| b.matches(keycode, keymod))
+                .then_some(action)
+        })
+    }
+
+    /// Whether a key bound to `action` is down right now, modifiers or not.
+    pub fn held(&self, action: Action, keyboard: &KeyboardState) -> bool {
+        self.0.get(&action).is_some_and(|bindings| {
+            bindings.iter().any(|b| {
+                Scancode::from_keycode(b.keycode)
+                    .is_some_and(|scancode| keyboard.is_scancode_pressed(scancode))
+            })
+        })
+    }
+
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let file = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let overrides: Self = toml::from_str(&file).map_err(|err| err.to_string())?;
+
+        let mut bindings = Self::default();
+        bindings.0.extend(overrides.0);
+
+        Ok(bindings)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("keybindings should be valid to serialize")
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self(BTreeMap::from([
+            (Action::Quit, vec![KeyBinding::new(Keycode::Escape)]),
+            (
+                Action::ToggleSimulation,
+                vec![KeyBinding::new(Keycode::Space)],
+            ),
+            (Action::ToggleParticles, vec![KeyBinding::new(Keycode::F1)]),
+            (Action::ToggleSprings, vec![KeyBinding::new(Keycode::F2)]),
+            (Action::ToggleTrails, vec![KeyBinding::new(Keycode::T)]),
+            (
+                Action::ToggleTemperatures,
+                vec![KeyBinding::new(Keycode::T).ctrl()],
+            ),
+            (Action::ToggleLog, vec![KeyBinding::new(Keycode::F3)]),
+            (Action::ToggleOutline, vec![KeyBinding::new(Keycode::Tab)]),
+            (Action::ToggleGrid, vec![KeyBinding::new(Keycode::G).ctrl()]),
+            (Action::ToggleSettings, vec![KeyBinding::new(Keycode::F8)]),
+            (Action::ToggleProfiler, vec![KeyBinding::new(Keycode::F12)]),
+            (Action::CycleTheme, vec![KeyBinding::new(Keycode::F11)]),
+            (Action::ToggleMute, vec![KeyBinding::new(Keycode::M)]),
+            (Action::ScrollLogUp, vec![KeyBinding::new(Keycode::PageUp)]),
+            (
+                Action::ScrollLogDown,
+                vec![KeyBinding::new(Keycode::PageDown)],
+            ),
+            (Action::FitCamera, vec![KeyBinding::new(Keycode::Home)]),
+            (Action::Save, vec![KeyBinding::new(Keycode::F4)]),
+            (Action::Load, vec![KeyBinding::new(Keycode::F5)]),
+            (Action::Screenshot, vec![KeyBinding::new(Keycode::F9)]),
+            (Action::ExportSvg, vec![KeyBinding::new(Keycode::F9).ctrl()]),
+            (Action::ToggleRecording, vec![KeyBinding::new(Keycode::F10)]),
+            (Action::ExportPrefab, vec![KeyBinding::new(Keycode::F6)]),
+            (Action::StampPrefab, vec![KeyBinding::new(Keycode::F7)]),
+            (Action::Copy, vec![KeyBinding::new(Keycode::C).ctrl()]),
+            (Action::Paste, vec![KeyBinding::new(Keycode::V).ctrl()]),
+            (Action::MirrorHorizontal, vec![KeyBinding::new(Keycode::H)]),
+            (Action::MirrorVertical, vec![KeyBinding::new(Keycode::V)]),
+            (Action::RotateSelection, vec![KeyBinding::new(Keycode::R)]),
+            (Action::ToggleFrozen, vec![KeyBinding::new(Keycode::I)]),
+            (
+                Action::UnfreezeAll,
+                vec![KeyBinding::new(Keycode::I).ctrl()],
+            ),
+            (Action::RandomScene, vec![KeyBinding::new(Keycode::G)]),
+            (Action::CycleObjectColor, vec![KeyBinding::new(Keycode::C)]),
+            (
+                Action::SpeedTenth,
+                vec![
+                    KeyBinding::new(Keycode::Kp1),
+                    KeyBinding::new(Keycode::Num1).ctrl(),
+                ],
+            ),
+            (
+                Action::SpeedQuarter,
+                vec![
+                    KeyBinding::new(Keycode::Kp2),
+                    KeyBinding::new(Keycode::Num2).ctrl(),
+                ],
+            ),
+            (
+                Action::SpeedHalf,
+                vec![
+                    KeyBinding::new(Keycode::Kp3),
+                    KeyBinding::new(Keycode::Num3).ctrl(),
+                ],
+            ),
+            (
+                Action::SpeedNormal,
+                vec![
+                    KeyBinding::new(Keycode::Kp4),
+                    KeyBinding::new(Keycode::Num4).ctrl(),
+                ],
+            ),
+            (
+                Action::SpeedDouble,
+                vec![
+                    KeyBinding::new(Keycode::Kp5),
+                    KeyBinding::new(Keycode::Num5).ctrl(),
+                ],
+            ),
+            (
+                Action::SpeedQuadruple,
+                vec![
+                    KeyBinding::new(Keycode::Kp6),
+                    KeyBinding::new(Keycode::Num6).ctrl(),
+                ],
+            ),
+            (
+                Action::ToggleFastForward,
+                vec![KeyBinding::new(Keycode::Right)],
+            ),
+            (
+                Action::RemoveLast,
+                vec![KeyBinding::new(Keycode::Backspace)],
+            ),
+            (Action::ClearWorld, vec![KeyBinding::new(Keycode::Delete)]),
+            (
+                Action::DeleteEdge,
+                vec![KeyBinding::new(Keycode::Delete).ctrl()],
+            ),
+            (Action::ToggleOneWay, vec![KeyBinding::new(Keycode::O)]),
+            (Action::ToggleSticky, vec![KeyBinding::new(Keycode::S)]),
+            (Action::ToggleBreakable, vec![KeyBinding::new(Keycode::B)]),
+            (Action::ToggleHot, vec![KeyBinding::new(Keycode::F)]),
+            (
+                Action::ToggleSharpCorners,
+                vec![KeyBinding::new(Keycode::J)],
+            ),
+            (Action::RecordKeyframe, vec![KeyBinding::new(Keycode::K)]),
+            (
+                Action::ToggleLooping,
+                vec![KeyBinding::new(Keycode::K).shift()],
+            ),
+            (
+                Action::ClearKeyframes,
+                vec![KeyBinding::new(Keycode::K).ctrl()],
+            ),
+            (Action::DeleteSelection, vec![KeyBinding::new(Keycode::X)]),
+            (Action::DriveForward, vec![KeyBinding::new(Keycode::D)]),
+            (Action::DriveBackward, vec![KeyBinding::new(Keycode::A)]),
+            (Action::SpinClockwise, vec![KeyBinding::new(Keycode::E)]),
+            (
+                Action::SpinCounterclockwise,
+                vec![KeyBinding::new(Keycode::Q)],
+            ),
+            (Action::SlowMotion, vec![KeyBinding::new(Keycode::Left)]),
+            (Action::SelectTool, vec![KeyBinding::new(Keycode::Num1)]),
+            (Action::SpawnRectTool, vec![KeyBinding::new(Keycode::Num2)]),
+            (Action::DrawEdgeTool, vec![KeyBinding::new(Keycode::Num3)]),
+            (Action::DrawRopeTool, vec![KeyBinding::new(Keycode::Num4)]),
+            (Action::DeleteTool, vec![KeyBinding::new(Keycode::Num5)]),
+            (Action::PinTool, vec![KeyBinding::new(Keycode::Num6)]),
+            (Action::GrabTool, vec![KeyBinding::new(Keycode::Num7)]),
+            (Action::SpawnRingTool, vec![KeyBinding::new(Keycode::Num8)]),
+            (Action::SpawnStarTool, vec![KeyBinding::new(Keycode::Num9)]),
+            (Action::SpawnWheelTool, vec![KeyBinding::new(Keycode::Num0)]),
+            (Action::SandTool, vec![KeyBinding::new(Keycode::Minus)]),
+            (Action::EmitterTool, vec![KeyBinding::new(Keycode::Equals)]),
+            (Action::DrainTool, vec![KeyBinding::new(Keycode::Backslash)]),
+            (
+                Action::GravityZoneTool,
+                vec![KeyBinding::new(Keycode::LeftBracket)],
+            ),
+            (
+                Action::InflateTool,
+                vec![KeyBinding::new(Keycode::RightBracket)],
+            ),
+            (
+                Action::SpringGroupsTool,
+                vec![KeyBinding::new(Keycode::Semicolon)],
+            ),
+            (Action::MoverTool, vec![KeyBinding::new(Keycode::Quote)]),
+            (Action::RodTool, vec![KeyBinding::new(Keycode::Comma)]),
+            (Action::MeasureTool, vec![KeyBinding::new(Keycode::Period)]),
+        ]))
+    }
+}
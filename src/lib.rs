@@ -5,11 +5,83 @@
     clippy::missing_errors_doc,
     clippy::missing_panics_doc
 )]
+// Conversions to f32 for the GPU and such are only needed when simulating in f64
+#![cfg_attr(feature = "f32", allow(clippy::unnecessary_cast))]
+// The browser frontend only uses part of the simulation and drawing code
+#![cfg_attr(target_arch = "wasm32", allow(dead_code))]
 
+#[cfg(not(target_arch = "wasm32"))]
 pub mod app;
+#[cfg(feature = "soft-bevy")]
+pub mod bevy_plugin;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod capture;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
+pub mod colliders;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod forces;
+#[cfg(feature = "wgpu")]
+pub mod gpu_collisions;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod keybindings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod savefile;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scene;
+pub mod slots;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod trajectory;
+pub mod vec2;
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+pub mod world;
 
+#[cfg(all(feature = "audio", not(target_arch = "wasm32")))]
+mod audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod backend;
+#[cfg(not(target_arch = "wasm32"))]
+mod camera;
+#[cfg(not(target_arch = "wasm32"))]
+mod config;
+#[cfg(not(target_arch = "wasm32"))]
 mod consts;
+#[cfg(not(target_arch = "wasm32"))]
+mod font;
+#[cfg(not(target_arch = "wasm32"))]
+mod framebuffer;
+#[cfg(not(target_arch = "wasm32"))]
+mod gamepad;
+#[cfg(not(target_arch = "wasm32"))]
+mod gui;
+#[cfg(not(target_arch = "wasm32"))]
+mod image_import;
+#[cfg(not(target_arch = "wasm32"))]
+mod log;
+#[cfg(not(target_arch = "wasm32"))]
+mod multiplayer;
+#[cfg(not(target_arch = "wasm32"))]
+mod physics;
+mod profile;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+mod python;
+#[cfg(all(feature = "remote", not(target_arch = "wasm32")))]
+mod remote;
 mod renderer;
+mod rng;
+#[cfg(not(target_arch = "wasm32"))]
 mod sdl2_renderer;
-mod vec2;
-mod world;
+#[cfg(not(target_arch = "wasm32"))]
+mod svg_import;
+#[cfg(not(target_arch = "wasm32"))]
+mod svg_renderer;
+mod texture;
+mod theme;
+#[cfg(not(target_arch = "wasm32"))]
+mod tool;
+#[cfg(feature = "wgpu")]
+mod wgpu_renderer;
@@ -0,0 +1,128 @@
+use crate::{renderer::Color, theme::Theme};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            Self::Info => theme.info,
+            Self::Warn => theme.warn,
+            Self::Error => theme.error,
+        }
+    }
+}
+
+pub struct Entry {
+    pub time: Duration,
+    pub level: Level,
+    pub msg: String,
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self.time.as_secs();
+        f.write_fmt(format_args!(
+            "[{:02}:{:02}.{:03}] {:<5} {}",
+            secs / 60,
+            secs % 60,
+            self.time.subsec_millis(),
+            self.level.name(),
+            self.msg
+        ))
+    }
+}
+
+pub struct Log<const N: usize> {
+    buffer: VecDeque<Entry>,
+    start: Instant,
+    scroll: usize,
+    sink: Option<File>,
+}
+
+impl<const N: usize> Log<N> {
+    pub fn new() -> Self {
+        Self {
+            buffer: VecDeque::with_capacity(N),
+            start: Instant::now(),
+            scroll: 0,
+            sink: None,
+        }
+    }
+
+    pub fn mirror_to(&mut self, path: &Path) -> std::io::Result<()> {
+        self.sink = Some(File::create(path)?);
+        Ok(())
+    }
+
+    pub fn log(&mut self, level: Level, msg: String) {
+        let entry = Entry {
+            time: self.start.elapsed(),
+            level,
+            msg,
+        };
+
+        if let Some(sink) = &mut self.sink {
+            if writeln!(sink, "{entry}").is_err() {
+                self.sink = None;
+            }
+        }
+
+        if self.buffer.len() == N {
+            self.buffer.pop_back();
+        }
+        self.buffer.push_front(entry);
+
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.buffer.len() - 1);
+        }
+    }
+
+    pub fn info(&mut self, msg: String) {
+        self.log(Level::Info, msg);
+    }
+
+    pub fn warn(&mut self, msg: String) {
+        self.log(Level::Warn, msg);
+    }
+
+    pub fn error(&mut self, msg: String) {
+        self.log(Level::Error, msg);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = (self.scroll + 1).min(self.buffer.len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Newest first, starting at the current scroll position.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Entry> + ExactSizeIterator {
+        self.buffer.iter().skip(self.scroll)
+    }
+
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+}
@@ -1,11 +1,49 @@
-use soft::app::App;
-
+#[cfg(not(target_arch = "wasm32"))]
 fn main() {
-    let mut app = App::new().unwrap_or_else(|err| {
+    use clap::Parser;
+    use soft::{app::App, cli::Args, headless, keybindings::Keybindings};
+
+    let args = Args::parse();
+
+    if args.dump_keybindings {
+        print!("{}", Keybindings::default().to_toml());
+        return;
+    }
+
+    if let Some(path) = &args.render {
+        if let Err(err) = headless::render(&args, path) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut app = App::new(&args).unwrap_or_else(|err| {
         eprintln!("{err}");
         panic!("app could not be inicialized")
     });
 
-    app.load_or_default();
+    if args.random.is_some() {
+        app.load_random_scene();
+    } else {
+        app.load_or_default(args.scene.as_deref());
+    }
+
+    if let Some(svg) = &args.svg {
+        app.import_svg(svg, args.svg_tolerance);
+    }
+
+    if let Some(image) = &args.image {
+        app.import_image(image, args.image_scale, args.image_threshold);
+    }
+
+    if args.paused {
+        app.pause();
+    }
+
     app.run();
 }
+
+// The browser build is a library, started from JavaScript through `soft::web::start`
+#[cfg(target_arch = "wasm32")]
+fn main() {}
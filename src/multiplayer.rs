@@ -0,0 +1,563 @@
+use crate::{
+    savefile::Format,
+    vec2::{Real, Vec2},
+    world::{BodyShape, Emitter, Mover, Particle, World},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+// Longest message read off a connection, a longer one drops it instead of being allocated
+const MAX_FRAME: usize = 64 << 20;
+// Sand guests pour at once, the sand tool's brush is smaller
+const MAX_SAND_RADIUS: Real = 60.0;
+// Longest rope guests spawn, as many particles as a side of the biggest rect
+const MAX_ROPE_LEN: Real = World::MAX_RECT_SIDE as Real * Particle::SPACING;
+// Biggest shape guests spawn, about as wide as a screen is high
+const MAX_SHAPE_RADIUS: Real = 500.0;
+// Least time between sending the whole world over because particles came or went without
+// an edit, see `Update`
+const RESEND_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Changes guests ask the host to make to the world, the ones made with the mouse. The host
+/// keeps them within what its tools allow, [`World::check_spawn_rect`] caps rects, and
+/// refuses them with a coordinate that's NaN or infinite.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Edit {
+    SpawnRect {
+        w: usize,
+        h: usize,
+        x: Real,
+        y: Real,
+    },
+    AddEdge {
+        start: Vec2,
+        end: Vec2,
+    },
+    SpawnRope {
+        start: Vec2,
+        end: Vec2,
+    },
+    SpawnShape {
+        shape: BodyShape,
+        center: Vec2,
+        radius: Real,
+    },
+    /// Fills the free spots of a disc with sand
+    SpawnSand {
+        center: Vec2,
+        radius: Real,
+    },
+    AddEmitter {
+        emitter: Emitter,
+    },
+    AddDrain {
+        a: Vec2,
+        b: Vec2,
+    },
+    AddGravityZone {
+        a: Vec2,
+        b: Vec2,
+        gravity: Vec2,
+    },
+    AddMover {
+        mover: Mover,
+    },
+    /// Between the particles at `a` and `b`
+    AddRod {
+        a: Vec2,
+        b: Vec2,
+    },
+    /// Removes the emitter, else the edge, else the mover, else the rod, else the object,
+    /// else the drain or else the gravity zone at `pos`
+    Delete {
+        pos: Vec2,
+    },
+    TogglePin {
+        pos: Vec2,
+    },
+}
+
+impl Edit {
+    pub fn apply(self, world: &mut World) -> Result<(), String> {
+        if !self.is_finite() {
+            return Err("cant apply edit, it is NaN or infinite somewhere".into());
+        }
+
+        match self {
+            Self::SpawnRect { w, h, x, y } => world
+                .check_spawn_rect(w, h, x, y)
+                .and_then(|()| {
+                    world
+                        .spawn_rect(w, h, x, y)
+                        .map_err(|_| "too small, it needs to be at least 2 x 2".into())
+                })
+                .map_err(|reason| format!("cant spawn rect, {reason}")),
+            Self::AddEdge { start, end } => world.add_edge(start, end).map_err(String::from),
+            Self::SpawnRope { start, end } => {
+                // Into a box first, the length of a far enough end overflows `f32`
+                let reach = Vec2::new(MAX_ROPE_LEN, MAX_ROPE_LEN);
+                let offset = (end - start).clamp(-reach, reach);
+                let end = start + offset.clamp_len(0.0, MAX_ROPE_LEN);
+                world.spawn_rope(start, end)?;
+                push_out_last(world)
+            }
+            Self::SpawnShape {
+                shape,
+                center,
+                radius,
+            } => {
+                world.spawn_shape(shape, center, radius.min(MAX_SHAPE_RADIUS))?;
+                push_out_last(world)
+            }
+            Self::SpawnSand { center, radius } => {
+                world.spawn_sand(center, radius.min(MAX_SAND_RADIUS));
+                Ok(())
+            }
+            Self::AddEmitter { emitter } => {
+                world.add_emitter(emitter.clamped());
+                Ok(())
+            }
+            Self::AddDrain { a, b } => world.add_drain(a, b).map_err(String::from),
+            Self::AddGravityZone { a, b, gravity } => {
+                world.add_gravity_zone(a, b, gravity).map_err(String::from)
+            }
+            Self::AddMover { mover } => world.add_mover(mover).map_err(String::from),
+            Self::AddRod { a, b } => match (world.particle_at(a), world.particle_at(b)) {
+                (Some(a), Some(b)) => world.add_rod(a, b).map_err(String::from),
+                _ => Err("cant add rod, it needs two particles".into()),
+            },
+            Self::Delete { pos } => {
+                if let Some(n) = world.emitter_at(pos) {
+                    world.remove_emitter(n);
+                } else if let Some(n) = world.edge_at(pos) {
+                    world.remove_edge(n);
+                } else if let Some(n) = world.mover_at(pos) {
+                    world.remove_mover(n);
+                } else if let Some(n) = world.rod_at(pos) {
+                    world.remove_rod(n);
+                } else if let Some(n) = world.object_at(pos) {
+                    world.remove_object(n);
+                } else if let Some(n) = world.drain_at(pos) {
+                    world.remove_drain(n);
+                } else if let Some(n) = world.gravity_zone_at(pos) {
+                    world.remove_gravity_zone(n);
+                }
+                Ok(())
+            }
+            Self::TogglePin { pos } => {
+                if let Some(n) = world.particle_at(pos) {
+                    world.toggle_pin(n);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether all its coordinates, radii and speeds are finite numbers.
+    fn is_finite(&self) -> bool {
+        match self {
+            Self::SpawnRect { x, y, .. } => x.is_finite() && y.is_finite(),
+            Self::AddEdge { start: a, end: b }
+            | Self::SpawnRope { start: a, end: b }
+            | Self::AddDrain { a, b }
+            | Self::AddRod { a, b } => a.is_finite() && b.is_finite(),
+            Self::SpawnShape { center, radius, .. } | Self::SpawnSand { center, radius } => {
+                center.is_finite() && radius.is_finite()
+            }
+            Self::AddEmitter { emitter } => emitter.pos.is_finite() && emitter.vel.is_finite(),
+            Self::AddGravityZone { a, b, gravity } => {
+                a.is_finite() && b.is_finite() && gravity.is_finite()
+            }
+            Self::AddMover { mover } => {
+                mover.speed.is_finite() && mover.track.iter().all(|p| p.is_finite())
+            }
+            Self::Delete { pos } | Self::TogglePin { pos } => pos.is_finite(),
+        }
+    }
+}
+
+/// Moves the object just spawned out of whatever it landed on.
+fn push_out_last(world: &mut World) -> Result<(), String> {
+    let (.., objects) = world.info();
+    if world.push_out(objects - 1) {
+        Ok(())
+    } else {
+        Err("new object is stuck overlapping others".into())
+    }
+}
+
+/// What the host sends guests: the whole world after every edit, and only where its
+/// particles are while it's just being simulated. Emitters, lifetimes and drains add and
+/// remove particles without an edit, which positions can't carry: the whole world goes
+/// again then too, but no more often than every [`RESEND_INTERVAL`]. Guests see the
+/// particles hold still until it does.
+#[derive(Serialize, Deserialize)]
+enum Update<'a> {
+    World {
+        world: Box<Cow<'a, World>>,
+        size: Vec2,
+        revision: u64,
+    },
+    Positions {
+        /// Revision of the world these are the particles of
+        revision: u64,
+        positions: Vec<[f32; 2]>,
+    },
+}
+
+type Outboxes = Arc<Mutex<Vec<Sender<Arc<Vec<u8>>>>>>;
+
+/// Runs the simulation for guests on other machines, taking their edits and sending
+/// them the world as it changes.
+pub struct Host {
+    edits: Receiver<Edit>,
+    outboxes: Outboxes,
+    /// Set when someone joined, they need the whole world first
+    joined: Arc<AtomicBool>,
+    /// Revision, simulated time and particle count of the world guests last got
+    sent: Option<(u64, Real, usize)>,
+    /// When the world was last sent whole
+    resent: Instant,
+}
+
+impl Host {
+    pub fn start(addr: &str) -> Result<Self, String> {
+        let listener = TcpListener::bind(addr).map_err(|err| err.to_string())?;
+        let (sender, edits) = mpsc::channel();
+        let outboxes = Outboxes::default();
+        let joined = Arc::new(AtomicBool::new(false));
+
+        {
+            let (outboxes, joined) = (Arc::clone(&outboxes), Arc::clone(&joined));
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // Updates are small and frequent, not worth holding back to batch
+                    let _ = stream.set_nodelay(true);
+                    let Ok(mut reader) = stream.try_clone() else {
+                        continue;
+                    };
+
+                    let sender = sender.clone();
+                    thread::spawn(move || {
+                        while let Ok(edit) = receive(&mut reader) {
+                            if sender.send(edit).is_err() {
+                                return;
+                            }
+                        }
+                    });
+
+                    // Writing on a thread of its own, a slow guest doesn't hold up the app
+                    let (outbox, updates) = mpsc::channel::<Arc<Vec<u8>>>();
+                    let mut writer = stream;
+                    thread::spawn(move || {
+                        for update in updates {
+                            if write_frame(&mut writer, &update).is_err() {
+                                return;
+                            }
+                        }
+                    });
+
+                    outboxes
+                        .lock()
+                        .expect("multiplayer lock should not be poisoned")
+                        .push(outbox);
+                    joined.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
+        Ok(Self {
+            edits,
+            outboxes,
+            joined,
+            sent: None,
+            resent: Instant::now(),
+        })
+    }
+
+    /// The next edit a guest asked for, if any.
+    pub fn poll(&self) -> Option<Edit> {
+        self.edits.try_recv().ok()
+    }
+
+    /// Sends guests whatever changed about `world` since they last got it.
+    pub fn share(&mut self, world: &World) {
+        let mut outboxes = self
+            .outboxes
+            .lock()
+            .expect("multiplayer lock should not be poisoned");
+        if outboxes.is_empty() {
+            return;
+        }
+
+        let (revision, time) = (world.revision(), world.time());
+        let (particles, ..) = world.info();
+        let edited = self.joined.swap(false, Ordering::Relaxed)
+            || self.sent.map(|(revision, ..)| revision) != Some(revision);
+        // Emitters and despawning change what there is without an edit
+        let respawned = self.sent.map(|(.., particles)| particles) != Some(particles);
+        let update = if edited || (respawned && self.resent.elapsed() >= RESEND_INTERVAL) {
+            self.resent = Instant::now();
+            Update::World {
+                world: Box::new(Cow::Borrowed(world)),
+                size: world.size(),
+                revision,
+            }
+        } else if respawned {
+            // Positions wouldn't line up with the particles guests have
+            return;
+        } else if self.sent.map(|(_, time, _)| time) != Some(time) {
+            let positions = world
+                .particles_iter()
+                .map(|p| [p.get_pos().x as f32, p.get_pos().y as f32])
+                .collect();
+            Update::Positions {
+                revision,
+                positions,
+            }
+        } else {
+            return;
+        };
+        self.sent = Some((revision, time, particles));
+
+        let bytes = Arc::new(
+            Format::MessagePack
+                .encode(&update)
+                .expect("updates should be valid to serialize"),
+        );
+        // Outboxes of guests that left are closed
+        outboxes.retain(|outbox| outbox.send(Arc::clone(&bytes)).is_ok());
+    }
+}
+
+/// The other end of a [`Host`]: sends it edits instead of making them, and shows its world
+/// instead of simulating one.
+pub struct Guest {
+    stream: TcpStream,
+    updates: Receiver<Update<'static>>,
+    /// The world as last sent whole, anything edited locally is rolled back to it
+    base: Option<World>,
+    /// Revision of `base` on the host
+    revision: u64,
+    /// The last two positions received, drawn sliding from one to the other over `interval`
+    /// so motion stays smooth a step behind the host
+    from: Vec<Vec2>,
+    to: Vec<Vec2>,
+    received: Instant,
+    /// Seconds between updates of positions, smoothed
+    interval: f64,
+}
+
+impl Guest {
+    // Longest gap between updates interpolated across, past it particles jump
+    const MAX_INTERVAL: f64 = 0.25;
+    const SMOOTHING: f64 = 0.1;
+
+    pub fn join(addr: &str) -> Result<Self, String> {
+        let stream = TcpStream::connect(addr).map_err(|err| err.to_string())?;
+        let _ = stream.set_nodelay(true);
+        let mut reader = stream.try_clone().map_err(|err| err.to_string())?;
+
+        let (sender, updates) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok(update) = receive(&mut reader) {
+                if sender.send(update).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(Self {
+            stream,
+            updates,
+            base: None,
+            revision: 0,
+            from: vec![],
+            to: vec![],
+            received: Instant::now(),
+            interval: 1.0 / 60.0,
+        })
+    }
+
+    pub fn send(&mut self, edit: Edit) -> Result<(), String> {
+        let bytes = Format::MessagePack.encode(&edit)?;
+        write_frame(&mut self.stream, &bytes).map_err(|err| format!("could not reach host: {err}"))
+    }
+
+    /// Brings `world` up to date with the host, returning whether it was replaced whole.
+    pub fn sync(&mut self, world: &mut World) -> Result<bool, String> {
+        let mut replaced = false;
+        loop {
+            match self.updates.try_recv() {
+                Ok(Update::World {
+                    world: received,
+                    size,
+                    revision,
+                }) => {
+                    let mut received = (*received).into_owned();
+                    received.resize(size.x, size.y);
+                    *world = received.clone();
+                    self.base = Some(received);
+                    self.revision = revision;
+                    self.to.clear();
+                    replaced = true;
+                }
+                Ok(Update::Positions {
+                    revision,
+                    positions,
+                }) if revision == self.revision => {
+                    let to: Vec<Vec2> = positions
+                        .into_iter()
+                        .map(|[x, y]| Vec2::new(Real::from(x), Real::from(y)))
+                        .collect();
+                    self.from = std::mem::replace(&mut self.to, to);
+                    if self.from.len() != self.to.len() {
+                        self.from.clone_from(&self.to);
+                    }
+
+                    let elapsed = self.received.elapsed().as_secs_f64();
+                    self.interval +=
+                        (elapsed.min(Self::MAX_INTERVAL) - self.interval) * Self::SMOOTHING;
+                    self.received = Instant::now();
+                }
+                // Of a world replaced since
+                Ok(Update::Positions { .. }) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Err("lost connection to host".into()),
+            }
+        }
+
+        if let Some(base) = self.base.as_ref() {
+            if base.revision() != world.revision() {
+                *world = base.clone();
+            }
+        }
+
+        if !self.to.is_empty() {
+            let t = (self.received.elapsed().as_secs_f64() / self.interval).min(1.0) as Real;
+            world.set_positions(self.from.iter().zip(&self.to).map(|(a, &b)| a.lerp(b, t)));
+        }
+        Ok(replaced)
+    }
+}
+
+/// Messages go over TCP as their length followed by them in MessagePack.
+fn write_frame(stream: &mut impl Write, bytes: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(bytes.len()).expect("updates should be under 4 GiB");
+    stream.write_all(&len.to_be_bytes())?;
+    stream.write_all(bytes)
+}
+
+fn receive<T: DeserializeOwned>(stream: &mut impl Read) -> Result<T, String> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len).map_err(|err| err.to_string())?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(format!("message of {len} bytes is too long"));
+    }
+    let mut bytes = vec![0; len];
+    stream
+        .read_exact(&mut bytes)
+        .map_err(|err| err.to_string())?;
+    Format::MessagePack.decode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec2::consts::TAU;
+
+    #[test]
+    fn long_frames_are_refused() {
+        let mut frame = u32::MAX.to_be_bytes().to_vec();
+        frame.extend([0; 16]);
+        assert!(receive::<Edit>(&mut frame.as_slice()).is_err());
+
+        let mut frame = vec![];
+        let edit = Format::MessagePack
+            .encode(&Edit::TogglePin {
+                pos: Vec2::new(1.0, 2.0),
+            })
+            .unwrap();
+        write_frame(&mut frame, &edit).unwrap();
+        assert!(receive::<Edit>(&mut frame.as_slice()).is_ok());
+    }
+
+    #[test]
+    fn guest_edits_are_clamped() {
+        // Wide enough for the longest rope
+        let mut world = World::new(5000.0, 600.0);
+        let rect = Edit::SpawnRect {
+            w: usize::MAX,
+            h: 2,
+            x: 100.0,
+            y: 100.0,
+        };
+        assert!(rect.apply(&mut world).is_err());
+
+        let mut emitter = Emitter::new(Vec2::new(400.0, 100.0), Vec2::null());
+        emitter.rate = Real::NAN;
+        emitter.max_alive = usize::MAX;
+        emitter.lifetime = Real::INFINITY;
+        Edit::AddEmitter { emitter }.apply(&mut world).unwrap();
+        let emitter = world.emitter(0).unwrap();
+        assert_eq!(emitter.rate, 0.1);
+        assert_eq!(emitter.max_alive, Emitter::MAX_ALIVE);
+        assert_eq!(emitter.lifetime, Emitter::MAX_LIFETIME);
+
+        let (particles, ..) = world.info();
+        let rope = Edit::SpawnRope {
+            start: Vec2::new(100.0, 300.0),
+            end: Vec2::new(1e30, 300.0),
+        };
+        rope.apply(&mut world).unwrap();
+        let spawned = world.info().0 - particles;
+        assert_eq!(
+            spawned,
+            world.rope_len(Vec2::null(), Vec2::new(MAX_ROPE_LEN, 0.0))
+        );
+
+        let (particles, ..) = world.info();
+        let shape = Edit::SpawnShape {
+            shape: BodyShape::Wheel,
+            center: Vec2::new(400.0, 300.0),
+            radius: 1e30,
+        };
+        // Bigger than the world, it's spawned but can't be pushed out of the borders
+        assert!(shape.apply(&mut world).is_err());
+        let rim = (TAU * MAX_SHAPE_RADIUS / Particle::SPACING).round() as usize;
+        assert_eq!(world.info().0 - particles, rim + 1);
+
+        let (particles, ..) = world.info();
+        let edits = [
+            Edit::SpawnRope {
+                start: Vec2::new(100.0, 300.0),
+                end: Vec2::new(Real::INFINITY, 300.0),
+            },
+            Edit::SpawnShape {
+                shape: BodyShape::Ring,
+                center: Vec2::new(400.0, 300.0),
+                radius: Real::INFINITY,
+            },
+            Edit::SpawnSand {
+                center: Vec2::new(Real::NAN, 0.0),
+                radius: 10.0,
+            },
+        ];
+        for edit in edits {
+            assert!(edit.apply(&mut world).is_err());
+        }
+        assert_eq!(world.info().0, particles);
+    }
+}
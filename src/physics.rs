@@ -0,0 +1,191 @@
+use crate::{
+    vec2::{Real, Vec2},
+    world::{Instability, World},
+};
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+/// Input the simulation picks up every time it catches up with the app.
+#[derive(Clone, Copy, Default)]
+pub struct Controls {
+    pub throttle: Real,
+    /// Object and torque to spin it with
+    pub torque: Option<(usize, Real)>,
+    /// Where the grabbed particle is pulled to
+    pub drag: Option<Vec2>,
+    pub record_trails: bool,
+    /// Measure the [`Metrics`](crate::world::Metrics) of every update
+    pub track_metrics: bool,
+    /// Seconds to simulate per second of real time
+    pub speed: Real,
+}
+
+#[derive(Default)]
+struct Exchange {
+    /// World edited by the app, replacing the one being simulated
+    edited: Option<World>,
+    /// Seconds of real time passed since the worker last looked
+    time: Real,
+    controls: Controls,
+    /// Snapshot of the latest update along with its result
+    finished: Option<(World, Result<(), Instability>)>,
+    /// Snapshot the app is done with, the next one is copied into its buffers
+    spare: Option<World>,
+    /// Share of real time the worker spent stepping, over its latest update
+    load: Real,
+    quit: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    exchange: Mutex<Exchange>,
+    wake: Condvar,
+}
+
+impl Shared {
+    fn exchange(&self) -> MutexGuard<'_, Exchange> {
+        self.exchange
+            .lock()
+            .expect("physics lock should not be poisoned")
+    }
+}
+
+/// Runs [`World::update`] on a thread of its own, so a simulation that can't keep up
+/// slows down only itself and not drawing and input. The app works on snapshots: edits
+/// go over as a whole world and finished updates come back as one, swapped for the world
+/// the app had so the next snapshot can reuse it.
+pub struct Physics {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+    /// Revision of the world the worker is simulating
+    sent: Option<u64>,
+}
+
+impl Physics {
+    pub fn start() -> Self {
+        let shared = Arc::new(Shared::default());
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || simulate(&shared))
+        };
+
+        Self {
+            shared,
+            worker: Some(worker),
+            sent: None,
+        }
+    }
+
+    /// Hands `world` over if it changed since it was last seen, then swaps in the latest
+    /// update of it if there is one. Updates of worlds edited since are dropped.
+    pub fn sync(&mut self, world: &mut World) -> Option<Result<(), Instability>> {
+        let mut exchange = self.shared.exchange();
+
+        if self.sent != Some(world.revision()) {
+            self.sent = Some(world.revision());
+            exchange.edited = Some(world.clone());
+            exchange.finished = None;
+            self.shared.wake.notify_one();
+            return None;
+        }
+
+        let (finished, result) = exchange.finished.take()?;
+        if Some(finished.revision()) != self.sent {
+            exchange.spare = Some(finished);
+            return None;
+        }
+        exchange.spare = Some(std::mem::replace(world, finished));
+        Some(result)
+    }
+
+    /// Asks for `dt` more seconds of real time to be simulated at `controls.speed`.
+    pub fn advance(&self, dt: Real, controls: Controls) {
+        let mut exchange = self.shared.exchange();
+        exchange.time += dt;
+        exchange.controls = controls;
+        self.shared.wake.notify_one();
+    }
+
+    /// Share of real time the simulation takes to compute, above 1 when it can't keep up.
+    pub fn load(&self) -> Real {
+        self.shared.exchange().load
+    }
+}
+
+impl Drop for Physics {
+    fn drop(&mut self) {
+        self.shared.exchange().quit = true;
+        self.shared.wake.notify_one();
+
+        if let Some(worker) = self.worker.take() {
+            // A panic there was already reported, there is nothing left to clean up
+            let _ = worker.join();
+        }
+    }
+}
+
+fn simulate(shared: &Shared) {
+    let mut world: Option<World> = None;
+
+    loop {
+        let (time, controls) = {
+            let mut exchange = shared
+                .wake
+                .wait_while(shared.exchange(), |exchange| {
+                    !exchange.quit
+                        && exchange.edited.is_none()
+                        && (world.is_none() || exchange.time <= 0.0)
+                })
+                .expect("physics lock should not be poisoned");
+
+            if exchange.quit {
+                return;
+            }
+            if let Some(edited) = exchange.edited.take() {
+                world = Some(edited);
+            }
+            (std::mem::take(&mut exchange.time), exchange.controls)
+        };
+
+        let Some(world) = world.as_mut().filter(|_| time > 0.0) else {
+            continue;
+        };
+
+        world.set_throttle(controls.throttle);
+        if let Some((n, torque)) = controls.torque {
+            world.apply_torque(n, torque);
+        }
+        if let Some(target) = controls.drag {
+            world.drag_to(target);
+        }
+        world.set_speed(controls.speed);
+        world.set_track_metrics(controls.track_metrics);
+        world.end_frame(time);
+        let started = Instant::now();
+        let result = world.update();
+        let load = started.elapsed().as_secs_f64() as Real / time;
+        if controls.record_trails {
+            world.record_trails();
+        }
+
+        let spare = shared.exchange().spare.take();
+        let mut snapshot = match spare {
+            Some(mut snapshot) => {
+                snapshot.copy_from(world);
+                snapshot
+            }
+            None => world.clone(),
+        };
+        let mut exchange = shared.exchange();
+        // Not picked up in time, it can still hold the next one
+        if let Some((missed, _)) = exchange.finished.take() {
+            snapshot.keep_quarantines(&missed);
+            exchange.spare = Some(missed);
+        }
+        exchange.finished = Some((snapshot, result));
+        exchange.load = load;
+    }
+}
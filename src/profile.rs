@@ -0,0 +1,97 @@
+/// Times the rest of the enclosing block as a tracing span called `$name`. Expands to
+/// nothing without the `profile` feature, so hot loops pay for it only when asked to.
+macro_rules! span {
+    ($name:literal) => {
+        #[cfg(feature = "profile")]
+        let _span = tracing::trace_span!($name).entered();
+    };
+}
+pub(crate) use span;
+
+#[cfg(feature = "profile")]
+pub use recorder::{Profiler, Record};
+
+#[cfg(feature = "profile")]
+mod recorder {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        thread::{self, ThreadId},
+        time::Instant,
+    };
+    use tracing::{span::Id, Subscriber};
+    use tracing_subscriber::{
+        layer::{Context, Layer, SubscriberExt},
+        registry::LookupSpan,
+    };
+
+    /// A finished span, timed in seconds from the start of its frame.
+    #[derive(Clone, Debug)]
+    pub struct Record {
+        pub name: &'static str,
+        pub depth: usize,
+        pub start: f64,
+        pub end: f64,
+    }
+
+    #[derive(Default)]
+    struct Frame {
+        begin: Option<Instant>,
+        /// Spans entered and not yet exited, per thread as physics runs on one of its own
+        open: HashMap<ThreadId, Vec<(&'static str, Instant)>>,
+        records: Vec<Record>,
+    }
+
+    /// Tracing layer collecting the spans of the current frame for the in-app timeline.
+    #[derive(Clone, Default)]
+    pub struct Profiler(Arc<Mutex<Frame>>);
+
+    impl Profiler {
+        /// Installs the profiler as the global tracing subscriber.
+        pub fn install() -> Result<Self, String> {
+            let profiler = Self::default();
+            let subscriber = tracing_subscriber::registry().with(profiler.clone());
+            tracing::subscriber::set_global_default(subscriber).map_err(|err| err.to_string())?;
+            Ok(profiler)
+        }
+
+        /// Starts timing a new frame and hands back the spans of the previous one.
+        pub fn next_frame(&self) -> Vec<Record> {
+            let mut frame = self.0.lock().expect("profiler lock should not be poisoned");
+            frame.begin = Some(Instant::now());
+            std::mem::take(&mut frame.records)
+        }
+    }
+
+    impl<S> Layer<S> for Profiler
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+            if let Some(span) = ctx.span(id) {
+                let mut frame = self.0.lock().expect("profiler lock should not be poisoned");
+                let open = frame.open.entry(thread::current().id()).or_default();
+                open.push((span.name(), Instant::now()));
+            }
+        }
+
+        fn on_exit(&self, _id: &Id, _ctx: Context<'_, S>) {
+            let now = Instant::now();
+            let mut frame = self.0.lock().expect("profiler lock should not be poisoned");
+
+            let Some(open) = frame.open.get_mut(&thread::current().id()) else {
+                return;
+            };
+            if let Some((name, start)) = open.pop() {
+                let depth = open.len();
+                let begin = frame.begin.unwrap_or(start);
+                frame.records.push(Record {
+                    name,
+                    depth,
+                    start: start.saturating_duration_since(begin).as_secs_f64(),
+                    end: now.saturating_duration_since(begin).as_secs_f64(),
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// The methods macro converts every returned error into a `PyErr`, even ones that already are
+#![allow(clippy::useless_conversion)]
+
+use crate::{
+    savefile::State,
+    vec2::{Real, Vec2},
+    world::{BodyShape, World},
+};
+use pyo3::{
+    exceptions::{PyIOError, PyRuntimeError, PyValueError},
+    prelude::*,
+};
+use std::path::PathBuf;
+
+type Point = (Real, Real);
+
+/// The simulation without a window, for scripting experiments from Python. It keeps the
+/// rest of what the app saves along, so files go back and forth between the two.
+#[pyclass(name = "World")]
+struct PyWorld(State);
+
+#[pymethods]
+impl PyWorld {
+    #[new]
+    fn new(width: Real, height: Real) -> Self {
+        Self(State::new(World::new(width, height)))
+    }
+
+    /// Advances exactly `n` physics steps, deterministically.
+    #[pyo3(signature = (n = 1))]
+    fn step(&mut self, n: usize) -> PyResult<()> {
+        self.0.world.step_n(n).map_err(|err| {
+            PyRuntimeError::new_err(format!("simulation blew up, diff_len={}", err.diff_len))
+        })
+    }
+
+    fn spawn_rect(&mut self, w: usize, h: usize, x: Real, y: Real) -> PyResult<()> {
+        self.0
+            .world
+            .spawn_rect(w, h, x, y)
+            .map_err(|(w, h)| PyValueError::new_err(format!("cant spawn a {w}x{h} rect")))
+    }
+
+    fn spawn_rope(&mut self, start: Point, end: Point) -> PyResult<()> {
+        self.0
+            .world
+            .spawn_rope(point(start), point(end))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// `shape` is one of "ring", "star" and "wheel".
+    fn spawn_shape(&mut self, shape: &str, center: Point, radius: Real) -> PyResult<()> {
+        let shape = match shape {
+            "ring" => BodyShape::Ring,
+            "star" => BodyShape::Star,
+            "wheel" => BodyShape::Wheel,
+            _ => return Err(PyValueError::new_err(format!("no {shape} shape"))),
+        };
+        self.0
+            .world
+            .spawn_shape(shape, point(center), radius)
+            .map_err(PyValueError::new_err)
+    }
+
+    fn spawn_outline(&mut self, outline: Vec<Point>) -> PyResult<()> {
+        let outline: Vec<Vec2> = outline.into_iter().map(point).collect();
+        self.0
+            .world
+            .spawn_outline(&outline)
+            .map_err(PyValueError::new_err)
+    }
+
+    fn add_edge(&mut self, start: Point, end: Point) -> PyResult<()> {
+        self.0
+            .world
+            .add_edge(point(start), point(end))
+            .map_err(PyValueError::new_err)
+    }
+
+    /// Particles, springs, boundary particles, edges and objects.
+    fn info(&self) -> (usize, usize, usize, usize, usize) {
+        self.0.world.info()
+    }
+
+    /// Seconds simulated since the world was created.
+    #[getter]
+    fn time(&self) -> Real {
+        self.0.world.time()
+    }
+
+    fn positions(&self) -> Vec<Point> {
+        self.0
+            .world
+            .particles_iter()
+            .map(|p| (p.get_pos().x, p.get_pos().y))
+            .collect()
+    }
+
+    fn velocities(&self) -> Vec<Point> {
+        self.0
+            .world
+            .particles_iter()
+            .map(|p| (p.get_vel().x, p.get_vel().y))
+            .collect()
+    }
+
+    /// Mass, center of mass and its velocity of object `n`, `None` past the last one.
+    fn object_state(&self, n: usize) -> Option<(Real, Point, Point)> {
+        let state = self.0.world.object_state(n)?;
+        let (center, vel) = (state.center_of_mass, state.vel);
+        Some((state.mass, (center.x, center.y), (vel.x, vel.y)))
+    }
+
+    fn state_hash(&self) -> u64 {
+        self.0.world.state_hash()
+    }
+
+    /// Writes a savefile the app opens, in the format the extension of `path` asks for.
+    fn save(&self, path: PathBuf) -> PyResult<()> {
+        self.0.write(&path).map_err(PyIOError::new_err)
+    }
+
+    /// Reads a savefile of the app or of `save`, laid out anew on `width` by `height`.
+    #[staticmethod]
+    fn load(path: PathBuf, width: Real, height: Real) -> PyResult<Self> {
+        let mut state = State::read(&path).map_err(PyIOError::new_err)?;
+        state.world.resize(width, height);
+        Ok(Self(state))
+    }
+
+    fn resize(&mut self, width: Real, height: Real) {
+        self.0.world.resize(width, height);
+    }
+}
+
+fn point((x, y): Point) -> Vec2 {
+    Vec2::new(x, y)
+}
+
+#[pymodule]
+fn soft(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyWorld>()
+}
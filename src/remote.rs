@@ -0,0 +1,256 @@
+use crate::{
+    vec2::{Real, Vec2},
+    world::World,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+use tungstenite::{Message, WebSocket};
+
+/// What clients send, as JSON text messages like `{"cmd": "spawn_rect", "w": 4, ...}`.
+#[derive(Deserialize, Debug)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    SpawnRect {
+        w: usize,
+        h: usize,
+        x: Real,
+        y: Real,
+    },
+    SpawnRope {
+        start: [Real; 2],
+        end: [Real; 2],
+    },
+    AddEdge {
+        start: [Real; 2],
+        end: [Real; 2],
+    },
+    SetGravity {
+        x: Real,
+        y: Real,
+    },
+    Pause,
+    Resume,
+    /// Replies with a single snapshot
+    Query,
+    /// Streams a snapshot every `every` simulated frames until unsubscribed
+    Subscribe {
+        every: u32,
+    },
+    Unsubscribe,
+}
+
+/// What the world looks like to clients.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub time: Real,
+    pub paused: bool,
+    pub particles: Vec<[Real; 2]>,
+    pub velocities: Vec<[Real; 2]>,
+    pub edges: Vec<[[Real; 2]; 2]>,
+    pub objects: usize,
+}
+
+impl Snapshot {
+    pub fn of(world: &World, paused: bool) -> Self {
+        let xy = |v: Vec2| [v.x, v.y];
+        Self {
+            time: world.time(),
+            paused,
+            particles: world.particles_iter().map(|p| xy(p.get_pos())).collect(),
+            velocities: world.particles_iter().map(|p| xy(p.get_vel())).collect(),
+            edges: world
+                .edges_iter()
+                .map(|e| [xy(e.get_start()), xy(e.get_end())])
+                .collect(),
+            objects: world.info().4,
+        }
+    }
+}
+
+/// What clients get back, one for every command and then the streamed snapshots.
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply<'a> {
+    Ok,
+    Error(String),
+    Snapshot(&'a Snapshot),
+}
+
+/// A command along with who sent it, to reply to.
+pub struct Request {
+    pub client: usize,
+    pub command: Command,
+}
+
+type Outboxes = Arc<Mutex<HashMap<usize, Sender<String>>>>;
+
+/// Lets tools outside of the app drive it over WebSocket. Connections are served on
+/// threads of their own, the app picks up their commands once a frame with
+/// [`Server::poll`] so they never touch the world while it's in use.
+pub struct Server {
+    requests: Receiver<Request>,
+    outboxes: Outboxes,
+    /// Frames between snapshots and until the next one, per subscribed client
+    subscribers: HashMap<usize, (u32, u32)>,
+}
+
+impl Server {
+    // How long a connection waits for a command before it checks for replies to send
+    const POLL: Duration = Duration::from_millis(10);
+
+    pub fn start(addr: &str) -> Result<Self, String> {
+        let listener = TcpListener::bind(addr).map_err(|err| err.to_string())?;
+        let (sender, requests) = mpsc::channel();
+        let outboxes = Outboxes::default();
+
+        {
+            let outboxes = Arc::clone(&outboxes);
+            thread::spawn(move || {
+                for (client, stream) in listener.incoming().flatten().enumerate() {
+                    let (outbox, replies) = mpsc::channel();
+                    outboxes
+                        .lock()
+                        .expect("remote lock should not be poisoned")
+                        .insert(client, outbox);
+
+                    let (sender, outboxes) = (sender.clone(), Arc::clone(&outboxes));
+                    thread::spawn(move || {
+                        // Gone either way, a failed handshake or a dropped connection
+                        let _ = serve(client, stream, &sender, &replies);
+                        outboxes
+                            .lock()
+                            .expect("remote lock should not be poisoned")
+                            .remove(&client);
+                    });
+                }
+            });
+        }
+
+        Ok(Self {
+            requests,
+            outboxes,
+            subscribers: HashMap::new(),
+        })
+    }
+
+    /// The next command that came in, if any.
+    pub fn poll(&mut self) -> Option<Request> {
+        let request = self.requests.try_recv().ok()?;
+        match request.command {
+            Command::Subscribe { every } => {
+                let every = every.max(1);
+                self.subscribers.insert(request.client, (every, every));
+            }
+            Command::Unsubscribe => {
+                self.subscribers.remove(&request.client);
+            }
+            _ => {}
+        }
+        Some(request)
+    }
+
+    pub fn reply(&self, client: usize, reply: &Reply) {
+        let text = serde_json::to_string(reply).expect("replies should be valid to serialize");
+        if let Some(outbox) = self.outboxes().get(&client) {
+            // The connection closing meanwhile is not an error
+            let _ = outbox.send(text);
+        }
+    }
+
+    /// Counts a simulated frame, sending those due one a snapshot made by `snapshot`.
+    pub fn stream(&mut self, snapshot: impl FnOnce() -> Snapshot) {
+        // Through the field, as the subscribers are borrowed alongside
+        let outboxes = self
+            .outboxes
+            .lock()
+            .expect("remote lock should not be poisoned");
+        self.subscribers
+            .retain(|client, _| outboxes.contains_key(client));
+
+        let mut due = vec![];
+        for (&client, (every, left)) in &mut self.subscribers {
+            *left -= 1;
+            if *left == 0 {
+                *left = *every;
+                due.push(client);
+            }
+        }
+        if due.is_empty() {
+            return;
+        }
+
+        let text = serde_json::to_string(&Reply::Snapshot(&snapshot()))
+            .expect("snapshots should be valid to serialize");
+        for client in due {
+            if let Some(outbox) = outboxes.get(&client) {
+                let _ = outbox.send(text.clone());
+            }
+        }
+    }
+
+    fn outboxes(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Sender<String>>> {
+        self.outboxes
+            .lock()
+            .expect("remote lock should not be poisoned")
+    }
+}
+
+/// Passes commands from `stream` on to the app and its replies back until either side
+/// hangs up.
+fn serve(
+    client: usize,
+    stream: TcpStream,
+    requests: &Sender<Request>,
+    replies: &Receiver<String>,
+) -> Result<(), String> {
+    let mut socket: WebSocket<TcpStream> =
+        tungstenite::accept(stream).map_err(|err| err.to_string())?;
+    socket
+        .get_mut()
+        .set_read_timeout(Some(Server::POLL))
+        .map_err(|err| err.to_string())?;
+
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let reply = match serde_json::from_str(&text) {
+                    Ok(command) => {
+                        requests
+                            .send(Request { client, command })
+                            .map_err(|err| err.to_string())?;
+                        None
+                    }
+                    Err(err) => Some(Reply::Error(format!("unknown command: {err}"))),
+                };
+                if let Some(reply) = reply {
+                    let text = serde_json::to_string(&reply)
+                        .expect("replies should be valid to serialize");
+                    socket
+                        .send(Message::Text(text))
+                        .map_err(|err| err.to_string())?;
+                }
+            }
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(err) => return Err(err.to_string()),
+        }
+
+        for text in replies.try_iter() {
+            socket
+                .send(Message::Text(text))
+                .map_err(|err| err.to_string())?;
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use crate::vec2::Vec2;
+use crate::vec2::{Real, Vec2};
 
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, serde::Serialize, serde::Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -40,15 +40,79 @@ impl Color {
     pub const CYAN: Self = Self::RGB(0, 255, 255);
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    None,
+    Blend,
+    Add,
+}
+
 pub trait Renderer {
-    fn filled_circle(&mut self, center: Vec2, radius: f64) -> &mut Self;
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self;
     fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self;
-    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: f64) -> &mut Self;
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self;
     fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self;
     fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self;
-    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: f64) -> &mut Self;
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self;
     fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self;
-    fn text(&mut self, pos: Vec2, text: &str) -> &mut Self;
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self;
+
+    fn polyline(&mut self, mut vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        if let Some(mut a) = vertices.next() {
+            for b in vertices {
+                self.line(a, b);
+                a = b;
+            }
+        }
+
+        self
+    }
+
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.line(a, b)
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        self.filled_circle(center, radius)
+    }
+
+    fn aa_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.polygon(vertices)
+    }
+
+    fn aa_polyline(&mut self, mut vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        if let Some(mut a) = vertices.next() {
+            for b in vertices {
+                self.aa_line(a, b);
+                a = b;
+            }
+        }
+
+        self
+    }
+
+    /// Anti-aliased polyline in `color` that fades in from fully transparent at the first
+    /// vertex. Leaves `color` as the current color.
+    fn fading_polyline(&mut self, vertices: impl Iterator<Item = Vec2>, color: Color) -> &mut Self {
+        let vertices: Vec<Vec2> = vertices.collect();
+        let segments = vertices.len().saturating_sub(1);
+
+        for (i, pair) in vertices.windows(2).enumerate() {
+            let a = u32::from(color.a) * (i as u32 + 1) / segments as u32;
+            self.set_color(Color::RGBA(color.r, color.g, color.b, a as u8))
+                .aa_line(pair[0], pair[1]);
+        }
+
+        self.set_color(color)
+    }
+
+    /// Backends that can't switch blending keep alpha blending.
+    fn set_blend_mode(&mut self, _mode: BlendMode) -> &mut Self {
+        self
+    }
+
+    /// Draws anti-aliased TrueType text `size` pixels tall with its top left corner at `pos`.
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self;
 
     fn size(&self) -> (usize, usize);
 
@@ -60,6 +124,10 @@ pub trait Renderer {
         self.size().1
     }
 
+    /// Reads back the current frame as tightly packed RGBA8 rows, `None` if the renderer
+    /// has no pixels, like the SVG one.
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)>;
+
     fn set_color(&mut self, color: Color) -> &mut Self;
     fn clear(&mut self) -> &mut Self;
 
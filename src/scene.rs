@@ -0,0 +1,367 @@
+use crate::{
+    rng::Rng,
+    vec2::{Real, Vec2},
+    world::{BodyShape, Edge, Particle, World},
+};
+
+/// A built-in world setup. Implement it and list it in [`PRESETS`] to add one to the gallery.
+pub trait ScenePreset {
+    fn name(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// Fills an empty `world` laid out on a screen of `size`.
+    fn build(&self, world: &mut World, size: Vec2);
+}
+
+/// The first one is what a fresh start without a savefile opens with.
+pub const PRESETS: [&dyn ScenePreset; 7] = [
+    &Playground,
+    &StackedBoxes,
+    &ClothOnPegs,
+    &BallPit,
+    &Catapult,
+    &Pendulums,
+    &Vehicle,
+];
+
+/// Scenes are laid out for 1920x1080 and scaled to whatever the window actually is.
+fn at(size: Vec2, x: Real, y: Real) -> Vec2 {
+    Vec2::new(x / 1920.0 * size.x, y / 1080.0 * size.y)
+}
+
+fn rect(world: &mut World, w: usize, h: usize, top_left: Vec2) {
+    world
+        .spawn_rect(w, h, top_left.x, top_left.y)
+        .expect("preset rects should be at least 2x2");
+}
+
+fn edge(world: &mut World, start: Vec2, end: Vec2) {
+    world
+        .add_edge(start, end)
+        .expect("preset edges should not be degenerate");
+}
+
+fn pin(world: &mut World, pos: Vec2) {
+    if let Some(n) = world.particle_at(pos) {
+        world.toggle_pin(n);
+    }
+}
+
+pub struct Playground;
+
+impl ScenePreset for Playground {
+    fn name(&self) -> &'static str {
+        "playground"
+    }
+
+    fn description(&self) -> &'static str {
+        "A few ledges and a slope to throw things at"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        edge(world, at(size, 0.0, 400.0), at(size, 280.0, 400.0));
+        edge(world, at(size, 400.0, 700.0), at(size, 680.0, 700.0));
+        edge(world, at(size, 850.0, 1080.0), at(size, 1920.0, 800.0));
+    }
+}
+
+pub struct StackedBoxes;
+
+impl ScenePreset for StackedBoxes {
+    fn name(&self) -> &'static str {
+        "stacked boxes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Three towers of boxes waiting to be knocked over"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        const LEVELS: usize = 5;
+        let box_size = 3.0 * Particle::SPACING;
+
+        for x in [700.0, 960.0, 1220.0] {
+            let left = at(size, x, 0.0).x - box_size / 2.0;
+            for level in 0..LEVELS {
+                let top = size.y - (level + 1) as Real * (box_size + 2.0 * Particle::R + 4.0);
+                rect(world, 4, 4, Vec2::new(left, top));
+            }
+        }
+    }
+}
+
+pub struct ClothOnPegs;
+
+impl ScenePreset for ClothOnPegs {
+    fn name(&self) -> &'static str {
+        "cloth on pegs"
+    }
+
+    fn description(&self) -> &'static str {
+        "A sheet hung from a row of pegs, with a box dropped onto it"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        const W: usize = 30;
+        const H: usize = 14;
+        let width = (W - 1) as Real * Particle::SPACING;
+        let top_left = Vec2::new(size.x / 2.0 - width / 2.0, at(size, 0.0, 200.0).y);
+
+        rect(world, W, H, top_left);
+        for i in (0..W).step_by(7).chain([W - 1]) {
+            pin(
+                world,
+                top_left + Vec2::new(i as Real * Particle::SPACING, 0.0),
+            );
+        }
+
+        rect(world, 4, 4, Vec2::new(size.x / 2.0 - 30.0, 20.0));
+    }
+}
+
+pub struct BallPit;
+
+impl ScenePreset for BallPit {
+    fn name(&self) -> &'static str {
+        "ball pit"
+    }
+
+    fn description(&self) -> &'static str {
+        "A pit full of small squishy bodies"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        edge(world, at(size, 480.0, 450.0), at(size, 600.0, 1000.0));
+        edge(world, at(size, 600.0, 1000.0), at(size, 1320.0, 1000.0));
+        edge(world, at(size, 1320.0, 1000.0), at(size, 1440.0, 450.0));
+
+        let start = at(size, 640.0, 100.0);
+        let end = at(size, 1280.0, 100.0);
+        let columns = ((end.x - start.x) / 75.0) as usize;
+        for row in 0..5 {
+            for column in 0..columns {
+                let offset = Vec2::new(
+                    column as Real * 75.0 + (row % 2) as Real * 30.0,
+                    row as Real * 75.0,
+                );
+                rect(world, 3, 3, start + offset);
+            }
+        }
+    }
+}
+
+pub struct Catapult;
+
+impl ScenePreset for Catapult {
+    fn name(&self) -> &'static str {
+        "catapult"
+    }
+
+    fn description(&self) -> &'static str {
+        "A plank on a fulcrum, about to have a heavy box land on one end"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        const PLANK: usize = 24;
+        let tip = Vec2::new(at(size, 960.0, 0.0).x, size.y - 120.0);
+        edge(world, Vec2::new(tip.x - 70.0, size.y), tip);
+        edge(world, tip, Vec2::new(tip.x + 70.0, size.y));
+
+        let plank_width = (PLANK - 1) as Real * Particle::SPACING;
+        // Resting just above the tip, rows are a spacing apart and particles need some room
+        let clearance = Particle::SPACING + 3.0 * Particle::R;
+        let plank = Vec2::new(tip.x - plank_width / 2.0, tip.y - clearance);
+        rect(world, PLANK, 2, plank);
+
+        // Projectile on the left end, counterweight falling onto the right one
+        rect(
+            world,
+            3,
+            3,
+            plank - Vec2::new(0.0, Particle::SPACING + clearance),
+        );
+        rect(
+            world,
+            6,
+            6,
+            Vec2::new(plank.x + plank_width - 5.0 * Particle::SPACING, 40.0),
+        );
+    }
+}
+
+pub struct Pendulums;
+
+impl ScenePreset for Pendulums {
+    fn name(&self) -> &'static str {
+        "pendulums"
+    }
+
+    fn description(&self) -> &'static str {
+        "A row of hanging ropes, the first one pulled aside"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        const LEN: Real = 420.0;
+        let (first, spacing) = (at(size, 760.0, 150.0), 100.0);
+
+        for i in 0..5 {
+            let start = first + Vec2::new(i as Real * spacing, 0.0);
+            let dir = if i == 0 {
+                Vec2::new(-0.8, 0.6)
+            } else {
+                Vec2::new(0.0, 1.0)
+            };
+
+            world
+                .spawn_rope(start, start + dir * LEN)
+                .expect("preset ropes should be long enough");
+            pin(world, start);
+        }
+    }
+}
+
+pub struct Vehicle;
+
+impl ScenePreset for Vehicle {
+    fn name(&self) -> &'static str {
+        "vehicle"
+    }
+
+    fn description(&self) -> &'static str {
+        "A cart on two motorized wheels over rolling hills, driven with A and D"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        const COLUMNS: usize = 10;
+        const WHEEL: Real = 50.0;
+        const TORQUE: Real = 500_000.0;
+
+        let hills = [
+            (0.0, 820.0),
+            (350.0, 900.0),
+            (700.0, 840.0),
+            (1000.0, 960.0),
+            (1350.0, 880.0),
+            (1650.0, 990.0),
+            (1920.0, 900.0),
+        ];
+        for pair in hills.windows(2) {
+            let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+            edge(world, at(size, ax, ay), at(size, bx, by));
+        }
+
+        let chassis = at(size, 150.0, 680.0);
+        rect(world, COLUMNS, 3, chassis);
+        let bottom = chassis.y + 2.0 * Particle::SPACING;
+
+        // Hubs hang far enough below the chassis for the rims to clear it, each on three
+        // links to its underside, which doubles as a soft suspension
+        for column in [1, COLUMNS - 2] {
+            let x = chassis.x + column as Real * Particle::SPACING;
+            let hub = Vec2::new(x, bottom + WHEEL + 3.0 * Particle::R);
+            world
+                .spawn_shape(BodyShape::Wheel, hub, WHEEL)
+                .expect("preset wheels should be large enough");
+            world.set_motor(world.info().4 - 1, TORQUE);
+
+            let hub = world.particle_at(hub).expect("wheels have a hub particle");
+            for dx in [-1.0, 0.0, 1.0] {
+                let mount = Vec2::new(x + dx * Particle::SPACING, bottom);
+                if let Some(mount) = world.particle_at(mount) {
+                    world.link(hub, mount);
+                }
+            }
+        }
+    }
+}
+
+/// `count` randomly placed rectangles, ropes and edges, the same ones for the same `seed`.
+/// Meant for stress tests and reproducible benchmark scenes.
+#[derive(Clone, Copy, Debug)]
+pub struct RandomScene {
+    pub count: usize,
+    pub seed: u64,
+}
+
+enum Shape {
+    Rect(usize, usize, Vec2),
+    Rope(Vec2, Vec2),
+    Edge(Vec2, Vec2),
+}
+
+impl Shape {
+    fn random(rng: &mut Rng, size: Vec2) -> Self {
+        let pos = Vec2::new(rng.range(0.0, size.x), rng.range(0.0, size.y));
+        let dir = Vec2::from_angle(rng.range(0.0, crate::vec2::consts::TAU));
+
+        match rng.next_f64() {
+            k if k < 0.6 => Self::Rect(rng.range_usize(2, 7), rng.range_usize(2, 7), pos),
+            k if k < 0.85 => Self::Rope(pos, pos + dir * rng.range(60.0, 320.0)),
+            _ => Self::Edge(pos, pos + dir * rng.range(100.0, 400.0)),
+        }
+    }
+
+    /// Corners of the box the shape occupies, including some room around it.
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let (min, max, r) = match *self {
+            Self::Rect(w, h, pos) => (
+                pos,
+                pos + Vec2::new((w - 1) as Real, (h - 1) as Real) * Particle::SPACING,
+                Particle::R,
+            ),
+            Self::Rope(a, b) => (a.min(b), a.max(b), Particle::R),
+            Self::Edge(a, b) => (a.min(b), a.max(b), Edge::R),
+        };
+        let pad = r + 2.0 * Particle::R;
+        (min - pad, max + pad)
+    }
+
+    fn spawn(&self, world: &mut World) {
+        match *self {
+            Self::Rect(w, h, pos) => rect(world, w, h, pos),
+            Self::Rope(a, b) => {
+                world
+                    .spawn_rope(a, b)
+                    .expect("random ropes should be long enough");
+            }
+            Self::Edge(a, b) => edge(world, a, b),
+        }
+    }
+}
+
+impl ScenePreset for RandomScene {
+    fn name(&self) -> &'static str {
+        "random"
+    }
+
+    fn description(&self) -> &'static str {
+        "Randomly placed rectangles, ropes and edges, reproducible by seed"
+    }
+
+    fn build(&self, world: &mut World, size: Vec2) {
+        // Attempts at finding a free spot before a shape is given up on
+        const TRIES: usize = 20;
+        let mut rng = Rng::new(self.seed);
+        let mut taken: Vec<(Vec2, Vec2)> = vec![];
+
+        for _ in 0..self.count {
+            for _ in 0..TRIES {
+                let shape = Shape::random(&mut rng, size);
+                let (min, max) = shape.bounds();
+
+                let on_screen = min.x >= 0.0 && min.y >= 0.0 && max.x <= size.x && max.y <= size.y;
+                let free = taken.iter().all(|&(other_min, other_max)| {
+                    max.x < other_min.x
+                        || min.x > other_max.x
+                        || max.y < other_min.y
+                        || min.y > other_max.y
+                });
+
+                if on_screen && free {
+                    shape.spawn(world);
+                    taken.push((min, max));
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,72 @@
 use crate::{
-    renderer::{Color, Renderer},
-    vec2::Vec2,
+    font::Font,
+    framebuffer::Framebuffer,
+    renderer::{BlendMode, Color, Renderer},
+    vec2::{Real, Vec2},
 };
 use sdl2::{
     gfx::primitives::DrawRenderer,
-    pixels::Color as Sdl2Color,
-    render::{Canvas, RenderTarget},
+    pixels::{Color as Sdl2Color, PixelFormatEnum},
+    rect::Point,
+    render::{BlendMode as Sdl2BlendMode, Canvas, RenderTarget, TextureCreator},
+    video::{Window, WindowContext},
 };
 
-pub struct SDL2CanvasWrapper<T: RenderTarget>(Canvas<T>, Sdl2Color);
+pub struct SDL2CanvasWrapper<T: RenderTarget>(Canvas<T>, Sdl2Color, Font, Scratch);
+
+// Text coverage is bucketed into this many alpha levels
+const TEXT_LEVELS: usize = 8;
+
+/// Buffers kept between draw calls, so drawing a frame doesn't allocate once they've grown.
+#[derive(Default)]
+struct Scratch {
+    vx: Vec<i16>,
+    vy: Vec<i16>,
+    levels: [Vec<Point>; TEXT_LEVELS],
+}
+
+impl Scratch {
+    fn coords(&mut self, vertices: impl Iterator<Item = Vec2>) -> (&[i16], &[i16]) {
+        self.vx.clear();
+        self.vy.clear();
+
+        for v in vertices {
+            self.vx.push(v.x as i16);
+            self.vy.push(v.y as i16);
+        }
+
+        (&self.vx, &self.vy)
+    }
+}
+
+impl<T: RenderTarget> SDL2CanvasWrapper<T> {
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.0
+            .set_logical_size(width, height)
+            .expect("could not set logical size");
+    }
+}
 
 impl<T: RenderTarget> From<sdl2::render::Canvas<T>> for SDL2CanvasWrapper<T> {
-    fn from(canvas: sdl2::render::Canvas<T>) -> Self {
-        Self(canvas, Sdl2Color::RGBA(0, 0, 0, 0))
+    fn from(mut canvas: sdl2::render::Canvas<T>) -> Self {
+        // The UI panels are translucent, so blending has to be on for the plain SDL calls too
+        canvas.set_blend_mode(Sdl2BlendMode::Blend);
+        Self(
+            canvas,
+            Sdl2Color::RGBA(0, 0, 0, 0),
+            Font::new(),
+            Scratch::default(),
+        )
+    }
+}
+
+impl From<BlendMode> for Sdl2BlendMode {
+    fn from(mode: BlendMode) -> Self {
+        match mode {
+            BlendMode::None => Self::None,
+            BlendMode::Blend => Self::Blend,
+            BlendMode::Add => Self::Add,
+        }
     }
 }
 
@@ -23,7 +77,7 @@ impl From<Color> for Sdl2Color {
 }
 
 impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
-    fn filled_circle(&mut self, center: Vec2, radius: f64) -> &mut Self {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
         self.0
             .filled_circle(center.x as i16, center.y as i16, radius as i16, self.1)
             .expect("could not draw filled circle");
@@ -39,7 +93,7 @@ impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
         self
     }
 
-    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: f64) -> &mut Self {
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
         self.0
             .thick_line(
                 a.x as i16,
@@ -70,7 +124,7 @@ impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
         self
     }
 
-    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: f64) -> &mut Self {
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
         self.0
             .rounded_box(
                 a.x as i16,
@@ -86,26 +140,86 @@ impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
     }
 
     fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
-        let n = vertices.size_hint().1.unwrap_or_default();
-        let mut vx = Vec::<i16>::with_capacity(n);
-        let mut vy = Vec::<i16>::with_capacity(n);
-
-        for v in vertices {
-            vx.push(v.x as i16);
-            vy.push(v.y as i16);
-        }
+        let (vx, vy) = self.3.coords(vertices);
 
         self.0
-            .polygon(&vx, &vy, self.1)
+            .polygon(vx, vy, self.1)
             .expect("could not draw polygon");
 
         self
     }
 
-    fn text(&mut self, pos: Vec2, text: &str) -> &mut Self {
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let (vx, vy) = self.3.coords(vertices);
+
+        self.0
+            .filled_polygon(vx, vy, self.1)
+            .expect("could not draw filled polygon");
+
+        self
+    }
+
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.0
+            .aa_line(a.x as i16, a.y as i16, b.x as i16, b.y as i16, self.1)
+            .expect("could not draw aa line");
+
+        self
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let (x, y, r) = (center.x as i16, center.y as i16, radius as i16);
+
+        // gfx has no filled aa circle, smooth the edge of a filled one with an aa outline
+        self.0
+            .filled_circle(x, y, r, self.1)
+            .expect("could not draw filled circle");
+        self.0
+            .aa_circle(x, y, r, self.1)
+            .expect("could not draw aa circle");
+
+        self
+    }
+
+    fn aa_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let (vx, vy) = self.3.coords(vertices);
+
         self.0
-            .string(pos.x as i16, pos.y as i16, text, self.1)
-            .expect("could not draw text");
+            .aa_polygon(vx, vy, self.1)
+            .expect("could not draw aa polygon");
+
+        self
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        self.0.set_blend_mode(mode.into());
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        // Bucket the coverage into a few alpha levels so a whole string is a handful of
+        // point batches instead of one draw call per pixel
+        let levels = &mut self.3.levels;
+        levels.iter_mut().for_each(Vec::clear);
+
+        self.2.rasterize(pos, text, size, |x, y, alpha| {
+            levels[usize::from(alpha) * TEXT_LEVELS / 256].push(Point::new(x as i32, y as i32));
+        });
+
+        let color = self.1;
+        for (i, points) in levels.iter().enumerate() {
+            if points.is_empty() {
+                continue;
+            }
+
+            let a = u32::from(color.a) * (2 * i as u32 + 1) / (2 * TEXT_LEVELS as u32);
+            self.0
+                .set_draw_color(Sdl2Color::RGBA(color.r, color.g, color.b, a as u8));
+            self.0
+                .draw_points(points.as_slice())
+                .expect("could not draw text");
+        }
+        self.0.set_draw_color(color);
 
         self
     }
@@ -115,6 +229,16 @@ impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
         (w as usize, h as usize)
     }
 
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        let (w, h) = self.0.output_size().expect("could not get output size");
+        let pixels = self
+            .0
+            .read_pixels(None, PixelFormatEnum::RGBA32)
+            .expect("could not read pixels");
+
+        Some((w as usize, h as usize, pixels))
+    }
+
     fn set_color(&mut self, color: Color) -> &mut Self {
         self.0.set_draw_color(color);
         self.1 = color.into();
@@ -131,3 +255,134 @@ impl<T: RenderTarget> Renderer for SDL2CanvasWrapper<T> {
         self.0.present();
     }
 }
+
+/// Rasterizes on the CPU into a [`Framebuffer`] and only uses SDL to present it.
+pub struct SDL2FramebufferWrapper {
+    canvas: Canvas<Window>,
+    textures: TextureCreator<WindowContext>,
+    framebuffer: Framebuffer,
+}
+
+impl SDL2FramebufferWrapper {
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.canvas
+            .set_logical_size(width, height)
+            .expect("could not set logical size");
+        self.framebuffer.resize(width as usize, height as usize);
+    }
+}
+
+impl From<Canvas<Window>> for SDL2FramebufferWrapper {
+    fn from(canvas: Canvas<Window>) -> Self {
+        let (w, h) = canvas.output_size().expect("could not get output size");
+
+        Self {
+            textures: canvas.texture_creator(),
+            canvas,
+            framebuffer: Framebuffer::new(w as usize, h as usize),
+        }
+    }
+}
+
+impl Renderer for SDL2FramebufferWrapper {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        self.framebuffer.filled_circle(center, radius);
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.framebuffer.line(a, b);
+        self
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        self.framebuffer.thick_line(a, b, thickness);
+        self
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.framebuffer.rectangle(a, b);
+        self
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.framebuffer.filled_rectangle(a, b);
+        self
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        self.framebuffer.filled_rounded_rectangle(a, b, radius);
+        self
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.framebuffer.polygon(vertices);
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.framebuffer.filled_polygon(vertices);
+        self
+    }
+
+    fn aa_line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.framebuffer.aa_line(a, b);
+        self
+    }
+
+    fn aa_filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        self.framebuffer.aa_filled_circle(center, radius);
+        self
+    }
+
+    fn aa_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.framebuffer.aa_polygon(vertices);
+        self
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        self.framebuffer.set_blend_mode(mode);
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        self.framebuffer.text_sized(pos, text, size);
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.framebuffer.size()
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        self.framebuffer.read_pixels()
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        self.framebuffer.set_color(color);
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.framebuffer.clear();
+        self
+    }
+
+    fn finish(&mut self) {
+        let (w, h) = self.framebuffer.size();
+        let mut texture = self
+            .textures
+            .create_texture_streaming(PixelFormatEnum::RGBA32, w as u32, h as u32)
+            .expect("could not create framebuffer texture");
+
+        texture
+            .update(None, self.framebuffer.pixels(), w * 4)
+            .expect("could not upload framebuffer");
+
+        self.canvas.clear();
+        self.canvas
+            .copy(&texture, None, None)
+            .expect("could not copy framebuffer");
+        self.canvas.present();
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Refers to a particle, spring, object or edge of a [`World`](crate::world::World) for as
+/// long as it's there. Unlike an index it can't end up at another one once things before it
+/// are removed, it just stops resolving.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct Id {
+    slot: u32,
+    generation: u32,
+}
+
+/// Worlds saved before springs and boundaries held ids have particle indices in their
+/// place. Those are the ids [`Slots::reset`] hands out again on loading.
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Saved {
+            Id { slot: u32, generation: u32 },
+            Index(u32),
+        }
+
+        Ok(match Saved::deserialize(deserializer)? {
+            Saved::Id { slot, generation } => Self { slot, generation },
+            Saved::Index(slot) => Self {
+                slot,
+                generation: 0,
+            },
+        })
+    }
+}
+
+/// Hands out ids for the items of a `Vec` that closes the gaps left by removals, slotmap
+/// style: an id names a slot, which knows where its item is now and is reused under the
+/// next generation once the item is gone.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct Slots {
+    /// Generation of every slot and the index of its item, `None` while free
+    slots: Vec<(u32, Option<usize>)>,
+    free: Vec<u32>,
+    /// Of every item, by index
+    ids: Vec<Id>,
+}
+
+impl Slots {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Gives the item just pushed onto the end an id.
+    pub fn push(&mut self) -> Id {
+        let index = self.ids.len();
+        let id = if let Some(slot) = self.free.pop() {
+            let (generation, item) = &mut self.slots[slot as usize];
+            *item = Some(index);
+            Id {
+                slot,
+                generation: *generation,
+            }
+        } else {
+            let slot = u32::try_from(self.slots.len()).expect("there should be under 4G slots");
+            self.slots.push((0, Some(index)));
+            Id {
+                slot,
+                generation: 0,
+            }
+        };
+        self.ids.push(id);
+        id
+    }
+
+    pub fn id(&self, n: usize) -> Option<Id> {
+        self.ids.get(n).copied()
+    }
+
+    pub fn index(&self, id: Id) -> Option<usize> {
+        match self.slots.get(id.slot as usize) {
+            Some(&(generation, item)) if generation == id.generation => item,
+            _ => None,
+        }
+    }
+
+    /// Index of `id`, held by something removed along with its item so it always resolves.
+    pub fn at(&self, id: Id) -> usize {
+        self.index(id)
+            .expect("items should outlive whatever holds their ids")
+    }
+
+    /// Frees the ids of the items marked `dead` and moves the rest down with their items,
+    /// any past the end of `dead` are kept.
+    pub fn retain_alive(&mut self, dead: &[bool]) {
+        let mut live = 0;
+        for (n, &id) in self.ids.iter().enumerate() {
+            let (generation, item) = &mut self.slots[id.slot as usize];
+            if dead.get(n).copied().unwrap_or(false) {
+                *generation = generation.wrapping_add(1);
+                *item = None;
+                self.free.push(id.slot);
+            } else {
+                *item = Some(live);
+                live += 1;
+            }
+        }
+        let mut n = 0;
+        self.ids.retain(|_| {
+            n += 1;
+            !dead.get(n - 1).copied().unwrap_or(false)
+        });
+    }
+
+    /// Frees the id of item `n`, the ones after it move down.
+    pub fn remove(&mut self, n: usize) {
+        let mut dead = vec![false; n + 1];
+        dead[n] = true;
+        self.retain_alive(&dead);
+    }
+
+    pub fn clear(&mut self) {
+        self.retain_alive(&vec![true; self.ids.len()]);
+    }
+
+    /// Fresh ids for `len` items, dropping the old ones. For worlds saved before ids were.
+    pub fn reset(&mut self, len: usize) {
+        *self = Self::default();
+        for _ in 0..len {
+            self.push();
+        }
+    }
+}
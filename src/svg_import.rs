@@ -0,0 +1,135 @@
+use crate::vec2::{Real, Vec2};
+use svgtypes::{PointsParser, SimplePathSegment, SimplifyingPathParser, Transform};
+
+// Whatever is inside these is only drawn when referenced from elsewhere, if at all
+const HIDDEN: [&str; 6] = ["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+/// Line segments tracing the paths, polylines, polygons and lines of `svg`, for turning
+/// drawn terrain into edges. Coordinates are taken as pixels, after transforms, and curves
+/// are flattened until they stray no more than `tolerance` from the real thing.
+pub fn segments(svg: &str, tolerance: Real) -> Result<Vec<(Vec2, Vec2)>, String> {
+    let document = roxmltree::Document::parse(svg).map_err(|err| err.to_string())?;
+    let mut segments = vec![];
+
+    for node in document.descendants().filter(roxmltree::Node::is_element) {
+        if node
+            .ancestors()
+            .skip(1)
+            .any(|parent| HIDDEN.contains(&parent.tag_name().name()))
+        {
+            continue;
+        }
+
+        let transform = node
+            .ancestors()
+            .filter_map(|node| node.attribute("transform"))
+            .map(str::parse)
+            .collect::<Result<Vec<Transform>, _>>()
+            .map_err(|err| err.to_string())?;
+        let to_world = |(x, y): (f64, f64)| {
+            let (x, y) = transform.iter().fold((x, y), |(x, y), t| {
+                (t.a * x + t.c * y + t.e, t.b * x + t.d * y + t.f)
+            });
+            Vec2::new(x as Real, y as Real)
+        };
+        let attribute = |name| node.attribute(name).unwrap_or_default();
+        let number = |name| attribute(name).parse::<f64>().unwrap_or(0.0);
+
+        let lines = match node.tag_name().name() {
+            "path" => path(attribute("d"), &to_world, tolerance)?,
+            "polyline" => vec![PointsParser::from(attribute("points"))
+                .map(to_world)
+                .collect()],
+            "polygon" => {
+                let mut points: Vec<Vec2> = PointsParser::from(attribute("points"))
+                    .map(to_world)
+                    .collect();
+                points.extend(points.first().copied());
+                vec![points]
+            }
+            "line" => vec![vec![
+                to_world((number("x1"), number("y1"))),
+                to_world((number("x2"), number("y2"))),
+            ]],
+            _ => continue,
+        };
+
+        for line in lines {
+            segments.extend(line.windows(2).map(|pair| (pair[0], pair[1])));
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Every subpath of the path data `d` as a polyline.
+fn path(
+    d: &str,
+    to_world: &impl Fn((f64, f64)) -> Vec2,
+    tolerance: Real,
+) -> Result<Vec<Vec<Vec2>>, String> {
+    let mut lines: Vec<Vec<Vec2>> = vec![];
+
+    for segment in SimplifyingPathParser::from(d) {
+        let segment = segment.map_err(|err| err.to_string())?;
+        if let SimplePathSegment::MoveTo { x, y } = segment {
+            lines.push(vec![to_world((x, y))]);
+            continue;
+        }
+
+        // The parser puts a move before anything else, this is only for malformed data
+        let Some(line) = lines.last_mut() else {
+            continue;
+        };
+        let from = *line.last().expect("subpaths start with a point");
+
+        match segment {
+            SimplePathSegment::LineTo { x, y } => line.push(to_world((x, y))),
+            SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                let (c, to) = (to_world((x1, y1)), to_world((x, y)));
+                line.extend(flatten(&[from, c, to], tolerance));
+            }
+            SimplePathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let (c1, c2, to) = (to_world((x1, y1)), to_world((x2, y2)), to_world((x, y)));
+                line.extend(flatten(&[from, c1, c2, to], tolerance));
+            }
+            SimplePathSegment::ClosePath => line.push(line[0]),
+            SimplePathSegment::MoveTo { .. } => unreachable!(),
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Points along the Bézier curve with `controls`, leaving out the first one. Wang's formula
+/// gives how many segments keep it within `tolerance`.
+fn flatten(controls: &[Vec2], tolerance: Real) -> impl Iterator<Item = Vec2> + '_ {
+    let degree = (controls.len() - 1) as Real;
+    let bend = controls
+        .windows(3)
+        .map(|c| (c[0] - 2.0 * c[1] + c[2]).len())
+        .fold(0.0, Real::max);
+    let n = (degree * (degree - 1.0) / 8.0 * bend / tolerance.max(1e-3))
+        .sqrt()
+        .ceil()
+        .max(1.0) as usize;
+
+    (1..=n).map(move |i| {
+        // De Casteljau
+        let t = i as Real / n as Real;
+        let mut points = controls.to_vec();
+        for len in (1..points.len()).rev() {
+            for j in 0..len {
+                points[j] = points[j] * (1.0 - t) + points[j + 1] * t;
+            }
+        }
+        points[0]
+    })
+}
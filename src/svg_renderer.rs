@@ -0,0 +1,183 @@
+use crate::{
+    renderer::{Color, Renderer},
+    vec2::{Real, Vec2},
+};
+use std::fmt::Write;
+
+/// Collects draw calls as SVG elements instead of rasterizing them.
+pub struct SvgRenderer {
+    width: usize,
+    height: usize,
+    color: Color,
+    body: String,
+}
+
+impl SvgRenderer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            color: Color::BLACK,
+            body: String::new(),
+        }
+    }
+
+    pub fn into_svg(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height,
+        )
+    }
+
+    fn fill(&self) -> String {
+        let Color { r, g, b, a } = self.color;
+        format!(
+            "fill=\"rgb({r},{g},{b})\" fill-opacity=\"{:.3}\"",
+            Real::from(a) / 255.0
+        )
+    }
+
+    fn stroke(&self, width: Real) -> String {
+        let Color { r, g, b, a } = self.color;
+        format!(
+            "fill=\"none\" stroke=\"rgb({r},{g},{b})\" stroke-opacity=\"{:.3}\" stroke-width=\"{width}\"",
+            Real::from(a) / 255.0
+        )
+    }
+
+    fn points(vertices: impl Iterator<Item = Vec2>) -> String {
+        let mut points = String::new();
+        for v in vertices {
+            let _ = write!(points, "{:.2},{:.2} ", v.x, v.y);
+        }
+        points
+    }
+
+    fn push(&mut self, element: String) -> &mut Self {
+        self.body.push_str(&element);
+        self.body.push('\n');
+        self
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let element = format!(
+            "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{radius:.2}\" {}/>",
+            center.x,
+            center.y,
+            self.fill()
+        );
+        self.push(element)
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.thick_line(a, b, 1.0)
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        let element = format!(
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke-linecap=\"round\" {}/>",
+            a.x,
+            a.y,
+            b.x,
+            b.y,
+            self.stroke(thickness)
+        );
+        self.push(element)
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (min, max) = (a.min(b), a.max(b));
+        let element = format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {}/>",
+            min.x,
+            min.y,
+            max.x - min.x,
+            max.y - min.y,
+            self.stroke(1.0)
+        );
+        self.push(element)
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.filled_rounded_rectangle(a, b, 0.0)
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        let (min, max) = (a.min(b), a.max(b));
+        let element = format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" rx=\"{radius:.2}\" {}/>",
+            min.x,
+            min.y,
+            max.x - min.x,
+            max.y - min.y,
+            self.fill()
+        );
+        self.push(element)
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let element = format!(
+            "<polygon points=\"{}\" {}/>",
+            Self::points(vertices),
+            self.stroke(1.0)
+        );
+        self.push(element)
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let element = format!(
+            "<polygon points=\"{}\" {}/>",
+            Self::points(vertices),
+            self.fill()
+        );
+        self.push(element)
+    }
+
+    fn polyline(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let element = format!(
+            "<polyline points=\"{}\" {}/>",
+            Self::points(vertices),
+            self.stroke(1.0)
+        );
+        self.push(element)
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        let escaped = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let element = format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-family=\"monospace\" font-size=\"{size}\" dominant-baseline=\"hanging\" {}>{escaped}</text>",
+            pos.x,
+            pos.y,
+            self.fill()
+        );
+        self.push(element)
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        None
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.body.clear();
+        let (w, h) = (self.width as Real, self.height as Real);
+        self.filled_rectangle(Vec2::new(0.0, 0.0), Vec2::new(w, h))
+    }
+
+    fn finish(&mut self) {}
+}
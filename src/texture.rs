@@ -0,0 +1,70 @@
+use crate::{renderer::Color, vec2::Real};
+use std::{fs::File, io::BufReader, path::Path};
+
+/// Decoded RGBA8 image that soft bodies can be painted with.
+pub struct Texture {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Texture {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+
+        let mut decoder = png::Decoder::new(BufReader::new(file));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut buf = vec![
+            0;
+            reader
+                .output_buffer_size()
+                .ok_or_else(|| String::from("image is too large"))?
+        ];
+        let info = reader.next_frame(&mut buf).map_err(|err| err.to_string())?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+            png::ColorType::Indexed => return Err(String::from("unexpanded palette image")),
+        };
+
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Nearest texel at normalized coordinates, clamped to the edges.
+    pub fn sample(&self, u: Real, v: Real) -> Color {
+        let x = ((u.clamp(0.0, 1.0) * self.width as Real) as usize).min(self.width - 1);
+        let y = ((v.clamp(0.0, 1.0) * self.height as Real) as usize).min(self.height - 1);
+        self.texel(x, y)
+    }
+
+    pub fn texel(&self, x: usize, y: usize) -> Color {
+        let i = (y * self.width + x) * 4;
+
+        Color::RGBA(
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        )
+    }
+}
@@ -0,0 +1,151 @@
+use crate::renderer::Color;
+use serde::{Deserialize, Serialize};
+
+/// Every color the world and the UI are drawn with.
+pub struct Theme {
+    pub background: Color,
+    pub panel: Color,
+    pub tooltip: Color,
+    pub text: Color,
+    pub highlight: Color,
+    pub alert: Color,
+    pub preview: Color,
+    pub edge: Color,
+    pub edge_end: Color,
+    pub particle: Color,
+    pub spring: Color,
+    pub pin: Color,
+    pub drag: Color,
+    pub selection: Color,
+    pub info: Color,
+    pub warn: Color,
+    pub error: Color,
+    /// Cycled through by objects that have no color of their own
+    pub objects: [Color; 7],
+}
+
+pub const DARK: Theme = Theme {
+    background: Color::RGB(11, 14, 20),
+    panel: Color::RGBA(88, 112, 160, 120),
+    tooltip: Color::RGBA(88, 112, 160, 200),
+    text: Color::RGB(176, 224, 255),
+    highlight: Color::CYAN,
+    alert: Color::RED,
+    preview: Color::RGB(44, 56, 80),
+    edge: Color::RGB(44, 56, 80),
+    edge_end: Color::RGB(88, 112, 161),
+    particle: Color::YELLOW,
+    spring: Color::CYAN,
+    pin: Color::RED,
+    drag: Color::WHITE,
+    selection: Color::WHITE,
+    info: Color::RGB(176, 224, 255),
+    warn: Color::RGB(255, 210, 90),
+    error: Color::RGB(255, 110, 110),
+    objects: [
+        Color::RED,
+        Color::YELLOW,
+        Color::BLUE,
+        Color::MAGENTA,
+        Color::CYAN,
+        Color::GREEN,
+        Color::WHITE,
+    ],
+};
+
+pub const LIGHT: Theme = Theme {
+    background: Color::RGB(236, 239, 244),
+    panel: Color::RGBA(120, 140, 180, 90),
+    tooltip: Color::RGBA(210, 220, 235, 230),
+    text: Color::RGB(30, 40, 60),
+    highlight: Color::RGB(0, 110, 190),
+    alert: Color::RGB(200, 30, 30),
+    preview: Color::RGB(150, 165, 190),
+    edge: Color::RGB(150, 160, 180),
+    edge_end: Color::RGB(80, 95, 130),
+    particle: Color::RGB(200, 140, 0),
+    spring: Color::RGB(0, 130, 170),
+    pin: Color::RGB(200, 30, 30),
+    drag: Color::RGB(30, 40, 60),
+    selection: Color::RGB(20, 20, 20),
+    info: Color::RGB(30, 60, 110),
+    warn: Color::RGB(160, 100, 0),
+    error: Color::RGB(190, 30, 30),
+    objects: [
+        Color::RGB(200, 40, 40),
+        Color::RGB(190, 140, 0),
+        Color::RGB(40, 80, 200),
+        Color::RGB(170, 40, 170),
+        Color::RGB(0, 140, 160),
+        Color::RGB(30, 140, 60),
+        Color::RGB(90, 90, 90),
+    ],
+};
+
+/// Dark theme built from the Okabe-Ito palette, which stays distinguishable under the
+/// common forms of color blindness.
+pub const COLORBLIND: Theme = Theme {
+    background: Color::RGB(11, 14, 20),
+    panel: Color::RGBA(88, 112, 160, 120),
+    tooltip: Color::RGBA(88, 112, 160, 200),
+    text: Color::RGB(176, 224, 255),
+    highlight: Color::RGB(86, 180, 233),
+    alert: Color::RGB(213, 94, 0),
+    preview: Color::RGB(44, 56, 80),
+    edge: Color::RGB(44, 56, 80),
+    edge_end: Color::RGB(88, 112, 161),
+    particle: Color::RGB(240, 228, 66),
+    spring: Color::RGB(86, 180, 233),
+    pin: Color::RGB(213, 94, 0),
+    drag: Color::WHITE,
+    selection: Color::WHITE,
+    info: Color::RGB(176, 224, 255),
+    warn: Color::RGB(230, 159, 0),
+    error: Color::RGB(213, 94, 0),
+    objects: [
+        Color::RGB(230, 159, 0),
+        Color::RGB(86, 180, 233),
+        Color::RGB(0, 158, 115),
+        Color::RGB(240, 228, 66),
+        Color::RGB(0, 114, 178),
+        Color::RGB(213, 94, 0),
+        Color::RGB(204, 121, 167),
+    ],
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeKind {
+    #[default]
+    Dark,
+    Light,
+    Colorblind,
+}
+
+impl ThemeKind {
+    pub const ALL: [Self; 3] = [Self::Dark, Self::Light, Self::Colorblind];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::Colorblind => "colorblind",
+        }
+    }
+
+    pub fn theme(self) -> &'static Theme {
+        match self {
+            Self::Dark => &DARK,
+            Self::Light => &LIGHT,
+            Self::Colorblind => &COLORBLIND,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|&kind| kind == self)
+            .unwrap_or_default();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
@@ -0,0 +1,70 @@
+use crate::world::BodyShape;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tool {
+    Select,
+    SpawnRect,
+    DrawEdge,
+    DrawRope,
+    Delete,
+    Pin,
+    Grab,
+    SpawnShape(BodyShape),
+    Sand,
+    Emitter,
+    Drain,
+    GravityZone,
+    Inflate,
+    SpringGroups,
+    Mover,
+    Rod,
+    Measure,
+}
+
+impl Tool {
+    pub const ALL: [Self; 19] = [
+        Self::Select,
+        Self::SpawnRect,
+        Self::DrawEdge,
+        Self::DrawRope,
+        Self::Delete,
+        Self::Pin,
+        Self::Grab,
+        Self::SpawnShape(BodyShape::Ring),
+        Self::SpawnShape(BodyShape::Star),
+        Self::SpawnShape(BodyShape::Wheel),
+        Self::Sand,
+        Self::Emitter,
+        Self::Drain,
+        Self::GravityZone,
+        Self::Inflate,
+        Self::SpringGroups,
+        Self::Mover,
+        Self::Rod,
+        Self::Measure,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Select => "select",
+            Self::SpawnRect => "spawn rect",
+            Self::DrawEdge => "draw edge",
+            Self::DrawRope => "draw rope",
+            Self::Delete => "delete",
+            Self::Pin => "pin",
+            Self::Grab => "grab",
+            Self::SpawnShape(BodyShape::Ring) => "spawn ring",
+            Self::SpawnShape(BodyShape::Star) => "spawn star",
+            Self::SpawnShape(BodyShape::Wheel) => "spawn wheel",
+            Self::Sand => "pour sand",
+            Self::Emitter => "place emitter",
+            Self::Drain => "place drain",
+            Self::GravityZone => "gravity zone",
+            Self::Inflate => "inflate",
+            Self::SpringGroups => "spring groups",
+            Self::Mover => "place mover",
+            Self::Rod => "connect rod",
+            Self::Measure => "measure",
+        }
+    }
+}
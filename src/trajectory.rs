@@ -0,0 +1,191 @@
+use crate::{
+    vec2::{Real, Vec2},
+    world::World,
+};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Position and velocity of one particle, or one object's center of mass, at one frame.
+struct Sample {
+    frame: u64,
+    id: u64,
+    time: Real,
+    pos: Vec2,
+    vel: Vec2,
+}
+
+/// Logs how particles move for plotting them elsewhere, written out once recording is
+/// over as Parquet when the path ends in .parquet and CSV otherwise. Holds at most `limit`
+/// samples, past that the oldest are dropped, so a long run keeps only its last stretch.
+pub struct Trajectory {
+    path: PathBuf,
+    /// Only every that many frames are recorded
+    every: u64,
+    /// Centers of mass of objects instead of every particle
+    objects: bool,
+    limit: usize,
+    frames: u64,
+    samples: VecDeque<Sample>,
+    dropped: usize,
+}
+
+impl Trajectory {
+    pub fn new(path: PathBuf, every: u64, objects: bool, limit: usize) -> Self {
+        Self {
+            path,
+            every: every.max(1),
+            objects,
+            limit,
+            frames: 0,
+            samples: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Records the world as of a simulated frame.
+    pub fn record(&mut self, world: &World) {
+        let frame = self.frames;
+        self.frames += 1;
+        if !frame.is_multiple_of(self.every) {
+            return;
+        }
+
+        let time = world.time();
+        let sample = |id: usize, pos, vel| Sample {
+            frame,
+            id: id as u64,
+            time,
+            pos,
+            vel,
+        };
+        if self.objects {
+            self.samples.extend((0..world.info().4).filter_map(|n| {
+                let state = world.object_state(n)?;
+                Some(sample(n, state.center_of_mass, state.vel))
+            }));
+        } else {
+            self.samples.extend(
+                world
+                    .particles_iter()
+                    .enumerate()
+                    .map(|(n, p)| sample(n, p.get_pos(), p.get_vel())),
+            );
+        }
+
+        let excess = self.samples.len().saturating_sub(self.limit);
+        self.samples.drain(..excess);
+        self.dropped += excess;
+    }
+
+    /// Writes what was recorded, returning where to and how many samples didn't fit.
+    pub fn finish(self) -> Result<(PathBuf, usize), String> {
+        let id = if self.objects { "object" } else { "particle" };
+        let samples = Vec::from(self.samples);
+
+        if self.path.extension().is_some_and(|ext| ext == "parquet") {
+            write_parquet(&self.path, id, &samples)?;
+        } else {
+            write_csv(&self.path, id, &samples).map_err(|err| err.to_string())?;
+        }
+        Ok((self.path, self.dropped))
+    }
+}
+
+fn write_csv(path: &Path, id: &str, samples: &[Sample]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+
+    writeln!(file, "frame,{id},time,x,y,vx,vy")?;
+    for s in samples {
+        writeln!(
+            file,
+            // Debug switches to exponents for tiny and huge values instead of printing every digit
+            "{},{},{:?},{:?},{:?},{:?},{:?}",
+            s.frame, s.id, s.time, s.pos.x, s.pos.y, s.vel.x, s.vel.y
+        )?;
+    }
+    file.flush()
+}
+
+#[cfg(not(feature = "parquet"))]
+fn write_parquet(_path: &Path, _id: &str, _samples: &[Sample]) -> Result<(), String> {
+    Err(String::from("built without the parquet feature"))
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet(path: &Path, id: &str, samples: &[Sample]) -> Result<(), String> {
+    use crate::vec2::to_f64;
+    use parquet::{
+        data_type::{DoubleType, Int64Type},
+        file::{properties::WriterProperties, writer::SerializedFileWriter},
+        schema::parser::parse_message_type,
+    };
+    use std::sync::Arc;
+
+    // Samples per row group, what readers load into memory at once
+    const ROWS: usize = 1 << 20;
+
+    let schema = parse_message_type(&format!(
+        "message trajectory {{
+            REQUIRED INT64 frame;
+            REQUIRED INT64 {id};
+            REQUIRED DOUBLE time;
+            REQUIRED DOUBLE x;
+            REQUIRED DOUBLE y;
+            REQUIRED DOUBLE vx;
+            REQUIRED DOUBLE vy;
+        }}"
+    ))
+    .map_err(|err| err.to_string())?;
+
+    let file = File::create(path).map_err(|err| err.to_string())?;
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(file, Arc::new(schema), properties)
+        .map_err(|err| err.to_string())?;
+
+    let ints: [fn(&Sample) -> i64; 2] = [|s| s.frame as i64, |s| s.id as i64];
+    let doubles: [fn(&Sample) -> f64; 5] = [
+        |s| to_f64(s.time),
+        |s| to_f64(s.pos.x),
+        |s| to_f64(s.pos.y),
+        |s| to_f64(s.vel.x),
+        |s| to_f64(s.vel.y),
+    ];
+
+    for rows in samples.chunks(ROWS) {
+        let mut group = writer.next_row_group().map_err(|err| err.to_string())?;
+
+        for column in ints {
+            let values: Vec<i64> = rows.iter().map(column).collect();
+            let mut writer = group
+                .next_column()
+                .map_err(|err| err.to_string())?
+                .expect("schema should have a column for every value");
+            writer
+                .typed::<Int64Type>()
+                .write_batch(&values, None, None)
+                .map_err(|err| err.to_string())?;
+            writer.close().map_err(|err| err.to_string())?;
+        }
+        for column in doubles {
+            let values: Vec<f64> = rows.iter().map(column).collect();
+            let mut writer = group
+                .next_column()
+                .map_err(|err| err.to_string())?
+                .expect("schema should have a column for every value");
+            writer
+                .typed::<DoubleType>()
+                .write_batch(&values, None, None)
+                .map_err(|err| err.to_string())?;
+            writer.close().map_err(|err| err.to_string())?;
+        }
+
+        group.close().map_err(|err| err.to_string())?;
+    }
+
+    writer.close().map_err(|err| err.to_string())?;
+    Ok(())
+}
@@ -1,24 +1,47 @@
 use auto_ops::{impl_op, impl_op_commutative};
 
+/// The scalar the simulation computes in, `f32` with the f32 feature for less memory
+/// traffic and the same numbers as the GPU and browser builds.
+#[cfg(not(feature = "f32"))]
+pub type Real = f64;
+#[cfg(feature = "f32")]
+pub type Real = f32;
+
+#[cfg(feature = "f32")]
+pub use std::f32::consts;
+#[cfg(not(feature = "f32"))]
+pub use std::f64::consts;
+
+/// Widens to what APIs outside of the simulation take, whichever `Real` is.
+#[allow(clippy::unnecessary_cast)]
+pub const fn to_f64(x: Real) -> f64 {
+    x as f64
+}
+
+#[cfg(all(feature = "glam", not(feature = "f32")))]
+type GlamVec2 = glam::DVec2;
+#[cfg(all(feature = "glam", feature = "f32"))]
+type GlamVec2 = glam::Vec2;
+
 #[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Vec2 {
-    pub x: f64,
-    pub y: f64,
+    pub x: Real,
+    pub y: Real,
 }
 
 impl Vec2 {
-    pub const fn new(x: f64, y: f64) -> Self {
+    pub const fn new(x: Real, y: Real) -> Self {
         Self { x, y }
     }
 
-    pub fn from_angle(angle: f64) -> Self {
+    pub fn from_angle(angle: Real) -> Self {
         Self {
             x: angle.cos(),
             y: angle.sin(),
         }
     }
 
-    pub fn from_angle_deg(angle: f64) -> Self {
+    pub fn from_angle_deg(angle: Real) -> Self {
         Self::from_angle(angle.to_radians())
     }
 
@@ -26,31 +49,42 @@ impl Vec2 {
         Self { x: 0.0, y: 0.0 }
     }
 
-    pub fn len(self) -> f64 {
+    pub fn len(self) -> Real {
         self.len_sqr().sqrt()
     }
 
-    pub fn len_sqr(self) -> f64 {
+    pub fn len_sqr(self) -> Real {
         self.x * self.x + self.y * self.y
     }
 
-    pub fn dot(self, other: Self) -> f64 {
+    pub fn dot(self, other: Self) -> Real {
         self.x * other.x + self.y * other.y
     }
 
-    pub fn dist(self, other: Self) -> f64 {
+    pub fn dist(self, other: Self) -> Real {
         self.dist_sqr(other).sqrt()
     }
 
-    pub fn dist_sqr(self, other: Self) -> f64 {
+    pub fn dist_sqr(self, other: Self) -> Real {
         (self.x - other.x) * (self.x - other.x) + (self.y - other.y) * (self.y - other.y)
     }
 
-    pub fn angle(self, other: Self) -> f64 {
-        f64::atan2(other.y, other.x) - f64::atan2(self.y, self.x)
+    /// The z component of the 3D cross product, positive when `other` is clockwise of
+    /// `self` on screen.
+    pub fn cross(self, other: Self) -> Real {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Direction from the x axis, the inverse of [`Vec2::from_angle`].
+    pub fn to_angle(self) -> Real {
+        Real::atan2(self.y, self.x)
     }
 
-    pub fn angle_deg(self, other: Self) -> f64 {
+    pub fn angle(self, other: Self) -> Real {
+        Real::atan2(other.y, other.x) - Real::atan2(self.y, self.x)
+    }
+
+    pub fn angle_deg(self, other: Self) -> Real {
         self.angle(other).to_degrees()
     }
 
@@ -64,7 +98,7 @@ impl Vec2 {
         }
     }
 
-    pub fn lerp(self, other: Self, factor: f64) -> Self {
+    pub fn lerp(self, other: Self, factor: Real) -> Self {
         Self {
             x: self.x + factor * (other.x - self.x),
             y: self.y + factor * (other.y - self.y),
@@ -80,7 +114,7 @@ impl Vec2 {
         }
     }
 
-    pub fn rotate(self, angle: f64) -> Self {
+    pub fn rotate(self, angle: Real) -> Self {
         let (sin, cos) = angle.sin_cos();
 
         Self {
@@ -89,14 +123,29 @@ impl Vec2 {
         }
     }
 
-    pub fn rotate_deg(self, angle: f64) -> Self {
+    pub fn rotate_deg(self, angle: Real) -> Self {
         self.rotate(angle.to_radians())
     }
 
+    /// The part of `self` along `onto`, null if `onto` is.
+    pub fn project(self, onto: Self) -> Self {
+        let len_sqr = onto.len_sqr();
+        if len_sqr > 0.0 {
+            onto * (self.dot(onto) / len_sqr)
+        } else {
+            Self::null()
+        }
+    }
+
+    /// The part of `self` across `from`, what's left after [`Vec2::project`]ing onto it.
+    pub fn reject(self, from: Self) -> Self {
+        self - self.project(from)
+    }
+
     pub fn recip(self) -> Self {
         Self {
             x: self.x.recip(),
-            y: self.x.recip(),
+            y: self.y.recip(),
         }
     }
 
@@ -135,14 +184,39 @@ impl Vec2 {
         }
     }
 
+    pub fn round(self) -> Self {
+        Self {
+            x: self.x.round(),
+            y: self.y.round(),
+        }
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        Self {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+        }
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+        }
+    }
+
     pub fn clamp(self, min: Self, max: Self) -> Self {
         Self {
-            x: f64::min(max.x, f64::max(min.x, self.x)),
-            y: f64::min(max.y, f64::max(min.y, self.y)),
+            x: Real::min(max.x, Real::max(min.x, self.x)),
+            y: Real::min(max.y, Real::max(min.y, self.y)),
         }
     }
 
-    pub fn clamp_len(self, min: f64, max: f64) -> Self {
+    pub fn clamp_len(self, min: Real, max: Real) -> Self {
         let len_sqr = self.len_sqr();
 
         if len_sqr <= 0.0 {
@@ -156,16 +230,37 @@ impl Vec2 {
         } else if len > max {
             self * (max / len)
         } else {
-            Self::null()
+            self
         }
     }
 
+    /// Turned a quarter clockwise on screen, like `rotate(FRAC_PI_2)` but exact and
+    /// keeping the length.
+    pub fn perp(self) -> Self {
+        Vec2::new(-self.y, self.x)
+    }
+
     pub fn normal(self) -> Self {
         let n = self.normalize();
         Vec2::new(n.y, -n.x)
     }
 }
 
+/// With `DVec2`, or `Vec2` for the f32 feature.
+#[cfg(feature = "glam")]
+impl From<GlamVec2> for Vec2 {
+    fn from(v: GlamVec2) -> Self {
+        Self::new(v.x, v.y)
+    }
+}
+
+#[cfg(feature = "glam")]
+impl From<Vec2> for GlamVec2 {
+    fn from(v: Vec2) -> Self {
+        Self::new(v.x, v.y)
+    }
+}
+
 impl Default for Vec2 {
     fn default() -> Self {
         Self::null()
@@ -180,9 +275,9 @@ impl_op_commutative!(+ |a: &Vec2, b: Vec2| -> Vec2 {Vec2 {x: a.x + b.x, y: a.y +
 impl_op!(+= |a: &mut Vec2, b: Vec2| {a.x += b.x; a.y += b.y;});
 impl_op!(+= |a: &mut Vec2, b: &Vec2| {a.x += b.x; a.y += b.y;});
 
-impl_op!(+ |a: Vec2, b: f64| -> Vec2 {Vec2 {x: a.x + b, y: a.y + b}});
-impl_op!(+ |a: &Vec2, b: f64| -> Vec2 {Vec2 {x: a.x + b, y: a.y + b}});
-impl_op!(+= |a: &mut Vec2, b: f64| {a.x += b; a.y += b;});
+impl_op!(+ |a: Vec2, b: Real| -> Vec2 {Vec2 {x: a.x + b, y: a.y + b}});
+impl_op!(+ |a: &Vec2, b: Real| -> Vec2 {Vec2 {x: a.x + b, y: a.y + b}});
+impl_op!(+= |a: &mut Vec2, b: Real| {a.x += b; a.y += b;});
 
 // Subtraction
 impl_op!(-|a: Vec2, b: Vec2| -> Vec2 {
@@ -207,19 +302,19 @@ impl_op_commutative!(-|a: &Vec2, b: Vec2| -> Vec2 {
 impl_op!(-= |a: &mut Vec2, b: Vec2| {a.x -= b.x; a.y -= b.y;});
 impl_op!(-= |a: &mut Vec2, b: &Vec2| {a.x -= b.x; a.y -= b.y;});
 
-impl_op!(-|a: Vec2, b: f64| -> Vec2 {
+impl_op!(-|a: Vec2, b: Real| -> Vec2 {
     Vec2 {
         x: a.x - b,
         y: a.y - b,
     }
 });
-impl_op!(-|a: &Vec2, b: f64| -> Vec2 {
+impl_op!(-|a: &Vec2, b: Real| -> Vec2 {
     Vec2 {
         x: a.x - b,
         y: a.y - b,
     }
 });
-impl_op!(-= |a: &mut Vec2, b: f64| {a.x -= b; a.y -= b;});
+impl_op!(-= |a: &mut Vec2, b: Real| {a.x -= b; a.y -= b;});
 
 // Multiplication
 impl_op!(*|a: Vec2, b: Vec2| -> Vec2 {
@@ -244,19 +339,19 @@ impl_op_commutative!(*|a: &Vec2, b: Vec2| -> Vec2 {
 impl_op!(*= |a: &mut Vec2, b: Vec2| {a.x *= b.x; a.y *= b.y;});
 impl_op!(*= |a: &mut Vec2, b: &Vec2| {a.x *= b.x; a.y *= b.y;});
 
-impl_op_commutative!(*|a: Vec2, b: f64| -> Vec2 {
+impl_op_commutative!(*|a: Vec2, b: Real| -> Vec2 {
     Vec2 {
         x: a.x * b,
         y: a.y * b,
     }
 });
-impl_op_commutative!(*|a: &Vec2, b: f64| -> Vec2 {
+impl_op_commutative!(*|a: &Vec2, b: Real| -> Vec2 {
     Vec2 {
         x: a.x * b,
         y: a.y * b,
     }
 });
-impl_op!(*= |a: &mut Vec2, b: f64| {a.x *= b; a.y *= b;});
+impl_op!(*= |a: &mut Vec2, b: Real| {a.x *= b; a.y *= b;});
 
 // Division
 impl_op!(/ |a: Vec2, b: Vec2| -> Vec2 {Vec2 {x: a.x / b.x, y: a.y / b.y}});
@@ -266,9 +361,9 @@ impl_op_commutative!(/ |a: &Vec2, b: Vec2| -> Vec2 {Vec2 {x: a.x / b.x, y: a.y /
 impl_op!(/= |a: &mut Vec2, b: Vec2| {a.x /= b.x; a.y /= b.y;});
 impl_op!(/= |a: &mut Vec2, b: &Vec2| {a.x /= b.x; a.y /= b.y;});
 
-impl_op!(/ |a: Vec2, b: f64| -> Vec2 {Vec2 {x: a.x / b, y: a.y / b}});
-impl_op!(/ |a: &Vec2, b: f64| -> Vec2 {Vec2 {x: a.x / b, y: a.y / b}});
-impl_op!(/= |a: &mut Vec2, b: f64| {a.x /= b; a.y /= b;});
+impl_op!(/ |a: Vec2, b: Real| -> Vec2 {Vec2 {x: a.x / b, y: a.y / b}});
+impl_op!(/ |a: &Vec2, b: Real| -> Vec2 {Vec2 {x: a.x / b, y: a.y / b}});
+impl_op!(/= |a: &mut Vec2, b: Real| {a.x /= b; a.y /= b;});
 
 // Misc
 impl_op!(-|a: Vec2| -> Vec2 { Vec2 { x: -a.x, y: -a.y } });
@@ -0,0 +1,304 @@
+use crate::{
+    renderer::{BlendMode, Color, Renderer},
+    theme::DARK,
+    vec2::{to_f64, Real, Vec2},
+    world::{Particle, World},
+};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, KeyboardEvent, MouseEvent};
+
+/// `Renderer` on top of a 2D canvas context.
+pub struct CanvasRenderer {
+    ctx: CanvasRenderingContext2d,
+    width: usize,
+    height: usize,
+}
+
+impl CanvasRenderer {
+    fn path(&mut self, vertices: impl Iterator<Item = Vec2>) {
+        self.ctx.begin_path();
+        for (i, v) in vertices.enumerate() {
+            if i == 0 {
+                self.ctx.move_to(to_f64(v.x), to_f64(v.y));
+            } else {
+                self.ctx.line_to(to_f64(v.x), to_f64(v.y));
+            }
+        }
+    }
+}
+
+impl Renderer for CanvasRenderer {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let [x, y, radius] = [center.x, center.y, radius].map(to_f64);
+        self.ctx.begin_path();
+        self.ctx
+            .arc(x, y, radius, 0.0, std::f64::consts::TAU)
+            .expect("could not draw filled circle");
+        self.ctx.fill();
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.thick_line(a, b, 1.0)
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        self.ctx.set_line_width(to_f64(thickness));
+        self.path([a, b].into_iter());
+        self.ctx.stroke();
+        self
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.ctx.set_line_width(1.0);
+        let [x, y, w, h] = [a.x, a.y, b.x - a.x, b.y - a.y].map(to_f64);
+        self.ctx.stroke_rect(x, y, w, h);
+        self
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let [x, y, w, h] = [a.x, a.y, b.x - a.x, b.y - a.y].map(to_f64);
+        self.ctx.fill_rect(x, y, w, h);
+        self
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        let [x, y, w, h, radius] = [a.x, a.y, b.x - a.x, b.y - a.y, radius].map(to_f64);
+        self.ctx.begin_path();
+        self.ctx
+            .round_rect_with_f64(x, y, w, h, radius)
+            .expect("could not draw rectangle");
+        self.ctx.fill();
+        self
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.ctx.set_line_width(1.0);
+        self.path(vertices);
+        self.ctx.close_path();
+        self.ctx.stroke();
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.path(vertices);
+        self.ctx.fill();
+        self
+    }
+
+    fn polyline(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        self.ctx.set_line_width(1.0);
+        self.path(vertices);
+        self.ctx.stroke();
+        self
+    }
+
+    // Canvas paths are always anti-aliased, so the aa_ defaults already apply
+
+    fn set_blend_mode(&mut self, mode: BlendMode) -> &mut Self {
+        let op = match mode {
+            BlendMode::None => "copy",
+            BlendMode::Blend => "source-over",
+            BlendMode::Add => "lighter",
+        };
+        self.ctx
+            .set_global_composite_operation(op)
+            .expect("could not set blend mode");
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        self.ctx.set_font(&format!("{size}px monospace"));
+        self.ctx
+            .fill_text(text, to_f64(pos.x), to_f64(pos.y))
+            .expect("could not draw text");
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        let data = self
+            .ctx
+            .get_image_data(0.0, 0.0, self.width as f64, self.height as f64)
+            .expect("could not read pixels");
+        Some((self.width, self.height, data.data().0))
+    }
+
+    fn set_color(&mut self, Color { r, g, b, a }: Color) -> &mut Self {
+        let style = format!("rgba({r},{g},{b},{})", f64::from(a) / 255.0);
+        self.ctx.set_fill_style_str(&style);
+        self.ctx.set_stroke_style_str(&style);
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        self.ctx
+            .fill_rect(0.0, 0.0, self.width as f64, self.height as f64);
+        self
+    }
+
+    fn finish(&mut self) {}
+}
+
+/// Browser counterpart of `App`: drives the world from `requestAnimationFrame` and DOM events.
+struct WebApp {
+    world: World,
+    canvas: CanvasRenderer,
+    simulate: bool,
+    rect_start: Option<Vec2>,
+    mouse: Vec2,
+    last_frame: Option<f64>,
+}
+
+impl WebApp {
+    fn mouse_down(&mut self, pos: Vec2) {
+        if let Some(n) = self.world.particle_at(pos) {
+            self.world.grab(n, pos);
+        } else {
+            self.rect_start = Some(pos);
+        }
+    }
+
+    fn mouse_up(&mut self, pos: Vec2) {
+        if let Some(start) = self.rect_start.take() {
+            let _ = self.world.spawn_rect(
+                ((start.x - pos.x).abs() / Particle::SPACING) as usize + 1,
+                ((start.y - pos.y).abs() / Particle::SPACING) as usize + 1,
+                Real::min(start.x, pos.x),
+                Real::min(start.y, pos.y),
+            );
+        }
+
+        self.world.release();
+    }
+
+    fn key_down(&mut self, key: &str) {
+        match key {
+            " " => self.simulate = !self.simulate,
+            "Backspace" => self.world.remove_last(),
+            "Delete" => self.world.clear(),
+            _ => {}
+        }
+    }
+
+    fn frame(&mut self, time: f64) {
+        let dt = self.last_frame.map_or(0.0, |last| (time - last) / 1000.0);
+        self.last_frame = Some(time);
+
+        if self.simulate {
+            if self.world.update().is_err() {
+                self.world.clear();
+            }
+            // Clamp so a backgrounded tab doesn't come back to a huge backlog of steps
+            self.world.end_frame(dt.min(0.1) as Real);
+        }
+
+        self.world.drag_to(self.mouse);
+
+        self.canvas.set_color(DARK.background).clear();
+        self.world.draw_polys(&DARK, &mut self.canvas);
+        self.world.draw_drag(&DARK, &mut self.canvas);
+        self.world.draw_edges(&DARK, &mut self.canvas);
+
+        if let Some(start) = self.rect_start {
+            self.canvas
+                .set_color(DARK.preview)
+                .rectangle(start, self.mouse);
+        }
+    }
+}
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+fn request_animation_frame(f: &FrameCallback) {
+    web_sys::window()
+        .expect("no window")
+        .request_animation_frame(f.as_ref().unchecked_ref())
+        .expect("could not request animation frame");
+}
+
+fn listen<E: JsCast + 'static>(
+    target: &web_sys::EventTarget,
+    event: &str,
+    mut handler: impl FnMut(E) + 'static,
+) -> Result<(), JsValue> {
+    let closure = Closure::<dyn FnMut(web_sys::Event)>::new(move |e: web_sys::Event| {
+        handler(e.unchecked_into());
+    });
+    target.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+    // The listeners live as long as the page
+    closure.forget();
+    Ok(())
+}
+
+/// Starts the sandbox on the `<canvas>` with the given id.
+#[wasm_bindgen]
+pub fn start(canvas_id: &str) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let element: HtmlCanvasElement = document
+        .get_element_by_id(canvas_id)
+        .ok_or("no canvas with the given id")?
+        .dyn_into()?;
+    let ctx: CanvasRenderingContext2d = element
+        .get_context("2d")?
+        .ok_or("no 2d context")?
+        .dyn_into()?;
+    ctx.set_text_baseline("top");
+
+    let (width, height) = (element.width() as usize, element.height() as usize);
+    let mut world = World::new(width as Real, height as Real);
+    let _ = world.add_edge(
+        Vec2::new(0.0, height as Real * 0.4),
+        Vec2::new(width as Real * 0.15, height as Real * 0.4),
+    );
+    let _ = world.add_edge(
+        Vec2::new(width as Real * 0.45, height as Real),
+        Vec2::new(width as Real, height as Real * 0.75),
+    );
+
+    let app = Rc::new(RefCell::new(WebApp {
+        world,
+        canvas: CanvasRenderer { ctx, width, height },
+        simulate: true,
+        rect_start: None,
+        mouse: Vec2::default(),
+        last_frame: None,
+    }));
+
+    let pos = |e: &MouseEvent| Vec2::new(e.offset_x() as Real, e.offset_y() as Real);
+
+    let target: &web_sys::EventTarget = element.as_ref();
+    let a = app.clone();
+    listen(target, "mousedown", move |e: MouseEvent| {
+        a.borrow_mut().mouse_down(pos(&e));
+    })?;
+    let a = app.clone();
+    listen(target, "mouseup", move |e: MouseEvent| {
+        a.borrow_mut().mouse_up(pos(&e));
+    })?;
+    let a = app.clone();
+    listen(target, "mousemove", move |e: MouseEvent| {
+        a.borrow_mut().mouse = pos(&e);
+    })?;
+    let a = app.clone();
+    listen(document.as_ref(), "keydown", move |e: KeyboardEvent| {
+        a.borrow_mut().key_down(&e.key());
+    })?;
+
+    // The frame closure has to reschedule itself, so it holds a handle to its own slot
+    let frame: Rc<RefCell<Option<FrameCallback>>> = Rc::new(RefCell::new(None));
+    let next = frame.clone();
+    *frame.borrow_mut() = Some(Closure::new(move |time: f64| {
+        app.borrow_mut().frame(time);
+        request_animation_frame(next.borrow().as_ref().expect("frame closure is set"));
+    }));
+    request_animation_frame(frame.borrow().as_ref().expect("frame closure is set"));
+
+    Ok(())
+}
@@ -0,0 +1,567 @@
+use crate::{
+    font::Font,
+    renderer::{Color, Renderer},
+    vec2::{Real, Vec2},
+};
+use sdl2::video::Window;
+use std::{borrow::Cow, ops::Range};
+use wgpu::util::DeviceExt;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    pos: [f32; 2],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Circle {
+    center: [f32; 2],
+    radius: [f32; 2],
+    color: [f32; 4],
+}
+
+/// A run of consecutive draw calls of one kind, replayed in order to keep painter's order.
+enum Batch {
+    Triangles(Range<u32>),
+    Circles(Range<u32>),
+}
+
+/// Collects primitives for the whole frame and submits them as a handful of draw calls:
+/// triangles in one vertex buffer and circles as instanced quads.
+pub struct WgpuRenderer {
+    surface: wgpu::Surface,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    triangle_pipeline: wgpu::RenderPipeline,
+    circle_pipeline: wgpu::RenderPipeline,
+    vertices: Vec<Vertex>,
+    circles: Vec<Circle>,
+    batches: Vec<Batch>,
+    clear_color: wgpu::Color,
+    color: [f32; 4],
+    size: (usize, usize),
+    font: Font,
+    window: Window,
+}
+
+const SHADER: &str = r"
+struct VertexOutput {
+    @builtin(position) pos: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) local: vec2<f32>,
+};
+
+@vertex
+fn vs_triangle(@location(0) pos: vec2<f32>, @location(1) color: vec4<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.pos = vec4<f32>(pos, 0.0, 1.0);
+    out.color = color;
+    out.local = vec2<f32>(0.0, 0.0);
+    return out;
+}
+
+@vertex
+fn vs_circle(
+    @builtin(vertex_index) i: u32,
+    @location(0) center: vec2<f32>,
+    @location(1) radius: vec2<f32>,
+    @location(2) color: vec4<f32>,
+) -> VertexOutput {
+    let local = vec2<f32>(f32(i & 1u) * 2.0 - 1.0, f32((i >> 1u) & 1u) * 2.0 - 1.0);
+
+    var out: VertexOutput;
+    out.pos = vec4<f32>(center + local * radius, 0.0, 1.0);
+    out.color = color;
+    out.local = local;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if (dot(in.local, in.local) > 1.0) {
+        discard;
+    }
+    return in.color;
+}
+";
+
+impl WgpuRenderer {
+    pub fn new(window: Window, vsync: bool) -> Result<Self, String> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let surface = unsafe { instance.create_surface(&window) };
+
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: Some(&surface),
+        }))
+        .ok_or_else(|| String::from("no compatible gpu adapter found"))?;
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("soft"),
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        ))
+        .map_err(|err| err.to_string())?;
+
+        // The colors are authored in sRGB already, so avoid a second conversion on write
+        let formats = surface.get_supported_formats(&adapter);
+        let format = formats
+            .iter()
+            .copied()
+            .find(|format| !format.describe().srgb)
+            .or_else(|| formats.first().copied())
+            .ok_or_else(|| String::from("surface is incompatible with the adapter"))?;
+
+        let (width, height) = window.drawable_size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            width,
+            height,
+            present_mode: if vsync {
+                wgpu::PresentMode::AutoVsync
+            } else {
+                wgpu::PresentMode::AutoNoVsync
+            },
+        };
+        surface.configure(&device, &config);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+        });
+
+        let triangle_pipeline = Self::pipeline(
+            &device,
+            &shader,
+            format,
+            "vs_triangle",
+            wgpu::PrimitiveTopology::TriangleList,
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Vertex>() as u64,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4],
+            },
+        );
+        let circle_pipeline = Self::pipeline(
+            &device,
+            &shader,
+            format,
+            "vs_circle",
+            wgpu::PrimitiveTopology::TriangleStrip,
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Circle>() as u64,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4],
+            },
+        );
+
+        Ok(Self {
+            surface,
+            device,
+            queue,
+            config,
+            triangle_pipeline,
+            circle_pipeline,
+            vertices: vec![],
+            circles: vec![],
+            batches: vec![],
+            clear_color: wgpu::Color::BLACK,
+            color: [0.0, 0.0, 0.0, 1.0],
+            size: (width as usize, height as usize),
+            font: Font::new(),
+            window,
+        })
+    }
+
+    fn pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        format: wgpu::TextureFormat,
+        entry_point: &str,
+        topology: wgpu::PrimitiveTopology,
+        buffer: wgpu::VertexBufferLayout,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(entry_point),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point,
+                buffers: &[buffer],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology,
+                ..wgpu::PrimitiveState::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.size = (width as usize, height as usize);
+
+        let (w, h) = self.window.drawable_size();
+        self.config.width = w.max(1);
+        self.config.height = h.max(1);
+        self.surface.configure(&self.device, &self.config);
+    }
+
+    fn ndc(&self, v: Vec2) -> [f32; 2] {
+        let (w, h) = self.size;
+        [
+            (v.x / w as Real * 2.0 - 1.0) as f32,
+            (1.0 - v.y / h as Real * 2.0) as f32,
+        ]
+    }
+
+    fn push_triangle(&mut self, a: Vec2, b: Vec2, c: Vec2) {
+        let start = self.vertices.len() as u32;
+        for v in [a, b, c] {
+            self.vertices.push(Vertex {
+                pos: self.ndc(v),
+                color: self.color,
+            });
+        }
+
+        match self.batches.last_mut() {
+            Some(Batch::Triangles(range)) => range.end = start + 3,
+            _ => self.batches.push(Batch::Triangles(start..start + 3)),
+        }
+    }
+
+    fn push_quad(&mut self, a: Vec2, b: Vec2, c: Vec2, d: Vec2) {
+        self.push_triangle(a, b, c);
+        self.push_triangle(a, c, d);
+    }
+
+    /// Ear clipping, so the concave outlines of squashed bodies fill correctly.
+    fn triangulate(&mut self, vertices: &[Vec2]) {
+        let mut remaining: Vec<Vec2> = vertices.to_vec();
+
+        let area: Real = (0..remaining.len())
+            .map(|i| {
+                let (a, b) = (remaining[i], remaining[(i + 1) % remaining.len()]);
+                a.x * b.y - b.x * a.y
+            })
+            .sum();
+        if area < 0.0 {
+            remaining.reverse();
+        }
+
+        let cross =
+            |a: Vec2, b: Vec2, c: Vec2| (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+
+        while remaining.len() > 3 {
+            let n = remaining.len();
+            let ear = (0..n).find(|&i| {
+                let (a, b, c) = (
+                    remaining[(i + n - 1) % n],
+                    remaining[i],
+                    remaining[(i + 1) % n],
+                );
+                cross(a, b, c) > 0.0
+                    && !remaining.iter().any(|&p| {
+                        p != a
+                            && p != b
+                            && p != c
+                            && cross(a, b, p) >= 0.0
+                            && cross(b, c, p) >= 0.0
+                            && cross(c, a, p) >= 0.0
+                    })
+            });
+
+            // Degenerate or self intersecting outline, clip the first vertex and move on
+            let i = ear.unwrap_or(0);
+            self.push_triangle(
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            remaining.remove(i);
+        }
+
+        if let [a, b, c] = remaining[..] {
+            self.push_triangle(a, b, c);
+        }
+    }
+
+    fn encode(&self, view: &wgpu::TextureView) -> wgpu::CommandEncoder {
+        let vertices = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("vertices"),
+                contents: bytemuck::cast_slice(&self.vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let circles = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("circles"),
+                contents: bytemuck::cast_slice(&self.circles),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+
+            for batch in &self.batches {
+                match batch {
+                    Batch::Triangles(range) => {
+                        pass.set_pipeline(&self.triangle_pipeline);
+                        pass.set_vertex_buffer(0, vertices.slice(..));
+                        pass.draw(range.clone(), 0..1);
+                    }
+                    Batch::Circles(range) => {
+                        pass.set_pipeline(&self.circle_pipeline);
+                        pass.set_vertex_buffer(0, circles.slice(..));
+                        pass.draw(0..4, range.clone());
+                    }
+                }
+            }
+        }
+
+        encoder
+    }
+}
+
+impl Renderer for WgpuRenderer {
+    fn filled_circle(&mut self, center: Vec2, radius: Real) -> &mut Self {
+        let (w, h) = self.size;
+        let start = self.circles.len() as u32;
+
+        self.circles.push(Circle {
+            center: self.ndc(center),
+            radius: [
+                (radius * 2.0 / w as Real) as f32,
+                (radius * 2.0 / h as Real) as f32,
+            ],
+            color: self.color,
+        });
+
+        match self.batches.last_mut() {
+            Some(Batch::Circles(range)) => range.end = start + 1,
+            _ => self.batches.push(Batch::Circles(start..start + 1)),
+        }
+
+        self
+    }
+
+    fn line(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.thick_line(a, b, 1.0)
+    }
+
+    fn thick_line(&mut self, a: Vec2, b: Vec2, thickness: Real) -> &mut Self {
+        if a == b {
+            return self;
+        }
+
+        let offset = (b - a).normal() * (thickness / 2.0);
+        self.push_quad(a + offset, b + offset, b - offset, a - offset);
+        self
+    }
+
+    fn rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        let (c, d) = (Vec2::new(b.x, a.y), Vec2::new(a.x, b.y));
+        self.polygon([a, c, b, d].into_iter())
+    }
+
+    fn filled_rectangle(&mut self, a: Vec2, b: Vec2) -> &mut Self {
+        self.push_quad(a, Vec2::new(b.x, a.y), b, Vec2::new(a.x, b.y));
+        self
+    }
+
+    fn filled_rounded_rectangle(&mut self, a: Vec2, b: Vec2, radius: Real) -> &mut Self {
+        let (min, max) = (a.min(b), a.max(b));
+        let r = radius.min((max.x - min.x) / 2.0).min((max.y - min.y) / 2.0);
+
+        let corners = [
+            (Vec2::new(max.x - r, min.y + r), -90.0),
+            (Vec2::new(max.x - r, max.y - r), 0.0),
+            (Vec2::new(min.x + r, max.y - r), 90.0),
+            (Vec2::new(min.x + r, min.y + r), 180.0),
+        ];
+        let outline: Vec<Vec2> = corners
+            .iter()
+            .flat_map(|&(center, start)| {
+                (0..=4).map(move |i| center + Vec2::from_angle_deg(start + 22.5 * i as Real) * r)
+            })
+            .collect();
+
+        self.filled_polygon(outline.into_iter())
+    }
+
+    fn polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let vertices: Vec<Vec2> = vertices.collect();
+
+        for (i, &a) in vertices.iter().enumerate() {
+            self.line(a, vertices[(i + 1) % vertices.len()]);
+        }
+
+        self
+    }
+
+    fn filled_polygon(&mut self, vertices: impl Iterator<Item = Vec2>) -> &mut Self {
+        let vertices: Vec<Vec2> = vertices.collect();
+        if vertices.len() >= 3 {
+            self.triangulate(&vertices);
+        }
+
+        self
+    }
+
+    fn text_sized(&mut self, pos: Vec2, text: &str, size: Real) -> &mut Self {
+        let mut covered = vec![];
+        self.font
+            .rasterize(pos, text, size, |x, y, alpha| covered.push((x, y, alpha)));
+
+        let color = self.color;
+        for (x, y, alpha) in covered {
+            self.color[3] = color[3] * f32::from(alpha) / 255.0;
+            let p = Vec2::new(x as Real, y as Real);
+            self.filled_rectangle(p, p + 1.0);
+        }
+        self.color = color;
+
+        self
+    }
+
+    fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    fn read_pixels(&self) -> Option<(usize, usize, Vec<u8>)> {
+        let (w, h) = (self.config.width, self.config.height);
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("readback"),
+            size: wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        });
+
+        // Rows of a texture to buffer copy have to be padded to 256 bytes
+        let row = w * 4;
+        let padded_row =
+            row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: u64::from(padded_row * h),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder =
+            self.encode(&texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: w,
+                height: h,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| {
+            result.expect("could not map readback buffer");
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let bgra = matches!(
+            self.config.format,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        );
+        let mut pixels = Vec::with_capacity((row * h) as usize);
+        for padded in slice.get_mapped_range().chunks(padded_row as usize) {
+            pixels.extend_from_slice(&padded[..row as usize]);
+        }
+        if bgra {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Some((w as usize, h as usize, pixels))
+    }
+
+    fn set_color(&mut self, color: Color) -> &mut Self {
+        self.color = [color.r, color.g, color.b, color.a].map(|c| f32::from(c) / 255.0);
+        self
+    }
+
+    fn clear(&mut self) -> &mut Self {
+        let [r, g, b, a] = self.color.map(f64::from);
+        self.clear_color = wgpu::Color { r, g, b, a };
+
+        self.vertices.clear();
+        self.circles.clear();
+        self.batches.clear();
+        self
+    }
+
+    fn finish(&mut self) {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(&self.device, &self.config);
+                return;
+            }
+            Err(err) => panic!("could not get surface texture: {err}"),
+        };
+
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.queue.submit([self.encode(&view).finish()]);
+        frame.present();
+    }
+}
@@ -1,10 +1,28 @@
+#[cfg(feature = "wgpu")]
+use crate::gpu_collisions::{GpuCollisions, GpuParticle};
 use crate::{
-    consts::{HEIGHT, WIDTH},
+    colliders::Collider,
+    forces::{ForceGenerator, Gravity},
+    profile::span,
     renderer::{Color, Renderer},
-    vec2::Vec2,
+    rng::Rng,
+    slots::{Id, Slots},
+    texture::Texture,
+    theme::Theme,
+    vec2::{
+        consts::{FRAC_PI_2, PI, TAU},
+        Real, Vec2,
+    },
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
-
-use serde::{Deserialize, Serialize};
 
 macro_rules! SQR {
     ($e:expr) => {
@@ -12,80 +30,596 @@ macro_rules! SQR {
     };
 }
 
+/// What a particle is made of, deciding how it's drawn and how it collides.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum ParticleKind {
+    /// Part of a body held together by springs
+    #[default]
+    Soft,
+    /// A loose grain, without springs and with a lot of friction
+    Sand,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Particle {
     pos: Vec2,
     vel: Vec2,
     acc: Vec2,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default = "Particle::default_mass")]
+    mass: Real,
+    /// Above the surroundings, only changes while heat is simulated
+    #[serde(default)]
+    temperature: Real,
+    #[serde(default)]
+    kind: ParticleKind,
+    /// Step it's removed at, see [`World::set_lifetime`]
+    #[serde(default)]
+    expires: Option<u64>,
+    /// Of its object's material, refreshed every step
+    #[serde(skip)]
+    friction: Friction,
+    #[serde(skip)]
+    contact: Contact,
+    /// Whether its object is frozen, refreshed every step too
+    #[serde(skip)]
+    frozen: bool,
 }
 
 impl Particle {
-    pub const R: f64 = 7.25;
-    pub const SPACING: f64 = 21.0;
-    pub const DIAG_SQR: f64 = 2.0 * SQR!(Particle::SPACING);
+    pub const R: Real = 7.25;
+    pub const SPACING: Real = 21.0;
+    pub const DIAG_SQR: Real = 2.0 * SQR!(Particle::SPACING);
+    /// Least friction coefficient of grains of sand, whatever they're made of
+    pub const SAND_FRICTION: Real = 0.6;
 
-    pub fn new(x: f64, y: f64) -> Self {
+    pub fn new(x: Real, y: Real) -> Self {
         Self {
             pos: Vec2::new(x, y),
             vel: Vec2::null(),
             acc: Vec2::null(),
+            pinned: false,
+            mass: 1.0,
+            temperature: 0.0,
+            kind: ParticleKind::Soft,
+            expires: None,
+            friction: Friction::default(),
+            contact: Contact::default(),
+            frozen: false,
+        }
+    }
+
+    fn default_mass() -> Real {
+        1.0
+    }
+
+    pub fn get_pos(&self) -> Vec2 {
+        self.pos
+    }
+
+    pub fn get_vel(&self) -> Vec2 {
+        self.vel
+    }
+
+    pub fn temperature(&self) -> Real {
+        self.temperature
+    }
+
+    pub fn kind(&self) -> ParticleKind {
+        self.kind
+    }
+
+    /// Pinned or frozen, either way it stays put.
+    fn is_fixed(&self) -> bool {
+        self.pinned || self.frozen
+    }
+
+    /// How it bounces off and slides along what it touches. Grains don't bounce off
+    /// anything, nor slide along it easily.
+    fn response(&self) -> Friction {
+        match self.kind {
+            ParticleKind::Soft => self.friction,
+            ParticleKind::Sand => Friction {
+                restitution: 0.0,
+                static_friction: self.friction.static_friction.max(Self::SAND_FRICTION),
+                kinetic_friction: self.friction.kinetic_friction.max(Self::SAND_FRICTION),
+            },
+        }
+    }
+
+    /// How it pushes off other particles, grains don't bounce off them either.
+    fn contact(&self) -> Contact {
+        match self.kind {
+            ParticleKind::Soft => self.contact,
+            ParticleKind::Sand => Contact {
+                restitution: 0.0,
+                ..self.contact
+            },
         }
     }
 
-    pub fn collide(&mut self, other: &mut Self) {
+    /// Returns the speed the two particles approached each other at, zero if they didn't touch.
+    pub fn collide(&mut self, other: &mut Self) -> Real {
         let diff = other.pos - self.pos;
         let diff_len_sqr = diff.len_sqr();
 
         if SQR!(2.0 * Particle::R) >= diff_len_sqr {
+            // Pinned particles do not move, the other one takes the whole response. Otherwise
+            // the lighter one gets pushed further
+            let (w_self, w_other) = match (self.is_fixed(), other.is_fixed()) {
+                (true, true) => return 0.0,
+                (true, false) => (0.0, 1.0),
+                (false, true) => (1.0, 0.0),
+                (false, false) => {
+                    let w_self = other.mass / (self.mass + other.mass);
+                    (w_self, 1.0 - w_self)
+                }
+            };
+
             // Static resolution
             let diff_len = diff_len_sqr.sqrt();
-            let offset = 0.5 * (2.0 * Particle::R - diff_len) * (diff / diff_len);
-            self.pos -= offset;
-            other.pos += offset;
+            let offset = (2.0 * Particle::R - diff_len) * (diff / diff_len);
+            self.pos -= w_self * offset;
+            other.pos += w_other * offset;
 
-            // Dynamic resolution
+            // Dynamic resolution, only while they're pressed together
             let diff_norm = (other.pos - self.pos) / (2.0 * Particle::R);
-            let vel_offset = (self.vel.dot(diff_norm) - other.vel.dot(diff_norm)) * diff_norm;
+            let approach = self.vel.dot(diff_norm) - other.vel.dot(diff_norm);
+            let contact = self.contact().mix(other.contact());
+            if approach > 0.0 {
+                let impulse = (1.0 + contact.restitution) * approach;
+                self.vel -= w_self * impulse * diff_norm;
+                other.vel += w_other * impulse * diff_norm;
+
+                let tangent = diff_norm.perp();
+                let friction = self.response().mix(other.response());
+                let slide = friction.slide((self.vel - other.vel).dot(tangent) * tangent, impulse);
+                self.vel -= w_self * slide;
+                other.vel += w_other * slide;
+            }
+
+            // Whichever way they move along the normal afterwards, some of it is damped away
+            let rel = contact.damping * (self.vel - other.vel).dot(diff_norm) * diff_norm;
+            self.vel -= w_self * rel;
+            other.vel += w_other * rel;
+
+            approach.max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Moves the particle `depth` along `normal`, out of something static, and bounces it
+    /// off. Returns the speed it hit at.
+    pub fn bounce_off(&mut self, depth: Real, normal: Vec2) -> Real {
+        if self.is_fixed() {
+            return 0.0;
+        }
 
-            self.vel -= vel_offset;
-            other.vel += vel_offset;
+        self.pos += depth * normal;
+        let dp = self.vel.dot(normal);
+        self.vel = self.response().respond(self.vel, normal);
+
+        (-dp).max(0.0)
+    }
+
+    /// Like [`Particle::bounce_off`] for something moving at `vel`, which carries the
+    /// particle along as it pushes it.
+    pub fn bounce_off_moving(&mut self, depth: Real, normal: Vec2, vel: Vec2) -> Real {
+        if self.is_fixed() {
+            return 0.0;
         }
+
+        self.pos += depth * normal;
+        let rel = self.vel - vel;
+        let dp = rel.dot(normal);
+        self.vel = self.response().respond(rel, normal) + vel;
+
+        (-dp).max(0.0)
     }
 
-    pub fn integrate(&mut self, dt: f64) {
-        self.pos += self.vel * dt + 0.5 * self.acc * dt * dt;
-        self.vel += self.acc * dt;
+    pub fn integrate(&mut self, dt: Real) {
+        if self.is_fixed() {
+            self.vel = Vec2::null();
+            self.acc = Vec2::null();
+            return;
+        }
+
+        #[cfg(not(all(feature = "simd", not(feature = "f32"))))]
+        {
+            self.pos += self.vel * dt + 0.5 * self.acc * dt * dt;
+            self.vel += self.acc * dt;
+        }
+        // Both components in one register, in the same order of operations as above
+        #[cfg(all(feature = "simd", not(feature = "f32")))]
+        {
+            use wide::f64x2;
+            let lanes = |v: Vec2| f64x2::new([v.x, v.y]);
+            let (pos, vel, acc) = (lanes(self.pos), lanes(self.vel), lanes(self.acc));
+            let [x, y] = (pos + (vel * dt + 0.5 * acc * dt * dt)).to_array();
+            self.pos = Vec2::new(x, y);
+            let [x, y] = (vel + acc * dt).to_array();
+            self.vel = Vec2::new(x, y);
+        }
 
         self.acc = Vec2::null();
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// The particles of a world as [`ForceGenerator`]s see them, in the order they were
+/// spawned in.
+pub struct ParticleView<'a> {
+    particles: &'a mut [Particle],
+}
+
+impl ParticleView<'_> {
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn pos(&self, n: usize) -> Vec2 {
+        self.particles[n].pos
+    }
+
+    pub fn vel(&self, n: usize) -> Vec2 {
+        self.particles[n].vel
+    }
+
+    pub fn mass(&self, n: usize) -> Real {
+        self.particles[n].mass
+    }
+
+    pub fn is_pinned(&self, n: usize) -> bool {
+        self.particles[n].pinned
+    }
+
+    /// Adds `force` to the ones acting on particle `n` this step, heavier ones pick up less speed.
+    pub fn apply_force(&mut self, n: usize, force: Vec2) {
+        let particle = &mut self.particles[n];
+        particle.acc += force / particle.mass;
+    }
+
+    /// Adds `acc` to the acceleration of particle `n` this step regardless of its mass.
+    pub fn accelerate(&mut self, n: usize, acc: Vec2) {
+        self.particles[n].acc += acc;
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 struct Spring {
-    a: usize,
-    b: usize,
-    l0: f64,
+    /// Particles at its ends
+    a: Id,
+    b: Id,
+    l0: Real,
+    /// Index of the muscle moving it, see [`World::set_springs_muscle`]
+    #[serde(default)]
+    muscle: Option<u32>,
 }
 
 impl Spring {
-    pub const KS: f64 = 6000.0;
-    pub const KD: f64 = 100.0;
+    pub fn new(a: Id, b: Id, l0: Real) -> Self {
+        Self {
+            a,
+            b,
+            l0,
+            muscle: None,
+        }
+    }
 
-    pub fn new(a: usize, b: usize, l0: f64) -> Self {
-        Self { a, b, l0 }
+    /// Rest length at the moment, `scales` are what every muscle stretches its springs by.
+    fn rest_length(&self, scales: &[Real]) -> Real {
+        match self.muscle {
+            Some(m) => self.l0 * scales[m as usize],
+            None => self.l0,
+        }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Holds two particles exactly `len` apart, unlike a spring it doesn't give at all.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Rod {
+    a: Id,
+    b: Id,
+    len: Real,
+}
+
+/// Swings the rest length of its springs around what it is, so they pull and push by
+/// themselves. Things built out of them crawl and walk.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Muscle {
+    /// What the group of springs it moves is called in the editor
+    #[serde(default)]
+    pub name: String,
+    /// Share of the rest length the springs get longer and shorter by
+    pub amplitude: Real,
+    /// Swings a second
+    pub frequency: Real,
+    /// Radians into the swing at time zero
+    pub phase: Real,
+}
+
+impl Default for Muscle {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            amplitude: 0.2,
+            frequency: 1.0,
+            phase: 0.0,
+        }
+    }
+}
+
+impl Muscle {
+    /// What rest lengths are scaled by at `time`.
+    fn scale(&self, time: Real) -> Real {
+        1.0 + self.amplitude * (TAU * self.frequency * time + self.phase).sin()
+    }
+}
+
+/// What the outer side of the corners between joined edges is like, see [`Edge::corners`].
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Corners {
+    /// Rounded off, like the ends of edges
+    #[default]
+    Rounded,
+    /// Pointed, the sides of the edges carry on until they meet
+    Sharp,
+}
+
+impl Corners {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rounded => "rounded",
+            Self::Sharp => "sharp",
+        }
+    }
+}
+
+/// A pose an animated edge passes through, see [`World::add_keyframe`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Seconds into the animation
+    pub time: Real,
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+/// Holds a particle to the sticky edge it touched, see [`Edge::stickiness`].
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Anchor {
+    particle: usize,
+    edge: usize,
+    /// Where along the edge, from 0 at its start to 1 at its end
+    t: Real,
+}
+
+/// Edges joined end to end, collided with as one surface: particles are pushed out of the
+/// closest one only, so they slide across joints instead of snagging on every edge they
+/// touch. One-way edges are never part of one.
+#[derive(Clone, Default)]
+struct Chain {
+    edges: Vec<usize>,
+    /// Of every edge of `edges`, the edge joined to its start and to its end
+    joints: Vec<[Option<usize>; 2]>,
+}
+
+impl Chain {
+    // Further passes push particles out of the other side of concave corners
+    const PASSES: usize = 2;
+    // How far pointed corners reach, in how far the sides of the edges are from them
+    const MITER_LIMIT: Real = 3.0;
+
+    /// Groups `edges` into chains, in the order of the first edge of each.
+    fn find(edges: &[Edge]) -> Vec<Self> {
+        let mut joints = vec![[None; 2]; edges.len()];
+        let mut joined = vec![vec![]; edges.len()];
+        for (i, a) in edges.iter().enumerate().filter(|(_, e)| !e.one_way) {
+            for (j, b) in edges.iter().enumerate().skip(i + 1) {
+                if b.one_way {
+                    continue;
+                }
+                for (ea, va) in [a.get_start(), a.get_end()].into_iter().enumerate() {
+                    for (eb, vb) in [b.get_start(), b.get_end()].into_iter().enumerate() {
+                        if va.dist_sqr(vb) <= SQR!(World::SAME_VERTEX) {
+                            joints[i][ea].get_or_insert(j);
+                            joints[j][eb].get_or_insert(i);
+                            joined[i].push(j);
+                            joined[j].push(i);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut taken = vec![false; edges.len()];
+        let mut chains = vec![];
+        for first in 0..edges.len() {
+            if std::mem::replace(&mut taken[first], true) {
+                continue;
+            }
+            let mut chain = Self::default();
+            let mut next = vec![first];
+            while let Some(n) = next.pop() {
+                chain.edges.push(n);
+                chain.joints.push(joints[n]);
+                for &m in &joined[n] {
+                    if !std::mem::replace(&mut taken[m], true) {
+                        next.push(m);
+                    }
+                }
+            }
+            chains.push(chain);
+        }
+        chains
+    }
+
+    fn bounds(&self, edges: &[Edge]) -> (Vec2, Vec2) {
+        self.edges
+            .iter()
+            .map(|&n| edges[n].bounds())
+            .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+            .unwrap_or_default()
+    }
+
+    /// Pushes `particle` out of the chain, returns the edge it hit and the speed it hit at,
+    /// zero if it didn't touch it.
+    fn collide(&self, edges: &[Edge], particle: &mut Particle) -> (usize, Real) {
+        // Exactly like on its own
+        if let [n] = self.edges[..] {
+            return (n, edges[n].collide(particle));
+        }
+
+        let mut hit = (self.edges[0], 0.0);
+        if particle.is_fixed() {
+            return hit;
+        }
+        for pass in 0..Self::PASSES {
+            let Some((k, depth, normal, closest)) = self.contact(edges, particle.pos) else {
+                break;
+            };
+            let edge = &edges[self.edges[k]];
+            let carried = edge.carried(closest);
+            if pass == 0 {
+                let speed = match carried {
+                    Some(vel) => particle.bounce_off_moving(depth, normal, vel),
+                    None => particle.bounce_off(depth, normal),
+                };
+                hit = (self.edges[k], speed);
+            } else {
+                // Only stopped going in, it's bounced and slowed down once already
+                particle.pos += depth * normal;
+                let dp = (particle.vel - carried.unwrap_or_default()).dot(normal);
+                if dp < 0.0 {
+                    particle.vel -= dp * normal;
+                }
+            }
+        }
+        hit
+    }
+
+    /// The edge `pos` is deepest in, how deep, the way out and the point of the edge closest.
+    fn contact(&self, edges: &[Edge], pos: Vec2) -> Option<(usize, Real, Vec2, Vec2)> {
+        let mut contact: Option<(usize, Real, Vec2, Vec2)> = None;
+        for (k, &n) in self.edges.iter().enumerate() {
+            let edge = &edges[n];
+            let closest = edge.closest_point(pos);
+            let (dist, normal) = match self.miter(edges, k, closest, pos) {
+                Some(miter) => miter,
+                None => {
+                    let diff = pos - closest;
+                    let len = diff.len();
+                    if len == 0.0 {
+                        continue;
+                    }
+                    (len, diff / len)
+                }
+            };
+            let depth = Particle::R + edge.radius - dist;
+            if depth >= 0.0 && contact.is_none_or(|(_, deepest, ..)| depth > deepest) {
+                contact = Some((k, depth, normal, closest));
+            }
+        }
+        contact
+    }
+
+    /// How far `pos` is from the pointed corner at `closest` and the way out of it, if
+    /// edge `k` has sharp corners and `pos` is off the corner's outer side.
+    fn miter(&self, edges: &[Edge], k: usize, closest: Vec2, pos: Vec2) -> Option<(Real, Vec2)> {
+        let edge = &edges[self.edges[k]];
+        if edge.corners != Corners::Sharp {
+            return None;
+        }
+        let end = if closest == edge.get_start() {
+            0
+        } else if closest == edge.get_end() {
+            1
+        } else {
+            return None;
+        };
+        // Past the ends of both edges, else it's on the inner side
+        let other = &edges[self.joints[k][end]?];
+        if other.closest_point(pos).dist_sqr(closest) > SQR!(World::SAME_VERTEX) {
+            return None;
+        }
+        let diff = pos - closest;
+        if diff.len() > Self::MITER_LIMIT * (Particle::R + edge.radius) {
+            return None;
+        }
+
+        let (a, b) = (edge.normal().dot(diff), other.normal().dot(diff));
+        Some(if a.abs() >= b.abs() {
+            (a.abs(), a.signum() * edge.normal())
+        } else {
+            (b.abs(), b.signum() * other.normal())
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Edge {
     start: Vec2,
     line: Vec2,
-    len_sqr: f64,
+    len_sqr: Real,
+    /// Half of how thick it is
+    #[serde(default = "Edge::default_radius")]
+    radius: Real,
+    /// Only solid from the side of [`Edge::normal`], particles pass through from the other
+    #[serde(default)]
+    one_way: bool,
+    /// Pull it takes to tear particles touching it away, zero for not sticky
+    #[serde(default)]
+    stickiness: Real,
+    /// Damage it takes to break, zero for unbreakable
+    #[serde(default)]
+    strength: Real,
+    /// Momentum particles hit it with so far
+    #[serde(default)]
+    damage: Real,
+    /// Particles touching it are warmed or cooled towards it while heat is simulated
+    #[serde(default)]
+    temperature: Real,
+    /// Of the corners at its ends, where it's joined to other edges
+    #[serde(default)]
+    corners: Corners,
+    /// Poses it moves through while simulated, sorted by time
+    #[serde(default)]
+    keyframes: Vec<Keyframe>,
+    /// Plays the keyframes over and over instead of stopping at the last one
+    #[serde(default)]
+    looping: bool,
+    /// Seconds its animation has been playing
+    #[serde(default)]
+    clock: Real,
+    /// How fast its start and end move while animated, it carries along what it touches
+    #[serde(skip)]
+    motion: Option<(Vec2, Vec2)>,
+    #[serde(default)]
+    name: Option<String>,
+    /// Index into [`World::layers`]
+    #[serde(default)]
+    layer: usize,
 }
 
 impl Edge {
-    pub const R: f64 = 1.5 * Particle::R;
-    const FRICTION: f64 = 0.990;
+    /// Radius of new edges
+    pub const R: Real = 1.5 * Particle::R;
+    pub const MIN_R: Real = 0.2 * Particle::R;
+    pub const MAX_R: Real = 10.0 * Particle::R;
+    /// Of edges made sticky in the app, enough to hold a small body to a ceiling
+    pub const STICKINESS: Real = 6000.0;
+    /// Of edges made breakable in the app, a handful of boxes dropped on one break it
+    pub const STRENGTH: Real = 10000.0;
+    /// Of edges made hot in the app, well past the default melting point
+    pub const HOT: Real = 300.0;
+    // Particles this close to the surface are touching it
+    const CONTACT: Real = 0.1 * Particle::R;
+    // Where cracks show up along damaged edges, in the order they do
+    const CRACKS: [Real; 3] = [0.5, 0.2, 0.75];
 
     pub fn new(start: Vec2, end: Vec2) -> Self {
         let line = end - start;
@@ -93,7 +627,128 @@ impl Edge {
             start,
             line,
             len_sqr: line.len_sqr(),
+            radius: Self::R,
+            one_way: false,
+            stickiness: 0.0,
+            strength: 0.0,
+            damage: 0.0,
+            temperature: 0.0,
+            corners: Corners::Rounded,
+            keyframes: vec![],
+            looping: false,
+            clock: 0.0,
+            motion: None,
+            name: None,
+            layer: 0,
+        }
+    }
+
+    fn default_radius() -> Real {
+        Self::R
+    }
+
+    /// The same edge moved by `offset`, along with its keyframes.
+    pub fn translated(&self, offset: Vec2) -> Self {
+        Self {
+            start: self.start + offset,
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|k| Keyframe {
+                    start: k.start + offset,
+                    end: k.end + offset,
+                    ..*k
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    pub fn radius(&self) -> Real {
+        self.radius
+    }
+
+    pub fn is_one_way(&self) -> bool {
+        self.one_way
+    }
+
+    pub fn stickiness(&self) -> Real {
+        self.stickiness
+    }
+
+    pub fn strength(&self) -> Real {
+        self.strength
+    }
+
+    pub fn damage(&self) -> Real {
+        self.damage
+    }
+
+    pub fn temperature(&self) -> Real {
+        self.temperature
+    }
+
+    pub fn corners(&self) -> Corners {
+        self.corners
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe] {
+        &self.keyframes
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+
+    /// How fast the point of it at `point` moves while it's animated.
+    fn carried(&self, point: Vec2) -> Option<Vec2> {
+        self.motion.map(|(start, end)| {
+            let t = self.line.dot(point - self.start) / self.len_sqr;
+            start.lerp(end, t)
+        })
+    }
+
+    /// Where its keyframes have its start and end at `time` into the animation and how
+    /// fast they move there, `None` if it isn't animated.
+    fn pose_at(&self, time: Real) -> Option<[Vec2; 4]> {
+        let (first, last) = (self.keyframes.first()?, self.keyframes.last()?);
+        let span = last.time - first.time;
+        let time = if self.looping && span > 0.0 && time > last.time {
+            first.time + (time - first.time).rem_euclid(span)
+        } else {
+            time
+        };
+
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        if next == 0 || next == self.keyframes.len() {
+            let k = if next == 0 { first } else { last };
+            return Some([k.start, k.end, Vec2::null(), Vec2::null()]);
         }
+        let (a, b) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let duration = b.time - a.time;
+        let t = (time - a.time) / duration;
+        Some([
+            a.start.lerp(b.start, t),
+            a.end.lerp(b.end, t),
+            (b.start - a.start) / duration,
+            (b.end - a.end) / duration,
+        ])
+    }
+
+    /// Whether a particle at `point` rests against it, on the solid side of one-way ones.
+    pub fn touches(&self, point: Vec2) -> bool {
+        let diff = point - self.closest_point(point);
+        diff.len_sqr() <= SQR!(self.radius + Particle::R + Self::CONTACT)
+            && !(self.one_way && diff.dot(self.normal()) < 0.0)
+    }
+
+    /// Unit length, pointing up on screen for an edge drawn left to right.
+    pub fn normal(&self) -> Vec2 {
+        self.line.normal()
     }
 
     pub fn get_start(&self) -> Vec2 {
@@ -110,398 +765,5352 @@ impl Edge {
         self.start = start;
     }
 
+    pub fn len(&self) -> Real {
+        self.len_sqr.sqrt()
+    }
+
     pub fn set_end(&mut self, end: Vec2) {
         self.line = end - self.start;
         self.len_sqr = self.line.len_sqr();
     }
 
-    pub fn collide(&self, particle: &mut Particle) {
-        let line2 = particle.pos - self.start;
-        let t = self.line.dot(line2).clamp(0.0, self.len_sqr) / self.len_sqr;
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let t = self.line.dot(point - self.start).clamp(0.0, self.len_sqr) / self.len_sqr;
+        self.start + t * self.line
+    }
+}
+
+impl Collider for Edge {
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let (start, end) = (self.get_start(), self.get_end());
+        (start.min(end) - self.radius, start.max(end) + self.radius)
+    }
+
+    fn distance(&self, point: Vec2) -> (Real, Vec2) {
+        let diff = point - self.closest_point(point);
+        (diff.len() - self.radius, diff.normalize())
+    }
+
+    // Worked out directly rather than through `distance`, which rounds differently, so
+    // scenes play out exactly as they did before colliders
+    fn collide(&self, particle: &mut Particle) -> Real {
+        if particle.is_fixed() {
+            return 0.0;
+        }
 
-        let closest_point = self.start + t * self.line;
+        let closest_point = self.closest_point(particle.pos);
 
         let diff = particle.pos - closest_point;
         let diff_len_sqr = diff.len_sqr();
 
-        if diff_len_sqr <= SQR!(Particle::R + Edge::R) {
+        if diff_len_sqr <= SQR!(Particle::R + self.radius) {
+            // From behind, or already on the way out through the solid side
+            if self.one_way {
+                let normal = self.normal();
+                if diff.dot(normal) < 0.0 || particle.vel.dot(normal) > 0.0 {
+                    return 0.0;
+                }
+            }
+
             let diff_len = diff_len_sqr.sqrt();
-            particle.pos += ((Particle::R + Edge::R) - diff_len) * (diff / diff_len);
+            particle.pos += ((Particle::R + self.radius) - diff_len) * (diff / diff_len);
+
+            let tangent = (particle.pos - closest_point) / (self.radius + Particle::R);
+            // Bouncing off a moving edge goes by the speed relative to it
+            let carried = self.carried(closest_point);
+            let vel = carried.map_or(particle.vel, |carried| particle.vel - carried);
+            let dp = vel.dot(tangent);
 
-            let tangent = (particle.pos - closest_point) / (Edge::R + Particle::R);
-            let dp = particle.vel.dot(tangent);
+            let vel = particle.response().respond(vel, tangent);
+            particle.vel = carried.map_or(vel, |carried| vel + carried);
 
-            particle.vel = (particle.vel - (dp * tangent) * 1.50) * Self::FRICTION;
+            (-dp).max(0.0)
+        } else {
+            0.0
         }
     }
 }
-#[allow(dead_code)]
-#[derive(Serialize, Deserialize)]
-struct ObjectDescriptor {
-    particle_start: usize,
-    particle_end: usize,
 
-    spring_start: usize,
-    spring_end: usize,
+/// What an [`Emitter`] sends out.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Emission {
+    /// Single loose particles
+    Particle,
+    /// Soft bodies of the shape at its smallest
+    Body(BodyShape),
+}
 
-    boundary_start: usize,
-    boundary_end: usize,
+impl Emission {
+    pub const ALL: [Self; 4] = [
+        Self::Particle,
+        Self::Body(BodyShape::Ring),
+        Self::Body(BodyShape::Star),
+        Self::Body(BodyShape::Wheel),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Particle => "particles",
+            Self::Body(BodyShape::Ring) => "rings",
+            Self::Body(BodyShape::Star) => "stars",
+            Self::Body(BodyShape::Wheel) => "wheels",
+        }
+    }
+
+    fn radius(self) -> Real {
+        match self {
+            Self::Particle => Particle::R,
+            Self::Body(shape) => shape.min_radius(),
+        }
+    }
 }
-#[allow(dead_code)]
-impl ObjectDescriptor {
-    pub fn new(
-        particle_start: usize,
-        particle_end: usize,
-        spring_start: usize,
-        spring_end: usize,
-        boundary_start: usize,
-        boundary_end: usize,
-    ) -> Self {
+
+/// Sends out particles or bodies at a steady rate while the world is simulated.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Emitter {
+    pub pos: Vec2,
+    /// What's emitted starts out moving at this
+    pub vel: Vec2,
+    /// Emissions per second
+    pub rate: Real,
+    /// Emissions of it alive at once, it waits while there are this many
+    pub max_alive: usize,
+    /// Seconds emissions are removed after, zero keeps them around
+    pub lifetime: Real,
+    pub emission: Emission,
+    /// Emissions owed, carried over between steps
+    #[serde(default)]
+    due: Real,
+}
+
+impl Emitter {
+    pub const R: Real = 1.5 * Particle::R;
+    // Settings go up to these, past them emissions would swamp the simulation
+    pub const MAX_RATE: Real = 60.0;
+    pub const MAX_ALIVE: usize = 500;
+    pub const MAX_LIFETIME: Real = 60.0;
+
+    pub fn new(pos: Vec2, vel: Vec2) -> Self {
         Self {
-            particle_start,
-            particle_end,
-            spring_start,
-            spring_end,
-            boundary_start,
-            boundary_end,
+            pos,
+            vel,
+            rate: 5.0,
+            max_alive: 50,
+            lifetime: 10.0,
+            emission: Emission::Particle,
+            due: 0.0,
         }
     }
 
-    pub fn particles_len(&self) -> usize {
-        self.particle_end - self.particle_start
+    /// With its settings within the limits above, for emitters from elsewhere than the
+    /// emitter panel.
+    #[allow(clippy::manual_clamp)]
+    pub fn clamped(self) -> Self {
+        Self {
+            // `max` before `min`, they drop NaN where `clamp` keeps it
+            rate: self.rate.max(0.1).min(Self::MAX_RATE),
+            max_alive: self.max_alive.clamp(1, Self::MAX_ALIVE),
+            lifetime: self.lifetime.max(0.0).min(Self::MAX_LIFETIME),
+            due: 0.0,
+            ..self
+        }
     }
+}
 
-    pub fn particles_range(&self) -> std::ops::Range<usize> {
-        self.particle_start..self.particle_end
-    }
+/// A box that removes whatever goes into it while the world is simulated, see
+/// [`World::add_drain`].
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Drain {
+    pub min: Vec2,
+    pub max: Vec2,
+}
 
-    pub fn springs_len(&self) -> usize {
-        self.spring_end - self.spring_start
+impl Drain {
+    /// Spanning from corner `a` to the opposite corner `b`.
+    pub fn new(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
     }
 
-    pub fn springs_range(&self) -> std::ops::Range<usize> {
-        self.spring_start..self.spring_end
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.clamp(self.min, self.max) == pos
     }
+}
 
-    pub fn boundaries_len(&self) -> usize {
-        self.boundary_end - self.boundary_start
+/// A box gravity is replaced in for everything inside it, see [`World::add_gravity_zone`].
+/// Where zones overlap, the one added last applies.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct GravityZone {
+    pub min: Vec2,
+    pub max: Vec2,
+    pub gravity: Vec2,
+}
+
+impl GravityZone {
+    /// Spanning from corner `a` to the opposite corner `b`.
+    pub fn new(a: Vec2, b: Vec2, gravity: Vec2) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+            gravity,
+        }
     }
 
-    pub fn boundaries_range(&self) -> std::ops::Range<usize> {
-        self.boundary_start..self.boundary_end
+    pub fn contains(&self, pos: Vec2) -> bool {
+        pos.clamp(self.min, self.max) == pos
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct World {
-    particles: Vec<Particle>,
-    springs: Vec<Spring>,
-    boundaries: Vec<usize>,
-    objects: Vec<ObjectDescriptor>,
-    edges: Vec<Edge>,
-    buckets: Vec<Vec<usize>>,
-    dt_acc: f64,
+/// What a [`Mover`] carries along its track.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum MoverShape {
+    /// Of this radius
+    Disc(Real),
+    /// Reaching this far to either side of the track, as thick as edges
+    Bar(Vec2),
 }
 
-impl World {
-    const DT: f64 = 0.00125;
-    const GRID: f64 = HEIGHT / (Particle::R * 2.0);
-    const GRAVITY: Vec2 = Vec2::new(0.0, 350.0);
+impl MoverShape {
+    /// The ones made in the app
+    pub const ALL: [Self; 2] = [
+        Self::Disc(4.0 * Particle::R),
+        Self::Bar(Vec2::new(6.0 * Particle::R, 0.0)),
+    ];
 
-    pub fn new() -> Self {
-        let mut world = World {
-            particles: vec![],
-            springs: vec![],
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Disc(_) => "disc",
+            Self::Bar(_) => "bar",
+        }
+    }
+}
+
+/// An obstacle going along a track at a steady speed and pushing what's in its way, see
+/// [`World::add_mover`]. Open tracks are driven back and forth, closed ones round and round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mover {
+    /// Corners of the track in the order they're driven past
+    pub track: Vec<Vec2>,
+    /// Goes on from the last corner back to the first
+    pub closed: bool,
+    /// Distance covered a second
+    pub speed: Real,
+    pub shape: MoverShape,
+    /// Distance covered so far
+    #[serde(default)]
+    travelled: Real,
+    /// Where it is and how fast it's going there, worked out once a step as the track is
+    /// walked to find it
+    #[serde(skip)]
+    pose: Option<(Vec2, Vec2)>,
+}
+
+impl Mover {
+    pub fn new(track: Vec<Vec2>, closed: bool, speed: Real, shape: MoverShape) -> Self {
+        Self {
+            track,
+            closed,
+            speed,
+            shape,
+            travelled: 0.0,
+            pose: None,
+        }
+    }
+
+    /// From corner to corner, including the one back to the start of closed tracks.
+    pub fn sections(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        let back = self.track.first().filter(|_| self.closed);
+        self.track
+            .iter()
+            .zip(self.track.iter().skip(1).chain(back))
+            .map(|(&a, &b)| (a, b))
+    }
+
+    pub fn length(&self) -> Real {
+        self.sections().map(|(a, b)| a.dist(b)).sum()
+    }
+
+    pub fn pos(&self) -> Vec2 {
+        self.pose.unwrap_or_else(|| self.locate()).0
+    }
+
+    pub fn vel(&self) -> Vec2 {
+        self.pose.unwrap_or_else(|| self.locate()).1
+    }
+
+    /// Where it is along the track and how fast it's going there.
+    fn locate(&self) -> (Vec2, Vec2) {
+        let Some(&first) = self.track.first() else {
+            return (Vec2::null(), Vec2::null());
+        };
+        let length = self.length();
+        if length <= 0.0 {
+            return (first, Vec2::null());
+        }
+
+        // Open tracks are one length there and one back
+        let (mut left, sign) = if self.closed {
+            (self.travelled.rem_euclid(length), 1.0)
+        } else {
+            let left = self.travelled.rem_euclid(2.0 * length);
+            if left > length {
+                (2.0 * length - left, -1.0)
+            } else {
+                (left, 1.0)
+            }
+        };
+        let mut last = first;
+        for (a, b) in self.sections() {
+            let len = a.dist(b);
+            if left <= len && len > 0.0 {
+                let dir = (b - a) / len;
+                return (a + left * dir, sign * self.speed * dir);
+            }
+            left -= len;
+            last = b;
+        }
+        (last, Vec2::null())
+    }
+
+    fn advance(&mut self, dt: Real) {
+        self.travelled += self.speed * dt;
+        self.pose = Some(self.locate());
+    }
+
+    /// Whether `pos` is on it, where it is now.
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.distance(pos).0 <= 0.0
+    }
+}
+
+impl Collider for Mover {
+    fn bounds(&self) -> (Vec2, Vec2) {
+        let pos = self.pos();
+        let reach = match self.shape {
+            MoverShape::Disc(radius) => Vec2::new(radius, radius),
+            MoverShape::Bar(half) => half.abs() + Edge::R,
+        };
+        (pos - reach, pos + reach)
+    }
+
+    fn distance(&self, point: Vec2) -> (Real, Vec2) {
+        let pos = self.pos();
+        let (closest, radius) = match self.shape {
+            MoverShape::Disc(radius) => (pos, radius),
+            MoverShape::Bar(half) => (
+                Edge::new(pos - half, pos + half).closest_point(point),
+                Edge::R,
+            ),
+        };
+        let diff = point - closest;
+        (diff.len() - radius, diff.normalize())
+    }
+
+    fn collide(&self, particle: &mut Particle) -> Real {
+        let (distance, normal) = self.distance(particle.get_pos());
+        if distance > Particle::R {
+            return 0.0;
+        }
+        particle.bounce_off_moving(Particle::R - distance, normal, self.vel())
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Fill {
+    Outline,
+    #[default]
+    Solid,
+    /// Path of a PNG stretched over the particle lattice
+    Texture(String),
+}
+
+/// How particles bounce off and slide along what they touch, Coulomb style: they stick
+/// while sliding slower than `static_friction` times how hard they're pressed against it,
+/// else slow down by `kinetic_friction` times that.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Friction {
+    /// Share of the speed into it they bounce back with
+    pub restitution: Real,
+    pub static_friction: Real,
+    pub kinetic_friction: Real,
+}
+
+impl Default for Friction {
+    fn default() -> Self {
+        Self {
+            restitution: 0.5,
+            static_friction: 0.3,
+            kinetic_friction: 0.2,
+        }
+    }
+}
+
+impl Friction {
+    /// Of two touching particles: the bouncier one and the mean of their friction.
+    fn mix(self, other: Self) -> Self {
+        Self {
+            restitution: self.restitution.max(other.restitution),
+            static_friction: (self.static_friction * other.static_friction).sqrt(),
+            kinetic_friction: (self.kinetic_friction * other.kinetic_friction).sqrt(),
+        }
+    }
+
+    /// How much of the velocity `along` a surface is taken away when pressed against it
+    /// by a change in velocity of `impulse`.
+    fn slide(&self, along: Vec2, impulse: Real) -> Vec2 {
+        let speed = along.len();
+        if speed <= self.static_friction * impulse {
+            along
+        } else {
+            along * (self.kinetic_friction * impulse / speed).min(1.0)
+        }
+    }
+
+    /// Velocity `vel`, relative to a surface facing `normal`, after hitting it.
+    fn respond(&self, vel: Vec2, normal: Vec2) -> Vec2 {
+        let dp = vel.dot(normal);
+        if dp >= 0.0 {
+            return vel;
+        }
+
+        let impulse = -(1.0 + self.restitution) * dp;
+        let along = vel - dp * normal;
+        along - self.slide(along, impulse) - self.restitution * dp * normal
+    }
+}
+
+/// How touching particles push off each other.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Contact {
+    /// Share of the speed they approached at they move apart with
+    pub restitution: Real,
+    /// Share of how fast they move toward or away from each other taken away every step
+    /// they touch, which settles piles
+    pub damping: Real,
+}
+
+/// Elastic, the way particles pushed off each other before contacts could be tuned.
+impl Default for Contact {
+    fn default() -> Self {
+        Self::ELASTIC
+    }
+}
+
+impl Contact {
+    pub const ELASTIC: Self = Self {
+        restitution: 1.0,
+        damping: 0.0,
+    };
+    /// Piles come to rest instead of jittering on, bodies bounce off each other less
+    pub const SETTLED: Self = Self {
+        restitution: 0.5,
+        damping: 0.3,
+    };
+
+    /// Of two touching particles: the less bouncy one, so grains bounce off nothing, and
+    /// the more damped one.
+    fn mix(self, other: Self) -> Self {
+        Self {
+            restitution: self.restitution.min(other.restitution),
+            damping: self.damping.max(other.damping),
+        }
+    }
+}
+
+/// Spring and particle parameters of one object.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Material {
+    pub stiffness: Real,
+    pub damping: Real,
+    /// Of every particle of the object
+    pub mass: Real,
+    #[serde(default)]
+    pub friction: Friction,
+    #[serde(default)]
+    pub contact: Contact,
+}
+
+/// Where an object is and how it moves as a whole, kept up to date every step.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct ObjectState {
+    pub mass: Real,
+    pub center_of_mass: Vec2,
+    /// Of the center of mass
+    pub vel: Vec2,
+    /// Corners of the box around the particle centers
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl ObjectState {
+    fn measure(particles: &[Particle]) -> Self {
+        let Some(first) = particles.first() else {
+            return Self::default();
+        };
+
+        let mut state = Self {
+            min: first.pos,
+            max: first.pos,
+            ..Self::default()
+        };
+        for p in particles {
+            state.mass += p.mass;
+            state.center_of_mass += p.pos * p.mass;
+            state.vel += p.vel * p.mass;
+            state.min = state.min.min(p.pos);
+            state.max = state.max.max(p.pos);
+        }
+        state.center_of_mass /= state.mass;
+        state.vel /= state.mass;
+
+        state
+    }
+}
+
+/// Objects and edges are on one each, to be hidden or locked together, like background
+/// terrain while working on the bodies in front of it.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    /// Not drawn, nor picked with the mouse. Still simulated
+    #[serde(default)]
+    pub hidden: bool,
+    /// Drawn but not picked with the mouse, so it can't be selected or edited
+    #[serde(default)]
+    pub locked: bool,
+}
+
+impl Layer {
+    pub fn is_pickable(&self) -> bool {
+        !(self.hidden || self.locked)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Serialize, Deserialize)]
+struct ObjectDescriptor {
+    particle_start: usize,
+    particle_end: usize,
+
+    spring_start: usize,
+    spring_end: usize,
+
+    boundary_start: usize,
+    boundary_end: usize,
+
+    #[serde(default)]
+    open: bool,
+
+    #[serde(default)]
+    fill: Fill,
+    #[serde(default)]
+    color: Option<Color>,
+    /// Columns and rows of the particle grid, for objects spawned as one
+    #[serde(default)]
+    lattice: Option<(usize, usize)>,
+    /// `None` follows the solver settings
+    #[serde(default)]
+    material: Option<Material>,
+    /// Torque the object is turned with at full throttle, see [`World::set_throttle`]
+    #[serde(default)]
+    motor: Real,
+    /// Turning it over the next update, see [`World::apply_torque`]
+    #[serde(skip)]
+    torque: Real,
+    /// Emitter that sent it out, it counts against that one's [`Emitter::max_alive`]
+    #[serde(default)]
+    emitter: Option<usize>,
+    /// Pinned where it was after a particle of it went NaN or infinite, see
+    /// [`World::is_quarantined`]
+    #[serde(default)]
+    quarantined: bool,
+    /// Held still, see [`World::set_frozen`]
+    #[serde(default)]
+    frozen: bool,
+    #[serde(default)]
+    name: Option<String>,
+    /// Index into [`World::layers`]
+    #[serde(default)]
+    layer: usize,
+    #[serde(skip)]
+    state: ObjectState,
+}
+#[allow(dead_code)]
+impl ObjectDescriptor {
+    pub fn new(
+        particle_start: usize,
+        particle_end: usize,
+        spring_start: usize,
+        spring_end: usize,
+        boundary_start: usize,
+        boundary_end: usize,
+    ) -> Self {
+        Self {
+            particle_start,
+            particle_end,
+            spring_start,
+            spring_end,
+            boundary_start,
+            boundary_end,
+            open: false,
+            fill: Fill::default(),
+            color: None,
+            lattice: None,
+            material: None,
+            motor: 0.0,
+            torque: 0.0,
+            emitter: None,
+            quarantined: false,
+            frozen: false,
+            name: None,
+            layer: 0,
+            state: ObjectState::default(),
+        }
+    }
+
+    pub fn particles_len(&self) -> usize {
+        self.particle_end - self.particle_start
+    }
+
+    pub fn particles_range(&self) -> std::ops::Range<usize> {
+        self.particle_start..self.particle_end
+    }
+
+    pub fn springs_len(&self) -> usize {
+        self.spring_end - self.spring_start
+    }
+
+    pub fn springs_range(&self) -> std::ops::Range<usize> {
+        self.spring_start..self.spring_end
+    }
+
+    pub fn boundaries_len(&self) -> usize {
+        self.boundary_end - self.boundary_start
+    }
+
+    pub fn boundaries_range(&self) -> std::ops::Range<usize> {
+        self.boundary_start..self.boundary_end
+    }
+}
+
+/// Whether two boxes, each given by its min and max corner, intersect.
+fn overlap((a_min, a_max): (Vec2, Vec2), (b_min, b_max): (Vec2, Vec2)) -> bool {
+    a_min.x <= b_max.x && b_min.x <= a_max.x && a_min.y <= b_max.y && b_min.y <= a_max.y
+}
+
+/// Whether the segment from `a` to `b` passes through the box given by its min and max
+/// corner, clipping it to the box one axis at a time.
+fn segment_crosses(a: Vec2, b: Vec2, (min, max): (Vec2, Vec2)) -> bool {
+    let dir = b - a;
+    let (mut enter, mut exit): (Real, Real) = (0.0, 1.0);
+    for (start, dir, lo, hi) in [(a.x, dir.x, min.x, max.x), (a.y, dir.y, min.y, max.y)] {
+        if dir == 0.0 {
+            if start < lo || start > hi {
+                return false;
+            }
+            continue;
+        }
+        let (near, far) = ((lo - start) / dir, (hi - start) / dir);
+        enter = enter.max(near.min(far));
+        exit = exit.min(near.max(far));
+        if enter > exit {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether `pos` is inside the polygon with the given corners, by counting crossings like
+/// [`World::object_at`] does for outlines.
+fn polygon_contains(polygon: &[Vec2], pos: Vec2) -> bool {
+    let mut inside = false;
+    for (&a, &b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.y > pos.y) != (b.y > pos.y) && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Drops the items marked `dead`, any past the end of it are kept.
+fn retain_alive<T>(items: &mut Vec<T>, dead: &[bool]) {
+    let mut i = 0;
+    items.retain(|_| {
+        i += 1;
+        !dead.get(i - 1).copied().unwrap_or(false)
+    });
+}
+
+#[derive(Clone, Copy, Default)]
+struct Grid {
+    /// Corner of the first cell
+    origin: Vec2,
+    cell: Real,
+    cols: usize,
+    rows: usize,
+}
+
+impl Grid {
+    const CELL: Real = Particle::R * 2.0;
+    // Past this many cells they grow instead, so particles scattered over a huge world
+    // don't need millions of buckets
+    const MAX_CELLS: Real = 262_144.0;
+
+    /// Covering the rectangle from `min` to `max`.
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        let size = max - min;
+        let cell = Self::CELL.max((size.x * size.y / Self::MAX_CELLS).sqrt());
+        // A minimized window can report a zero size, keep at least one cell to file into
+        Self {
+            origin: min,
+            cell,
+            cols: ((size.x / cell).ceil() as usize).max(1),
+            rows: ((size.y / cell).ceil() as usize).max(1),
+        }
+    }
+
+    pub fn len(self) -> usize {
+        self.cols * self.rows
+    }
+
+    /// Cell of `pos`, with each axis clamped onto the grid on its own. Far off particles
+    /// share the border cells next to where they left, which keeps them near the particles
+    /// they can actually reach instead of in whatever cell a wrapped-around index hits.
+    pub fn pos(self, pos: Vec2) -> (usize, usize) {
+        let axis = |v: Real, cells: usize| {
+            // A NaN position can't be anywhere, it goes in the first cell and gets sanitized
+            if v.is_nan() {
+                return 0;
+            }
+            (v / self.cell).floor().clamp(0.0, (cells - 1) as Real) as usize
+        };
+
+        let pos = pos - self.origin;
+        (axis(pos.x, self.cols), axis(pos.y, self.rows))
+    }
+
+    pub fn idx(self, x: usize, y: usize) -> usize {
+        x + y * self.cols
+    }
+
+    /// Buckets a particle in cell `x`, `y` is tested against: its own and the four neighbors
+    /// west, north-west, north and south-west of it. The other four neighbors see this cell
+    /// in the mirrored position, so over all cells every adjacent pair comes up exactly once.
+    pub fn half_neighborhood(self, x: usize, y: usize) -> impl Iterator<Item = usize> {
+        [(0, 0), (-1, -1), (0, -1), (-1, 0), (-1, 1)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| {
+                let (x, y) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                (x < self.cols && y < self.rows).then(|| self.idx(x, y))
+            })
+    }
+
+    /// Particles that particle `i`, filed under `cell`, is tested against. Pairs sharing a
+    /// cell are met from both ends, the lower index takes those.
+    pub fn partners<'a>(
+        self,
+        buckets: &'a [Vec<usize>],
+        i: usize,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = usize> + 'a {
+        let home = self.idx(x, y);
+        self.half_neighborhood(x, y).flat_map(move |z| {
+            buckets[z]
+                .iter()
+                .copied()
+                .filter(move |&j| z != home || j > i)
+        })
+    }
+}
+
+/// A [`Spring`] of a prefab, between particles given by index in it.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct PrefabSpring {
+    a: usize,
+    b: usize,
+    l0: Real,
+    #[serde(default)]
+    muscle: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Prefab {
+    particles: Vec<Particle>,
+    springs: Vec<PrefabSpring>,
+    /// Of the springs, they refer to them by index in here
+    #[serde(default)]
+    muscles: Vec<Muscle>,
+    boundaries: Vec<usize>,
+    #[serde(default)]
+    open: bool,
+    #[serde(default)]
+    fill: Fill,
+    #[serde(default)]
+    color: Option<Color>,
+    #[serde(default)]
+    lattice: Option<(usize, usize)>,
+    #[serde(default)]
+    material: Option<Material>,
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl Prefab {
+    /// Whether every index in it points at something in it, prefab files can be edited
+    /// by hand or come out corrupt.
+    pub fn check(&self) -> Result<(), String> {
+        let particles = self.particles.len();
+        if let Some(s) = self
+            .springs
+            .iter()
+            .find(|s| s.a >= particles || s.b >= particles)
+        {
+            return Err(format!(
+                "spring between particles {} and {} of {particles}",
+                s.a, s.b
+            ));
+        }
+        if let Some(m) = self
+            .springs
+            .iter()
+            .filter_map(|s| s.muscle)
+            .find(|&m| m as usize >= self.muscles.len())
+        {
+            return Err(format!("spring in muscle {m} of {}", self.muscles.len()));
+        }
+        if let Some(i) = self.boundaries.iter().find(|&&i| i >= particles) {
+            return Err(format!("boundary at particle {i} of {particles}"));
+        }
+        match self.lattice {
+            Some((w, h)) if w < 2 || h < 2 || w.saturating_mul(h) > particles => {
+                Err(format!("{w} x {h} lattice over {particles} particles"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Objects and edges lifted out of a world, placed relative to their common center.
+#[derive(Serialize, Deserialize)]
+pub struct Group {
+    objects: Vec<(Vec2, Prefab)>,
+    edges: Vec<Edge>,
+}
+
+/// What happens at the edges of the window.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum WorldBounds {
+    /// Walls keep every particle on screen
+    #[default]
+    Closed,
+    /// Bodies are free to leave the screen
+    Open,
+    /// Bodies leaving one side come back on the opposite one
+    Wrap,
+}
+
+impl WorldBounds {
+    pub const ALL: [Self; 3] = [Self::Closed, Self::Open, Self::Wrap];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::Wrap => "wrap",
+        }
+    }
+}
+
+/// Round bodies spawned from a center and a radius, see [`World::spawn_shape`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum BodyShape {
+    /// A hollow donut, two circles of particles braced against each other
+    Ring,
+    /// Five points, their corners held to a hub particle in the middle
+    Star,
+    /// A rim held by a dozen spokes to a hub particle
+    Wheel,
+}
+
+impl BodyShape {
+    /// Below it neighboring particles on the inner parts would overlap, and a wheel would
+    /// have fewer rim particles than spokes.
+    pub fn min_radius(self) -> Real {
+        match self {
+            Self::Ring => 4.0 * Particle::SPACING,
+            Self::Star => 3.0 * Particle::SPACING,
+            Self::Wheel => 2.0 * Particle::SPACING,
+        }
+    }
+
+    /// Corners of the outside of the shape, circles approximated by a polygon.
+    pub fn outline(self, center: Vec2, radius: Real) -> Vec<Vec2> {
+        const SEGMENTS: usize = 48;
+        const POINTS: usize = 5;
+
+        match self {
+            Self::Ring | Self::Wheel => (0..SEGMENTS)
+                .map(|i| center + Vec2::from_angle(i as Real * TAU / SEGMENTS as Real) * radius)
+                .collect(),
+            Self::Star => (0..2 * POINTS)
+                .map(|k| {
+                    let dir = Vec2::from_angle(k as Real * PI / POINTS as Real - FRAC_PI_2);
+                    center + dir * if k % 2 == 0 { radius } else { radius / 2.0 }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SolverSettings {
+    pub gravity: Vec2,
+    pub stiffness: Real,
+    pub damping: Real,
+    #[serde(default)]
+    pub bounds: WorldBounds,
+    /// Simulate temperatures: hot edges warm particles, heat spreads along springs and
+    /// springs soften past the melting point
+    #[serde(default)]
+    pub heat: bool,
+    #[serde(default = "SolverSettings::default_melting_point")]
+    pub melting_point: Real,
+    /// Cap the force of every spring at `max_spring_force`, which keeps very stiff objects
+    /// from blowing up at the cost of letting them stretch under heavy loads
+    #[serde(default)]
+    pub limit_spring_force: bool,
+    #[serde(default = "SolverSettings::default_max_spring_force")]
+    pub max_spring_force: Real,
+    /// Slow every particle down to `max_speed`, which keeps anything that shot off from
+    /// tunneling through the world
+    #[serde(default)]
+    pub limit_speed: bool,
+    #[serde(default = "SolverSettings::default_max_speed")]
+    pub max_speed: Real,
+    /// Passes over the springs after every step, each pulling them all back to their rest
+    /// lengths
+    #[serde(default)]
+    pub relaxation_iterations: usize,
+    /// Of objects without a material of their own
+    #[serde(default)]
+    pub friction: Friction,
+    #[serde(default)]
+    pub contact: Contact,
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            gravity: Vec2::new(0.0, 350.0),
+            stiffness: 6000.0,
+            damping: 100.0,
+            bounds: WorldBounds::default(),
+            heat: false,
+            melting_point: Self::default_melting_point(),
+            limit_spring_force: false,
+            max_spring_force: Self::default_max_spring_force(),
+            limit_speed: false,
+            max_speed: Self::default_max_speed(),
+            relaxation_iterations: 0,
+            friction: Friction::default(),
+            contact: Contact::default(),
+        }
+    }
+}
+
+impl SolverSettings {
+    fn default_melting_point() -> Real {
+        100.0
+    }
+
+    fn default_max_spring_force() -> Real {
+        2000.0
+    }
+
+    fn default_max_speed() -> Real {
+        5000.0
+    }
+
+    /// What objects without a material of their own are made of.
+    pub fn material(&self) -> Material {
+        Material {
+            stiffness: self.stiffness,
+            damping: self.damping,
+            mass: 1.0,
+            friction: self.friction,
+            contact: self.contact,
+        }
+    }
+}
+
+/// How healthy the simulation is, measured after every [`World::update`] while
+/// [`World::set_track_metrics`] is on.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Metrics {
+    pub kinetic_energy: Real,
+    /// Mean of how far springs are off their rest lengths, relative to them
+    pub spring_error: Real,
+    /// Deepest any particle is in another one or in an edge
+    pub max_penetration: Real,
+    /// Steps the update took
+    pub steps: usize,
+}
+
+/// A spring stretched to more than five times its rest length, which happens when the
+/// simulation blows up. Stepping stops there, see [`World::update`].
+#[derive(Clone, Copy, Debug)]
+pub struct Instability {
+    /// Particles at the ends of the spring
+    pub a: Id,
+    pub b: Id,
+    pub diff_len: Real,
+}
+
+/// Something that happened during the last [`World::update`], for effects like sound.
+#[derive(Clone, Copy, Debug)]
+pub enum WorldEvent {
+    /// A particle hit an edge or another particle at `speed` along the contact normal
+    Impact { pos: Vec2, speed: Real },
+    /// A spring was squeezed below [`World::COMPRESSION`] of its rest length, `closing`
+    /// is how fast its ends were approaching
+    Compression { pos: Vec2, closing: Real },
+    /// An edge took more damage than its strength and was removed, `pos` is its middle
+    EdgeBroken { pos: Vec2 },
+    /// Object `object` outlived its lifetime or went down a drain and was removed, the
+    /// ones after it moved down
+    Despawned { object: usize },
+    /// A particle of object `object` went NaN or infinite and the object was quarantined,
+    /// see [`World::is_quarantined`]
+    Quarantined { object: usize },
+}
+
+/// Serialized through the impls below, which fill in what older saves lack.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+pub struct World {
+    particles: Vec<Particle>,
+    springs: Vec<Spring>,
+    boundaries: Vec<Id>,
+    objects: Vec<ObjectDescriptor>,
+    /// Springs joining particles of different objects, like the axles of a vehicle
+    #[serde(default)]
+    links: Vec<Spring>,
+    #[serde(default)]
+    rods: Vec<Rod>,
+    /// Particles stuck to sticky edges
+    #[serde(default)]
+    anchors: Vec<Anchor>,
+    edges: Vec<Edge>,
+    #[serde(default)]
+    emitters: Vec<Emitter>,
+    #[serde(default)]
+    drains: Vec<Drain>,
+    #[serde(default)]
+    gravity_zones: Vec<GravityZone>,
+    #[serde(default)]
+    movers: Vec<Mover>,
+    #[serde(default)]
+    muscles: Vec<Muscle>,
+    /// Ids of the objects, edges, springs and particles, see [`World::object_id`]
+    #[serde(default)]
+    object_ids: Slots,
+    #[serde(default)]
+    edge_ids: Slots,
+    #[serde(default)]
+    spring_ids: Slots,
+    #[serde(default)]
+    particle_ids: Slots,
+    /// Never empty once sized, see [`World::resize`]
+    #[serde(default)]
+    layers: Vec<Layer>,
+    /// Where new objects and edges go
+    #[serde(default)]
+    active_layer: usize,
+    /// Zero in worlds saved before they had a size of their own, apart from the window's
+    #[serde(default)]
+    size: Vec2,
+    #[serde(skip)]
+    borders: Vec<Edge>,
+    /// The edges joined up, worked out again once they change
+    #[serde(skip)]
+    chains: Option<Vec<Chain>>,
+    #[serde(skip)]
+    grid: Grid,
+    #[serde(skip)]
+    buckets: Vec<Vec<usize>>,
+    /// Grid cell of every particle as of the last broadphase
+    #[serde(skip)]
+    cells: Vec<(usize, usize)>,
+    /// Object of every particle as of the last broadphase
+    #[serde(skip)]
+    owners: Vec<usize>,
+    /// Bounds of every object grown by how close particles of another object have to come
+    /// to collide with it, as of the last broadphase
+    #[serde(skip)]
+    reach: Vec<(Vec2, Vec2)>,
+    #[serde(skip)]
+    drag: Option<(Id, Vec2)>,
+    #[serde(skip)]
+    textures: HashMap<String, Arc<Texture>>,
+    /// Particle positions before the last step, drawn blended towards the current ones
+    #[serde(skip)]
+    prev_pos: Vec<Vec2>,
+    /// Recent positions of boundary particles, oldest first, indexed by particle
+    #[serde(skip)]
+    trails: Vec<VecDeque<Vec2>>,
+    #[serde(skip)]
+    events: Vec<WorldEvent>,
+    #[serde(skip)]
+    pace: Real,
+    /// Of the last update, `None` while not tracked
+    #[serde(skip)]
+    metrics: Option<Metrics>,
+    /// Seconds simulated per second of real time, see [`World::set_speed`]
+    #[serde(skip, default = "World::default_speed")]
+    speed: Real,
+    /// How hard motors are driven, from -1 for full reverse to 1
+    #[serde(skip)]
+    throttle: Real,
+    #[serde(default)]
+    settings: SolverSettings,
+    dt_acc: Real,
+    /// Physics steps taken since the world was created
+    #[serde(default)]
+    steps: u64,
+    #[serde(skip)]
+    revision: u64,
+    /// Added by library users on top of gravity, shared by clones like the one simulated
+    #[serde(skip)]
+    forces: Vec<Arc<Mutex<Box<dyn ForceGenerator>>>>,
+    /// Added by library users besides edges
+    #[serde(skip)]
+    colliders: Vec<Arc<dyn Collider>>,
+    /// Particle collisions run as compute shaders on this when set
+    #[cfg(feature = "wgpu")]
+    #[serde(skip)]
+    gpu: Option<Arc<Mutex<GpuCollisions>>>,
+}
+
+impl Serialize for World {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        World::serialize(self, serializer)
+    }
+}
+
+/// Worlds saved before they had ids for everything get fresh ones, in the order things
+/// are in. Springs and the like saved with particle indices then refer to the same ones.
+impl<'de> Deserialize<'de> for World {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut world = World::deserialize(deserializer)?;
+        if world.object_ids.len() != world.objects.len() {
+            world.object_ids.reset(world.objects.len());
+        }
+        if world.edge_ids.len() != world.edges.len() {
+            world.edge_ids.reset(world.edges.len());
+        }
+        if world.spring_ids.len() != world.springs.len() {
+            world.spring_ids.reset(world.springs.len());
+        }
+        if world.particle_ids.len() != world.particles.len() {
+            world.particle_ids.reset(world.particles.len());
+        }
+        Ok(world)
+    }
+}
+
+impl World {
+    const DT: Real = 0.00125;
+    const TRAIL_LEN: usize = 30;
+    // Most steps one update may take at normal speed, faster ones get as many more. Past
+    // that the rest of the time is dropped: the simulation falls behind real time instead
+    // of every frame taking longer than the last
+    const MAX_STEPS: usize = 40;
+    const DRAG_KS: Real = 3000.0;
+    const DRAG_KD: Real = 80.0;
+    const ANCHOR_KS: Real = 3000.0;
+    // About critically damped for particles of unit mass, stuck bodies settle instead of
+    // bouncing off again
+    const ANCHOR_KD: Real = 110.0;
+    // Rates per second heat flows at from edges into particles touching them, along
+    // springs, and out of everything into the surroundings
+    const EDGE_CONDUCTION: Real = 5.0;
+    const CONDUCTION: Real = 10.0;
+    const COOLING: Real = 0.2;
+    // How far past the melting point springs are at their softest, and what they keep of
+    // their stiffness then
+    const MELTING_RANGE: Real = 100.0;
+    const MOLTEN: Real = 0.01;
+    // Slower contacts are resting or rolling, not worth reporting
+    const IMPACT_SPEED: Real = 100.0;
+    pub const COMPRESSION: Real = 0.7;
+    /// Longest side of a rect [`World::check_spawn_rect`] lets through, wider than any screen
+    /// while keeping what a remote client can ask for bounded
+    pub const MAX_RECT_SIDE: usize = 200;
+    // Slack around object bounds in the broadphase, they are measured once a step while
+    // particles keep moving during it
+    const BROADPHASE_MARGIN: Real = Particle::R;
+    /// Angular velocity motors drive objects up to at full throttle, in radians per second
+    pub const MOTOR_SPIN: Real = 8.0;
+    // Between grains of freshly spawned sand, besides the jitter
+    const SAND_GAP: Real = 2.0;
+    // Ways a freshly spawned object is tried moving out of what it overlaps, and how far
+    // further it's tried every time none of them work
+    const PUSH_OUT_DIRECTIONS: usize = 16;
+    const PUSH_OUT_STEP: Real = 0.5 * Particle::R;
+    // Overlaps shallower than this are left to the solver
+    const PUSH_OUT_SLOP: Real = 0.01 * Particle::R;
+    // Passes over the rods every step, enough for chains of a few dozen to come out exact
+    const ROD_ITERATIONS: usize = 10;
+    const ROD_WIDTH: Real = 0.6 * Particle::R;
+    // Longest the arrow showing which way gravity pulls in a zone gets
+    const ZONE_ARROW: Real = 30.0;
+    /// Largest a world gets either way
+    pub const MAX_SIZE: Real = 16384.0;
+    /// New edge ends dropped this close to an existing endpoint are joined to it
+    pub const VERTEX_SNAP: Real = 2.0 * Edge::R;
+    // Endpoints closer than this are one shared vertex, `get_end` is only exact up to rounding
+    const SAME_VERTEX: Real = 1e-6;
+
+    pub fn new(width: Real, height: Real) -> Self {
+        let mut world = World {
+            particles: vec![],
+            springs: vec![],
             boundaries: vec![],
             objects: vec![],
+            links: vec![],
+            rods: vec![],
+            anchors: vec![],
             edges: vec![],
+            emitters: vec![],
+            drains: vec![],
+            gravity_zones: vec![],
+            movers: vec![],
+            muscles: vec![],
+            object_ids: Slots::default(),
+            edge_ids: Slots::default(),
+            spring_ids: Slots::default(),
+            particle_ids: Slots::default(),
+            layers: vec![],
+            active_layer: 0,
+            size: Vec2::null(),
+            borders: vec![],
+            chains: None,
+            grid: Grid::default(),
             buckets: vec![],
+            cells: vec![],
+            owners: vec![],
+            reach: vec![],
+            drag: None,
+            textures: HashMap::new(),
+            prev_pos: vec![],
+            trails: vec![],
+            events: vec![],
+            pace: 1.0,
+            metrics: None,
+            speed: 1.0,
+            throttle: 0.0,
+            settings: SolverSettings::default(),
             dt_acc: 0.0,
+            steps: 0,
+            revision: 0,
+            forces: vec![],
+            colliders: vec![],
+            #[cfg(feature = "wgpu")]
+            gpu: None,
+        };
+
+        world.resize(width, height);
+        world
+    }
+
+    /// Identifies the last edit, shared by clones of the world and unique otherwise, even
+    /// across worlds. Edits are anything but running the simulation: spawning, removing,
+    /// moving things by hand, changing settings.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Turns this into a clone of `source`, keeping the per particle buffers it has instead
+    /// of allocating them again, for copies made over and over like the physics snapshots.
+    /// Those buffers are moved out of `source` while the rest of it is cloned, and back.
+    pub fn copy_from(&mut self, source: &mut World) {
+        macro_rules! reusing {
+            ($($field:ident),*) => {
+                let spare = ($(std::mem::take(&mut self.$field),)*);
+                let held = ($(std::mem::take(&mut source.$field),)*);
+                *self = source.clone();
+                ($(source.$field,)*) = held;
+                ($(self.$field,)*) = spare;
+                $(self.$field.clone_from(&source.$field);)*
+            };
+        }
+        reusing!(particles, springs, boundaries, prev_pos, trails, buckets, cells, owners);
+    }
+
+    fn edit(&mut self) {
+        static REVISIONS: AtomicU64 = AtomicU64::new(1);
+        self.revision = REVISIONS.fetch_add(1, Ordering::Relaxed);
+        self.chains = None;
+    }
+
+    /// Clamped to [`World::MAX_SIZE`].
+    pub fn resize(&mut self, width: Real, height: Real) {
+        self.edit();
+        let (width, height) = (width.min(Self::MAX_SIZE), height.min(Self::MAX_SIZE));
+        self.size = Vec2::new(width, height);
+
+        let (l, t) = (-Edge::R, -Edge::R);
+        let (r, b) = (width + Edge::R, height + Edge::R);
+
+        self.borders = vec![
+            Edge::new(Vec2::new(l, t), Vec2::new(l, b)),
+            Edge::new(Vec2::new(l, t), Vec2::new(r, t)),
+            Edge::new(Vec2::new(l, b), Vec2::new(r, b)),
+            Edge::new(Vec2::new(r, t), Vec2::new(r, b)),
+        ];
+
+        // Not saved either, a freshly loaded world gets them here
+        self.measure_objects();
+        if self.layers.is_empty() {
+            self.layers.push(Layer {
+                name: "default".into(),
+                ..Layer::default()
+            });
+        }
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn can_add_edge(&self, start: Vec2, end: Vec2) -> bool {
+        start != end
+    }
+
+    pub fn add_edge(&mut self, start: Vec2, end: Vec2) -> Result<(), &'static str> {
+        self.edit();
+        if !self.can_add_edge(start, end) {
+            return Err("cant add edge, length cannot be 0");
+        }
+        self.edges.push(Edge {
+            layer: self.active_layer,
+            ..Edge::new(start, end)
+        });
+        self.edge_ids.push();
+        Ok(())
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn can_spawn_rect(&self, w: usize, h: usize) -> bool {
+        w >= 2 && h >= 2
+    }
+
+    /// Why a `w` by `h` rect spawned at `x`, `y` like [`World::spawn_rect`] does would come
+    /// out tangled up with what's already there, pushed apart the moment it's simulated, or
+    /// is more than [`World::MAX_RECT_SIDE`] particles across.
+    pub fn check_spawn_rect(&self, w: usize, h: usize, x: Real, y: Real) -> Result<(), String> {
+        if !self.can_spawn_rect(w, h) {
+            return Err("too small, it needs to be at least 2 x 2".into());
+        }
+        if w > Self::MAX_RECT_SIDE || h > Self::MAX_RECT_SIDE {
+            let max = Self::MAX_RECT_SIDE;
+            return Err(format!("too big, it can be at most {max} x {max}"));
+        }
+        let min = Vec2::new(x, y);
+        let max = min + Vec2::new((w - 1) as Real, (h - 1) as Real) * Particle::SPACING;
+
+        if self.edges.iter().any(|e| {
+            let reach = Particle::R + e.radius;
+            segment_crosses(e.get_start(), e.get_end(), (min - reach, max + reach))
+        }) {
+            return Err("overlaps an edge".into());
+        }
+
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        if self
+            .particles
+            .iter()
+            .any(|p| p.pos.clamp(min, max).dist_sqr(p.pos) < SQR!(2.0 * Particle::R))
+            || self.objects.iter().any(|obj| {
+                !obj.open
+                    && !Self::is_loose(obj)
+                    && corners.iter().any(|&c| self.outline_contains(obj, c))
+            })
+        {
+            return Err("overlaps an object".into());
+        }
+
+        // Anything else particles bounce off, tested at every one of them
+        let reach = Particle::R;
+        let colliders = self
+            .movers
+            .iter()
+            .map(|m| m as &dyn Collider)
+            .chain(self.colliders.iter().map(AsRef::as_ref))
+            .filter(|c| overlap(c.bounds(), (min - reach, max + reach)));
+        for collider in colliders {
+            let blocked = (0..w)
+                .flat_map(|i| (0..h).map(move |j| Vec2::new(i as Real, j as Real)))
+                .any(|lattice| {
+                    collider.distance(min + lattice * Particle::SPACING).0 < Particle::R
+                });
+            if blocked {
+                return Err("overlaps a mover or collider".into());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn spawn_rect(
+        &mut self,
+        w: usize,
+        h: usize,
+        x: Real,
+        y: Real,
+    ) -> Result<(), (usize, usize)> {
+        self.spawn_rect_spaced(w, h, x, y, Particle::SPACING)
+    }
+
+    /// A `w` by `h` lattice with `spacing` between neighboring particles instead of the usual
+    /// [`Particle::SPACING`]. Anything under twice [`Particle::R`] leaves neighbors overlapping.
+    pub fn spawn_rect_spaced(
+        &mut self,
+        w: usize,
+        h: usize,
+        x: Real,
+        y: Real,
+        spacing: Real,
+    ) -> Result<(), (usize, usize)> {
+        if !self.can_spawn_rect(w, h) {
+            return Err((w, h));
+        }
+        let diag = (2.0 * spacing * spacing).sqrt();
+
+        self.particles.reserve(w * h);
+        self.springs.reserve(w * h * 4);
+        self.boundaries.reserve(2 * w + 2 * h);
+
+        let p_start = self.particles.len();
+        let s_start = self.springs.len();
+
+        for i in 0..w {
+            for j in 0..h {
+                self.add_particle(Vec2::new(i as Real * spacing + x, j as Real * spacing + y));
+            }
+        }
+        // Only once they all have ids, springs go to ones further on
+        for i in 0..w {
+            for j in 0..h {
+                let ind = p_start + i * h + j;
+                if i < w - 1 {
+                    self.spring(ind, ind + h, spacing);
+                }
+                if j < h - 1 {
+                    self.spring(ind, ind + 1, spacing);
+                }
+                if i < w - 1 && j < h - 1 {
+                    self.spring(ind, ind + h + 1, diag);
+                }
+                if i > 0 && j < h - 1 {
+                    self.spring(ind, ind - h + 1, diag);
+                }
+            }
+        }
+
+        let b_start = self.boundaries.len();
+
+        self.bound((0..w).map(|n| p_start + n * h));
+        self.bound(((w - 1) * h + 1..w * h).map(|n| p_start + n));
+        self.bound((1..w - 1).rev().map(|n| p_start + (n + 1) * h - 1));
+        self.bound((1..h).rev().map(|n| p_start + n));
+
+        self.push_object(ObjectDescriptor {
+            lattice: Some((w, h)),
+            ..ObjectDescriptor::new(
+                p_start,
+                self.particles.len(),
+                s_start,
+                self.springs.len(),
+                b_start,
+                self.boundaries.len(),
+            )
+        });
+
+        Ok(())
+    }
+
+    pub fn can_spawn_rope(&self, start: Vec2, end: Vec2) -> bool {
+        self.rope_len(start, end) >= 2
+    }
+
+    pub fn spawn_rope(&mut self, start: Vec2, end: Vec2) -> Result<(), &'static str> {
+        if !self.can_spawn_rope(start, end) {
+            return Err("cant spawn rope, it is too short");
+        }
+
+        let n = self.rope_len(start, end);
+        let step = (end - start).normalize() * Particle::SPACING;
+
+        let p_start = self.particles.len();
+        let s_start = self.springs.len();
+        let b_start = self.boundaries.len();
+
+        for i in 0..n {
+            self.add_particle(start + step * i as Real);
+            self.bound([p_start + i]);
+
+            if i > 0 {
+                self.spring(p_start + i - 1, p_start + i, Particle::SPACING);
+            }
+        }
+
+        self.push_object(ObjectDescriptor {
+            open: true,
+            ..ObjectDescriptor::new(
+                p_start,
+                self.particles.len(),
+                s_start,
+                self.springs.len(),
+                b_start,
+                self.boundaries.len(),
+            )
+        });
+
+        Ok(())
+    }
+
+    /// Scatters loose grains of sand over the free spots of a disc, as one object without
+    /// springs. Returns how many fit, nothing changes if none did.
+    pub fn spawn_sand(&mut self, center: Vec2, radius: Real) -> usize {
+        let spacing = 2.0 * Particle::R + Self::SAND_GAP;
+        let steps = (radius / spacing) as i32;
+        // Jittered so grains don't stack up in perfect columns
+        let mut rng = Rng::new(self.particles.len() as u64 ^ self.steps);
+        let (min, max) = (Vec2::new(Particle::R, Particle::R), self.size - Particle::R);
+
+        let p_start = self.particles.len();
+        for i in -steps..=steps {
+            for j in -steps..=steps {
+                let jitter = Vec2::new(rng.range(-0.5, 0.5), rng.range(-0.5, 0.5));
+                let pos = center + (Vec2::new(i as Real, j as Real) + jitter * 0.5) * spacing;
+                let free = pos.dist(center) <= radius
+                    && pos.clamp(min, max) == pos
+                    && self
+                        .particles
+                        .iter()
+                        .all(|p| p.pos.dist_sqr(pos) > SQR!(2.0 * Particle::R))
+                    && self.edges.iter().all(|e| e.distance(pos).0 > Particle::R);
+                if free {
+                    self.push_particle(Particle {
+                        kind: ParticleKind::Sand,
+                        ..Particle::new(pos.x, pos.y)
+                    });
+                }
+            }
+        }
+
+        let count = self.particles.len() - p_start;
+        if count == 0 {
+            return 0;
+        }
+
+        let b_start = self.boundaries.len();
+        self.bound(p_start..self.particles.len());
+        self.push_object(ObjectDescriptor {
+            open: true,
+            ..ObjectDescriptor::new(
+                p_start,
+                self.particles.len(),
+                self.springs.len(),
+                self.springs.len(),
+                b_start,
+                self.boundaries.len(),
+            )
+        });
+        count
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn rope_len(&self, start: Vec2, end: Vec2) -> usize {
+        (start.dist(end) / Particle::SPACING) as usize + 1
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn can_spawn_shape(&self, shape: BodyShape, radius: Real) -> bool {
+        radius >= shape.min_radius()
+    }
+
+    pub fn spawn_shape(
+        &mut self,
+        shape: BodyShape,
+        center: Vec2,
+        radius: Real,
+    ) -> Result<(), &'static str> {
+        if !self.can_spawn_shape(shape, radius) {
+            return Err("cant spawn shape, it is too small");
+        }
+
+        let obj = self.shape_object(shape, center, radius);
+        self.push_object(obj);
+
+        Ok(())
+    }
+
+    /// Adds the particles, springs and boundaries of a shape, returning the object to push
+    /// for them.
+    fn shape_object(&mut self, shape: BodyShape, center: Vec2, radius: Real) -> ObjectDescriptor {
+        let p_start = self.particles.len();
+        let s_start = self.springs.len();
+        let b_start = self.boundaries.len();
+
+        match shape {
+            BodyShape::Ring => self.ring(center, radius),
+            BodyShape::Star => self.star(center, radius),
+            BodyShape::Wheel => self.wheel(center, radius),
+        }
+
+        ObjectDescriptor::new(
+            p_start,
+            self.particles.len(),
+            s_start,
+            self.springs.len(),
+            b_start,
+            self.boundaries.len(),
+        )
+    }
+
+    /// A body filling the closed polygon `outline`: particles about a spacing apart along
+    /// it, braced like the sides of a star, around a lattice like a rect's on the inside.
+    pub fn spawn_outline(&mut self, outline: &[Vec2]) -> Result<(), &'static str> {
+        // Keeps lattice particles off the ones along the outline
+        const CLEARANCE: Real = 0.75 * Particle::SPACING;
+        // Lattice particles this close to one on the outline are held to it
+        const REACH: Real = 1.5 * Particle::SPACING;
+
+        let sides: Vec<Edge> = outline
+            .iter()
+            .zip(outline.iter().cycle().skip(1))
+            .map(|(&a, &b)| Edge::new(a, b))
+            .filter(|side| side.len_sqr > 0.0)
+            .collect();
+        let perimeter: Real = sides.iter().map(Edge::len).sum();
+        let n = (perimeter / Particle::SPACING).round() as usize;
+        if n < 4 {
+            return Err("cant spawn outline, it is too small");
+        }
+
+        // Clockwise on screen, like the boundaries of every other body
+        let area: Real = sides.iter().map(|side| side.start.cross(side.line)).sum();
+        let sides: Vec<Edge> = if area < 0.0 {
+            sides
+                .iter()
+                .rev()
+                .map(|side| Edge::new(side.get_end(), side.start))
+                .collect()
+        } else {
+            sides
+        };
+
+        let p_start = self.particles.len();
+        let s_start = self.springs.len();
+        let b_start = self.boundaries.len();
+
+        // Evenly spread over the length of the outline
+        let step = perimeter / n as Real;
+        let (mut side, mut walked) = (0, 0.0);
+        for k in 0..n {
+            let at = k as Real * step;
+            while side + 1 < sides.len() && walked + sides[side].len() < at {
+                walked += sides[side].len();
+                side += 1;
+            }
+            let t = ((at - walked) / sides[side].len()).min(1.0);
+            self.add_particle(sides[side].start + sides[side].line * t);
+        }
+        self.brace_loop(p_start, n);
+        self.bound(p_start..p_start + n);
+
+        let inside = |pos: Vec2| {
+            let crossings = sides.iter().filter(|side| {
+                let (a, b) = (side.get_start(), side.get_end());
+                (a.y > pos.y) != (b.y > pos.y)
+                    && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            });
+            crossings.count() % 2 == 1
+                && sides
+                    .iter()
+                    .all(|side| side.closest_point(pos).dist(pos) >= CLEARANCE)
+        };
+
+        let min = outline.iter().fold(outline[0], |min, &p| min.min(p));
+        let max = outline.iter().fold(outline[0], |max, &p| max.max(p));
+        let cols = ((max.x - min.x) / Particle::SPACING) as usize + 1;
+        let rows = ((max.y - min.y) / Particle::SPACING) as usize + 1;
+        // Centered in the bounds so the margins on opposite sides match
+        let origin = (min + max) / 2.0
+            - Vec2::new((cols - 1) as Real, (rows - 1) as Real) * Particle::SPACING / 2.0;
+
+        let mut lattice = vec![None; cols * rows];
+        for i in 0..cols {
+            for j in 0..rows {
+                let pos = origin + Vec2::new(i as Real, j as Real) * Particle::SPACING;
+                if inside(pos) {
+                    lattice[i * rows + j] = Some(self.add_particle(pos));
+                }
+            }
+        }
+
+        for i in 0..cols {
+            for j in 0..rows {
+                let Some(a) = lattice[i * rows + j] else {
+                    continue;
+                };
+                let right = (i + 1 < cols).then(|| i + 1);
+                let down = (j + 1 < rows).then(|| j + 1);
+                let up = j.checked_sub(1);
+
+                let neighbors = [
+                    right.map(|i| (i, j)),
+                    down.map(|j| (i, j)),
+                    right.zip(down),
+                    right.zip(up),
+                ];
+                for (i, j) in neighbors.into_iter().flatten() {
+                    if let Some(b) = lattice[i * rows + j] {
+                        self.connect(a, b);
+                    }
+                }
+            }
+        }
+
+        let interior = p_start + n..self.particles.len();
+        for b in p_start..p_start + n {
+            for a in interior.clone() {
+                if self.particles[a].pos.dist(self.particles[b].pos) <= REACH {
+                    self.connect(a, b);
+                }
+            }
+        }
+
+        self.push_object(ObjectDescriptor::new(
+            p_start,
+            self.particles.len(),
+            s_start,
+            self.springs.len(),
+            b_start,
+            self.boundaries.len(),
+        ));
+
+        Ok(())
+    }
+
+    fn ring(&mut self, center: Vec2, radius: Real) {
+        // Spaced along the middle, a little wider outside and a little tighter inside
+        let n = (TAU * (radius - Particle::SPACING / 2.0) / Particle::SPACING).round() as usize;
+        let outer = self.circle(center, radius, n);
+        let inner = self.circle(center, radius - Particle::SPACING, n);
+
+        for i in 0..n {
+            let j = (i + 1) % n;
+            self.connect(outer + i, outer + j);
+            self.connect(inner + i, inner + j);
+            self.connect(outer + i, inner + i);
+            self.connect(outer + i, inner + j);
+            self.connect(inner + i, outer + j);
+        }
+
+        // Around the outside, across to the inner circle and back around it the other way.
+        // The seam is walked in both directions, so it cancels out of the fill
+        self.bound(outer..outer + n);
+        self.bound([outer, inner]);
+        self.bound((inner + 1..inner + n).rev());
+        self.bound([inner]);
+    }
+
+    fn star(&mut self, center: Vec2, radius: Real) {
+        let hub = self.add_particle(center);
+        let corners = BodyShape::Star.outline(center, radius);
+
+        // Sides split into roughly particle spaced steps, so nothing slips through them
+        let first = self.particles.len();
+        for (k, &a) in corners.iter().enumerate() {
+            let b = corners[(k + 1) % corners.len()];
+            let steps = (a.dist(b) / Particle::SPACING).round().max(1.0) as usize;
+
+            let corner = self.add_particle(a);
+            self.connect(hub, corner);
+            for step in 1..steps {
+                self.add_particle(a.lerp(b, step as Real / steps as Real));
+            }
+        }
+
+        let n = self.particles.len() - first;
+        self.brace_loop(first, n);
+        self.bound(first..first + n);
+    }
+
+    fn wheel(&mut self, center: Vec2, radius: Real) {
+        const SPOKES: usize = 12;
+        let n = (TAU * radius / Particle::SPACING).round() as usize;
+        let hub = self.add_particle(center);
+        let rim = self.circle(center, radius, n);
+
+        self.brace_loop(rim, n);
+        for spoke in 0..SPOKES {
+            self.connect(hub, rim + spoke * n / SPOKES);
+        }
+        self.bound(rim..rim + n);
+    }
+
+    /// Connects a loop of `n` particles to their neighbors and, to stiffen it against
+    /// bending, to the ones after those.
+    fn brace_loop(&mut self, first: usize, n: usize) {
+        for i in 0..n {
+            self.connect(first + i, first + (i + 1) % n);
+            self.connect(first + i, first + (i + 2) % n);
+        }
+    }
+
+    /// `n` particles evenly spread around a circle, returns the index of the first one.
+    fn circle(&mut self, center: Vec2, radius: Real, n: usize) -> usize {
+        let first = self.particles.len();
+        for i in 0..n {
+            self.add_particle(center + Vec2::from_angle(i as Real * TAU / n as Real) * radius);
+        }
+        first
+    }
+
+    fn add_particle(&mut self, pos: Vec2) -> usize {
+        self.push_particle(Particle::new(pos.x, pos.y))
+    }
+
+    fn push_particle(&mut self, particle: Particle) -> usize {
+        self.particles.push(particle);
+        self.particle_ids.push();
+        self.particles.len() - 1
+    }
+
+    /// Id of particle `n`, which has to be there.
+    fn handle(&self, n: usize) -> Id {
+        self.particle_ids.id(n).expect("particle should have an id")
+    }
+
+    /// Index of a particle held onto by something removed along with it.
+    fn index(&self, particle: Id) -> usize {
+        self.particle_ids.at(particle)
+    }
+
+    /// Adds particles `n` to the end of the boundaries.
+    fn bound(&mut self, n: impl IntoIterator<Item = usize>) {
+        for n in n {
+            let id = self.handle(n);
+            self.boundaries.push(id);
+        }
+    }
+
+    fn push_object(&mut self, obj: ObjectDescriptor) {
+        self.edit();
+        self.add_object(obj);
+    }
+
+    // Without counting as an edit, emitters also spawn while simulating
+    fn add_object(&mut self, mut obj: ObjectDescriptor) {
+        obj.state = ObjectState::measure(&self.particles[obj.particles_range()]);
+        obj.layer = self.active_layer;
+        self.objects.push(obj);
+        self.object_ids.push();
+        // Its springs went on the end before it
+        while self.spring_ids.len() < self.springs.len() {
+            self.spring_ids.push();
+        }
+    }
+
+    /// Files every particle under the cell of the grid it's in. The grid covers the world
+    /// and wherever particles went past it, up to another world's size out.
+    fn bin_particles(&mut self) {
+        let (min, max) = self
+            .particles
+            .iter()
+            .fold((Vec2::null(), self.size), |(min, max), p| {
+                (min.min(p.pos), max.max(p.pos))
+            });
+        let (lo, hi) = (-self.size, self.size * 2.0);
+        self.grid = Grid::new(min.clamp(lo, hi), max.clamp(lo, hi));
+        if self.buckets.len() != self.grid.len() {
+            self.buckets.resize(self.grid.len(), vec![]);
+        }
+        self.buckets.iter_mut().for_each(Vec::clear);
+        self.cells.clear();
+
+        for (i, particle) in self.particles.iter().enumerate() {
+            let (x, y) = self.grid.pos(particle.pos);
+            self.cells.push((x, y));
+            self.buckets[self.grid.idx(x, y)].push(i);
+        }
+    }
+
+    /// Refreshes the [`ObjectState`] of every object from its particles.
+    fn measure_objects(&mut self) {
+        for obj in &mut self.objects {
+            obj.state = ObjectState::measure(&self.particles[obj.particles_range()]);
+        }
+    }
+
+    /// A spring between two particles, at rest where they are now.
+    fn connect(&mut self, a: usize, b: usize) {
+        let len = self.particles[a].pos.dist(self.particles[b].pos);
+        self.spring(a, b, len);
+    }
+
+    fn spring(&mut self, a: usize, b: usize, l0: Real) {
+        let spring = Spring::new(self.handle(a), self.handle(b), l0);
+        self.springs.push(spring);
+    }
+
+    pub fn update(&mut self) -> Result<(), Instability> {
+        span!("update");
+        self.events.clear();
+
+        let max_steps = (Self::MAX_STEPS as Real * self.speed.max(1.0)).ceil() as usize;
+        let mut steps = 0;
+        let mut dropped = 0.0;
+        while self.dt_acc >= Self::DT {
+            if steps == max_steps {
+                dropped = self.dt_acc - self.dt_acc % Self::DT;
+                self.dt_acc %= Self::DT;
+                break;
+            }
+
+            // Snapshot before the last step, the one the next frame interpolates across
+            if self.dt_acc < 2.0 * Self::DT || steps + 1 == max_steps {
+                self.prev_pos.clear();
+                self.prev_pos.extend(self.particles.iter().map(|p| p.pos));
+            }
+
+            if let Err(err) = self.step_once() {
+                self.discard_forces();
+                return Err(err);
+            }
+            self.dt_acc -= Self::DT;
+            steps += 1;
+        }
+
+        if steps > 0 {
+            self.objects.iter_mut().for_each(|obj| obj.torque = 0.0);
+        }
+
+        let simulated = steps as Real * Self::DT;
+        self.pace = if dropped > 0.0 {
+            simulated / (simulated + dropped)
+        } else {
+            1.0
+        };
+
+        if self.metrics.is_some() {
+            span!("metrics");
+            self.metrics = Some(self.measure(steps));
+        }
+
+        Ok(())
+    }
+
+    /// Metrics of the world as it is now, after `steps` steps.
+    fn measure(&mut self, steps: usize) -> Metrics {
+        let kinetic_energy = self
+            .particles
+            .iter()
+            .map(|p| 0.5 * p.mass * p.vel.len_sqr())
+            .sum();
+
+        let scales: Vec<Real> = self.muscles.iter().map(|m| m.scale(self.time())).collect();
+        let (mut error, mut springs) = (0.0, 0);
+        for spring in self.springs.iter().chain(&self.links) {
+            let rest = spring.rest_length(&scales);
+            if rest > 0.0 {
+                let len = self.particles[self.index(spring.a)]
+                    .pos
+                    .dist(self.particles[self.index(spring.b)].pos);
+                error += (len - rest).abs() / rest;
+                springs += 1;
+            }
+        }
+
+        // Binned again, particles moved and came and went since the last step was
+        self.bin_particles();
+        let mut max_penetration: Real = 0.0;
+        for (i, &cell) in self.cells.iter().enumerate() {
+            for j in self.grid.partners(&self.buckets, i, cell) {
+                let dist = self.particles[i].pos.dist(self.particles[j].pos);
+                max_penetration = max_penetration.max(2.0 * Particle::R - dist);
+            }
+        }
+        let borders: &[Edge] = match self.settings.bounds {
+            WorldBounds::Closed => &self.borders,
+            WorldBounds::Open | WorldBounds::Wrap => &[],
+        };
+        // Particles go through one way edges from behind
+        for edge in self.edges.iter().chain(borders).filter(|e| !e.one_way) {
+            for &i in &self.boundaries {
+                let (distance, _) = edge.distance(self.particles[self.index(i)].pos);
+                max_penetration = max_penetration.max(Particle::R - distance);
+            }
+        }
+
+        Metrics {
+            kinetic_energy,
+            spring_error: if springs > 0 {
+                error / springs as Real
+            } else {
+                0.0
+            },
+            max_penetration,
+            steps,
+        }
+    }
+
+    /// Forgets the forces of a step given up on halfway, they'd add up with the next one's.
+    fn discard_forces(&mut self) {
+        self.particles.iter_mut().for_each(|p| p.acc = Vec2::null());
+    }
+
+    /// Advances exactly `n` physics steps no matter how much time has been accumulated,
+    /// for benchmarks and other headless runs. Deterministic: the same world stepped the
+    /// same number of times ends up bit for bit the same, see [`World::state_hash`].
+    pub fn step_n(&mut self, n: usize) -> Result<(), Instability> {
+        self.events.clear();
+
+        // The GPU sums collision responses in whatever order its threads get to them
+        #[cfg(feature = "wgpu")]
+        let gpu = self.gpu.take();
+
+        let result = (0..n).try_for_each(|_| self.step_once());
+
+        #[cfg(feature = "wgpu")]
+        {
+            self.gpu = gpu;
+        }
+        if result.is_err() {
+            self.discard_forces();
+        }
+        result?;
+
+        self.objects.iter_mut().for_each(|obj| obj.torque = 0.0);
+
+        Ok(())
+    }
+
+    /// FNV-1a over the exact bits of the simulated state, stable across runs, versions and
+    /// platforms. Two worlds with the same hash move the same from then on.
+    pub fn state_hash(&self) -> u64 {
+        #[cfg(not(feature = "f32"))]
+        fn bits(x: Real) -> u64 {
+            x.to_bits()
+        }
+        #[cfg(feature = "f32")]
+        fn bits(x: Real) -> u64 {
+            u64::from(x.to_bits())
+        }
+
+        const OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+        const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+        let particles = self.particles.iter().flat_map(|p| {
+            [p.pos.x, p.pos.y, p.vel.x, p.vel.y, p.mass, p.temperature]
+                .map(bits)
+                .into_iter()
+                .chain([
+                    u64::from(p.pinned),
+                    p.kind as u64,
+                    p.expires.unwrap_or(u64::MAX),
+                ])
+        });
+        // By index, ids depend on what came and went before
+        let springs = self.springs.iter().flat_map(|s| {
+            [
+                self.index(s.a) as u64,
+                self.index(s.b) as u64,
+                bits(s.l0),
+                s.muscle.map_or(u64::MAX, u64::from),
+            ]
+        });
+        let rods = self
+            .rods
+            .iter()
+            .flat_map(|r| [self.index(r.a) as u64, self.index(r.b) as u64, bits(r.len)]);
+        let muscles = self
+            .muscles
+            .iter()
+            .flat_map(|m| [m.amplitude, m.frequency, m.phase].map(bits));
+        let edges = self.edges.iter().flat_map(|e| {
+            [
+                e.start.x,
+                e.start.y,
+                e.line.x,
+                e.line.y,
+                e.radius,
+                e.strength,
+                e.damage,
+                e.temperature,
+            ]
+            .map(bits)
+            .into_iter()
+            .chain([
+                u64::from(e.one_way),
+                e.corners as u64,
+                u64::from(e.looping),
+                bits(e.clock),
+            ])
+            .chain(
+                e.keyframes
+                    .iter()
+                    .flat_map(|k| [k.time, k.start.x, k.start.y, k.end.x, k.end.y].map(bits)),
+            )
+        });
+        let objects = self.objects.iter().flat_map(|obj| {
+            [obj.motor, obj.torque]
+                .map(bits)
+                .into_iter()
+                .chain([obj.emitter.map_or(u64::MAX, |k| k as u64)])
+        });
+        let drains = self
+            .drains
+            .iter()
+            .flat_map(|d| [d.min.x, d.min.y, d.max.x, d.max.y].map(bits));
+        let gravity_zones = self
+            .gravity_zones
+            .iter()
+            .flat_map(|z| [z.min.x, z.min.y, z.max.x, z.max.y, z.gravity.x, z.gravity.y].map(bits));
+        let movers = self.movers.iter().flat_map(|m| {
+            let shape = match m.shape {
+                MoverShape::Disc(radius) => [0.0, radius, 0.0],
+                MoverShape::Bar(half) => [1.0, half.x, half.y],
+            };
+            [m.speed, m.travelled]
+                .into_iter()
+                .chain(shape)
+                .chain(m.track.iter().flat_map(|v| [v.x, v.y]))
+                .map(bits)
+                .chain([u64::from(m.closed)])
+        });
+        let emitters = self.emitters.iter().flat_map(|e| {
+            [
+                e.pos.x, e.pos.y, e.vel.x, e.vel.y, e.rate, e.lifetime, e.due,
+            ]
+            .map(bits)
+            .into_iter()
+            .chain([
+                e.max_alive as u64,
+                Emission::ALL
+                    .iter()
+                    .position(|&m| m == e.emission)
+                    .unwrap_or(0) as u64,
+            ])
+        });
+        let anchors = self
+            .anchors
+            .iter()
+            .flat_map(|a| [a.particle as u64, a.edge as u64, bits(a.t)]);
+
+        particles
+            .chain(springs)
+            .chain(rods)
+            .chain(muscles)
+            .chain(edges)
+            .chain(objects)
+            .chain(anchors)
+            .chain(emitters)
+            .chain(drains)
+            .chain(gravity_zones)
+            .chain(movers)
+            .chain([self.throttle, self.dt_acc].map(bits))
+            .flat_map(u64::to_le_bytes)
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+            })
+    }
+
+    // Every phase walks particles, springs, objects and edges in index order, and buckets
+    // are filled in particle order, so a step depends on nothing but the world itself
+    fn step_once(&mut self) -> Result<(), Instability> {
+        span!("step");
+
+        {
+            span!("quarantine");
+            // Edits since the last step can bring them in too, and the broadphase files a
+            // NaN under the first cell, among particles of other objects
+            self.quarantine();
+        }
+
+        {
+            span!("broadphase");
+            // Up front, an unstable step bails out halfway through
+            self.bin_particles();
+
+            // Objects whose reach doesn't overlap can't have colliding particles
+            let pad = Particle::R + Self::BROADPHASE_MARGIN;
+            self.owners.clear();
+            self.reach.clear();
+            for (n, obj) in self.objects.iter().enumerate() {
+                self.owners
+                    .extend(std::iter::repeat_n(n, obj.particles_len()));
+                self.reach.push((obj.state.min - pad, obj.state.max + pad));
+
+                let material = obj.material.unwrap_or(self.settings.material());
+                for particle in &mut self.particles[obj.particles_range()] {
+                    particle.friction = material.friction;
+                    particle.contact = material.contact;
+                    particle.frozen = obj.frozen;
+                }
+            }
+        }
+
+        {
+            span!("keyframes");
+            self.animate_edges();
+        }
+
+        {
+            span!("movers");
+            // Where they end the step, like the particles they're about to meet
+            for mover in &mut self.movers {
+                mover.advance(Self::DT);
+            }
+        }
+
+        let scales: Vec<Real> = self.muscles.iter().map(|m| m.scale(self.time())).collect();
+        {
+            span!("springs");
+            for obj in &self.objects {
+                let material = obj.material.unwrap_or(self.settings.material());
+                Self::update_springs(
+                    &self.springs[obj.springs_range()],
+                    &self.particle_ids,
+                    &scales,
+                    &mut self.particles,
+                    &material,
+                    &self.settings,
+                    &mut self.events,
+                )?;
+            }
+
+            let material = self.settings.material();
+            Self::update_springs(
+                &self.links,
+                &self.particle_ids,
+                &scales,
+                &mut self.particles,
+                &material,
+                &self.settings,
+                &mut self.events,
+            )?;
+        }
+
+        {
+            span!("torques");
+            let target = self.throttle * Self::MOTOR_SPIN;
+            for obj in &self.objects {
+                let mut torque = obj.torque;
+                if obj.motor != 0.0 && self.throttle != 0.0 {
+                    if let Some((_, _, spin)) = Self::spin(obj, &self.particles) {
+                        // Full torque from a standstill, fading out as the target speed is reached
+                        torque += obj.motor * ((target - spin) / Self::MOTOR_SPIN).clamp(-1.0, 1.0);
+                    }
+                }
+
+                if torque != 0.0 {
+                    Self::turn(obj, torque, &mut self.particles);
+                }
+            }
+        }
+
+        if let Some((n, target)) = self.drag {
+            let particle = &mut self.particles[self.particle_ids.at(n)];
+            particle.acc += ((target - particle.pos) * Self::DRAG_KS
+                - particle.vel * Self::DRAG_KD)
+                / particle.mass;
+        }
+
+        {
+            span!("anchors");
+            self.pull_anchors();
+        }
+
+        {
+            span!("forces");
+            let mut view = ParticleView {
+                particles: &mut self.particles,
+            };
+            if self.gravity_zones.is_empty() {
+                Gravity(self.settings.gravity).apply(&mut view, Self::DT);
+            } else {
+                for n in 0..view.len() {
+                    let gravity =
+                        Self::zone_gravity(&self.gravity_zones, view.pos(n), self.settings.gravity);
+                    view.accelerate(n, gravity);
+                }
+            }
+            for force in &self.forces {
+                force
+                    .lock()
+                    .expect("force generator lock should not be poisoned")
+                    .apply(&mut view, Self::DT);
+            }
+        }
+
+        {
+            span!("collisions");
+            let grid = self.grid;
+            #[cfg(feature = "wgpu")]
+            let on_gpu = self.collide_on_gpu();
+            #[cfg(not(feature = "wgpu"))]
+            let on_gpu = false;
+
+            for i in 0..self.particles.len() {
+                let mut particle = self.particles[i].clone();
+
+                // Resolved for all particles at once already
+                if !on_gpu {
+                    // The cell the particle was filed under, collisions may have pushed it
+                    // out since
+                    for j in grid.partners(&self.buckets, i, self.cells[i]) {
+                        let (a, b) = (self.owners[i], self.owners[j]);
+                        if a != b && !overlap(self.reach[a], self.reach[b]) {
+                            continue;
+                        }
+
+                        let speed = particle.collide(&mut self.particles[j]);
+                        if speed > Self::IMPACT_SPEED {
+                            self.events.push(WorldEvent::Impact {
+                                pos: particle.pos,
+                                speed,
+                            });
+                        }
+                    }
+                }
+
+                particle.integrate(Self::DT);
+
+                self.particles[i] = particle;
+            }
+        }
+
+        {
+            span!("quarantine");
+            // Before rods, edges and sticking pass them on to whatever they touch
+            self.quarantine();
+        }
+
+        if self.settings.relaxation_iterations > 0 {
+            span!("relaxation");
+            self.relax_springs(self.settings.relaxation_iterations, &scales);
+        }
+
+        if !self.rods.is_empty() {
+            span!("rods");
+            for _ in 0..Self::ROD_ITERATIONS {
+                for rod in &self.rods {
+                    let (a, b) = (self.particle_ids.at(rod.a), self.particle_ids.at(rod.b));
+                    Self::project(&mut self.particles, a, b, rod.len);
+                }
+            }
+        }
+
+        {
+            span!("edges");
+            let borders: &[Edge] = match self.settings.bounds {
+                WorldBounds::Closed => &self.borders,
+                WorldBounds::Open | WorldBounds::Wrap => &[],
+            };
+            let pad = Particle::R + Self::BROADPHASE_MARGIN;
+            let chains = self.chains.get_or_insert_with(|| Chain::find(&self.edges));
+            let mut hits = vec![];
+            for chain in chains.iter() {
+                let bounds = chain.bounds(&self.edges);
+                for obj in &self.objects {
+                    if !overlap((obj.state.min - pad, obj.state.max + pad), bounds) {
+                        continue;
+                    }
+
+                    for &i in &self.boundaries[obj.boundaries_range()] {
+                        let particle = &mut self.particles[self.particle_ids.at(i)];
+                        let (n, speed) = chain.collide(&self.edges, particle);
+                        if speed > Self::IMPACT_SPEED {
+                            self.events.push(WorldEvent::Impact {
+                                pos: particle.pos,
+                                speed,
+                            });
+                            hits.push((n, speed * particle.mass));
+                        }
+                    }
+                }
+            }
+
+            let colliders = borders.iter().map(|e| e as &dyn Collider);
+            let colliders = colliders
+                .chain(self.movers.iter().map(|m| m as &dyn Collider))
+                .chain(self.colliders.iter().map(AsRef::as_ref));
+            for collider in colliders {
+                let bounds = collider.bounds();
+                for obj in &self.objects {
+                    if !overlap((obj.state.min - pad, obj.state.max + pad), bounds) {
+                        continue;
+                    }
+
+                    for &i in &self.boundaries[obj.boundaries_range()] {
+                        let particle = &mut self.particles[self.particle_ids.at(i)];
+                        let speed = collider.collide(particle);
+                        if speed > Self::IMPACT_SPEED {
+                            self.events.push(WorldEvent::Impact {
+                                pos: particle.pos,
+                                speed,
+                            });
+                        }
+                    }
+                }
+            }
+            self.damage_edges(hits);
+        }
+
+        {
+            span!("sticking");
+            self.stick();
+        }
+
+        if self.settings.heat {
+            span!("heat");
+            self.conduct_heat();
+        }
+
+        {
+            span!("despawn");
+            self.despawn();
+        }
+
+        {
+            span!("emitters");
+            self.emit();
+        }
+
+        {
+            span!("sanitize");
+            self.sanitize();
+        }
+
+        {
+            span!("measure");
+            self.measure_objects();
+        }
+
+        // Every step rather than every update, so how far objects get past the edge doesn't
+        // depend on how many steps a frame takes
+        if self.settings.bounds == WorldBounds::Wrap {
+            span!("wrap");
+            self.wrap_objects();
+        }
+
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Slows particles down to the speed limit, if any, and quarantines what went NaN or
+    /// infinite since the last check.
+    fn sanitize(&mut self) {
+        if self.settings.limit_speed {
+            let max = self.settings.max_speed;
+            for particle in &mut self.particles {
+                particle.vel = particle.vel.clamp_len(0.0, max);
+            }
+        }
+        self.quarantine();
+    }
+
+    /// Quarantines objects with a particle that went NaN or infinite before it spreads
+    /// through springs and contacts or ends up filed under the wrong cell by the
+    /// broadphase. Their bad particles are put back at where the center of mass was and
+    /// all of them are pinned. Objects quarantined before are checked all the same, they
+    /// can be unpinned or pulled along by links, and go again with another event.
+    fn quarantine(&mut self) {
+        for (n, obj) in self.objects.iter_mut().enumerate() {
+            let particles = &mut self.particles[obj.particles_range()];
+            if particles
+                .iter()
+                .all(|p| p.pos.is_finite() && p.vel.is_finite())
+            {
+                continue;
+            }
+
+            let center = Some(obj.state.center_of_mass)
+                .filter(|c| c.is_finite())
+                .unwrap_or_default();
+            for particle in particles {
+                if !particle.pos.is_finite() {
+                    particle.pos = center;
+                }
+                particle.vel = Vec2::null();
+                particle.acc = Vec2::null();
+                particle.pinned = true;
+            }
+            obj.quarantined = true;
+            self.events.push(WorldEvent::Quarantined { object: n });
+        }
+    }
+
+    /// Moves the ends of every spring to its rest length, `iterations` times over, starting
+    /// from where the spring forces left them. Takes out the stretch the forces alone leave
+    /// in stacked bodies. The ends stop moving apart or together too, or they'd carry on
+    /// right back. Like in collisions, pinned particles stay put and otherwise the lighter
+    /// end moves further.
+    fn relax_springs(&mut self, iterations: usize, scales: &[Real]) {
+        for _ in 0..iterations {
+            for spring in self.springs.iter().chain(&self.links) {
+                Self::project(
+                    &mut self.particles,
+                    self.particle_ids.at(spring.a),
+                    self.particle_ids.at(spring.b),
+                    spring.rest_length(scales),
+                );
+            }
+        }
+    }
+
+    /// Moves particles `a` and `b` to `len` apart and stops them moving apart or together.
+    fn project(particles: &mut [Particle], a: usize, b: usize, len: Real) {
+        let (p1, p2) = (&particles[a], &particles[b]);
+        let (w1, w2) = match (p1.is_fixed(), p2.is_fixed()) {
+            (true, true) => return,
+            (true, false) => (0.0, 1.0),
+            (false, true) => (1.0, 0.0),
+            (false, false) => {
+                let w1 = p2.mass / (p1.mass + p2.mass);
+                (w1, 1.0 - w1)
+            }
+        };
+
+        let diff = p2.pos - p1.pos;
+        let diff_len = diff.len();
+        if diff_len == 0.0 {
+            return;
+        }
+        let diff_norm = diff / diff_len;
+        let offset = (diff_len - len) * diff_norm;
+        let vel_offset = (p2.vel - p1.vel).dot(diff_norm) * diff_norm;
+        particles[a].pos += w1 * offset;
+        particles[a].vel += w1 * vel_offset;
+        particles[b].pos -= w2 * offset;
+        particles[b].vel -= w2 * vel_offset;
+    }
+
+    /// Adds up the damage of `hits` to breakable edges, removing the ones it breaks.
+    fn damage_edges(&mut self, hits: Vec<(usize, Real)>) {
+        let mut broken = vec![];
+        for (n, damage) in hits {
+            let edge = &mut self.edges[n];
+            if edge.strength > 0.0 {
+                edge.damage += damage;
+                if edge.damage >= edge.strength {
+                    broken.push(n);
+                }
+            }
+        }
+        broken.sort_unstable();
+        broken.dedup();
+
+        // Back to front, so the indices still to go stay valid
+        for &n in broken.iter().rev() {
+            let edge = &self.edges[n];
+            let pos = edge.start + 0.5 * edge.line;
+            self.delete_edge(n);
+            self.events.push(WorldEvent::EdgeBroken { pos });
+        }
+    }
+
+    /// Removes particles whose lifetime is up or that went down a drain. Loose ones go on
+    /// their own, a body goes whole once one of its particles expires or its center is
+    /// drained.
+    fn despawn(&mut self) {
+        let steps = self.steps;
+        let expired = |p: &Particle| p.expires.is_some_and(|step| step <= steps);
+        if self.drains.is_empty() && !self.particles.iter().any(expired) {
+            return;
+        }
+
+        let drained = |pos: Vec2| self.drains.iter().any(|d| d.contains(pos));
+        let mut dead: Vec<bool> = self.particles.iter().map(expired).collect();
+        for obj in &self.objects {
+            let range = obj.particles_range();
+            if Self::is_loose(obj) {
+                for i in range {
+                    dead[i] |= drained(self.particles[i].pos);
+                }
+            } else if dead[range.clone()].contains(&true) || drained(obj.state.center_of_mass) {
+                dead[range].fill(true);
+            }
+        }
+
+        if dead.contains(&true) {
+            // Back to front, the index of each is as of after the ones before it
+            for n in self.remove_particles(&dead).into_iter().rev() {
+                self.events.push(WorldEvent::Despawned { object: n });
+            }
+        }
+    }
+
+    /// Removes the particles marked `dead` along with their springs, links, rods and anchors,
+    /// closing the gaps they leave in one pass over everything. Objects left without
+    /// particles are removed too, returns their indices from before.
+    // Without counting as an edit, particles also despawn while simulating
+    fn remove_particles(&mut self, dead: &[bool]) -> Vec<usize> {
+        // Where every particle moves to, and one past the end for the end of ranges
+        let mut index = Vec::with_capacity(dead.len() + 1);
+        let mut live = 0;
+        for &d in dead {
+            index.push(live);
+            live += usize::from(!d);
+        }
+        index.push(live);
+        // Springs, boundaries, links, rods and the drag hold on by id, they only go
+        let ids = &self.particle_ids;
+        let alive = |id: Id| !dead[ids.at(id)];
+
+        let (mut springs, mut boundaries) = (vec![], vec![]);
+        let mut dead_springs = vec![true; self.springs.len()];
+        let mut removed = vec![];
+        for (n, mut obj) in std::mem::take(&mut self.objects).into_iter().enumerate() {
+            let (s_start, b_start) = (springs.len(), boundaries.len());
+            for i in obj.springs_range() {
+                let s = self.springs[i];
+                if alive(s.a) && alive(s.b) {
+                    dead_springs[i] = false;
+                    springs.push(s);
+                }
+            }
+            boundaries.extend(
+                self.boundaries[obj.boundaries_range()]
+                    .iter()
+                    .filter(|&&i| alive(i)),
+            );
+
+            (obj.particle_start, obj.particle_end) =
+                (index[obj.particle_start], index[obj.particle_end]);
+            (obj.spring_start, obj.spring_end) = (s_start, springs.len());
+            (obj.boundary_start, obj.boundary_end) = (b_start, boundaries.len());
+            if obj.particles_len() == 0 {
+                removed.push(n);
+            } else {
+                self.objects.push(obj);
+            }
+        }
+        let mut dead_objects = vec![false; self.objects.len() + removed.len()];
+        for &n in &removed {
+            dead_objects[n] = true;
+        }
+        self.object_ids.retain_alive(&dead_objects);
+        self.spring_ids.retain_alive(&dead_springs);
+        self.springs = springs;
+        self.boundaries = boundaries;
+
+        self.links.retain(|l| alive(l.a) && alive(l.b));
+        self.rods.retain(|r| alive(r.a) && alive(r.b));
+        self.drag = self.drag.filter(|&(i, _)| alive(i));
+        self.anchors.retain(|a| !dead[a.particle]);
+        for anchor in &mut self.anchors {
+            anchor.particle = index[anchor.particle];
+        }
+
+        self.particle_ids.retain_alive(dead);
+        retain_alive(&mut self.particles, dead);
+        retain_alive(&mut self.prev_pos, dead);
+        retain_alive(&mut self.trails, dead);
+
+        removed
+    }
+
+    /// Sends out what emitters owe by now, as far as their caps and the room in front of
+    /// them allow.
+    fn emit(&mut self) {
+        let mut alive = vec![0; self.emitters.len()];
+        for k in self.objects.iter().filter_map(|obj| obj.emitter) {
+            alive[k] += 1;
+        }
+
+        for (k, alive) in alive.into_iter().enumerate() {
+            let mut emitter = self.emitters[k];
+            emitter.due += emitter.rate * Self::DT;
+
+            // Bodies spawned on top of the last one would fly apart
+            let radius = emitter.emission.radius();
+            let free = self
+                .particles
+                .iter()
+                .all(|p| p.pos.dist_sqr(emitter.pos) > SQR!(radius + Particle::R));
+            if emitter.due >= 1.0 && alive < emitter.max_alive && free {
+                emitter.due -= 1.0;
+
+                let obj = match emitter.emission {
+                    Emission::Particle => {
+                        let i = self.add_particle(emitter.pos);
+                        self.bound([i]);
+                        ObjectDescriptor {
+                            open: true,
+                            ..ObjectDescriptor::new(
+                                i,
+                                i + 1,
+                                self.springs.len(),
+                                self.springs.len(),
+                                self.boundaries.len() - 1,
+                                self.boundaries.len(),
+                            )
+                        }
+                    }
+                    Emission::Body(shape) => self.shape_object(shape, emitter.pos, radius),
+                };
+                let expires = (emitter.lifetime > 0.0).then(|| self.step_after(emitter.lifetime));
+                for particle in &mut self.particles[obj.particles_range()] {
+                    particle.vel = emitter.vel;
+                    particle.expires = expires;
+                }
+
+                self.add_object(ObjectDescriptor {
+                    emitter: Some(k),
+                    ..obj
+                });
+            }
+
+            // Nothing piles up while it waits, it sends out one at a time when it can again
+            emitter.due = emitter.due.min(1.0);
+            self.emitters[k] = emitter;
+        }
+    }
+
+    /// Puts animated edges where their keyframes have them this step and moves their
+    /// clocks on. Not an edit, they move by themselves.
+    fn animate_edges(&mut self) {
+        for edge in self.edges.iter_mut().filter(|e| !e.keyframes.is_empty()) {
+            // They may come apart from the edges they're joined to, or join others
+            self.chains = None;
+            if let Some([start, end, start_vel, end_vel]) = edge.pose_at(edge.clock) {
+                edge.set_start(start);
+                edge.set_end(end);
+                edge.motion = Some((start_vel, end_vel));
+            }
+            edge.clock += Self::DT;
+        }
+    }
+
+    /// Pulls stuck particles back towards where they touched their edge, letting go of
+    /// those pulled harder than the edge's stickiness.
+    fn pull_anchors(&mut self) {
+        let (particles, edges) = (&mut self.particles, &self.edges);
+        self.anchors.retain(|anchor| {
+            let edge = &edges[anchor.edge];
+            let particle = &mut particles[anchor.particle];
+
+            let diff = edge.start + anchor.t * edge.line - particle.pos;
+            let len = diff.len();
+            let stretch = len - (edge.radius + Particle::R);
+            if stretch <= 0.0 {
+                return true;
+            }
+            if Self::ANCHOR_KS * stretch > edge.stickiness {
+                return false;
+            }
+
+            let dir = diff / len;
+            let force = Self::ANCHOR_KS * stretch - Self::ANCHOR_KD * particle.vel.dot(dir);
+            particle.acc += force.max(0.0) * dir / particle.mass;
+            true
+        });
+    }
+
+    /// Anchors boundary particles that touch a sticky edge and aren't stuck yet.
+    fn stick(&mut self) {
+        if self.edges.iter().all(|e| e.stickiness <= 0.0) {
+            return;
+        }
+
+        let mut stuck = vec![false; self.particles.len()];
+        for anchor in &self.anchors {
+            stuck[anchor.particle] = true;
+        }
+
+        let pad = Particle::R + Self::BROADPHASE_MARGIN;
+        for (n, edge) in self.edges.iter().enumerate() {
+            if edge.stickiness <= 0.0 {
+                continue;
+            }
+
+            let bounds = edge.bounds();
+            for obj in &self.objects {
+                if !overlap((obj.state.min - pad, obj.state.max + pad), bounds) {
+                    continue;
+                }
+
+                for &id in &self.boundaries[obj.boundaries_range()] {
+                    let i = self.particle_ids.at(id);
+                    let particle = &self.particles[i];
+                    if stuck[i] || particle.is_fixed() || !edge.touches(particle.pos) {
+                        continue;
+                    }
+
+                    let t = edge.line.dot(particle.pos - edge.start) / edge.len_sqr;
+                    self.anchors.push(Anchor {
+                        particle: i,
+                        edge: n,
+                        t: t.clamp(0.0, 1.0),
+                    });
+                    stuck[i] = true;
+                }
+            }
+        }
+    }
+
+    /// Warms or cools particles touching edges that aren't at the temperature of the
+    /// surroundings, evens out temperatures along springs, then lets everything cool.
+    fn conduct_heat(&mut self) {
+        let pad = Particle::R + Self::BROADPHASE_MARGIN;
+        for edge in self.edges.iter().filter(|e| e.temperature != 0.0) {
+            let bounds = edge.bounds();
+            for obj in &self.objects {
+                if !overlap((obj.state.min - pad, obj.state.max + pad), bounds) {
+                    continue;
+                }
+
+                for &i in &self.boundaries[obj.boundaries_range()] {
+                    let particle = &mut self.particles[self.particle_ids.at(i)];
+                    if edge.touches(particle.pos) {
+                        particle.temperature += (edge.temperature - particle.temperature)
+                            * Self::EDGE_CONDUCTION
+                            * Self::DT;
+                    }
+                }
+            }
+        }
+
+        for spring in self.springs.iter().chain(&self.links) {
+            let (a, b) = (
+                self.particle_ids.at(spring.a),
+                self.particle_ids.at(spring.b),
+            );
+            let flow = (self.particles[b].temperature - self.particles[a].temperature)
+                * Self::CONDUCTION
+                * Self::DT;
+            self.particles[a].temperature += flow;
+            self.particles[b].temperature -= flow;
+        }
+
+        for particle in &mut self.particles {
+            particle.temperature -= particle.temperature * Self::COOLING * Self::DT;
+        }
+    }
+
+    /// What springs keep of their stiffness with their ends at `a` and `b` degrees.
+    fn softening(a: Real, b: Real, melting_point: Real) -> Real {
+        let temperature = 0.5 * (a + b);
+        if temperature <= melting_point {
+            return 1.0;
+        }
+        (1.0 - (temperature - melting_point) / Self::MELTING_RANGE).max(Self::MOLTEN)
+    }
+
+    /// Center of mass, moment of inertia and angular velocity of `obj`. `None` for an
+    /// object without any extent.
+    fn spin(obj: &ObjectDescriptor, particles: &[Particle]) -> Option<(Vec2, Real, Real)> {
+        let particles = &particles[obj.particles_range()];
+        let mass: Real = particles.iter().map(|p| p.mass).sum();
+        let center = particles
+            .iter()
+            .fold(Vec2::null(), |acc, p| acc + p.pos * p.mass)
+            / mass;
+
+        let (mut inertia, mut momentum) = (0.0, 0.0);
+        for p in particles {
+            let arm = p.pos - center;
+            inertia += p.mass * arm.len_sqr();
+            momentum += p.mass * arm.cross(p.vel);
+        }
+
+        (inertia > 0.0).then(|| (center, inertia, momentum / inertia))
+    }
+
+    /// Spreads `torque` over the particles of `obj` as forces perpendicular to the way to
+    /// its center of mass, growing with the distance like in a spinning rigid body. They
+    /// add up to no net force, so the object is turned without being pushed anywhere.
+    fn turn(obj: &ObjectDescriptor, torque: Real, particles: &mut [Particle]) {
+        let Some((center, inertia, _)) = Self::spin(obj, particles) else {
+            return;
+        };
+
+        for particle in &mut particles[obj.particles_range()] {
+            let arm = particle.pos - center;
+            particle.acc += arm.perp() * torque / inertia;
+        }
+    }
+
+    /// Moves every object whose center left the screen to the opposite side. Whole
+    /// bodies are shifted so their springs never straddle the seam.
+    fn wrap_objects(&mut self) {
+        for obj in &mut self.objects {
+            let particles = &mut self.particles[obj.particles_range()];
+            if particles.is_empty() {
+                continue;
+            }
+
+            let center =
+                particles.iter().fold(Vec2::null(), |acc, p| acc + p.pos) / particles.len() as Real;
+            let wrap = |c: Real, size: Real| {
+                if c < 0.0 {
+                    size
+                } else if c > size {
+                    -size
+                } else {
+                    0.0
+                }
+            };
+            let shift = Vec2::new(wrap(center.x, self.size.x), wrap(center.y, self.size.y));
+            if shift == Vec2::null() {
+                continue;
+            }
+
+            for (i, particle) in obj.particles_range().zip(particles) {
+                particle.pos += shift;
+                if let Some(prev) = self.prev_pos.get_mut(i) {
+                    *prev += shift;
+                }
+                if let Some(trail) = self.trails.get_mut(i) {
+                    trail.clear();
+                }
+            }
+            obj.state.center_of_mass += shift;
+            obj.state.min += shift;
+            obj.state.max += shift;
+        }
+    }
+
+    /// Measured after the last update, if tracked.
+    pub fn metrics(&self) -> Option<Metrics> {
+        self.metrics
+    }
+
+    /// Has every update measure the [`Metrics`] of the world, which takes about as long
+    /// as a step. Not saved.
+    pub fn set_track_metrics(&mut self, track: bool) {
+        if track != self.metrics.is_some() {
+            self.metrics = track.then(Metrics::default);
+        }
+    }
+
+    /// Share of the time handed to the last [`World::update`] that was actually simulated.
+    pub fn pace(&self) -> Real {
+        self.pace
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    /// Puts particles where another copy of the world has them, one position per particle
+    /// in order. Not an edit, like simulating isn't, and drawn without interpolation.
+    pub fn set_positions(&mut self, positions: impl IntoIterator<Item = Vec2>) {
+        for (particle, pos) in self.particles.iter_mut().zip(positions) {
+            particle.pos = pos;
+        }
+        self.prev_pos.clear();
+        self.measure_objects();
+    }
+
+    /// Seconds simulated since the world was created.
+    pub fn time(&self) -> Real {
+        self.steps as Real * Self::DT
+    }
+
+    /// The step `seconds` of simulation from now end on.
+    fn step_after(&self, seconds: Real) -> u64 {
+        self.steps + (seconds.max(0.0) / Self::DT).round() as u64
+    }
+
+    pub fn events(&self) -> &[WorldEvent] {
+        &self.events
+    }
+
+    /// Takes over the quarantines of `older`, an update of this world nobody looked at,
+    /// so they still get reported. The impacts and the like it had are stale by now.
+    pub fn keep_quarantines(&mut self, older: &World) {
+        let quarantines = older
+            .events
+            .iter()
+            .filter(|e| matches!(e, WorldEvent::Quarantined { .. }));
+        self.events.splice(0..0, quarantines.copied());
+    }
+
+    /// Hands over `dt` more seconds of real time, for the next [`World::update`] to step
+    /// through at the speed set.
+    pub fn end_frame(&mut self, dt: Real) {
+        self.dt_acc += dt * self.speed;
+    }
+
+    /// Runs the simulation `speed` times as fast as real time. Faster means more steps of
+    /// the same length per update rather than longer ones, so it moves the same at any speed.
+    pub fn set_speed(&mut self, speed: Real) {
+        self.speed = speed.max(0.0);
+    }
+
+    fn default_speed() -> Real {
+        1.0
+    }
+
+    /// Where to draw particle `n`: between its last two physics states, by how far the
+    /// accumulator has run into the next step.
+    fn render_pos(&self, n: usize) -> Vec2 {
+        let pos = self.particles[n].pos;
+        self.prev_pos
+            .get(n)
+            .map_or(pos, |&prev| prev.lerp(pos, self.dt_acc / Self::DT))
+    }
+
+    /// Appends the current position of every boundary particle to its trail.
+    pub fn record_trails(&mut self) {
+        self.trails.resize_with(self.particles.len(), VecDeque::new);
+
+        for &id in &self.boundaries {
+            let i = self.particle_ids.at(id);
+            let trail = &mut self.trails[i];
+            if trail.len() == Self::TRAIL_LEN {
+                trail.pop_front();
+            }
+            trail.push_back(self.particles[i].pos);
+        }
+    }
+
+    pub fn clear_trails(&mut self) {
+        self.edit();
+        self.trails.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.edit();
+        self.particles.clear();
+        self.particle_ids.clear();
+        self.springs.clear();
+        self.spring_ids.clear();
+        self.muscles.clear();
+        self.boundaries.clear();
+        self.objects.clear();
+        self.object_ids.clear();
+        self.links.clear();
+        self.rods.clear();
+        self.anchors.clear();
+        self.prev_pos.clear();
+        self.trails.clear();
+        self.drag = None;
+    }
+
+    pub fn info(&self) -> (usize, usize, usize, usize, usize) {
+        (
+            self.particles.len(),
+            self.springs.len(),
+            self.boundaries.len(),
+            self.edges.len(),
+            self.objects.len(),
+        )
+    }
+
+    /// Object, position, velocity and spring count of the particle `id`, `None` once it
+    /// was removed.
+    pub fn particle_info(&self, id: Id) -> Option<(Option<usize>, Vec2, Vec2, usize)> {
+        let n = self.particle_index(id)?;
+        let particle = &self.particles[n];
+
+        Some((
+            self.objects
+                .iter()
+                .position(|obj| obj.particles_range().contains(&n)),
+            particle.pos,
+            particle.vel,
+            self.springs
+                .iter()
+                .chain(&self.links)
+                .filter(|s| s.a == id || s.b == id)
+                .count(),
+        ))
+    }
+
+    pub fn edge_info(&self, n: usize) -> Real {
+        self.edges[n].len()
+    }
+
+    /// Particle and spring count of object `n`.
+    pub fn object_info(&self, n: usize) -> Option<(usize, usize)> {
+        let obj = self.objects.get(n)?;
+        Some((obj.particles_len(), obj.springs_len()))
+    }
+
+    pub fn draw_particles(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        let hidden = self.particles_on(|layer| layer.hidden);
+        canvas.set_color(theme.particle);
+        for n in (0..self.particles.len()).filter(|&n| hidden.get(n) != Some(&true)) {
+            canvas.aa_filled_circle(self.render_pos(n), Particle::R);
+        }
+    }
+
+    /// Particles tinted red the warmer and blue the colder they are, fully at twice the
+    /// melting point.
+    pub fn draw_temperatures(&self, canvas: &mut impl Renderer) {
+        let scale = 0.5 / self.settings.melting_point.max(1.0);
+        let hidden = self.particles_on(|layer| layer.hidden);
+        for (n, particle) in self.particles.iter().enumerate() {
+            if hidden.get(n) == Some(&true) {
+                continue;
+            }
+
+            let heat = (particle.temperature * scale).clamp(-1.0, 1.0);
+            let alpha = (255.0 * heat.abs()) as u8;
+            if alpha == 0 {
+                continue;
+            }
+
+            let color = if heat > 0.0 {
+                Color::RGBA(255, (200.0 * (1.0 - heat)) as u8, 0, alpha)
+            } else {
+                Color::RGBA(80, 160, 255, alpha)
+            };
+            canvas
+                .set_color(color)
+                .aa_filled_circle(self.render_pos(n), Particle::R);
+        }
+    }
+
+    pub fn draw_springs(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        let hidden = self.particles_on(|layer| layer.hidden);
+        let shown = |spring: &&Spring| hidden.get(self.index(spring.a)) != Some(&true);
+        let line = |spring: &Spring| {
+            let (a, b) = (self.index(spring.a), self.index(spring.b));
+            (self.render_pos(a), self.render_pos(b))
+        };
+        canvas.set_color(theme.spring);
+        for spring in self.springs.iter().chain(&self.links).filter(shown) {
+            let (a, b) = line(spring);
+            canvas.aa_line(a, b);
+        }
+        canvas.set_color(theme.warn);
+        for spring in self
+            .springs
+            .iter()
+            .filter(shown)
+            .filter(|s| s.muscle.is_some())
+        {
+            let (a, b) = line(spring);
+            canvas.aa_line(a, b);
+        }
+        for anchor in &self.anchors {
+            let edge = &self.edges[anchor.edge];
+            if hidden.get(anchor.particle) == Some(&true) || self.layer_is(edge.layer, |l| l.hidden)
+            {
+                continue;
+            }
+
+            canvas.aa_line(
+                self.render_pos(anchor.particle),
+                edge.start + anchor.t * edge.line,
+            );
+        }
+    }
+
+    fn display_color(&self, n: usize, theme: &Theme) -> Color {
+        self.objects[n]
+            .color
+            .unwrap_or(theme.objects[n % theme.objects.len()])
+    }
+
+    pub fn draw_polys(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        for (n, obj) in self.objects.iter().enumerate() {
+            if self.layer_is(obj.layer, |layer| layer.hidden) {
+                continue;
+            }
+
+            let color = self.display_color(n, theme);
+
+            if Self::is_loose(obj) {
+                canvas.set_color(color);
+                self.draw_outline(obj, Vec2::null(), canvas);
+                continue;
+            }
+
+            if !obj.open {
+                self.draw_fill(obj, color, canvas);
+            }
+
+            canvas.set_color(color);
+            self.draw_outline(obj, Vec2::null(), canvas);
+        }
+    }
+
+    fn draw_fill(&self, obj: &ObjectDescriptor, color: Color, canvas: &mut impl Renderer) {
+        let texture = match &obj.fill {
+            Fill::Outline => return,
+            Fill::Solid => None,
+            Fill::Texture(path) => self.textures.get(path).zip(obj.lattice),
+        };
+
+        let Some((texture, (w, h))) = texture else {
+            let vertices = obj
+                .boundaries_range()
+                .map(|i| self.render_pos(self.index(self.boundaries[i])));
+            canvas
+                .set_color(Color::RGBA(color.r, color.g, color.b, 70))
+                .filled_polygon(vertices);
+            return;
+        };
+
+        // One quad per lattice cell, painted with the texel under its center, so the
+        // image deforms with the body
+        let pos = |i: usize, j: usize| self.render_pos(obj.particle_start + i * h + j);
+        for i in 0..w - 1 {
+            for j in 0..h - 1 {
+                let texel = texture.sample(
+                    (i as Real + 0.5) / (w - 1) as Real,
+                    (j as Real + 0.5) / (h - 1) as Real,
+                );
+                if texel.a == 0 {
+                    continue;
+                }
+
+                canvas.set_color(texel).filled_polygon(
+                    [pos(i, j), pos(i + 1, j), pos(i + 1, j + 1), pos(i, j + 1)].into_iter(),
+                );
+            }
+        }
+    }
+
+    pub fn draw_trails(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        for (n, obj) in self.objects.iter().enumerate() {
+            if self.layer_is(obj.layer, |layer| layer.hidden) {
+                continue;
+            }
+
+            let color = self.display_color(n, theme);
+
+            for &id in &self.boundaries[obj.boundaries_range()] {
+                let i = self.index(id);
+                if let Some(trail) = self.trails.get(i) {
+                    let vertices = trail.iter().copied().chain([self.render_pos(i)]);
+                    canvas.fading_polyline(vertices, color);
+                }
+            }
+        }
+    }
+
+    pub fn draw_pins(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        let hidden = self.particles_on(|layer| layer.hidden);
+        canvas.set_color(theme.pin);
+        for n in (0..self.particles.len())
+            .filter(|&n| self.particles[n].pinned && hidden.get(n) != Some(&true))
+        {
+            canvas.aa_filled_circle(self.render_pos(n), Particle::R * 0.5);
+        }
+    }
+
+    pub fn draw_drains(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        canvas.set_color(theme.alert);
+        for drain in &self.drains {
+            canvas.rectangle(drain.min, drain.max);
+        }
+    }
+
+    pub fn draw_gravity_zones(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        canvas.set_color(theme.info);
+        for zone in &self.gravity_zones {
+            canvas.rectangle(zone.min, zone.max);
+
+            // An arrow the way things fall in it, or a dot where they float
+            let (center, extent) = (0.5 * (zone.min + zone.max), zone.max - zone.min);
+            let len = (0.5 * extent.x.min(extent.y)).min(Self::ZONE_ARROW);
+            let dir = zone.gravity.normalize();
+            if dir == Vec2::null() {
+                canvas.aa_filled_circle(center, 0.1 * len);
+                continue;
+            }
+            let (tail, tip) = (center - 0.5 * len * dir, center + 0.5 * len * dir);
+            let back = tip - 0.3 * len * dir;
+            let side = 0.2 * len * dir.perp();
+            canvas
+                .aa_line(tail, tip)
+                .aa_line(tip, back + side)
+                .aa_line(tip, back - side);
+        }
+    }
+
+    pub fn draw_movers(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        for mover in &self.movers {
+            canvas.set_color(theme.edge_end);
+            for (a, b) in mover.sections() {
+                canvas.aa_line(a, b);
+            }
+
+            canvas.set_color(theme.edge);
+            let pos = mover.pos();
+            match mover.shape {
+                MoverShape::Disc(radius) => {
+                    canvas.aa_filled_circle(pos, radius);
+                }
+                MoverShape::Bar(half) => {
+                    canvas.thick_line(pos - half, pos + half, 2.0 * Edge::R);
+                }
+            }
+        }
+    }
+
+    pub fn draw_emitters(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        for emitter in &self.emitters {
+            // A nozzle pointing the way emissions leave
+            let dir = emitter.vel.normalize();
+            canvas
+                .set_color(theme.edge)
+                .thick_line(
+                    emitter.pos,
+                    emitter.pos + dir * 2.0 * Emitter::R,
+                    Emitter::R,
+                )
+                .set_color(theme.edge_end)
+                .aa_filled_circle(emitter.pos, Emitter::R);
+        }
+    }
+
+    pub fn draw_drag(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        if let Some((n, target)) = self.drag {
+            canvas
+                .set_color(theme.drag)
+                .aa_line(self.render_pos(self.index(n)), target)
+                .aa_filled_circle(target, Particle::R * 0.5);
+        }
+    }
+
+    fn draw_outline(&self, obj: &ObjectDescriptor, offset: Vec2, canvas: &mut impl Renderer) {
+        // There's no outline through loose particles
+        if Self::is_loose(obj) {
+            for i in obj.particles_range() {
+                canvas.aa_filled_circle(self.render_pos(i) + offset, Particle::R);
+            }
+            return;
+        }
+
+        let vertices = obj
+            .boundaries_range()
+            .map(|i| self.render_pos(self.index(self.boundaries[i])) + offset);
+
+        if obj.open {
+            canvas.aa_polyline(vertices);
+        } else {
+            canvas.aa_polygon(vertices);
+        }
+    }
+
+    pub fn draw_rods(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        let hidden = self.particles_on(|layer| layer.hidden);
+        canvas.set_color(theme.edge);
+        for rod in &self.rods {
+            let (a, b) = (self.index(rod.a), self.index(rod.b));
+            if hidden.get(a) == Some(&true) || hidden.get(b) == Some(&true) {
+                continue;
+            }
+
+            let (a, b) = (self.render_pos(a), self.render_pos(b));
+            canvas
+                .thick_line(a, b, Self::ROD_WIDTH)
+                .aa_filled_circle(a, 0.5 * Self::ROD_WIDTH)
+                .aa_filled_circle(b, 0.5 * Self::ROD_WIDTH);
+        }
+    }
+
+    pub fn draw_edges(&self, theme: &Theme, canvas: &mut impl Renderer) {
+        for edge in self
+            .edges
+            .iter()
+            .filter(|e| !self.layer_is(e.layer, |l| l.hidden))
+        {
+            let body = if edge.temperature > 0.0 {
+                theme.alert
+            } else {
+                theme.edge
+            };
+            canvas
+                .set_color(body)
+                .thick_line(edge.start, edge.get_end(), edge.radius * 2.0)
+                .set_color(theme.edge_end)
+                .aa_filled_circle(edge.start, edge.radius)
+                .aa_filled_circle(edge.get_end(), edge.radius);
+
+            // The solid half lit up
+            if edge.one_way {
+                let face = edge.normal() * (edge.radius * 0.5);
+                canvas.thick_line(edge.start + face, edge.get_end() + face, edge.radius);
+            }
+
+            // A crack more every quarter of the way to breaking
+            if edge.strength > 0.0 {
+                let cracks = (4.0 * edge.damage / edge.strength) as usize;
+                let (across, along) = (edge.normal() * edge.radius, edge.line.normalize());
+                canvas.set_color(theme.background);
+                for &t in &Edge::CRACKS[..cracks.min(Edge::CRACKS.len())] {
+                    let mid = edge.start + t * edge.line;
+                    let jag = along * (0.5 * edge.radius);
+                    canvas.aa_polyline(
+                        [
+                            mid + across,
+                            mid + across / 3.0 + jag,
+                            mid - across / 3.0 - jag,
+                            mid - across,
+                        ]
+                        .into_iter(),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn remove_last(&mut self) {
+        if let Some(n) = self.objects.len().checked_sub(1) {
+            self.remove_object(n);
+        }
+    }
+
+    /// Removes object `n` and everything attached to it, the objects after it move down.
+    pub fn remove_object(&mut self, n: usize) {
+        self.remove_objects(&[n]);
+    }
+
+    /// Removes a set of objects at once, given by their indices before the removal. Indices
+    /// past the end are skipped.
+    pub fn remove_objects(&mut self, objects: &[usize]) {
+        self.edit();
+        let mut dead = vec![false; self.particles.len()];
+        for obj in objects.iter().filter_map(|&n| self.objects.get(n)) {
+            dead[obj.particles_range()].fill(true);
+        }
+        self.remove_particles(&dead);
+    }
+
+    /// Picking with the mouse, like the other `_at` methods of objects and edges it skips
+    /// the ones on layers that aren't [pickable](Layer::is_pickable).
+    pub fn object_at(&self, pos: Vec2) -> Option<usize> {
+        self.objects.iter().rposition(|obj| {
+            self.layer_is(obj.layer, Layer::is_pickable)
+                && ((!obj.open && self.outline_contains(obj, pos))
+                    || obj
+                        .particles_range()
+                        .any(|i| self.particles[i].pos.dist_sqr(pos) <= SQR!(Particle::R)))
+        })
+    }
+
+    pub fn particle_at(&self, pos: Vec2) -> Option<Id> {
+        let unpickable = self.particles_on(|layer| !layer.is_pickable());
+        self.particles
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| unpickable.get(i) != Some(&true))
+            .map(|(i, p)| (i, p.pos.dist_sqr(pos)))
+            .filter(|&(_, d)| d <= SQR!(Particle::R))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| self.handle(i))
+    }
+
+    pub fn edge_at(&self, pos: Vec2) -> Option<usize> {
+        self.edges.iter().rposition(|e| {
+            self.layer_is(e.layer, Layer::is_pickable)
+                && e.closest_point(pos).dist_sqr(pos) <= SQR!(e.radius)
+        })
+    }
+
+    /// Id of object `n`, to hold on to it while objects before it come and go.
+    pub fn object_id(&self, n: usize) -> Option<Id> {
+        self.object_ids.id(n)
+    }
+
+    /// Index the object `id` is at now, `None` once it was removed.
+    pub fn object_index(&self, id: Id) -> Option<usize> {
+        self.object_ids.index(id)
+    }
+
+    /// Id of edge `n`, see [`World::object_id`].
+    pub fn edge_id(&self, n: usize) -> Option<Id> {
+        self.edge_ids.id(n)
+    }
+
+    pub fn edge_index(&self, id: Id) -> Option<usize> {
+        self.edge_ids.index(id)
+    }
+
+    /// Id of spring `n`, see [`World::object_id`].
+    pub fn spring_id(&self, n: usize) -> Option<Id> {
+        self.spring_ids.id(n)
+    }
+
+    pub fn spring_index(&self, id: Id) -> Option<usize> {
+        self.spring_ids.index(id)
+    }
+
+    /// Id of particle `n`, see [`World::object_id`].
+    pub fn particle_id(&self, n: usize) -> Option<Id> {
+        self.particle_ids.id(n)
+    }
+
+    pub fn particle_index(&self, id: Id) -> Option<usize> {
+        self.particle_ids.index(id)
+    }
+
+    /// The edge endpoint closest to `pos`, if one is within [`World::VERTEX_SNAP`].
+    pub fn edge_vertex_near(&self, pos: Vec2) -> Option<Vec2> {
+        self.edges
+            .iter()
+            .filter(|e| self.layer_is(e.layer, Layer::is_pickable))
+            .flat_map(|e| [e.get_start(), e.get_end()])
+            .filter(|v| v.dist_sqr(pos) <= SQR!(Self::VERTEX_SNAP))
+            .min_by(|a, b| a.dist_sqr(pos).total_cmp(&b.dist_sqr(pos)))
+    }
+
+    /// Moves every edge endpoint at `from` to `to`, so chains of edges stay joined. Edges
+    /// on layers that aren't pickable stay.
+    pub fn move_edge_vertex(&mut self, from: Vec2, to: Vec2) {
+        self.edit();
+        let layers = &self.layers;
+        for edge in self
+            .edges
+            .iter_mut()
+            .filter(|e| layers.get(e.layer).is_some_and(Layer::is_pickable))
+        {
+            if edge.get_start().dist_sqr(from) <= SQR!(Self::SAME_VERTEX) {
+                edge.set_start(to);
+            }
+            if edge.get_end().dist_sqr(from) <= SQR!(Self::SAME_VERTEX) {
+                edge.set_end(to);
+            }
+        }
+    }
+
+    pub fn toggle_pin(&mut self, id: Id) {
+        self.edit();
+        if let Some(n) = self.particle_index(id) {
+            let particle = &mut self.particles[n];
+            particle.pinned = !particle.pinned;
+        }
+    }
+
+    pub fn grab(&mut self, id: Id, target: Vec2) {
+        self.edit();
+        self.drag = self.particle_index(id).map(|_| (id, target));
+    }
+
+    pub fn drag_to(&mut self, target: Vec2) {
+        if let Some((_, t)) = &mut self.drag {
+            *t = target;
+        }
+    }
+
+    pub fn release(&mut self) {
+        self.edit();
+        self.drag = None;
+    }
+
+    pub fn dragged_object(&self) -> Option<usize> {
+        let n = self.index(self.drag?.0);
+        self.objects
+            .iter()
+            .position(|obj| obj.particles_range().contains(&n))
+    }
+
+    pub fn add_velocity_to_object(&mut self, n: usize, vel: Vec2) {
+        self.edit();
+        if let Some(obj) = self.objects.get(n) {
+            for particle in &mut self.particles[obj.particles_range()] {
+                if !particle.is_fixed() {
+                    particle.vel += vel;
+                }
+            }
+        }
+    }
+
+    /// Sand and single particles, without springs holding them together.
+    fn is_loose(obj: &ObjectDescriptor) -> bool {
+        obj.springs_len() == 0
+    }
+
+    fn outline_contains(&self, obj: &ObjectDescriptor, pos: Vec2) -> bool {
+        let vertices = &self.boundaries[obj.boundaries_range()];
+        let mut inside = false;
+
+        for (&i, &j) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            let (a, b) = (
+                self.particles[self.index(i)].pos,
+                self.particles[self.index(j)].pos,
+            );
+
+            if (a.y > pos.y) != (b.y > pos.y)
+                && pos.x < a.x + (pos.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+
+        inside
+    }
+
+    /// Highlights the springs given by index.
+    pub fn draw_spring_selection(
+        &self,
+        springs: &[usize],
+        theme: &Theme,
+        canvas: &mut impl Renderer,
+    ) {
+        canvas.set_color(theme.selection);
+        for spring in springs.iter().filter_map(|&i| self.springs.get(i)) {
+            canvas.thick_line(
+                self.render_pos(self.index(spring.a)),
+                self.render_pos(self.index(spring.b)),
+                Particle::R * 0.5,
+            );
+        }
+    }
+
+    pub fn draw_selection(&self, n: usize, theme: &Theme, canvas: &mut impl Renderer) {
+        if let Some(obj) = self.objects.get(n) {
+            canvas.set_color(theme.selection);
+            self.draw_outline(obj, Vec2::null(), canvas);
+        }
+    }
+
+    /// Highlights the spring that blew up and the object it belongs to.
+    pub fn draw_instability(
+        &self,
+        instability: &Instability,
+        theme: &Theme,
+        canvas: &mut impl Renderer,
+    ) {
+        let (Some(a), Some(b)) = (
+            self.particle_index(instability.a),
+            self.particle_index(instability.b),
+        ) else {
+            return;
+        };
+
+        canvas.set_color(theme.alert);
+        if let Some(obj) = self
+            .objects
+            .iter()
+            .find(|obj| obj.particles_range().contains(&a))
+        {
+            self.draw_outline(obj, Vec2::null(), canvas);
+        }
+        canvas.thick_line(self.render_pos(a), self.render_pos(b), Particle::R);
+    }
+
+    /// Highlights a group of objects and edges, drawn shifted by `offset` to preview a move.
+    pub fn draw_group(
+        &self,
+        objects: &[usize],
+        edges: &[usize],
+        offset: Vec2,
+        theme: &Theme,
+        canvas: &mut impl Renderer,
+    ) {
+        canvas.set_color(theme.selection);
+        for obj in objects.iter().filter_map(|&n| self.objects.get(n)) {
+            self.draw_outline(obj, offset, canvas);
+        }
+        for edge in edges.iter().filter_map(|&n| self.edges.get(n)) {
+            canvas.thick_line(
+                edge.start + offset,
+                edge.get_end() + offset,
+                edge.radius * 2.0,
+            );
+        }
+    }
+
+    /// Objects with every particle, and edges with both ends, inside the rectangle
+    /// spanned by `a` and `b`.
+    pub fn group_in(&self, a: Vec2, b: Vec2) -> (Vec<usize>, Vec<usize>) {
+        let (min, max) = (a.min(b), a.max(b));
+        let inside = |p: Vec2| p.clamp(min, max) == p;
+
+        let objects = (0..self.objects.len())
+            .filter(|&n| {
+                let obj = &self.objects[n];
+                self.layer_is(obj.layer, Layer::is_pickable)
+                    && obj.particles_len() > 0
+                    && self.particles[obj.particles_range()]
+                        .iter()
+                        .all(|p| inside(p.pos))
+            })
+            .collect();
+        let edges = (0..self.edges.len())
+            .filter(|&n| {
+                let edge = &self.edges[n];
+                self.layer_is(edge.layer, Layer::is_pickable)
+                    && inside(edge.get_start())
+                    && inside(edge.get_end())
+            })
+            .collect();
+
+        (objects, edges)
+    }
+
+    /// Shifts a group of objects and edges by `offset`. Indices past the end are skipped.
+    pub fn translate(&mut self, objects: &[usize], edges: &[usize], offset: Vec2) {
+        self.edit();
+        for obj in objects.iter().filter_map(|&n| self.objects.get(n)) {
+            for i in obj.particles_range() {
+                self.particles[i].pos += offset;
+                if let Some(prev) = self.prev_pos.get_mut(i) {
+                    *prev += offset;
+                }
+                if let Some(trail) = self.trails.get_mut(i) {
+                    trail.clear();
+                }
+            }
+        }
+        for &n in edges {
+            if let Some(edge) = self.edges.get_mut(n) {
+                *edge = edge.translated(offset);
+            }
+        }
+        self.measure_objects();
+    }
+
+    /// Moves object `n`, just spawned, out of the edges, colliders and other objects it
+    /// overlaps, so they don't fling each other apart on the first step. It's moved whole,
+    /// the shortest way out found among a fan of directions. Returns whether it came free.
+    pub fn push_out(&mut self, n: usize) -> bool {
+        let Some(range) = self.objects.get(n).map(ObjectDescriptor::particles_range) else {
+            return false;
+        };
+        let own: Vec<Vec2> = self.particles[range.clone()]
+            .iter()
+            .map(|p| p.pos)
+            .collect();
+        let Some(&first) = own.first() else {
+            return true;
+        };
+        let (min, max) = own
+            .iter()
+            .fold((first, first), |(min, max), &p| (min.min(p), max.max(p)));
+        // Out of anything it could be inside of, if there's a way out at all
+        let farthest = min.dist(max) + 4.0 * Particle::R;
+
+        let reach = (min - farthest, max + farthest);
+        let others: Vec<Vec2> = self
+            .particles
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| !range.contains(i) && overlap(reach, (p.pos, p.pos)))
+            .map(|(_, p)| p.pos)
+            .collect();
+        let borders: &[Edge] = match self.settings.bounds {
+            WorldBounds::Closed => &self.borders,
+            WorldBounds::Open | WorldBounds::Wrap => &[],
+        };
+        // Particles go through one way edges from behind anyway
+        let colliders: Vec<&dyn Collider> = self
+            .edges
+            .iter()
+            .chain(borders)
+            .filter(|e| !e.one_way)
+            .map(|e| e as &dyn Collider)
+            .chain(self.movers.iter().map(|m| m as &dyn Collider))
+            .chain(self.colliders.iter().map(AsRef::as_ref))
+            .filter(|c| overlap(c.bounds(), reach))
+            .collect();
+
+        // Walls only keep out what's inside, anything past them is as good as stuck
+        let walled = self.settings.bounds == WorldBounds::Closed;
+        let size = self.size;
+        let blocked = |offset: Vec2| {
+            own.iter().map(|&p| p + offset).any(|p| {
+                (walled && p != p.clamp(Vec2::null(), size))
+                    || colliders
+                        .iter()
+                        .any(|c| c.distance(p).0 < Particle::R - Self::PUSH_OUT_SLOP)
+                    || others
+                        .iter()
+                        .any(|&o| p.dist(o) < 2.0 * Particle::R - Self::PUSH_OUT_SLOP)
+            })
+        };
+        if !blocked(Vec2::null()) {
+            return true;
+        }
+
+        let directions: Vec<Vec2> = (0..Self::PUSH_OUT_DIRECTIONS)
+            .map(|k| Vec2::from_angle(k as Real * TAU / Self::PUSH_OUT_DIRECTIONS as Real))
+            .collect();
+        let mut distance = 0.0;
+        let way_out = loop {
+            distance += Self::PUSH_OUT_STEP;
+            if distance > farthest {
+                return false;
+            }
+            if let Some(&dir) = directions.iter().find(|&&dir| !blocked(dir * distance)) {
+                break dir;
+            }
+        };
+        // Closer in between the last step that was blocked and the first that wasn't
+        let (mut near, mut far) = (distance - Self::PUSH_OUT_STEP, distance);
+        for _ in 0..8 {
+            let mid = 0.5 * (near + far);
+            if blocked(way_out * mid) {
+                near = mid;
+            } else {
+                far = mid;
+            }
+        }
+
+        self.translate(&[n], &[], way_out * far);
+        true
+    }
+
+    /// Average of the centers of a group's objects and edges.
+    pub fn group_center(&self, objects: &[usize], edges: &[usize]) -> Vec2 {
+        let centers: Vec<_> = objects
+            .iter()
+            .filter_map(|&n| self.object_center(n))
+            .chain(
+                edges
+                    .iter()
+                    .filter_map(|&n| self.edges.get(n))
+                    .map(|e| e.start + e.line * 0.5),
+            )
+            .collect();
+
+        centers.iter().fold(Vec2::null(), |acc, &c| acc + c) / centers.len().max(1) as Real
+    }
+
+    pub fn extract_group(&self, objects: &[usize], edges: &[usize]) -> Group {
+        let center = self.group_center(objects, edges);
+
+        Group {
+            objects: objects
+                .iter()
+                .filter_map(|&n| Some((self.object_center(n)? - center, self.extract_prefab(n)?)))
+                .collect(),
+            edges: edges
+                .iter()
+                .filter_map(|&n| self.edges.get(n))
+                .map(|e| e.translated(-center))
+                .collect(),
+        }
+    }
+
+    /// Places `group` centered on `pos` and returns the indices of its objects and edges.
+    /// Nothing of it is placed if any of its objects fails [`Prefab::check`].
+    pub fn insert_group(
+        &mut self,
+        group: &Group,
+        pos: Vec2,
+    ) -> Result<(Vec<usize>, Vec<usize>), String> {
+        for (_, prefab) in &group.objects {
+            prefab.check()?;
+        }
+        self.edit();
+        let (first_object, first_edge) = (self.objects.len(), self.edges.len());
+
+        for (offset, prefab) in &group.objects {
+            self.insert_prefab(prefab, pos + *offset)?;
+        }
+        for edge in &group.edges {
+            self.edges.push(Edge {
+                layer: self.active_layer,
+                ..edge.translated(pos)
+            });
+            self.edge_ids.push();
+        }
+
+        Ok((
+            (first_object..self.objects.len()).collect(),
+            (first_edge..self.edges.len()).collect(),
+        ))
+    }
+
+    /// Mirrors a group left to right across its center, or top to bottom if `vertical`.
+    pub fn mirror_group(&mut self, objects: &[usize], edges: &[usize], vertical: bool) {
+        self.map_group(objects, edges, |v| {
+            if vertical {
+                Vec2::new(v.x, -v.y)
+            } else {
+                Vec2::new(-v.x, v.y)
+            }
+        });
+    }
+
+    /// Turns a group a quarter turn clockwise around its center.
+    pub fn rotate_group(&mut self, objects: &[usize], edges: &[usize]) {
+        self.map_group(objects, edges, Vec2::perp);
+    }
+
+    /// Applies `map` to a group's positions relative to its center and to its velocities.
+    /// Only for maps that keep distances, spring rest lengths are left as they are.
+    fn map_group(&mut self, objects: &[usize], edges: &[usize], map: impl Fn(Vec2) -> Vec2) {
+        self.edit();
+        let center = self.group_center(objects, edges);
+
+        for obj in objects.iter().filter_map(|&n| self.objects.get(n)) {
+            for i in obj.particles_range() {
+                let particle = &mut self.particles[i];
+                particle.pos = center + map(particle.pos - center);
+                particle.vel = map(particle.vel);
+                if let Some(prev) = self.prev_pos.get_mut(i) {
+                    *prev = center + map(*prev - center);
+                }
+                if let Some(trail) = self.trails.get_mut(i) {
+                    trail.clear();
+                }
+            }
+        }
+        // Mirroring turns edges around, swapping the ends of one-way ones keeps them solid
+        // from the mirrored side
+        let mirrored = map(Vec2::new(1.0, 0.0)).cross(map(Vec2::new(0.0, 1.0))) < 0.0;
+        for &n in edges {
+            if let Some(edge) = self.edges.get_mut(n) {
+                let (mut start, mut end) = (edge.get_start(), edge.get_end());
+                if mirrored && edge.one_way {
+                    std::mem::swap(&mut start, &mut end);
+                }
+                edge.set_start(center + map(start - center));
+                edge.set_end(center + map(end - center));
+                for keyframe in &mut edge.keyframes {
+                    if mirrored && edge.one_way {
+                        std::mem::swap(&mut keyframe.start, &mut keyframe.end);
+                    }
+                    keyframe.start = center + map(keyframe.start - center);
+                    keyframe.end = center + map(keyframe.end - center);
+                }
+            }
+        }
+        self.measure_objects();
+    }
+
+    /// Adds copies of a group of objects and edges on top of them and returns their indices.
+    pub fn duplicate(&mut self, objects: &[usize], edges: &[usize]) -> (Vec<usize>, Vec<usize>) {
+        let group = self.extract_group(objects, edges);
+        self.insert_group(&group, self.group_center(objects, edges))
+            .expect("groups taken out of a world should be valid")
+    }
+
+    /// Removes a group of objects and edges, given by their indices before the removal.
+    pub fn remove_group(&mut self, objects: &[usize], edges: &[usize]) {
+        let mut edges = edges.to_vec();
+        edges.sort_unstable();
+        edges.dedup();
+
+        self.remove_objects(objects);
+        // Back to front, so the indices still to go stay valid
+        for &n in edges.iter().rev() {
+            if n < self.edges.len() {
+                self.remove_edge(n);
+            }
+        }
+    }
+
+    /// The particles of object `n`, in the order they were spawned in.
+    pub fn object_particles(&self, n: usize) -> Option<&[Particle]> {
+        Some(&self.particles[self.objects.get(n)?.particles_range()])
+    }
+
+    /// Average position of the particles of object `n`.
+    pub fn object_center(&self, n: usize) -> Option<Vec2> {
+        let particles = &self.particles[self.objects.get(n)?.particles_range()];
+        Some(particles.iter().fold(Vec2::null(), |acc, p| acc + p.pos) / particles.len() as Real)
+    }
+
+    pub fn extract_prefab(&self, n: usize) -> Option<Prefab> {
+        let obj = self.objects.get(n)?;
+        let center = self.object_center(n)?;
+
+        let mut muscles = vec![];
+        let mut springs: Vec<_> = self.springs[obj.springs_range()]
+            .iter()
+            .map(|s| PrefabSpring {
+                a: self.index(s.a) - obj.particle_start,
+                b: self.index(s.b) - obj.particle_start,
+                l0: s.l0,
+                muscle: s.muscle,
+            })
+            .collect();
+        for spring in &mut springs {
+            if let Some(m) = &mut spring.muscle {
+                let muscle = &self.muscles[*m as usize];
+                let local = match muscles.iter().position(|other| other == muscle) {
+                    Some(local) => local,
+                    None => {
+                        muscles.push(muscle.clone());
+                        muscles.len() - 1
+                    }
+                };
+                *m = local as u32;
+            }
+        }
+
+        Some(Prefab {
+            particles: self.particles[obj.particles_range()]
+                .iter()
+                .map(|p| Particle {
+                    pinned: p.pinned,
+                    kind: p.kind,
+                    ..Particle::new(p.pos.x - center.x, p.pos.y - center.y)
+                })
+                .collect(),
+            springs,
+            muscles,
+            boundaries: self.boundaries[obj.boundaries_range()]
+                .iter()
+                .map(|&i| self.index(i) - obj.particle_start)
+                .collect(),
+            open: obj.open,
+            fill: obj.fill.clone(),
+            color: obj.color,
+            lattice: obj.lattice,
+            material: obj.material,
+            name: obj.name.clone(),
+        })
+    }
+
+    /// Places a copy of `prefab` at `pos`, unless it fails [`Prefab::check`].
+    pub fn insert_prefab(&mut self, prefab: &Prefab, pos: Vec2) -> Result<(), String> {
+        prefab.check()?;
+        let p_start = self.particles.len();
+        let s_start = self.springs.len();
+        let b_start = self.boundaries.len();
+
+        let mass = prefab.material.map_or(1.0, |m| m.mass);
+        for p in &prefab.particles {
+            self.push_particle(Particle {
+                mass,
+                pinned: p.pinned,
+                kind: p.kind,
+                ..Particle::new(p.pos.x + pos.x, p.pos.y + pos.y)
+            });
+        }
+        // The prefab's muscles move its springs only, not those of what was there already
+        let m_start = self.muscles.len() as u32;
+        self.muscles.extend(prefab.muscles.iter().cloned());
+        for s in &prefab.springs {
+            let (a, b) = (self.handle(s.a + p_start), self.handle(s.b + p_start));
+            self.springs.push(Spring {
+                muscle: s.muscle.map(|m| m + m_start),
+                ..Spring::new(a, b, s.l0)
+            });
+        }
+        self.bound(prefab.boundaries.iter().map(|i| i + p_start));
+
+        self.push_object(ObjectDescriptor {
+            open: prefab.open,
+            fill: prefab.fill.clone(),
+            color: prefab.color,
+            lattice: prefab.lattice,
+            material: prefab.material,
+            name: prefab.name.clone(),
+            ..ObjectDescriptor::new(
+                p_start,
+                self.particles.len(),
+                s_start,
+                self.springs.len(),
+                b_start,
+                self.boundaries.len(),
+            )
+        });
+        Ok(())
+    }
+
+    pub fn object_name(&self, n: usize) -> Option<&str> {
+        self.objects.get(n)?.name.as_deref()
+    }
+
+    /// An empty name is the same as none.
+    pub fn set_object_name(&mut self, n: usize, name: Option<String>) {
+        self.edit();
+        if let Some(obj) = self.objects.get_mut(n) {
+            obj.name = name.filter(|name| !name.is_empty());
+        }
+    }
+
+    /// An empty name is the same as none.
+    pub fn set_edge_name(&mut self, n: usize, name: Option<String>) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.name = name.filter(|name| !name.is_empty());
+        }
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Adds a layer on top and returns its index.
+    pub fn add_layer(&mut self, name: String) -> usize {
+        self.edit();
+        self.layers.push(Layer {
+            name,
+            ..Layer::default()
+        });
+        self.layers.len() - 1
+    }
+
+    pub fn set_layer(&mut self, n: usize, layer: Layer) {
+        self.edit();
+        if let Some(old) = self.layers.get_mut(n) {
+            *old = layer;
+        }
+    }
+
+    /// Moves everything on layer `n` to the first one, the layers after it move down. The
+    /// first layer can't be removed.
+    pub fn remove_layer(&mut self, n: usize) {
+        self.edit();
+        if n == 0 || n >= self.layers.len() {
+            return;
+        }
+
+        self.layers.remove(n);
+        let shift = |layer: &mut usize| {
+            *layer = match (*layer).cmp(&n) {
+                std::cmp::Ordering::Less => *layer,
+                std::cmp::Ordering::Equal => 0,
+                std::cmp::Ordering::Greater => *layer - 1,
+            };
+        };
+        self.objects
+            .iter_mut()
+            .for_each(|obj| shift(&mut obj.layer));
+        self.edges
+            .iter_mut()
+            .for_each(|edge| shift(&mut edge.layer));
+        shift(&mut self.active_layer);
+    }
+
+    pub fn active_layer(&self) -> usize {
+        self.active_layer
+    }
+
+    /// Indices past the end are ignored.
+    pub fn set_active_layer(&mut self, n: usize) {
+        self.edit();
+        if n < self.layers.len() {
+            self.active_layer = n;
+        }
+    }
+
+    pub fn object_layer(&self, n: usize) -> Option<usize> {
+        Some(self.objects.get(n)?.layer)
+    }
+
+    /// Layers past the end are ignored.
+    pub fn set_object_layer(&mut self, n: usize, layer: usize) {
+        self.edit();
+        if let Some(obj) = self
+            .objects
+            .get_mut(n)
+            .filter(|_| layer < self.layers.len())
+        {
+            obj.layer = layer;
+        }
+    }
+
+    pub fn edge_layer(&self, n: usize) -> Option<usize> {
+        Some(self.edges.get(n)?.layer)
+    }
+
+    /// Layers past the end are ignored.
+    pub fn set_edge_layer(&mut self, n: usize, layer: usize) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n).filter(|_| layer < self.layers.len()) {
+            edge.layer = layer;
+        }
+    }
+
+    /// Whether layer `n` is there and `test` holds for it.
+    fn layer_is(&self, n: usize, test: impl FnOnce(&Layer) -> bool) -> bool {
+        self.layers.get(n).is_some_and(test)
+    }
+
+    /// Of every particle, whether `test` holds for its object's layer. Empty while it holds
+    /// for no layer, as it mostly does.
+    fn particles_on(&self, test: impl Fn(&Layer) -> bool) -> Vec<bool> {
+        let mut on = vec![];
+        if self.layers.iter().any(&test) {
+            on.resize(self.particles.len(), false);
+            for obj in self
+                .objects
+                .iter()
+                .filter(|obj| self.layer_is(obj.layer, &test))
+            {
+                on[obj.particles_range()].fill(true);
+            }
+        }
+        on
+    }
+
+    pub fn object_color(&self, n: usize) -> Option<Color> {
+        self.objects.get(n).and_then(|obj| obj.color)
+    }
+
+    /// `None` returns the object to the theme's palette cycle.
+    pub fn set_object_color(&mut self, n: usize, color: Option<Color>) {
+        self.edit();
+        if let Some(obj) = self.objects.get_mut(n) {
+            obj.color = color;
+        }
+    }
+
+    /// Material of object `n`, the solver's one if it has none of its own.
+    pub fn material(&self, n: usize) -> Option<Material> {
+        self.objects
+            .get(n)
+            .map(|obj| obj.material.unwrap_or(self.settings.material()))
+    }
+
+    /// Whether object `n` went NaN or infinite and was pinned where it was, see
+    /// [`WorldEvent::Quarantined`].
+    pub fn is_quarantined(&self, n: usize) -> bool {
+        self.objects.get(n).is_some_and(|obj| obj.quarantined)
+    }
+
+    pub fn is_frozen(&self, n: usize) -> bool {
+        self.objects.get(n).is_some_and(|obj| obj.frozen)
+    }
+
+    /// Holds object `n` still where it is, or lets it go again. Frozen objects are left
+    /// out of integration and their springs, things still bump into them like into pins.
+    pub fn set_frozen(&mut self, n: usize, frozen: bool) {
+        self.edit();
+        let Some(obj) = self.objects.get_mut(n) else {
+            return;
         };
 
-        world.buckets.resize(SQR!(Self::GRID) as usize, vec![]);
-        world
+        obj.frozen = frozen;
+        for particle in &mut self.particles[obj.particles_range()] {
+            particle.frozen = frozen;
+            particle.vel = Vec2::null();
+        }
+    }
+
+    pub fn has_material(&self, n: usize) -> bool {
+        self.objects
+            .get(n)
+            .is_some_and(|obj| obj.material.is_some())
+    }
+
+    /// `None` makes the object follow the solver settings again.
+    pub fn set_material(&mut self, n: usize, material: Option<Material>) {
+        self.edit();
+        let Some(obj) = self.objects.get_mut(n) else {
+            return;
+        };
+
+        obj.material = material;
+        let mass = material.map_or(1.0, |m| m.mass);
+        for particle in &mut self.particles[obj.particles_range()] {
+            particle.mass = mass;
+        }
+        obj.state = ObjectState::measure(&self.particles[obj.particles_range()]);
+    }
+
+    /// Turns object `n` clockwise with `torque` through every step of the next update,
+    /// counterclockwise when negative. Calls before that add up.
+    pub fn apply_torque(&mut self, n: usize, torque: Real) {
+        if let Some(obj) = self.objects.get_mut(n) {
+            obj.torque += torque;
+        }
+    }
+
+    pub fn object_state(&self, n: usize) -> Option<ObjectState> {
+        self.objects.get(n).map(|obj| obj.state)
+    }
+
+    /// Center of mass, moment of inertia and angular velocity of object `n`, the last
+    /// positive when it turns clockwise.
+    pub fn rotation(&self, n: usize) -> Option<(Vec2, Real, Real)> {
+        Self::spin(self.objects.get(n)?, &self.particles)
+    }
+
+    pub fn motor(&self, n: usize) -> Option<Real> {
+        self.objects.get(n).map(|obj| obj.motor)
+    }
+
+    /// Scales the rest length of every spring of object `n` by `factor`, so it inflates
+    /// above 1 and shrinks below.
+    pub fn scale_rest_lengths(&mut self, n: usize, factor: Real) {
+        self.edit();
+        let Some(obj) = self.objects.get(n) else {
+            return;
+        };
+
+        let springs = &mut self.springs[obj.springs_range()];
+        // Particles can't get closer than touching, shorter springs would fight collisions
+        let shortest = springs.iter().map(|s| s.l0).fold(Real::INFINITY, Real::min);
+        let factor = factor.max((2.0 * Particle::R / shortest).min(1.0));
+        for spring in springs {
+            spring.l0 *= factor;
+        }
+    }
+
+    /// Adds a muscle that doesn't move anything yet, see [`World::set_springs_muscle`].
+    /// Returns its index.
+    pub fn add_muscle(&mut self, muscle: Muscle) -> usize {
+        self.edit();
+        self.muscles.push(muscle);
+        self.muscles.len() - 1
+    }
+
+    pub fn muscles_iter(&self) -> impl Iterator<Item = &Muscle> {
+        self.muscles.iter()
+    }
+
+    pub fn muscle(&self, n: usize) -> Option<&Muscle> {
+        self.muscles.get(n)
+    }
+
+    pub fn set_muscle(&mut self, n: usize, muscle: Muscle) {
+        self.edit();
+        if let Some(old) = self.muscles.get_mut(n) {
+            *old = muscle;
+        }
+    }
+
+    /// Removes muscle `n`, its springs go back to being plain ones. The muscles after it
+    /// move down.
+    pub fn remove_muscle(&mut self, n: usize) {
+        self.edit();
+        if n >= self.muscles.len() {
+            return;
+        }
+        self.muscles.remove(n);
+
+        let n = n as u32;
+        for spring in &mut self.springs {
+            spring.muscle = match spring.muscle {
+                Some(m) if m == n => None,
+                Some(m) if m > n => Some(m - 1),
+                muscle => muscle,
+            };
+        }
+    }
+
+    /// Indices of the springs muscle `n` moves.
+    pub fn muscle_springs(&self, n: usize) -> Vec<usize> {
+        (0..self.springs.len())
+            .filter(|&i| self.springs[i].muscle.is_some_and(|m| m as usize == n))
+            .collect()
+    }
+
+    /// Indices of the springs of objects with both ends inside `polygon`.
+    pub fn springs_in(&self, polygon: &[Vec2]) -> Vec<usize> {
+        let unpickable = self.particles_on(|layer| !layer.is_pickable());
+        let inside = |id: Id| {
+            let i = self.index(id);
+            unpickable.get(i) != Some(&true) && polygon_contains(polygon, self.particles[i].pos)
+        };
+        (0..self.springs.len())
+            .filter(|&i| inside(self.springs[i].a) && inside(self.springs[i].b))
+            .collect()
+    }
+
+    /// Indices of the springs of `objects`.
+    pub fn springs_of(&self, objects: &[usize]) -> Vec<usize> {
+        objects
+            .iter()
+            .filter_map(|&n| self.objects.get(n))
+            .flat_map(ObjectDescriptor::springs_range)
+            .collect()
+    }
+
+    /// Makes `springs` part of muscle `muscle`, or plain springs again with `None`.
+    pub fn set_springs_muscle(&mut self, springs: &[usize], muscle: Option<usize>) {
+        self.edit();
+        let muscle = muscle
+            .filter(|&m| m < self.muscles.len())
+            .map(|m| u32::try_from(m).expect("there should be under 4G muscles"));
+        for &i in springs {
+            if let Some(spring) = self.springs.get_mut(i) {
+                spring.muscle = muscle;
+            }
+        }
+    }
+
+    /// The muscle moving the springs of object `n`, the first one found if there are several.
+    pub fn object_muscle(&self, n: usize) -> Option<usize> {
+        let obj = self.objects.get(n)?;
+        self.springs[obj.springs_range()]
+            .iter()
+            .find_map(|s| s.muscle)
+            .map(|m| m as usize)
+    }
+
+    /// Zero takes the motor out of object `n`.
+    pub fn set_motor(&mut self, n: usize, torque: Real) {
+        self.edit();
+        if let Some(obj) = self.objects.get_mut(n) {
+            obj.motor = torque;
+        }
+    }
+
+    /// Drives every motor at `throttle` of its torque, negative values turn them
+    /// counterclockwise. Stays in effect until changed.
+    pub fn set_throttle(&mut self, throttle: Real) {
+        self.throttle = throttle.clamp(-1.0, 1.0);
+    }
+
+    /// Joins particles `a` and `b` with a rod keeping them at their current distance.
+    /// It goes away with either of them.
+    pub fn add_rod(&mut self, a: Id, b: Id) -> Result<(), &'static str> {
+        let (i, j) = match (self.particle_index(a), self.particle_index(b)) {
+            (Some(i), Some(j)) if i != j => (i, j),
+            _ => return Err("cant add rod, it needs two particles"),
+        };
+        if self
+            .rods
+            .iter()
+            .any(|r| (r.a, r.b) == (a, b) || (r.a, r.b) == (b, a))
+        {
+            return Err("cant add rod, the particles are joined by one already");
+        }
+
+        self.edit();
+        let len = self.particles[i].pos.dist(self.particles[j].pos);
+        self.rods.push(Rod { a, b, len });
+        Ok(())
+    }
+
+    /// The rod passing by `pos`, away from the particles at its ends.
+    pub fn rod_at(&self, pos: Vec2) -> Option<usize> {
+        self.rods.iter().rposition(|r| {
+            let (a, b) = (
+                self.particles[self.index(r.a)].pos,
+                self.particles[self.index(r.b)].pos,
+            );
+            a.dist_sqr(pos) > SQR!(Particle::R)
+                && b.dist_sqr(pos) > SQR!(Particle::R)
+                && Edge::new(a, b).closest_point(pos).dist_sqr(pos) <= SQR!(Self::ROD_WIDTH)
+        })
+    }
+
+    pub fn remove_rod(&mut self, n: usize) {
+        self.edit();
+        if n < self.rods.len() {
+            self.rods.remove(n);
+        }
     }
 
-    #[allow(clippy::unused_self)]
-    pub fn can_add_edge(&self, start: Vec2, end: Vec2) -> bool {
-        start != end
+    /// Joins particles `a` and `b`, usually of different objects, with a spring at rest at
+    /// their current distance. It goes away with either of the objects.
+    pub fn link(&mut self, a: Id, b: Id) {
+        self.edit();
+        if let (Some(i), Some(j)) = (self.particle_index(a), self.particle_index(b)) {
+            let len = self.particles[i].pos.dist(self.particles[j].pos);
+            self.links.push(Spring::new(a, b, len));
+        }
     }
 
-    pub fn add_edge(&mut self, start: Vec2, end: Vec2) -> Result<(), &'static str> {
-        if !self.can_add_edge(start, end) {
-            return Err("cant add edge, length cannot be 0");
+    pub fn fill(&self, n: usize) -> Option<&Fill> {
+        self.objects.get(n).map(|obj| &obj.fill)
+    }
+
+    pub fn set_fill(&mut self, n: usize, fill: Fill) -> Result<(), String> {
+        self.edit();
+        if let Fill::Texture(path) = &fill {
+            self.load_texture(path)?;
+        }
+
+        if let Some(obj) = self.objects.get_mut(n) {
+            obj.fill = fill;
         }
-        self.edges.push(Edge::new(start, end));
         Ok(())
     }
 
-    #[allow(clippy::unused_self)]
-    pub fn can_spawn_rect(&self, w: usize, h: usize) -> bool {
-        w >= 2 && h >= 2
+    fn load_texture(&mut self, path: &str) -> Result<(), String> {
+        if !self.textures.contains_key(path) {
+            let texture = Texture::load(Path::new(path)).map_err(|err| format!("{path}: {err}"))?;
+            self.textures.insert(path.to_owned(), Arc::new(texture));
+        }
+
+        Ok(())
     }
 
-    pub fn spawn_rect(&mut self, w: usize, h: usize, x: f64, y: f64) -> Result<(), (usize, usize)> {
-        if !self.can_spawn_rect(w, h) {
-            return Err((w, h));
-        }
+    /// Loads the textures referenced by objects, e.g. after deserializing, returning
+    /// the ones that failed.
+    pub fn load_textures(&mut self) -> Vec<String> {
+        self.edit();
+        let paths: Vec<String> = self
+            .objects
+            .iter()
+            .filter_map(|obj| match &obj.fill {
+                Fill::Texture(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect();
 
-        self.particles.reserve(w * h);
-        self.springs.reserve(w * h * 4);
-        self.boundaries.reserve(2 * w + 2 * h);
+        paths
+            .iter()
+            .filter_map(|path| self.load_texture(path).err())
+            .collect()
+    }
 
-        let p_start = self.particles.len();
-        let s_start = self.springs.len();
+    pub fn edges_iter_mut(&mut self) -> impl Iterator<Item = &'_ mut Edge> {
+        self.edit();
+        self.edges.iter_mut()
+    }
 
-        for i in 0..w {
-            for j in 0..h {
-                self.particles.push(Particle::new(
-                    i as f64 * Particle::SPACING + x,
-                    j as f64 * Particle::SPACING + y,
-                ));
+    pub fn edges_iter(&self) -> impl Iterator<Item = &'_ Edge> {
+        self.edges.iter()
+    }
 
-                let ind = self.particles.len() - 1;
-                if i < w - 1 {
-                    self.springs
-                        .push(Spring::new(ind, ind + h, Particle::SPACING));
-                }
-                if j < h - 1 {
-                    self.springs
-                        .push(Spring::new(ind, ind + 1, Particle::SPACING));
-                }
-                if i < w - 1 && j < h - 1 {
-                    self.springs
-                        .push(Spring::new(ind, ind + h + 1, Particle::DIAG_SQR.sqrt()));
-                }
-                if i > 0 && j < h - 1 {
-                    self.springs
-                        .push(Spring::new(ind, ind - h + 1, Particle::DIAG_SQR.sqrt()));
-                }
-            }
-        }
+    pub fn particles_iter(&self) -> impl Iterator<Item = &'_ Particle> {
+        self.particles.iter()
+    }
 
-        let b_start = self.boundaries.len();
+    pub fn emitters_iter(&self) -> impl Iterator<Item = &'_ Emitter> {
+        self.emitters.iter()
+    }
 
-        for n in 0..w {
-            self.boundaries.push(p_start + n * h);
-        }
-        for n in (w - 1) * h + 1..w * h {
-            self.boundaries.push(p_start + n);
+    pub fn emitter(&self, n: usize) -> Option<&Emitter> {
+        self.emitters.get(n)
+    }
+
+    pub fn emitter_at(&self, pos: Vec2) -> Option<usize> {
+        self.emitters
+            .iter()
+            .rposition(|e| e.pos.dist_sqr(pos) <= SQR!(Emitter::R))
+    }
+
+    /// Returns the index of the new emitter.
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        self.edit();
+        self.emitters.push(emitter);
+        self.emitters.len() - 1
+    }
+
+    /// Replaces emitter `n`, keeping how far along it is to its next emission.
+    pub fn set_emitter(&mut self, n: usize, emitter: Emitter) {
+        self.edit();
+        if let Some(e) = self.emitters.get_mut(n) {
+            *e = Emitter {
+                due: e.due,
+                ..emitter
+            };
         }
-        for n in (1..w - 1).rev() {
-            self.boundaries.push(p_start + (n + 1) * h - 1);
+    }
+
+    /// What it sent out stays, without counting against any emitter's cap.
+    pub fn remove_emitter(&mut self, n: usize) {
+        self.edit();
+        if n >= self.emitters.len() {
+            return;
         }
-        for n in (1..h).rev() {
-            self.boundaries.push(p_start + n);
+
+        self.emitters.remove(n);
+        for obj in &mut self.objects {
+            obj.emitter = match obj.emitter {
+                Some(k) if k == n => None,
+                Some(k) if k > n => Some(k - 1),
+                k => k,
+            };
         }
+    }
 
-        self.objects.push(ObjectDescriptor::new(
-            p_start,
-            self.particles.len(),
-            s_start,
-            self.springs.len(),
-            b_start,
-            self.boundaries.len(),
-        ));
+    pub fn drains_iter(&self) -> impl Iterator<Item = &'_ Drain> {
+        self.drains.iter()
+    }
+
+    #[allow(clippy::unused_self)]
+    pub fn can_add_drain(&self, a: Vec2, b: Vec2) -> bool {
+        a.x != b.x && a.y != b.y
+    }
+
+    /// A drain from corner `a` to the opposite corner `b`, see [`Drain`].
+    pub fn add_drain(&mut self, a: Vec2, b: Vec2) -> Result<(), &'static str> {
+        if !self.can_add_drain(a, b) {
+            return Err("cant add drain, it is empty");
+        }
 
+        self.edit();
+        self.drains.push(Drain::new(a, b));
         Ok(())
     }
 
-    pub fn update(&mut self) -> Result<(), f64> {
-        while self.dt_acc >= Self::DT {
-            for (i, particle) in self.particles.iter().enumerate() {
-                let x = ((particle.pos.x / WIDTH) * Self::GRID) as usize;
-                let y = ((particle.pos.y / HEIGHT) * Self::GRID) as usize;
+    pub fn drain_at(&self, pos: Vec2) -> Option<usize> {
+        self.drains.iter().rposition(|d| d.contains(pos))
+    }
 
-                self.buckets[(x + y * Self::GRID as usize)
-                    .clamp(0, (Self::GRID * Self::GRID) as usize - 1)]
-                .push(i);
-            }
+    pub fn remove_drain(&mut self, n: usize) {
+        self.edit();
+        if n < self.drains.len() {
+            self.drains.remove(n);
+        }
+    }
 
-            for spring in &self.springs {
-                Self::update_spring(spring, &mut self.particles)?;
-            }
+    pub fn gravity_zones_iter(&self) -> impl Iterator<Item = &'_ GravityZone> {
+        self.gravity_zones.iter()
+    }
 
-            for i in 0..self.particles.len() {
-                let mut particle = self.particles[i].clone();
+    #[allow(clippy::unused_self)]
+    pub fn can_add_gravity_zone(&self, a: Vec2, b: Vec2) -> bool {
+        a.x != b.x && a.y != b.y
+    }
 
-                //TODO: CLEAR THIS SHIT UP
-                let (x, y) = Self::grid_pos(&particle);
+    /// A zone from corner `a` to the opposite corner `b` where gravity is `gravity` instead
+    /// of the one from the settings, see [`GravityZone`].
+    pub fn add_gravity_zone(
+        &mut self,
+        a: Vec2,
+        b: Vec2,
+        gravity: Vec2,
+    ) -> Result<(), &'static str> {
+        if !self.can_add_gravity_zone(a, b) {
+            return Err("cant add gravity zone, it is empty");
+        }
 
-                let mut collide_bucket = |z: usize| {
-                    for j in &self.buckets[z] {
-                        if i != *j {
-                            particle.collide(&mut self.particles[*j]);
-                        }
-                    }
-                };
+        self.edit();
+        self.gravity_zones.push(GravityZone::new(a, b, gravity));
+        Ok(())
+    }
 
-                collide_bucket(Self::grid_idx(x, y));
+    pub fn gravity_zone_at(&self, pos: Vec2) -> Option<usize> {
+        self.gravity_zones.iter().rposition(|z| z.contains(pos))
+    }
 
-                if y > 0 {
-                    collide_bucket(Self::grid_idx(x, y - 1));
-                }
+    pub fn remove_gravity_zone(&mut self, n: usize) {
+        self.edit();
+        if n < self.gravity_zones.len() {
+            self.gravity_zones.remove(n);
+        }
+    }
 
-                if y > 0 && x > 0 {
-                    collide_bucket(Self::grid_idx(x - 1, y - 1));
-                }
+    pub fn movers_iter(&self) -> impl Iterator<Item = &'_ Mover> {
+        self.movers.iter()
+    }
 
-                if x > 0 {
-                    collide_bucket(Self::grid_idx(x - 1, y));
-                }
+    /// Adds `mover` where it is along its track, see [`Mover`].
+    pub fn add_mover(&mut self, mover: Mover) -> Result<(), &'static str> {
+        if mover.length() <= 0.0 {
+            return Err("cant add mover, its track is empty");
+        }
 
-                if x > 0 && y < Self::GRID as usize {
-                    collide_bucket(Self::grid_idx(x - 1, y + 1));
-                }
+        self.edit();
+        self.movers.push(mover);
+        Ok(())
+    }
 
-                //Gravity
-                particle.acc += Self::GRAVITY;
+    pub fn mover_at(&self, pos: Vec2) -> Option<usize> {
+        self.movers.iter().rposition(|m| m.contains(pos))
+    }
 
-                particle.integrate(Self::DT);
+    pub fn remove_mover(&mut self, n: usize) {
+        self.edit();
+        if n < self.movers.len() {
+            self.movers.remove(n);
+        }
+    }
 
-                self.particles[i] = particle;
-            }
+    /// Gravity things at `pos` fall with, of the zone there or else of the settings.
+    pub fn gravity_at(&self, pos: Vec2) -> Vec2 {
+        Self::zone_gravity(&self.gravity_zones, pos, self.settings.gravity)
+    }
 
-            for i in &self.boundaries {
-                for edge in &self.edges {
-                    edge.collide(&mut self.particles[*i]);
-                }
-            }
+    fn zone_gravity(zones: &[GravityZone], pos: Vec2, gravity: Vec2) -> Vec2 {
+        zones
+            .iter()
+            .rev()
+            .find(|z| z.contains(pos))
+            .map_or(gravity, |z| z.gravity)
+    }
+
+    /// Removes particle `n` after `lifetime` more seconds of simulation, or never for
+    /// `None`. A particle of a body takes the whole body along.
+    pub fn set_lifetime(&mut self, n: usize, lifetime: Option<Real>) {
+        self.edit();
+        let expires = lifetime.map(|lifetime| self.step_after(lifetime));
+        if let Some(particle) = self.particles.get_mut(n) {
+            particle.expires = expires;
+        }
+    }
 
-            self.buckets.iter_mut().for_each(Vec::clear);
+    pub fn remove_edge(&mut self, n: usize) {
+        self.edit();
+        self.delete_edge(n);
+    }
 
-            self.dt_acc -= Self::DT;
+    // Without counting as an edit, edges also break while simulating
+    fn delete_edge(&mut self, n: usize) {
+        self.chains = None;
+        self.edges.remove(n);
+        self.edge_ids.remove(n);
+        self.anchors.retain(|a| a.edge != n);
+        for anchor in &mut self.anchors {
+            if anchor.edge > n {
+                anchor.edge -= 1;
+            }
         }
+    }
 
-        Ok(())
+    /// Shifts edge `n` by `offset`. Edges it shared a vertex with stay where they are.
+    pub fn move_edge(&mut self, n: usize, offset: Vec2) {
+        self.edit();
+        self.translate(&[], &[n], offset);
     }
 
-    pub fn end_frame(&mut self, dt: f64) {
-        self.dt_acc += dt;
+    /// Adds a copy of edge `n` on top of it and returns the copy's index.
+    pub fn duplicate_edge(&mut self, n: usize) -> Option<usize> {
+        let copy = self.edges.get(n)?.clone();
+        self.edit();
+        self.edges.push(copy);
+        self.edge_ids.push();
+        Some(self.edges.len() - 1)
     }
 
-    pub fn clear(&mut self) {
-        self.particles.clear();
-        self.springs.clear();
-        self.boundaries.clear();
-        self.objects.clear();
+    /// Clamped to [`Edge::MIN_R`] and [`Edge::MAX_R`].
+    pub fn set_edge_radius(&mut self, n: usize, radius: Real) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.radius = radius.clamp(Edge::MIN_R, Edge::MAX_R);
+        }
     }
 
-    pub fn info(&self) -> (usize, usize, usize, usize, usize) {
-        (
-            self.particles.len(),
-            self.springs.len(),
-            self.boundaries.len(),
-            self.edges.len(),
-            self.objects.len(),
-        )
+    /// Zero makes it let go of everything stuck to it.
+    pub fn set_edge_stickiness(&mut self, n: usize, stickiness: Real) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.stickiness = stickiness.max(0.0);
+        }
+        if stickiness <= 0.0 {
+            self.anchors.retain(|a| a.edge != n);
+        }
     }
 
-    pub fn draw_particles(&self, canvas: &mut impl Renderer) {
-        canvas.set_color(Color::YELLOW);
-        for particle in &self.particles {
-            canvas.filled_circle(particle.pos, Particle::R);
+    /// Zero is the temperature of the surroundings, see [`SolverSettings::heat`].
+    pub fn set_edge_temperature(&mut self, n: usize, temperature: Real) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.temperature = temperature;
         }
     }
 
-    pub fn draw_springs(&self, canvas: &mut impl Renderer) {
-        canvas.set_color(Color::CYAN);
-        for spring in &self.springs {
-            canvas.line(self.particles[spring.a].pos, self.particles[spring.b].pos);
+    /// Zero makes it unbreakable. Either way it starts over undamaged.
+    pub fn set_edge_strength(&mut self, n: usize, strength: Real) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.strength = strength.max(0.0);
+            edge.damage = 0.0;
         }
     }
 
-    pub fn draw_polys(&self, canvas: &mut impl Renderer) {
-        const COLORS: [Color; 7] = [
-            Color::RED,
-            Color::YELLOW,
-            Color::BLUE,
-            Color::MAGENTA,
-            Color::CYAN,
-            Color::GREEN,
-            Color::WHITE,
-        ];
+    pub fn toggle_one_way(&mut self, n: usize) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.one_way = !edge.one_way;
+        }
+    }
 
-        for (obj, &color) in self.objects.iter().zip(COLORS.iter().cycle()) {
-            let vertices = obj
-                .boundaries_range()
-                .map(|i| self.particles[self.boundaries[i]].pos);
+    /// Edges joined to edge `n` end to end, directly or through others, along with it.
+    pub fn chain_of(&self, n: usize) -> Vec<usize> {
+        let chains;
+        let chains = match &self.chains {
+            Some(chains) => chains,
+            None => {
+                chains = Chain::find(&self.edges);
+                &chains
+            }
+        };
+        chains
+            .iter()
+            .find(|c| c.edges.contains(&n))
+            .map_or_else(Vec::new, |c| c.edges.clone())
+    }
 
-            canvas.set_color(color).polygon(vertices);
+    /// Gives every edge of the chain of edge `n` `corners`, see [`World::chain_of`].
+    pub fn set_chain_corners(&mut self, n: usize, corners: Corners) {
+        for m in self.chain_of(n) {
+            self.edges[m].corners = corners;
         }
+        self.edit();
     }
 
-    pub fn draw_edges(&self, canvas: &mut impl Renderer) {
-        for edge in &self.edges {
-            canvas
-                .set_color(Color::RGB(44, 56, 80))
-                .thick_line(edge.start, edge.get_end(), Edge::R * 2.0)
-                .set_color(Color::RGB(88, 112, 161))
-                .filled_circle(edge.start, Edge::R)
-                .filled_circle(edge.get_end(), Edge::R);
+    /// Records where edge `n` is now as a keyframe at the current time of its animation,
+    /// replacing one recorded at that time already. The first one starts the animation.
+    pub fn add_keyframe(&mut self, n: usize) {
+        self.edit();
+        let Some(edge) = self.edges.get_mut(n) else {
+            return;
+        };
+        if edge.keyframes.is_empty() {
+            edge.clock = 0.0;
+        }
+        let keyframe = Keyframe {
+            time: edge.clock,
+            start: edge.get_start(),
+            end: edge.get_end(),
+        };
+        let next = edge.keyframes.partition_point(|k| k.time < keyframe.time);
+        match edge.keyframes.get_mut(next) {
+            Some(k) if k.time - keyframe.time < 0.5 * Self::DT => *k = keyframe,
+            _ => edge.keyframes.insert(next, keyframe),
         }
     }
 
-    pub fn remove_last(&mut self) {
-        if let Some(obj) = self.objects.pop() {
-            self.particles.truncate(obj.particle_start);
-            self.springs.truncate(obj.spring_start);
-            self.boundaries.truncate(obj.boundary_start);
+    /// Stops edge `n` where it is.
+    pub fn clear_keyframes(&mut self, n: usize) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.keyframes.clear();
+            edge.clock = 0.0;
+            edge.motion = None;
         }
     }
 
-    pub fn edges_iter_mut(&mut self) -> impl Iterator<Item = &'_ mut Edge> {
-        self.edges.iter_mut()
+    /// Switches edge `n` between playing its keyframes once and over and over, either way
+    /// from the start.
+    pub fn toggle_looping(&mut self, n: usize) {
+        self.edit();
+        if let Some(edge) = self.edges.get_mut(n) {
+            edge.looping = !edge.looping;
+            edge.clock = 0.0;
+        }
     }
 
-    pub fn edges_iter(&mut self) -> impl Iterator<Item = &'_ Edge> {
-        self.edges.iter()
+    /// Runs `force` every step from now on, see [`ForceGenerator`]. Not saved.
+    pub fn add_force_generator(&mut self, force: Box<dyn ForceGenerator>) {
+        self.edit();
+        self.forces.push(Arc::new(Mutex::new(force)));
     }
 
-    pub fn remove_edge(&mut self, n: usize) {
-        self.edges.remove(n);
+    pub fn clear_force_generators(&mut self) {
+        self.edit();
+        self.forces.clear();
+    }
+
+    /// Has boundary particles bounce off `collider` from now on, see [`Collider`]. Not saved.
+    pub fn add_collider(&mut self, collider: Box<dyn Collider>) {
+        self.edit();
+        self.colliders.push(Arc::from(collider));
+    }
+
+    pub fn clear_colliders(&mut self) {
+        self.edit();
+        self.colliders.clear();
+    }
+
+    pub fn settings(&self) -> &SolverSettings {
+        &self.settings
+    }
+
+    /// An edit whether or not anything is changed through it, see [`World::set_settings`]
+    /// for settings that may be the same as before.
+    pub fn settings_mut(&mut self) -> &mut SolverSettings {
+        self.edit();
+        &mut self.settings
+    }
+
+    /// Only an edit when `settings` differ from the ones there are.
+    pub fn set_settings(&mut self, settings: SolverSettings) {
+        if settings != self.settings {
+            self.edit();
+            self.settings = settings;
+        }
+    }
+
+    /// Springs soften past the melting point and pull no harder than the most spring force
+    /// when `settings` say so. Muscles stretch theirs by `scales`.
+    #[cfg(not(all(feature = "simd", not(feature = "f32"))))]
+    fn update_springs(
+        springs: &[Spring],
+        ids: &Slots,
+        scales: &[Real],
+        particles: &mut [Particle],
+        material: &Material,
+        settings: &SolverSettings,
+        events: &mut Vec<WorldEvent>,
+    ) -> Result<(), Instability> {
+        let melting = settings.heat.then_some(settings.melting_point);
+        let max_force = settings
+            .limit_spring_force
+            .then_some(settings.max_spring_force);
+        for spring in springs {
+            events.extend(Self::update_spring(
+                spring, ids, scales, particles, material, melting, max_force,
+            )?);
+        }
+        Ok(())
     }
 
-    fn grid_pos(particle: &Particle) -> (usize, usize) {
-        let x = ((particle.pos.x / WIDTH) * Self::GRID) as usize;
-        let y = ((particle.pos.y / HEIGHT) * Self::GRID) as usize;
+    /// Same results as the scalar version, bit for bit: forces of four springs at a time
+    /// are computed on SIMD lanes, then applied one after another in the original order.
+    /// Particles are laid out as structs, so lanes are gathered from and scattered to them.
+    #[cfg(all(feature = "simd", not(feature = "f32")))]
+    fn update_springs(
+        springs: &[Spring],
+        ids: &Slots,
+        scales: &[Real],
+        particles: &mut [Particle],
+        material: &Material,
+        settings: &SolverSettings,
+        events: &mut Vec<WorldEvent>,
+    ) -> Result<(), Instability> {
+        let melting = settings.heat.then_some(settings.melting_point);
+        let max_force = settings
+            .limit_spring_force
+            .then_some(settings.max_spring_force);
+        use wide::{f64x4, CmpGe};
+
+        let mut chunks = springs.chunks_exact(4);
+        for chunk in &mut chunks {
+            let (mut dx, mut dy, mut dvx, mut dvy, mut rest) =
+                ([0.0; 4], [0.0; 4], [0.0; 4], [0.0; 4], [0.0; 4]);
+            let mut stiffness = [material.stiffness; 4];
+            let ends = [0, 1, 2, 3].map(|i| (ids.at(chunk[i].a), ids.at(chunk[i].b)));
+            for (i, spring) in chunk.iter().enumerate() {
+                let (a, b) = ends[i];
+                let (p1, p2) = (&particles[a], &particles[b]);
+                (dx[i], dy[i]) = (p2.pos.x - p1.pos.x, p2.pos.y - p1.pos.y);
+                (dvx[i], dvy[i]) = (p2.vel.x - p1.vel.x, p2.vel.y - p1.vel.y);
+                rest[i] = spring.rest_length(scales);
+                if let Some(point) = melting {
+                    stiffness[i] *= Self::softening(p1.temperature, p2.temperature, point);
+                }
+            }
+            let (dx, dy) = (f64x4::new(dx), f64x4::new(dy));
+            let (dvx, dvy, l0) = (f64x4::new(dvx), f64x4::new(dvy), f64x4::new(rest));
+            let stiffness = f64x4::new(stiffness);
+
+            let diff_len = (dx * dx + dy * dy).sqrt();
+            let (nx, ny) = (dx / diff_len, dy / diff_len);
+            let dl = diff_len - l0;
+            let dist_factor = dl.cmp_ge(f64x4::ZERO).blend(dl, f64x4::ONE);
+
+            let dv = nx * dvx + ny * dvy;
+            let fs = dist_factor * dl * stiffness;
+            let fd = dv * material.damping;
+            let mut force = fs + fd;
+            if let Some(max) = max_force {
+                force = force.min(f64x4::splat(max)).max(f64x4::splat(-max));
+            }
+            let (fx, fy) = (force * nx, force * ny);
+
+            let (diff_len, dv) = (diff_len.to_array(), dv.to_array());
+            let (fx, fy) = (fx.to_array(), fy.to_array());
+            for (i, (spring, &(a, b))) in chunk.iter().zip(&ends).enumerate() {
+                if diff_len[i] > rest[i] * 5.0 {
+                    return Err(Instability {
+                        a: spring.a,
+                        b: spring.b,
+                        diff_len: diff_len[i],
+                    });
+                }
+
+                let threshold = rest[i] * Self::COMPRESSION;
+                if diff_len[i] < threshold && diff_len[i] - dv[i] * Self::DT >= threshold {
+                    let (p1, p2) = (particles[a].pos, particles[b].pos);
+                    events.push(WorldEvent::Compression {
+                        pos: p1 + (p2 - p1) * 0.5,
+                        closing: -dv[i],
+                    });
+                }
+
+                let f = Vec2::new(fx[i], fy[i]);
+                particles[a].acc += f / particles[a].mass;
+                particles[b].acc -= f / particles[b].mass;
+            }
+        }
+
+        for spring in chunks.remainder() {
+            events.extend(Self::update_spring(
+                spring, ids, scales, particles, material, melting, max_force,
+            )?);
+        }
+        Ok(())
+    }
 
-        (x, y)
+    /// Moves particle collisions onto `gpu`, or back onto the CPU with `None`. Not an edit:
+    /// it's the same scene either way.
+    #[cfg(feature = "wgpu")]
+    pub fn set_gpu_collisions(&mut self, gpu: Option<Arc<Mutex<GpuCollisions>>>) {
+        self.gpu = gpu;
     }
 
-    fn grid_idx(x: usize, y: usize) -> usize {
-        (x + y * Self::GRID as usize).clamp(0, SQR!(Self::GRID) as usize - 1)
+    /// Resolves particle collisions on the GPU, if there is one to use.
+    #[cfg(feature = "wgpu")]
+    fn collide_on_gpu(&mut self) -> bool {
+        let Some(gpu) = &self.gpu else {
+            return false;
+        };
+
+        let mut gpu = gpu.lock().expect("gpu lock should not be poisoned");
+        let particles = self.particles.iter().zip(&self.owners).map(|(p, &owner)| {
+            let (pos, vel) = ([p.pos.x, p.pos.y], [p.vel.x, p.vel.y]);
+            GpuParticle::new(
+                pos.map(|v| v as f32),
+                vel.map(|v| v as f32),
+                p.mass as f32,
+                p.is_fixed(),
+                owner,
+                {
+                    let friction = p.response();
+                    [friction.static_friction, friction.kinetic_friction].map(|v| v as f32)
+                },
+                {
+                    let contact = p.contact();
+                    [contact.restitution, contact.damping].map(|v| v as f32)
+                },
+            )
+        });
+        let reach = self
+            .reach
+            .iter()
+            .map(|&(min, max)| [min.x, min.y, max.x, max.y].map(|v| v as f32));
+        let responses = gpu.resolve(
+            (self.grid.cols, self.grid.rows, self.grid.cell),
+            self.grid.origin,
+            Particle::R,
+            particles,
+            reach,
+        );
+
+        for (particle, response) in self.particles.iter_mut().zip(responses) {
+            let ([x, y], [vx, vy]) = (response.pos.map(Real::from), response.vel.map(Real::from));
+            particle.pos += Vec2::new(x, y);
+            particle.vel += Vec2::new(vx, vy);
+
+            let speed = Real::from(response.speed);
+            if speed > Self::IMPACT_SPEED {
+                self.events.push(WorldEvent::Impact {
+                    pos: particle.pos,
+                    speed,
+                });
+            }
+        }
+
+        true
     }
 
-    fn update_spring(spring: &Spring, particles: &mut [Particle]) -> Result<(), f64> {
-        let p1 = &particles[spring.a];
-        let p2 = &particles[spring.b];
+    fn update_spring(
+        spring: &Spring,
+        ids: &Slots,
+        scales: &[Real],
+        particles: &mut [Particle],
+        material: &Material,
+        melting: Option<Real>,
+        max_force: Option<Real>,
+    ) -> Result<Option<WorldEvent>, Instability> {
+        let (a, b) = (ids.at(spring.a), ids.at(spring.b));
+        let p1 = &particles[a];
+        let p2 = &particles[b];
 
         let diff = p2.pos - p1.pos;
         let diff_len = diff.len();
+        let l0 = spring.rest_length(scales);
 
         /*NOTE: If the current length of the spring
         is greater than - lets say - five times the initial length
         we have probably detected an instabil explosion. We need to report this because
         these explosions can bog down the application and make it unresponsive.*/
-        if diff_len > spring.l0 * 5.0 {
-            return Err(diff_len);
+        if diff_len > l0 * 5.0 {
+            return Err(Instability {
+                a: spring.a,
+                b: spring.b,
+                diff_len,
+            });
         }
 
         let diff_norm = diff / diff_len;
 
-        let dl = diff_len - spring.l0;
+        let dl = diff_len - l0;
 
-        let dist_factor = if dl.is_sign_positive() { dl } else { 1.0 };
+        // The same comparison as the SIMD version, which has no sign test: -0.0 counts as
+        // stretched there too
+        let dist_factor = if dl >= 0.0 { dl } else { 1.0 };
+
+        let dv = diff_norm.dot(p2.vel - p1.vel);
+        let stiffness = match melting {
+            Some(point) => {
+                material.stiffness * Self::softening(p1.temperature, p2.temperature, point)
+            }
+            None => material.stiffness,
+        };
+        let fs = dist_factor * dl * stiffness;
+        let fd = dv * material.damping;
 
-        let fs = dist_factor * dl * Spring::KS;
-        let fd = diff_norm.dot(p2.vel - p1.vel) * Spring::KD;
+        let force = match max_force {
+            Some(max) => (fs + fd).clamp(-max, max),
+            None => fs + fd,
+        };
+        let f = force * diff_norm;
 
-        let f = (fs + fd) * diff_norm;
+        // Only report the step the spring crosses the threshold, not every step it stays there
+        let threshold = l0 * Self::COMPRESSION;
+        let event = (diff_len < threshold && diff_len - dv * Self::DT >= threshold).then(|| {
+            WorldEvent::Compression {
+                pos: p1.pos + diff * 0.5,
+                closing: -dv,
+            }
+        });
 
-        particles[spring.a].acc += f;
-        particles[spring.b].acc -= f;
+        // Springs joining objects of different materials move the lighter end further
+        particles[a].acc += f / particles[a].mass;
+        particles[b].acc -= f / particles[b].mass;
 
-        Ok(())
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt_prefabs_are_refused() {
+        let mut world = World::new(800.0, 600.0);
+        world.spawn_rect(3, 3, 100.0, 100.0).unwrap();
+        let prefab = world.extract_prefab(0).unwrap();
+        assert!(world
+            .insert_prefab(&prefab, Vec2::new(300.0, 100.0))
+            .is_ok());
+
+        let mut springs = prefab.springs.clone();
+        springs[0].b = prefab.particles.len();
+        let corrupt = Prefab { springs, ..prefab };
+        let (particles, springs, ..) = world.info();
+        assert!(world
+            .insert_prefab(&corrupt, Vec2::new(500.0, 100.0))
+            .is_err());
+        assert_eq!(world.info().0, particles);
+        assert_eq!(world.info().1, springs);
+
+        let boundaries = vec![corrupt.particles.len()];
+        let corrupt = Prefab {
+            springs: vec![],
+            boundaries,
+            ..corrupt
+        };
+        assert!(world
+            .insert_prefab(&corrupt, Vec2::new(500.0, 100.0))
+            .is_err());
+    }
+
+    #[test]
+    fn spring_ids_follow_their_springs() {
+        let mut world = World::new(800.0, 600.0);
+        world.spawn_rect(2, 2, 100.0, 100.0).unwrap();
+        world.spawn_rect(3, 3, 300.0, 100.0).unwrap();
+        let n = world.springs_of(&[1])[2];
+        let (id, (a, b)) = (
+            world.spring_id(n).unwrap(),
+            (world.springs[n].a, world.springs[n].b),
+        );
+        let i = world.particle_index(a).unwrap();
+        let first = world.spring_id(0).unwrap();
+        let gone = world.particle_id(0).unwrap();
+
+        world.remove_object(0);
+        let moved = world.spring_index(id).unwrap();
+        assert!(moved < n);
+        // The ends keep their ids, though the particles of the removed 2 x 2 rect came before
+        assert_eq!((world.springs[moved].a, world.springs[moved].b), (a, b));
+        assert_eq!(world.particle_index(a), Some(i - 4));
+        assert_eq!(world.spring_index(first), None);
+        assert_eq!(world.particle_index(gone), None);
+
+        // Saved and loaded, ids stay with the springs
+        let json = serde_json::to_string(&world).unwrap();
+        let mut loaded: World = serde_json::from_str(&json).unwrap();
+        loaded.resize(800.0, 600.0);
+        assert_eq!(loaded.spring_index(id), Some(moved));
+
+        world.clear();
+        assert_eq!(world.spring_index(id), None);
+    }
+
+    #[test]
+    fn oversized_rects_are_refused() {
+        let world = World::new(800.0, 600.0);
+        let side = World::MAX_RECT_SIDE;
+        assert!(world.check_spawn_rect(side + 1, 2, 0.0, 0.0).is_err());
+        assert!(world.check_spawn_rect(2, usize::MAX, 0.0, 0.0).is_err());
+        assert!(world.check_spawn_rect(side, side, 0.0, 0.0).is_ok());
+    }
+
+    #[test]
+    fn quarantines_are_checked_every_step() {
+        let quarantines = |world: &World| {
+            world
+                .events()
+                .iter()
+                .filter(|e| matches!(e, WorldEvent::Quarantined { object: 0 }))
+                .count()
+        };
+        let mut world = World::new(800.0, 600.0);
+        world.spawn_rect(3, 3, 300.0, 100.0).unwrap();
+        // Where the broadphase would file a NaN
+        world.spawn_rect(3, 3, 0.0, 0.0).unwrap();
+
+        world.particles[0].pos = Vec2::new(Real::NAN, 0.0);
+        world.step_n(1).unwrap();
+        assert!(world.is_quarantined(0) && !world.is_quarantined(1));
+        assert_eq!(quarantines(&world), 1);
+        assert!(world.particles_iter().all(|p| p.pos.is_finite()));
+
+        world.particles.iter_mut().for_each(|p| p.pinned = false);
+        world.particles[1].vel = Vec2::new(0.0, Real::INFINITY);
+        world.step_n(1).unwrap();
+        assert_eq!(quarantines(&world), 1);
+        assert!(!world.is_quarantined(1));
+        assert!(world.particles_iter().all(|p| p.vel.is_finite()));
+    }
+
+    #[test]
+    fn animated_edges_move_with_their_keyframes() {
+        let ends = |world: &World| {
+            let edge = world.edges_iter().next().unwrap();
+            (edge.get_start(), edge.get_end())
+        };
+        let mut world = World::new(800.0, 600.0);
+        world
+            .add_edge(Vec2::new(100.0, 300.0), Vec2::new(300.0, 350.0))
+            .unwrap();
+        world.add_keyframe(0);
+
+        world.move_edge(0, Vec2::new(50.0, -20.0));
+        let moved = ends(&world);
+        world.step_n(1).unwrap();
+        assert_eq!(ends(&world), moved);
+        assert_eq!(moved.0, Vec2::new(150.0, 280.0));
+
+        world.mirror_group(&[], &[0], false);
+        world.rotate_group(&[], &[0]);
+        let turned = ends(&world);
+        world.step_n(1).unwrap();
+        assert_eq!(ends(&world), turned);
+    }
+
+    #[test]
+    fn grid_clamps_each_axis_on_its_own() {
+        let grid = Grid::new(Vec2::null(), Vec2::new(100.0, 50.0));
+        let (last_x, last_y) = (grid.cols - 1, grid.rows - 1);
+        let inside = Vec2::new(grid.cell * 1.5, grid.cell * 1.5);
+
+        assert_eq!(grid.pos(inside), (1, 1));
+        assert_eq!(grid.pos(Vec2::new(-30.0, inside.y)), (0, 1));
+        assert_eq!(grid.pos(Vec2::new(inside.x, -30.0)), (1, 0));
+        assert_eq!(grid.pos(Vec2::new(-30.0, -30.0)), (0, 0));
+        assert_eq!(grid.pos(Vec2::new(1e9, inside.y)), (last_x, 1));
+        assert_eq!(grid.pos(Vec2::new(inside.x, 1e9)), (1, last_y));
+        assert_eq!(grid.pos(Vec2::new(Real::INFINITY, -1e9)), (last_x, 0));
+        assert_eq!(grid.pos(Vec2::new(Real::NAN, inside.y)), (0, 1));
+        assert_eq!(grid.pos(Vec2::new(inside.x, Real::NAN)), (1, 0));
+    }
+
+    #[test]
+    fn off_grid_particles_step() {
+        let offsets = [
+            Vec2::new(-500.0, 0.0),
+            Vec2::new(0.0, -500.0),
+            Vec2::new(5000.0, 0.0),
+            Vec2::new(0.0, 5000.0),
+            Vec2::new(-1e12, 1e12),
+            Vec2::new(Real::NAN, 0.0),
+            Vec2::new(0.0, Real::NAN),
+            Vec2::null(),
+        ];
+        let mut world = World::new(200.0, 150.0);
+        world.settings.bounds = WorldBounds::Open;
+        for n in 0..offsets.len() {
+            world
+                .spawn_rect(2, 2, 10.0 + n as Real * 20.0, 70.0)
+                .unwrap();
+        }
+        for (obj, offset) in world.objects.iter().zip(offsets) {
+            for particle in &mut world.particles[obj.particles_range()] {
+                particle.pos += offset;
+            }
+        }
+        // Whatever becomes of the broken objects, stepping them must not index off the grid
+        let _ = world.step_n(10);
+    }
+
+    #[test]
+    fn copies_reuse_buffers() {
+        let mut world = World::new(400.0, 300.0);
+        world.spawn_rect(5, 5, 100.0, 100.0).unwrap();
+        world.step_n(20).unwrap();
+
+        let mut copy = World::new(10.0, 10.0);
+        copy.spawn_rect(8, 8, 2.0, 2.0).unwrap();
+        let buffer = copy.particles.as_ptr();
+        copy.copy_from(&mut world);
+        assert_eq!(copy.particles.as_ptr(), buffer);
+        assert_eq!(copy.state_hash(), world.state_hash());
+        assert_eq!(copy.revision(), world.revision());
+        assert_eq!(world.particles.len(), 25);
+
+        world.step_n(20).unwrap();
+        copy.step_n(20).unwrap();
+        assert_eq!(copy.state_hash(), world.state_hash());
+    }
+
+    /// Every pair closer than a cell, found by the grid exactly once.
+    fn assert_pairs_match(world: &mut World) {
+        world.bin_particles();
+        let grid = world.grid;
+        let mut visited = std::collections::HashMap::new();
+        for (i, &cell) in world.cells.iter().enumerate() {
+            for j in grid.partners(&world.buckets, i, cell) {
+                *visited.entry((i.min(j), i.max(j))).or_insert(0) += 1;
+            }
+        }
+        if let Some((pair, count)) = visited.iter().find(|(_, &count)| count > 1) {
+            panic!("pair {pair:?} visited {count} times");
+        }
+
+        let n = world.particles.len();
+        for i in 0..n {
+            for j in i + 1..n {
+                if world.particles[i].pos.dist(world.particles[j].pos) < grid.cell {
+                    assert!(visited.contains_key(&(i, j)), "pair {:?} missed", (i, j));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn broadphase_finds_every_pair_once() {
+        let mut world = World::new(200.0, 150.0);
+        let cell = Grid::CELL;
+        // A lattice offset so that neighbors straddle every cell border, up to the far edges
+        // of the last row and column
+        let mut y = cell * 0.5;
+        while y < 150.0 {
+            let mut x = cell * 0.5;
+            while x < 200.0 {
+                world.particles.push(Particle::new(x, y));
+                x += cell * 0.5;
+            }
+            y += cell * 0.5;
+        }
+        // Right on the borders and in the corners too
+        for (x, y) in [
+            (0.0, 0.0),
+            (200.0, 150.0),
+            (200.0, 0.0),
+            (0.0, 150.0),
+            (cell, cell),
+            (cell * 3.0, 150.0 - 1e-3),
+        ] {
+            world.particles.push(Particle::new(x, y));
+        }
+        assert_pairs_match(&mut world);
+
+        // Scattered, with a cheap deterministic generator
+        let mut world = World::new(200.0, 150.0);
+        let mut seed: u32 = 12345;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (seed >> 8) as Real / (1 << 24) as Real
+        };
+        for _ in 0..2000 {
+            let (x, y) = (next() * 200.0, next() * 150.0);
+            world.particles.push(Particle::new(x, y));
+        }
+        assert_pairs_match(&mut world);
     }
 }
@@ -0,0 +1,51 @@
+use soft::{scene::PRESETS, vec2::Vec2, world::World};
+
+// Two seconds of simulated time, enough for everything to land and settle
+const STEPS: usize = 1600;
+
+// `World::state_hash` of every preset after `STEPS`, in the order of `PRESETS`. A change in
+// any of them means the simulation moves differently now: if that was the point, run
+// `cargo test --test determinism -- --nocapture` with each set of features and paste the
+// hashes printed for the failures here.
+//
+// The SIMD lanes reproduce the scalar results bit for bit, only the precision decides.
+#[cfg(not(feature = "f32"))]
+const HASHES: [u64; 7] = [
+    0x2f0f8cd36d38bf6e, // playground
+    0x4879c19210930436, // stacked boxes
+    0x50b9648ca5ea8c5d, // cloth on pegs
+    0x8b1f939f534fce65, // ball pit
+    0x913720a2a8ec5361, // catapult
+    0x251667fab9df73be, // pendulums
+    0xdfd07f16803abaad, // vehicle
+];
+#[cfg(feature = "f32")]
+const HASHES: [u64; 7] = [
+    0x8acbb43bd40a9a3b, // playground
+    0xb78ff39f1d6ce7af, // stacked boxes
+    0x8af493a1db8ddab9, // cloth on pegs
+    0x8aa965d40016f8a5, // ball pit
+    0xf58f5f39bfc2a953, // catapult
+    0x54eaf5d26ab641b0, // pendulums
+    0x20f685fad5c507c6, // vehicle
+];
+
+#[test]
+fn presets_step_the_same() {
+    let mut changed = vec![];
+
+    for (preset, expected) in PRESETS.into_iter().zip(HASHES) {
+        let size = Vec2::new(1920.0, 1080.0);
+        let mut world = World::new(size.x, size.y);
+        preset.build(&mut world, size);
+        world.step_n(STEPS).unwrap();
+
+        let hash = world.state_hash();
+        println!("    0x{hash:016x}, // {}", preset.name());
+        if hash != expected {
+            changed.push(preset.name());
+        }
+    }
+
+    assert!(changed.is_empty(), "changed: {changed:?}");
+}
@@ -0,0 +1,31 @@
+use soft::savefile::State;
+use std::path::Path;
+
+/// Written by the app, with the view settings changed from the defaults.
+const APP_SAVE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/app_save.json");
+
+#[test]
+fn app_saves_round_trip() {
+    let saved = State::read(Path::new(APP_SAVE)).unwrap();
+    assert_eq!(saved.speed, 0.5);
+    assert!(!saved.simulate);
+    assert!(saved.draw_springs && saved.draw_trails && !saved.draw_particles);
+    assert_eq!(saved.world.info().0, 6);
+
+    for extension in ["json", "msgpack", "cbor"] {
+        let path = std::env::temp_dir().join(format!(
+            "soft-round-trip-{}.{extension}",
+            std::process::id()
+        ));
+        saved.write(&path).unwrap();
+        let loaded = State::read(&path);
+        let _ = std::fs::remove_file(&path);
+        let loaded = loaded.unwrap();
+
+        assert_eq!(loaded.world.state_hash(), saved.world.state_hash());
+        assert_eq!(loaded.speed, saved.speed);
+        assert_eq!(loaded.simulate, saved.simulate);
+        assert_eq!(loaded.draw_springs, saved.draw_springs);
+        assert_eq!(loaded.draw_trails, saved.draw_trails);
+    }
+}